@@ -71,9 +71,21 @@ pub struct CompileCommand {
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
+    /// Tags the PDF with its logical structure for assistive technology
+    #[arg(long = "tagged")]
+    pub tagged: bool,
+
+    /// Makes the PDF conform to a standard, e.g. for archival
+    #[arg(long = "pdf-standard", value_name = "STANDARD")]
+    pub pdf_standard: Option<PdfStandard>,
+
     /// Produces a flamegraph of the compilation process
     #[arg(long = "flamegraph", value_name = "OUTPUT_SVG")]
     pub flamegraph: Option<Option<PathBuf>>,
+
+    /// Records the timings of the compilation phases as a Chrome trace (JSON)
+    #[arg(long = "timings", value_name = "OUTPUT_JSON")]
+    pub timings: Option<Option<PathBuf>>,
 }
 
 /// Processes an input file to extract provided metadata
@@ -94,6 +106,10 @@ pub struct QueryCommand {
     #[clap(long = "one", default_value = "false")]
     pub one: bool,
 
+    /// Includes the location, page, and position (in pt) of each element
+    #[clap(long = "positions", default_value = "false", conflicts_with = "field")]
+    pub positions: bool,
+
     /// The format to serialize in
     #[clap(long = "format", default_value = "json")]
     pub format: SerializationFormat,
@@ -189,6 +205,8 @@ pub enum OutputFormat {
     Pdf,
     Png,
     Svg,
+    Html,
+    Epub,
 }
 
 impl Display for OutputFormat {
@@ -199,3 +217,11 @@ impl Display for OutputFormat {
             .fmt(f)
     }
 }
+
+/// A standard that an exported PDF can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for long-term archival
+    #[value(name = "pdf-a-2b")]
+    A2b,
+}
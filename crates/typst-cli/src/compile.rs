@@ -15,7 +15,7 @@ use typst::syntax::{FileId, Source, Span};
 use typst::visualize::Color;
 use typst::{World, WorldExt};
 
-use crate::args::{CompileCommand, DiagnosticFormat, OutputFormat};
+use crate::args::{CompileCommand, DiagnosticFormat, OutputFormat, PdfStandard};
 use crate::watch::Status;
 use crate::world::SystemWorld;
 use crate::{color_stream, set_failed};
@@ -32,6 +32,8 @@ impl CompileCommand {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
                     OutputFormat::Svg => "svg",
+                    OutputFormat::Html => "html",
+                    OutputFormat::Epub => "epub",
                 },
             )
         })
@@ -48,6 +50,8 @@ impl CompileCommand {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
+                Some(ext) if ext.eq_ignore_ascii_case("html") => OutputFormat::Html,
+                Some(ext) if ext.eq_ignore_ascii_case("epub") => OutputFormat::Epub,
                 _ => bail!("could not infer output format for path {}.\nconsider providing the format manually with `--format/-f`", output.display()),
             }
         } else {
@@ -96,13 +100,13 @@ pub fn compile_once(
 
     let mut tracer = Tracer::new();
     let result = typst::compile(world, &mut tracer);
-    let warnings = tracer.warnings();
 
     match result {
         // Export the PDF / PNG.
         Ok(document) => {
-            export(world, &document, command, watching)?;
+            export(world, &document, command, watching, &mut tracer)?;
             let duration = start.elapsed();
+            let warnings = tracer.warnings();
 
             tracing::info!("Compilation succeeded in {duration:?}");
             if watching {
@@ -133,7 +137,7 @@ pub fn compile_once(
             print_diagnostics(
                 world,
                 &errors,
-                &warnings,
+                &tracer.warnings(),
                 command.common.diagnostic_format,
             )
             .map_err(|err| eco_format!("failed to print diagnostics ({err})"))?;
@@ -144,11 +148,13 @@ pub fn compile_once(
 }
 
 /// Export into the target format.
+#[tracing::instrument(skip_all)]
 fn export(
     world: &mut SystemWorld,
     document: &Document,
     command: &CompileCommand,
     watching: bool,
+    tracer: &mut Tracer,
 ) -> StrResult<()> {
     match command.output_format()? {
         OutputFormat::Png => {
@@ -157,7 +163,9 @@ fn export(
         OutputFormat::Svg => {
             export_image(world, document, command, watching, ImageExportFormat::Svg)
         }
-        OutputFormat::Pdf => export_pdf(document, command, world),
+        OutputFormat::Pdf => export_pdf(document, command, world, tracer),
+        OutputFormat::Html => export_html(document, command),
+        OutputFormat::Epub => export_epub(document, command, world),
     }
 }
 
@@ -166,15 +174,41 @@ fn export_pdf(
     document: &Document,
     command: &CompileCommand,
     world: &SystemWorld,
+    tracer: &mut Tracer,
 ) -> StrResult<()> {
     let ident = world.input().to_string_lossy();
-    let buffer = typst_pdf::pdf(document, Some(&ident), now());
+    let standard = command.pdf_standard.map(|standard| match standard {
+        PdfStandard::A2b => typst_pdf::PdfStandard::A2b,
+    });
+    let options = typst_pdf::PdfOptions { tagged: command.tagged, standard };
+    let buffer = typst_pdf::pdf(document, Some(&ident), now(), options, tracer)?;
     let output = command.output();
     fs::write(output, buffer)
         .map_err(|err| eco_format!("failed to write PDF file ({err})"))?;
     Ok(())
 }
 
+/// Export to an HTML file.
+fn export_html(document: &Document, command: &CompileCommand) -> StrResult<()> {
+    let html = typst_html::html(document);
+    fs::write(command.output(), html)
+        .map_err(|err| eco_format!("failed to write HTML file ({err})"))?;
+    Ok(())
+}
+
+/// Export to an EPUB file.
+fn export_epub(
+    document: &Document,
+    command: &CompileCommand,
+    world: &SystemWorld,
+) -> StrResult<()> {
+    let ident = world.input().to_string_lossy();
+    let buffer = typst_html::epub(document, Some(&ident), now())?;
+    fs::write(command.output(), buffer)
+        .map_err(|err| eco_format!("failed to write EPUB file ({err})"))?;
+    Ok(())
+}
+
 /// Get the current date and time in UTC.
 fn now() -> Option<Datetime> {
     let now = chrono::Local::now().naive_utc();
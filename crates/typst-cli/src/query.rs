@@ -3,8 +3,8 @@ use ecow::{eco_format, EcoString};
 use serde::Serialize;
 use typst::diag::{bail, StrResult};
 use typst::eval::{eval_string, EvalMode, Tracer};
-use typst::foundations::{Content, IntoValue, LocatableSelector, Scope};
-use typst::model::Document;
+use typst::foundations::{Content, IntoValue, LocatableSelector, Scope, Selector};
+use typst::introspection::QueryMatch;
 use typst::syntax::Span;
 use typst::World;
 
@@ -29,8 +29,19 @@ pub fn query(command: &QueryCommand) -> StrResult<()> {
     match result {
         // Retrieve and print query results.
         Ok(document) => {
-            let data = retrieve(&world, command, &document)?;
-            let serialized = format(data, command)?;
+            let selector = selector(&world, command)?;
+            let serialized = if command.positions {
+                let data = document.introspector.query_export(&selector);
+                format_positions(data, command)?
+            } else {
+                let data = document
+                    .introspector
+                    .query(&selector)
+                    .into_iter()
+                    .map(|x| x.into_inner())
+                    .collect::<Vec<_>>();
+                format(data, command)?
+            };
             println!("{serialized}");
             print_diagnostics(&world, &[], &warnings, command.common.diagnostic_format)
                 .map_err(|err| eco_format!("failed to print diagnostics ({err})"))?;
@@ -52,12 +63,8 @@ pub fn query(command: &QueryCommand) -> StrResult<()> {
     Ok(())
 }
 
-/// Retrieve the matches for the selector.
-fn retrieve(
-    world: &dyn World,
-    command: &QueryCommand,
-    document: &Document,
-) -> StrResult<Vec<Content>> {
+/// Evaluate the selector.
+fn selector(world: &dyn World, command: &QueryCommand) -> StrResult<Selector> {
     let selector = eval_string(
         world.track(),
         &command.selector,
@@ -75,12 +82,7 @@ fn retrieve(
     })?
     .cast::<LocatableSelector>()?;
 
-    Ok(document
-        .introspector
-        .query(&selector.0)
-        .into_iter()
-        .map(|x| x.into_inner())
-        .collect::<Vec<_>>())
+    Ok(selector.0)
 }
 
 /// Format the query result in the output format.
@@ -107,6 +109,21 @@ fn format(elements: Vec<Content>, command: &QueryCommand) -> StrResult<String> {
     }
 }
 
+/// Format the query result with positions in the output format.
+fn format_positions(
+    matches: Vec<QueryMatch>,
+    command: &QueryCommand,
+) -> StrResult<String> {
+    if command.one {
+        if matches.len() != 1 {
+            bail!("expected exactly one element, found {}", matches.len());
+        }
+        serialize(&matches[0], command.format)
+    } else {
+        serialize(&matches, command.format)
+    }
+}
+
 /// Serialize data to the output format.
 fn serialize(data: &impl Serialize, format: SerializationFormat) -> StrResult<String> {
     match format {
@@ -1,33 +1,49 @@
 use std::fs::File;
 use std::io::{self, BufReader, BufWriter, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use inferno::flamegraph::Options;
+use serde::Serialize;
 use tracing::metadata::LevelFilter;
+use tracing::span::Id;
+use tracing::Subscriber;
 use tracing_error::ErrorLayer;
 use tracing_flame::{FlameLayer, FlushGuard};
-use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, Layer};
 
 use crate::args::{CliArguments, Command};
 
 /// Initializes the tracing system and returns a guard that will flush the
-/// flamegraph to disk when dropped.
+/// flamegraph and timings to disk when dropped.
 pub fn setup_tracing(args: &CliArguments) -> io::Result<Option<impl Drop>> {
-    let flamegraph = match &args.command {
-        Command::Compile(command) => command.flamegraph.as_ref(),
+    let (flamegraph, timings) = match &args.command {
+        Command::Compile(command) => {
+            (command.flamegraph.as_ref(), command.timings.as_ref())
+        }
         Command::Watch(command) if command.flamegraph.is_some() => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "cannot use --flamegraph with watch command",
             ));
         }
-        _ => None,
+        Command::Watch(command) if command.timings.is_some() => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot use --timings with watch command",
+            ));
+        }
+        _ => (None, None),
     };
 
-    // Short circuit if we don't need to initialize flamegraph or debugging.
-    if flamegraph.is_none() && args.verbosity == 0 {
+    // Short circuit if we don't need to initialize flamegraph, timings, or
+    // debugging.
+    if flamegraph.is_none() && timings.is_none() && args.verbosity == 0 {
         tracing_subscriber::fmt()
             .without_time()
             .with_max_level(level_filter(args))
@@ -45,36 +61,51 @@ pub fn setup_tracing(args: &CliArguments) -> io::Result<Option<impl Drop>> {
     // Build the registry.
     let registry = tracing_subscriber::registry().with(fmt_layer).with(error_layer);
 
-    let Some(path) = flamegraph else {
+    if flamegraph.is_none() && timings.is_none() {
         registry.init();
         return Ok(None);
-    };
+    }
 
-    // Create a temporary file to store the flamegraph data.
-    let temp_file = tempfile::tempfile()?;
-    let writer = BufWriter::new(temp_file.try_clone()?);
+    // Build the flamegraph layer, writing to a temporary file.
+    let mut flame_layer = None;
+    let mut flame = None;
+    if let Some(path) = flamegraph {
+        let temp_file = tempfile::tempfile()?;
+        let writer = BufWriter::new(temp_file.try_clone()?);
+        let layer = FlameLayer::new(writer)
+            .with_empty_samples(false)
+            .with_threads_collapsed(true)
+            .with_module_path(false)
+            .with_file_and_line(true);
+        flame = Some(Flamegraph {
+            flush_guard: Some(layer.flush_on_drop()),
+            temp_file,
+            output_svg: path.clone().unwrap_or_else(|| "flamegraph.svg".into()),
+        });
+        flame_layer = Some(layer);
+    }
 
-    // Build the flamegraph layer.
-    let flame_layer = FlameLayer::new(writer)
-        .with_empty_samples(false)
-        .with_threads_collapsed(true)
-        .with_module_path(false)
-        .with_file_and_line(true);
-    let flush_guard = flame_layer.flush_on_drop();
+    // Build the timings layer, which records span entries and exits in memory.
+    let timings = timings.map(|path| {
+        Arc::new(Timings {
+            output_json: path.clone().unwrap_or_else(|| "timings.json".into()),
+            start: Instant::now(),
+            events: Mutex::new(vec![]),
+        })
+    });
+    let timings_layer = timings.clone().map(TimingsLayer);
 
     // Build the subscriber.
-    registry.with(flame_layer).init();
-
-    tracing::warn!(
-        "Flamegraph is enabled, this can create a large temporary \
-         file and slow down the compilation process."
-    );
-
-    Ok(Some(TracingGuard {
-        flush_guard: Some(flush_guard),
-        temp_file,
-        output_svg: path.clone().unwrap_or_else(|| "flamegraph.svg".into()),
-    }))
+    registry.with(flame_layer).with(timings_layer).init();
+
+    if flame.is_some() {
+        tracing::warn!(
+            "Flamegraph is enabled, this can create a large temporary \
+             file and slow down the compilation process."
+        );
+    }
+
+    Ok(Some(TracingGuard { flame, timings }))
 }
 
 /// Returns the log level filter for the given verbosity level.
@@ -88,14 +119,44 @@ fn level_filter(args: &CliArguments) -> LevelFilter {
     }
 }
 
-/// Will flush the flamegraph to disk when dropped.
+/// Will flush the flamegraph and timings to disk when dropped.
 struct TracingGuard {
+    flame: Option<Flamegraph>,
+    timings: Option<Arc<Timings>>,
+}
+
+impl TracingGuard {
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(flame) = &mut self.flame {
+            flame.finish()?;
+        }
+        if let Some(timings) = &self.timings {
+            timings.finish()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            if let Err(err) = self.finish() {
+                // Since we are finished, we cannot rely on tracing to log the
+                // error.
+                eprintln!("failed to flush tracing output ({err})");
+            }
+        }
+    }
+}
+
+/// The flamegraph data that is still to be written.
+struct Flamegraph {
     flush_guard: Option<FlushGuard<BufWriter<File>>>,
     temp_file: File,
     output_svg: PathBuf,
 }
 
-impl TracingGuard {
+impl Flamegraph {
     fn finish(&mut self) -> io::Result<()> {
         if self.flush_guard.is_none() {
             return Ok(());
@@ -126,14 +187,80 @@ impl TracingGuard {
     }
 }
 
-impl Drop for TracingGuard {
-    fn drop(&mut self) {
-        if !std::thread::panicking() {
-            if let Err(err) = self.finish() {
-                // Since we are finished, we cannot rely on tracing to log the
-                // error.
-                eprintln!("failed to flush tracing flamegraph ({err})");
-            }
+/// A tracing layer that records the entries and exits of all instrumented
+/// spans (parsing, evaluation, layout, introspection, export, ...).
+struct TimingsLayer(Arc<Timings>);
+
+/// The recorded span timings.
+///
+/// They are written in the Chrome trace event format, which can be opened in
+/// `chrome://tracing`, Perfetto, or Speedscope.
+struct Timings {
+    output_json: PathBuf,
+    start: Instant,
+    events: Mutex<Vec<TimingEvent>>,
+}
+
+/// A single event in the Chrome trace event format.
+#[derive(Serialize)]
+struct TimingEvent {
+    name: &'static str,
+    cat: &'static str,
+    ph: char,
+    ts: f64,
+    pid: u64,
+    tid: u64,
+}
+
+impl Timings {
+    /// Record the entry or exit of a span.
+    fn record(&self, name: &'static str, cat: &'static str, ph: char) {
+        let ts = self.start.elapsed().as_nanos() as f64 / 1000.0;
+        let event = TimingEvent { name, cat, ph, ts, pid: 1, tid: thread_id() };
+        self.events.lock().unwrap().push(event);
+    }
+
+    /// Write the recorded events to the output file.
+    fn finish(&self) -> io::Result<()> {
+        tracing::info!("Writing timings...");
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Trace<'a> {
+            trace_events: &'a [TimingEvent],
+        }
+
+        let events = self.events.lock().unwrap();
+        let output = BufWriter::new(File::create(&self.output_json)?);
+        serde_json::to_writer(output, &Trace { trace_events: &events })
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let meta = span.metadata();
+            self.0.record(meta.name(), meta.target(), 'B');
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let meta = span.metadata();
+            self.0.record(meta.name(), meta.target(), 'E');
         }
     }
 }
+
+/// A small, stable identifier for the current thread.
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+
+use ecow::{eco_format, EcoString};
+use typst::diag::StrResult;
+use typst::foundations::Datetime;
+use typst::model::Document;
+use typst::text::{Font, FontStyle};
+use typst::visualize::Image;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::{
+    collect_runs, css_string, escape, first_run, media_type, write_blocks, write_style,
+    Block, Exporter, Run,
+};
+
+/// Export a document into an EPUB 3 file.
+///
+/// The document is split into one chapter per level-1 heading. Each chapter
+/// is exported in the same way as by [`html`](crate::html), with images and
+/// fonts packaged as separate files. Title, authors, keywords, and date are
+/// taken from the document's metadata.
+///
+/// The `ident` and `timestamp` parameters work as for PDF export: `ident`
+/// stably identifies the book across compilations and `timestamp` is used as
+/// the modification date if `set document(date: ..)` is `auto`.
+#[tracing::instrument(skip_all)]
+pub fn epub(
+    document: &Document,
+    ident: Option<&str>,
+    timestamp: Option<Datetime>,
+) -> StrResult<Vec<u8>> {
+    let mut exporter = Exporter::default();
+    for page in &document.pages {
+        exporter.page(page);
+    }
+
+    let chapters = split_chapters(exporter.blocks);
+    let first = chapters.iter().find_map(|chapter| first_run(&chapter.blocks));
+    let lang = first.map_or("en", |run| run.lang.as_str());
+    let title = document
+        .title
+        .clone()
+        .or_else(|| chapters.iter().find_map(|chapter| chapter.title.clone()))
+        .unwrap_or_else(|| "Untitled".into());
+
+    let mut resources = Resources::default();
+    let pages: Vec<String> = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let title = chapter.title.as_deref().unwrap_or(&title);
+            write_chapter(&mut resources, chapter, title, lang, i + 1)
+        })
+        .collect();
+
+    for chapter in &chapters {
+        let mut runs = vec![];
+        collect_runs(&chapter.blocks, &mut runs);
+        resources.register_fonts(runs);
+    }
+
+    let identifier = match ident {
+        Some(ident) => typst::util::hash128(&ident),
+        None => typst::util::hash128(&pages),
+    };
+
+    let mut files: Vec<(EcoString, Vec<u8>)> = vec![
+        ("META-INF/container.xml".into(), CONTAINER.as_bytes().to_vec()),
+        ("OEBPS/style.css".into(), resources.style(first).into_bytes()),
+        ("OEBPS/nav.xhtml".into(), write_nav(&chapters, &title, lang).into_bytes()),
+        ("OEBPS/toc.ncx".into(), write_ncx(&chapters, &title, identifier).into_bytes()),
+    ];
+
+    let metadata = Metadata {
+        document,
+        title: &title,
+        lang,
+        identifier,
+        date: document.date.unwrap_or(timestamp),
+    };
+    let opf = write_opf(&metadata, &resources, pages.len());
+    files.push(("OEBPS/content.opf".into(), opf.into_bytes()));
+
+    for (i, page) in pages.into_iter().enumerate() {
+        files.push((eco_format!("OEBPS/{}", chapter_path(i + 1)), page.into_bytes()));
+    }
+
+    for (i, image) in resources.images.iter().enumerate() {
+        let path = eco_format!("OEBPS/{}", image_path(image, i));
+        files.push((path, image.data().to_vec()));
+    }
+
+    for (i, font) in resources.fonts.iter().enumerate() {
+        let path = eco_format!("OEBPS/{}", font_path(font, i));
+        files.push((path, font.data().to_vec()));
+    }
+
+    package(files).map_err(|err| eco_format!("failed to write EPUB file ({err})"))
+}
+
+/// The container file pointing to the package document.
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// A chapter of the book.
+struct Chapter {
+    /// The plain text of the chapter's level-1 heading, if any.
+    title: Option<EcoString>,
+    /// The chapter's blocks, starting with its heading.
+    blocks: Vec<Block>,
+}
+
+/// The images and fonts packaged with the book.
+#[derive(Default)]
+struct Resources {
+    /// The images in order of first use.
+    images: Vec<Image>,
+    /// Maps from images to their indices in `images`.
+    image_map: HashMap<Image, usize>,
+    /// The fonts in order of first use.
+    fonts: Vec<Font>,
+}
+
+/// The metadata of the package document.
+struct Metadata<'a> {
+    document: &'a Document,
+    title: &'a str,
+    lang: &'a str,
+    identifier: u128,
+    date: Option<Datetime>,
+}
+
+impl Resources {
+    /// The path of an image, registering it if necessary.
+    fn image(&mut self, image: &Image) -> EcoString {
+        let len = self.images.len();
+        let index = *self.image_map.entry(image.clone()).or_insert(len);
+        if index == len {
+            self.images.push(image.clone());
+        }
+        image_path(image, index)
+    }
+
+    /// Register the fonts of runs of text.
+    ///
+    /// Fonts from collections are skipped since they cannot be packaged on
+    /// their own. Reading systems fall back to their own fonts for them.
+    fn register_fonts<'a>(&mut self, runs: impl IntoIterator<Item = &'a Run>) {
+        for run in runs {
+            if run.font.index() == 0
+                && !run.font.data().starts_with(b"ttcf")
+                && !self.fonts.contains(&run.font)
+            {
+                self.fonts.push(run.font.clone());
+            }
+        }
+    }
+
+    /// Write the style sheet, including the font faces.
+    fn style(&self, first: Option<&Run>) -> String {
+        let mut css = String::new();
+        for (i, font) in self.fonts.iter().enumerate() {
+            let info = font.info();
+            let style = match info.variant.style {
+                FontStyle::Normal => "normal",
+                FontStyle::Italic => "italic",
+                FontStyle::Oblique => "oblique",
+            };
+            writeln!(
+                css,
+                "@font-face {{ font-family: \"{}\"; font-weight: {}; \
+                 font-style: {style}; src: url(\"{}\"); }}",
+                css_string(&info.family),
+                info.variant.weight.to_number(),
+                font_path(font, i),
+            )
+            .unwrap();
+        }
+        write_style(&mut css, first);
+        css
+    }
+}
+
+/// Split blocks into chapters at level-1 headings.
+fn split_chapters(blocks: Vec<Block>) -> Vec<Chapter> {
+    let mut chapters = vec![];
+    let mut current = Chapter { title: None, blocks: vec![] };
+    for block in blocks {
+        if let Block::Heading(level, runs) = &block {
+            if level.get() == 1 {
+                let title = Some(plain_text(runs));
+                if !current.blocks.is_empty() {
+                    chapters.push(std::mem::replace(
+                        &mut current,
+                        Chapter { title, blocks: vec![] },
+                    ));
+                } else {
+                    current.title = title;
+                }
+            }
+        }
+        current.blocks.push(block);
+    }
+
+    if !current.blocks.is_empty() || chapters.is_empty() {
+        chapters.push(current);
+    }
+
+    chapters
+}
+
+/// The plain text of runs, separating lines with spaces.
+fn plain_text(runs: &[Run]) -> EcoString {
+    let mut text = EcoString::new();
+    let mut prev: Option<&Run> = None;
+    for run in runs {
+        if let Some(prev) = prev {
+            if !prev.pos.y.approx_eq(run.pos.y)
+                && !prev.text.ends_with(char::is_whitespace)
+                && !run.text.starts_with(char::is_whitespace)
+            {
+                text.push(' ');
+            }
+        }
+        text.push_str(&run.text);
+        prev = Some(run);
+    }
+    text.trim().into()
+}
+
+/// Write the XHTML content document of a chapter.
+fn write_chapter(
+    resources: &mut Resources,
+    chapter: &Chapter,
+    title: &str,
+    lang: &str,
+    number: usize,
+) -> String {
+    let mut xhtml = String::new();
+    write_xhtml_head(&mut xhtml, title, lang);
+    xhtml.push_str("<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />\n");
+    xhtml.push_str("</head>\n");
+    writeln!(xhtml, "<body id=\"chapter-{number}\">").unwrap();
+    write_blocks(&mut xhtml, &chapter.blocks, &mut |image| resources.image(image));
+    xhtml.push_str("</body>\n</html>\n");
+    xhtml
+}
+
+/// Write the navigation document.
+fn write_nav(chapters: &[Chapter], title: &str, lang: &str) -> String {
+    let mut xhtml = String::new();
+    write_xhtml_head(&mut xhtml, title, lang);
+    xhtml.push_str("</head>\n<body>\n<nav epub:type=\"toc\" id=\"toc\">\n");
+    writeln!(xhtml, "<h1>{}</h1>\n<ol>", escape(title)).unwrap();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let label = chapter_label(chapter, i + 1);
+        let path = chapter_path(i + 1);
+        writeln!(xhtml, "<li><a href=\"{path}\">{}</a></li>", escape(&label)).unwrap();
+    }
+    xhtml.push_str("</ol>\n</nav>\n</body>\n</html>\n");
+    xhtml
+}
+
+/// Write the table of contents for EPUB 2 reading systems.
+fn write_ncx(chapters: &[Chapter], title: &str, identifier: u128) -> String {
+    let mut ncx = String::new();
+    ncx.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    ncx.push_str(
+        "<ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">\n",
+    );
+    ncx.push_str("<head>\n");
+    writeln!(ncx, "<meta name=\"dtb:uid\" content=\"{}\" />", urn(identifier)).unwrap();
+    ncx.push_str("<meta name=\"dtb:depth\" content=\"1\" />\n");
+    ncx.push_str("</head>\n");
+    writeln!(ncx, "<docTitle><text>{}</text></docTitle>", escape(title)).unwrap();
+    ncx.push_str("<navMap>\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        let number = i + 1;
+        let label = chapter_label(chapter, number);
+        writeln!(
+            ncx,
+            "<navPoint id=\"nav-{number}\" playOrder=\"{number}\">\
+             <navLabel><text>{}</text></navLabel>\
+             <content src=\"{}\" /></navPoint>",
+            escape(&label),
+            chapter_path(number),
+        )
+        .unwrap();
+    }
+    ncx.push_str("</navMap>\n</ncx>\n");
+    ncx
+}
+
+/// Write the package document, which lists the book's metadata and files.
+fn write_opf(metadata: &Metadata, resources: &Resources, chapters: usize) -> String {
+    let Metadata { document, title, lang, identifier, date } = metadata;
+
+    let mut opf = String::new();
+    opf.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    writeln!(
+        opf,
+        "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+         unique-identifier=\"uid\" xml:lang=\"{}\">",
+        escape(lang),
+    )
+    .unwrap();
+
+    opf.push_str("<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+    writeln!(opf, "<dc:identifier id=\"uid\">{}</dc:identifier>", urn(*identifier))
+        .unwrap();
+    writeln!(opf, "<dc:title>{}</dc:title>", escape(title)).unwrap();
+    writeln!(opf, "<dc:language>{}</dc:language>", escape(lang)).unwrap();
+    for author in &document.author {
+        writeln!(opf, "<dc:creator>{}</dc:creator>", escape(author)).unwrap();
+    }
+    for keyword in &document.keywords {
+        writeln!(opf, "<dc:subject>{}</dc:subject>", escape(keyword)).unwrap();
+    }
+    if let Some(date) = date.filter(|date| date.year().is_some_and(|y| y >= 0)) {
+        writeln!(
+            opf,
+            "<dc:date>{:04}-{:02}-{:02}</dc:date>",
+            date.year().unwrap(),
+            date.month().unwrap_or(1),
+            date.day().unwrap_or(1),
+        )
+        .unwrap();
+    }
+    writeln!(opf, "<meta property=\"dcterms:modified\">{}</meta>", modified(*date))
+        .unwrap();
+    opf.push_str("</metadata>\n");
+
+    opf.push_str("<manifest>\n");
+    opf.push_str(
+        "<item id=\"nav\" href=\"nav.xhtml\" \
+         media-type=\"application/xhtml+xml\" properties=\"nav\" />\n",
+    );
+    opf.push_str(
+        "<item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\" />\n",
+    );
+    opf.push_str("<item id=\"style\" href=\"style.css\" media-type=\"text/css\" />\n");
+    for number in 1..=chapters {
+        writeln!(
+            opf,
+            "<item id=\"chapter-{number}\" href=\"{}\" \
+             media-type=\"application/xhtml+xml\" />",
+            chapter_path(number),
+        )
+        .unwrap();
+    }
+    for (i, image) in resources.images.iter().enumerate() {
+        writeln!(
+            opf,
+            "<item id=\"image-{}\" href=\"{}\" media-type=\"{}\" />",
+            i + 1,
+            image_path(image, i),
+            media_type(image),
+        )
+        .unwrap();
+    }
+    for (i, font) in resources.fonts.iter().enumerate() {
+        let media_type = if is_otf(font) { "font/otf" } else { "font/ttf" };
+        writeln!(
+            opf,
+            "<item id=\"font-{}\" href=\"{}\" media-type=\"{media_type}\" />",
+            i + 1,
+            font_path(font, i),
+        )
+        .unwrap();
+    }
+    opf.push_str("</manifest>\n");
+
+    opf.push_str("<spine toc=\"ncx\">\n");
+    for number in 1..=chapters {
+        writeln!(opf, "<itemref idref=\"chapter-{number}\" />").unwrap();
+    }
+    opf.push_str("</spine>\n</package>\n");
+    opf
+}
+
+/// Write the start of an XHTML document up to the title.
+fn write_xhtml_head(xhtml: &mut String, title: &str, lang: &str) {
+    let lang = escape(lang);
+    xhtml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n");
+    writeln!(
+        xhtml,
+        "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+         xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+         lang=\"{lang}\" xml:lang=\"{lang}\">",
+    )
+    .unwrap();
+    xhtml.push_str("<head>\n<meta charset=\"utf-8\" />\n");
+    writeln!(xhtml, "<title>{}</title>", escape(title)).unwrap();
+}
+
+/// Package files into an EPUB container.
+fn package(files: Vec<(EcoString, Vec<u8>)>) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+
+    // The media type must come first and be stored uncompressed so that
+    // reading systems can identify the file by its first bytes.
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, data) in files {
+        zip.start_file(path.as_str(), deflated)?;
+        zip.write_all(&data)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The label of a chapter in the table of contents.
+fn chapter_label(chapter: &Chapter, number: usize) -> EcoString {
+    chapter
+        .title
+        .clone()
+        .unwrap_or_else(|| eco_format!("Chapter {number}"))
+}
+
+/// The path of a chapter relative to the package document.
+fn chapter_path(number: usize) -> EcoString {
+    eco_format!("chapter-{number}.xhtml")
+}
+
+/// The path of an image relative to the package document.
+fn image_path(image: &Image, index: usize) -> EcoString {
+    let ext = match media_type(image) {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        other => other.trim_start_matches("image/"),
+    };
+    eco_format!("images/image-{}.{ext}", index + 1)
+}
+
+/// The path of a font relative to the package document.
+fn font_path(font: &Font, index: usize) -> EcoString {
+    let ext = if is_otf(font) { "otf" } else { "ttf" };
+    eco_format!("fonts/font-{}.{ext}", index + 1)
+}
+
+/// Whether a font has CFF outlines.
+fn is_otf(font: &Font) -> bool {
+    font.data().starts_with(b"OTTO")
+}
+
+/// The unique identifier of the book.
+fn urn(identifier: u128) -> EcoString {
+    eco_format!("urn:typst:{identifier:032x}")
+}
+
+/// Format the modification date of the book.
+///
+/// EPUB requires a modification date, so the Unix epoch is used if none is
+/// known.
+fn modified(date: Option<Datetime>) -> EcoString {
+    let Some(date) = date.filter(|date| date.year().is_some_and(|y| y >= 0)) else {
+        return "1970-01-01T00:00:00Z".into();
+    };
+
+    eco_format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year().unwrap(),
+        date.month().unwrap_or(1),
+        date.day().unwrap_or(1),
+        date.hour().unwrap_or(0),
+        date.minute().unwrap_or(0),
+        date.second().unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use typst::layout::{Abs, Frame, FrameItem, Point, Size};
+    use typst::syntax::Span;
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::tests::{document, heading, image, text, REGULAR};
+
+    #[test]
+    fn test_epub_package() {
+        let mut first = Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)));
+        heading(&mut first, 0.0, "First");
+        text(&mut first, 40.0, REGULAR, "Hello");
+        let mut second = Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)));
+        heading(&mut second, 0.0, "Second");
+        let size = Size::new(Abs::pt(50.0), Abs::pt(30.0));
+        let item = FrameItem::Image(image(None), size, Span::detached());
+        second.push(Point::with_y(Abs::pt(30.0)), item);
+
+        let mut document = document(vec![first, second]);
+        document.title = Some("Book".into());
+        document.author = vec!["Jane".into()];
+        let data = epub(&document, Some("book"), None).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+
+        // The media type comes first and uncompressed.
+        let mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        drop(mimetype);
+        assert_eq!(read(&mut archive, "mimetype"), "application/epub+zip");
+        assert_eq!(read(&mut archive, "META-INF/container.xml"), CONTAINER);
+
+        let opf = read(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("<dc:title>Book</dc:title>"));
+        assert!(opf.contains("<dc:creator>Jane</dc:creator>"));
+        assert!(opf.contains("<dc:language>en</dc:language>"));
+        assert!(opf.contains(&format!(
+            "<dc:identifier id=\"uid\">{}</dc:identifier>",
+            urn(typst::util::hash128(&"book")),
+        )));
+        assert!(opf.contains("<meta property=\"dcterms:modified\">1970-01-01T00:00:00Z"));
+        assert!(opf.contains(
+            "<item id=\"chapter-2\" href=\"chapter-2.xhtml\" \
+             media-type=\"application/xhtml+xml\" />"
+        ));
+        assert!(opf.contains(
+            "<item id=\"image-1\" href=\"images/image-1.png\" media-type=\"image/png\" />"
+        ));
+        assert!(opf.contains(
+            "<item id=\"font-1\" href=\"fonts/font-1.ttf\" media-type=\"font/ttf\" />"
+        ));
+        assert!(opf.contains(
+            "<spine toc=\"ncx\">\n\
+             <itemref idref=\"chapter-1\" />\n\
+             <itemref idref=\"chapter-2\" />\n\
+             </spine>"
+        ));
+
+        let nav = read(&mut archive, "OEBPS/nav.xhtml");
+        assert!(nav.contains(
+            "<ol>\n\
+             <li><a href=\"chapter-1.xhtml\">First</a></li>\n\
+             <li><a href=\"chapter-2.xhtml\">Second</a></li>\n\
+             </ol>"
+        ));
+
+        let chapter = read(&mut archive, "OEBPS/chapter-2.xhtml");
+        assert!(chapter.contains("<h1>Second</h1>\n<img src=\"images/image-1.png\""));
+        assert!(archive.by_name("OEBPS/images/image-1.png").is_ok());
+        assert!(archive.by_name("OEBPS/fonts/font-1.ttf").is_ok());
+    }
+
+    /// Read a text file from the package.
+    fn read(archive: &mut ZipArchive<Cursor<Vec<u8>>>, path: &str) -> String {
+        let mut text = String::new();
+        archive.by_name(path).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+}
@@ -0,0 +1,530 @@
+//! Exporting of Typst documents into HTML and EPUB.
+
+mod epub;
+
+pub use self::epub::epub;
+
+use std::fmt::Write;
+use std::num::NonZeroUsize;
+
+use base64::Engine;
+use ecow::{eco_format, EcoString};
+use typst::layout::{Frame, Point, Size};
+use typst::model::{
+    Destination, Document, LeafKind, StructChild, StructKind, StructLeaf, StructNode,
+};
+use typst::text::{Font, FontStyle, FontWeight, Lang, TextItem};
+use typst::visualize::{Image, ImageFormat, RasterFormat, VectorFormat};
+
+/// Export a document into an HTML file.
+///
+/// The HTML is reflowable: Headings, paragraphs, lists, tables, links, and
+/// images are [recovered](typst::model::structure) from the laid-out pages,
+/// but the page layout itself and other graphics are not preserved.
+/// Paragraphs are detected from the spacing between lines and may thus be
+/// split or merged differently than in the source.
+#[tracing::instrument(skip_all)]
+pub fn html(document: &Document) -> String {
+    let mut exporter = Exporter::default();
+    for page in &document.pages {
+        exporter.page(page);
+    }
+    exporter.finish(document)
+}
+
+/// Collects the blocks of all pages.
+#[derive(Default)]
+struct Exporter {
+    /// The blocks of all pages so far.
+    blocks: Vec<Block>,
+}
+
+/// A run of text at a position on the page.
+struct Run {
+    /// The start of the run's baseline.
+    pos: Point,
+    /// The run's plain text.
+    text: EcoString,
+    /// How the text is presented.
+    style: Style,
+    /// The natural language of the text.
+    lang: Lang,
+    /// The run's font.
+    font: Font,
+}
+
+/// How a run of text is presented.
+#[derive(Clone, PartialEq)]
+struct Style {
+    strong: bool,
+    emph: bool,
+    link: Option<EcoString>,
+}
+
+/// A block-level element of the HTML.
+enum Block {
+    Heading(NonZeroUsize, Vec<Run>),
+    Par(Vec<Run>),
+    Image(Image, Size),
+    List(Vec<ListItem>),
+    Table(Vec<Vec<Cell>>),
+}
+
+/// An item of a list.
+struct ListItem {
+    /// The item's marker or term.
+    label: Vec<Block>,
+    /// The item's body or description.
+    body: Vec<Block>,
+}
+
+/// A cell of a table row.
+struct Cell {
+    /// Whether the cell labels the column below it.
+    header: bool,
+    /// The cell's content.
+    body: Vec<Block>,
+}
+
+impl Exporter {
+    /// Extract the blocks of a page.
+    fn page(&mut self, frame: &Frame) {
+        for node in typst::model::structure(frame) {
+            convert(&node, &mut self.blocks);
+        }
+    }
+
+    /// Write the HTML file.
+    fn finish(self, document: &Document) -> String {
+        let first = first_run(&self.blocks);
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n");
+        let lang = first.map_or("en", |run| run.lang.as_str());
+        writeln!(html, "<html lang=\"{}\">", escape(lang)).unwrap();
+        html.push_str("<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+        );
+
+        let title = document.title.as_deref().unwrap_or_default();
+        writeln!(html, "<title>{}</title>", escape(title)).unwrap();
+        if !document.author.is_empty() {
+            let author = document.author.join(", ");
+            writeln!(html, "<meta name=\"author\" content=\"{}\">", escape(&author))
+                .unwrap();
+        }
+        if !document.keywords.is_empty() {
+            let keywords = document.keywords.join(", ");
+            writeln!(html, "<meta name=\"keywords\" content=\"{}\">", escape(&keywords))
+                .unwrap();
+        }
+
+        html.push_str("<style>\n");
+        write_style(&mut html, first);
+        html.push_str("</style>\n</head>\n<body>\n");
+        write_blocks(&mut html, &self.blocks, &mut image_url);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Convert a unit of a page into blocks.
+fn convert(node: &StructNode, blocks: &mut Vec<Block>) {
+    match node.kind {
+        StructKind::Heading(level) => blocks.push(Block::Heading(level, runs(node))),
+        StructKind::Par => blocks.push(Block::Par(runs(node))),
+        StructKind::List => {
+            let items = nodes(node).map(|item| {
+                let mut label = vec![];
+                let mut body = vec![];
+                for part in nodes(item) {
+                    let blocks = match part.kind {
+                        StructKind::ListLabel => &mut label,
+                        _ => &mut body,
+                    };
+                    nodes(part).for_each(|node| convert(node, blocks));
+                }
+                ListItem { label, body }
+            });
+            blocks.push(Block::List(items.collect()));
+        }
+        StructKind::Table => {
+            let rows = nodes(node).map(|row| {
+                nodes(row)
+                    .map(|cell| {
+                        let mut body = vec![];
+                        nodes(cell).for_each(|node| convert(node, &mut body));
+                        let header = matches!(
+                            cell.kind,
+                            StructKind::TableCell { header: true, .. }
+                        );
+                        Cell { header, body }
+                    })
+                    .collect()
+            });
+            blocks.push(Block::Table(rows.collect()));
+        }
+        _ => {
+            for child in &node.children {
+                match child {
+                    StructChild::Node(child) => convert(child, blocks),
+                    StructChild::Leaf(StructLeaf {
+                        kind: LeafKind::Image(image, size),
+                        ..
+                    }) => blocks.push(Block::Image((*image).clone(), *size)),
+                    StructChild::Leaf(_) => {}
+                }
+            }
+        }
+    }
+}
+
+/// The nested units of a unit.
+fn nodes<'a, 'b>(node: &'b StructNode<'a>) -> impl Iterator<Item = &'b StructNode<'a>> {
+    node.children.iter().filter_map(|child| match child {
+        StructChild::Node(node) => Some(node),
+        StructChild::Leaf(_) => None,
+    })
+}
+
+/// The runs of text that belong directly to a unit.
+fn runs(node: &StructNode) -> Vec<Run> {
+    let heading = matches!(node.kind, StructKind::Heading(_));
+    node.children
+        .iter()
+        .filter_map(|child| match child {
+            StructChild::Leaf(leaf @ StructLeaf { kind: LeafKind::Text(text), .. }) => {
+                let mut style = style_of(text);
+                style.link = match leaf.link {
+                    Some(Destination::Url(url)) => Some(url.clone()),
+                    _ => None,
+                };
+
+                // Headings are typically bold, which isn't worth a mention.
+                if heading {
+                    style.strong = false;
+                }
+
+                Some(Run {
+                    pos: leaf.pos,
+                    text: text.text.clone(),
+                    style,
+                    lang: text.lang,
+                    font: text.font.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Determine how a run of text is presented based on its font.
+fn style_of(text: &TextItem) -> Style {
+    let variant = text.font.info().variant;
+    Style {
+        strong: variant.weight >= FontWeight::SEMIBOLD,
+        emph: variant.style != FontStyle::Normal,
+        link: None,
+    }
+}
+
+/// The first run of text in the blocks, whose language and font apply to the
+/// whole document.
+fn first_run(blocks: &[Block]) -> Option<&Run> {
+    let mut runs = vec![];
+    collect_runs(blocks, &mut runs);
+    runs.first().copied()
+}
+
+/// Collect the runs of text in the blocks, including those in lists.
+fn collect_runs<'a>(blocks: &'a [Block], runs: &mut Vec<&'a Run>) {
+    for block in blocks {
+        match block {
+            Block::Heading(_, inner) | Block::Par(inner) => runs.extend(inner),
+            Block::Image(..) => {}
+            Block::List(items) => {
+                for item in items {
+                    collect_runs(&item.label, runs);
+                    collect_runs(&item.body, runs);
+                }
+            }
+            Block::Table(rows) => {
+                for cell in rows.iter().flatten() {
+                    collect_runs(&cell.body, runs);
+                }
+            }
+        }
+    }
+}
+
+/// Write the style rules for the body and images.
+fn write_style(css: &mut String, first: Option<&Run>) {
+    let family = first.map_or(EcoString::new(), |run| {
+        eco_format!("\"{}\", ", css_string(&run.font.info().family))
+    });
+    writeln!(
+        css,
+        "body {{ max-width: 40em; margin: 2em auto; padding: 0 1em; \
+         line-height: 1.5; font-family: {family}serif; }}",
+    )
+    .unwrap();
+    css.push_str("img { display: block; max-width: 100%; height: auto; }\n");
+    css.push_str("ul { list-style: none; padding-left: 1em; }\n");
+    css.push_str("li { display: flex; gap: 0.5em; }\n");
+    css.push_str("li > div > :first-child { margin-top: 0; }\n");
+    css.push_str("table { border-collapse: collapse; }\n");
+    css.push_str("th, td { padding: 0.25em 0.5em; vertical-align: top; }\n");
+    css.push_str("th > :first-child, td > :first-child { margin-top: 0; }\n");
+}
+
+/// Write block-level elements, using `src` to determine the source of each
+/// image.
+fn write_blocks(
+    html: &mut String,
+    blocks: &[Block],
+    src: &mut dyn FnMut(&Image) -> EcoString,
+) {
+    for block in blocks {
+        match block {
+            Block::Heading(level, runs) => {
+                let level = level.get().min(6);
+                write!(html, "<h{level}>").unwrap();
+                write_runs(html, runs);
+                writeln!(html, "</h{level}>").unwrap();
+            }
+            Block::Par(runs) => {
+                html.push_str("<p>");
+                write_runs(html, runs);
+                html.push_str("</p>\n");
+            }
+            Block::Image(image, size) => {
+                write!(html, "<img src=\"{}\"", escape(&src(image))).unwrap();
+                let alt = image.alt().unwrap_or_default();
+                write!(html, " alt=\"{}\"", escape(alt)).unwrap();
+                writeln!(html, " style=\"width: {}pt\" />", size.x.to_pt()).unwrap();
+            }
+            Block::List(items) => {
+                html.push_str("<ul>\n");
+                for item in items {
+                    html.push_str("<li><div>\n");
+                    write_blocks(html, &item.label, src);
+                    html.push_str("</div><div>\n");
+                    write_blocks(html, &item.body, src);
+                    html.push_str("</div></li>\n");
+                }
+                html.push_str("</ul>\n");
+            }
+            Block::Table(rows) => {
+                html.push_str("<table>\n");
+                for row in rows {
+                    html.push_str("<tr>\n");
+                    for cell in row {
+                        html.push_str(if cell.header {
+                            "<th scope=\"col\">\n"
+                        } else {
+                            "<td>\n"
+                        });
+                        write_blocks(html, &cell.body, src);
+                        html.push_str(if cell.header { "</th>\n" } else { "</td>\n" });
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+        }
+    }
+}
+
+/// Write runs of text, merging adjacent runs with the same style and
+/// separating lines with spaces.
+fn write_runs(html: &mut String, runs: &[Run]) {
+    let mut prev: Option<&Run> = None;
+    for run in runs {
+        let style = &run.style;
+        let prev_style = prev.map(|prev| &prev.style);
+        if prev_style != Some(style) {
+            if let Some(prev_style) = prev_style {
+                close(html, prev_style);
+            }
+        }
+
+        if let Some(prev) = prev {
+            let new_line = !prev.pos.y.approx_eq(run.pos.y);
+            if new_line
+                && !prev.text.ends_with(char::is_whitespace)
+                && !run.text.starts_with(char::is_whitespace)
+            {
+                html.push(' ');
+            }
+        }
+
+        if prev_style != Some(style) {
+            open(html, style);
+        }
+
+        html.push_str(&escape(&run.text));
+        prev = Some(run);
+    }
+
+    if let Some(prev) = prev {
+        close(html, &prev.style);
+    }
+}
+
+/// Open the tags for a style.
+fn open(html: &mut String, style: &Style) {
+    if let Some(url) = &style.link {
+        write!(html, "<a href=\"{}\">", escape(url)).unwrap();
+    }
+    if style.strong {
+        html.push_str("<strong>");
+    }
+    if style.emph {
+        html.push_str("<em>");
+    }
+}
+
+/// Close the tags for a style in reverse order.
+fn close(html: &mut String, style: &Style) {
+    if style.emph {
+        html.push_str("</em>");
+    }
+    if style.strong {
+        html.push_str("</strong>");
+    }
+    if style.link.is_some() {
+        html.push_str("</a>");
+    }
+}
+
+/// Encode an image as a data URL.
+fn image_url(image: &Image) -> EcoString {
+    let mut url = eco_format!("data:{};base64,", media_type(image));
+    let data = base64::engine::general_purpose::STANDARD.encode(image.data());
+    url.push_str(&data);
+    url
+}
+
+/// The media type of an image.
+fn media_type(image: &Image) -> &'static str {
+    match image.format() {
+        ImageFormat::Raster(f) => match f {
+            RasterFormat::Png => "image/png",
+            RasterFormat::Jpg => "image/jpeg",
+            RasterFormat::Gif => "image/gif",
+        },
+        ImageFormat::Vector(f) => match f {
+            VectorFormat::Svg => "image/svg+xml",
+        },
+    }
+}
+
+/// Prepare text for use in a quoted CSS string.
+///
+/// Style sheets are not subject to HTML escaping, so we drop all characters
+/// that could end the string or a style element.
+fn css_string(text: &str) -> EcoString {
+    text.chars().filter(|c| !matches!(c, '"' | '\\' | '<')).collect()
+}
+
+/// Escape text for use in HTML content and attribute values.
+fn escape(text: &str) -> EcoString {
+    let mut escaped = EcoString::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::foundations::{Bytes, NativeElement};
+    use typst::introspection::{Location, Meta};
+    use typst::layout::{Abs, FrameItem};
+    use typst::model::HeadingElem;
+    use typst::syntax::Span;
+    use typst::text::TextElem;
+    use typst::visualize::Color;
+
+    use super::*;
+
+    pub(crate) const REGULAR: &[u8] =
+        include_bytes!("../../../assets/fonts/LinLibertine_R.ttf");
+    const BOLD: &[u8] = include_bytes!("../../../assets/fonts/LinLibertine_RB.ttf");
+    const GRAPH: &[u8] = include_bytes!("../../../assets/files/graph.png");
+
+    #[test]
+    fn test_html_headings_and_paragraphs() {
+        let mut frame = Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)));
+        heading(&mut frame, 0.0, "Introduction");
+        text(&mut frame, 40.0, REGULAR, "Hello");
+        text(&mut frame, 52.0, BOLD, "bold");
+        text(&mut frame, 64.0, REGULAR, "world & more.");
+        text(&mut frame, 100.0, REGULAR, "Next paragraph.");
+
+        let html = html(&document(vec![frame]));
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+        assert!(html.contains(
+            "<h1>Introduction</h1>\n\
+             <p>Hello <strong>bold</strong> world &amp; more.</p>\n\
+             <p>Next paragraph.</p>\n"
+        ));
+    }
+
+    #[test]
+    fn test_html_images() {
+        let mut frame = Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)));
+        let image = image(Some("A graph"));
+        let size = Size::new(Abs::pt(50.0), Abs::pt(30.0));
+        frame.push(Point::zero(), FrameItem::Image(image, size, Span::detached()));
+
+        let html = html(&document(vec![frame]));
+        assert!(html.contains("<img src=\"data:image/png;base64,"));
+        assert!(html.contains(" alt=\"A graph\" style=\"width: 50pt\" />\n"));
+    }
+
+    /// Create a document with the given pages.
+    pub(crate) fn document(pages: Vec<Frame>) -> Document {
+        Document { pages, ..Default::default() }
+    }
+
+    /// Add a level-1 heading with the given text at a vertical position.
+    pub(crate) fn heading(frame: &mut Frame, y: f64, body: &str) {
+        let mut elem = HeadingElem::new(TextElem::packed(body)).pack();
+        elem.set_location(Location {
+            hash: typst::util::hash128(&(body, y.to_bits())),
+            disambiguator: 0,
+            variant: 0,
+        });
+        let size = Size::new(frame.width(), Abs::pt(20.0));
+        frame.push(Point::with_y(Abs::pt(y)), FrameItem::Meta(Meta::Elem(elem), size));
+        text(frame, y + 15.0, BOLD, body);
+    }
+
+    /// Add a line of 10pt text with its baseline at a vertical position.
+    pub(crate) fn text(frame: &mut Frame, y: f64, font: &'static [u8], text: &str) {
+        let item = TextItem {
+            font: Font::new(Bytes::from_static(font), 0).unwrap(),
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            glyphs: vec![],
+        };
+        frame.push(Point::with_y(Abs::pt(y)), FrameItem::Text(item));
+    }
+
+    /// Create a PNG image.
+    pub(crate) fn image(alt: Option<&str>) -> Image {
+        let format = ImageFormat::Raster(RasterFormat::Png);
+        Image::new(Bytes::from_static(GRAPH), format, alt.map(Into::into)).unwrap()
+    }
+}
@@ -1,7 +1,8 @@
+use ecow::{eco_format, EcoString};
 use once_cell::sync::Lazy;
-use pdf_writer::types::DeviceNSubtype;
+use pdf_writer::types::{ColorSpaceOperand, DeviceNSubtype};
 use pdf_writer::{writers, Chunk, Dict, Filter, Name, Ref};
-use typst::visualize::{Color, ColorSpace, Paint};
+use typst::visualize::{Cmyk, Color, ColorSpace, Paint, Spot};
 
 use crate::deflate;
 use crate::page::{PageContext, Transforms};
@@ -48,6 +49,7 @@ pub struct ColorSpaces {
     hsv: Option<Ref>,
     hsl: Option<Ref>,
     use_linear_rgb: bool,
+    spots: Vec<(Spot, Ref)>,
 }
 
 impl ColorSpaces {
@@ -93,6 +95,21 @@ impl ColorSpaces {
         self.use_linear_rgb = true;
     }
 
+    /// Get the resource name of the separation color space for a spot color.
+    ///
+    /// Spot colors are identified by their name. If the same name is used
+    /// with different alternates, the first one wins.
+    pub fn spot(&mut self, spot: Spot, alloc: &mut Ref) -> EcoString {
+        let index = match self.spots.iter().position(|(s, _)| s.name == spot.name) {
+            Some(index) => index,
+            None => {
+                self.spots.push((spot, alloc.bump()));
+                self.spots.len() - 1
+            }
+        };
+        eco_format!("Sp{index}")
+    }
+
     /// Write the color space on usage.
     pub fn write(
         &mut self,
@@ -162,6 +179,16 @@ impl ColorSpaces {
         if self.use_linear_rgb {
             self.write(ColorSpace::LinearRgb, spaces.insert(LINEAR_SRGB).start(), alloc);
         }
+
+        for (i, (spot, tint)) in self.spots.iter().enumerate() {
+            let name = eco_format!("Sp{i}");
+            let mut separation = spaces
+                .insert(Name(name.as_bytes()))
+                .start::<writers::ColorSpace>()
+                .separation(Name(spot.name.resolve().as_bytes()));
+            separation.alternate_color_space().device_cmyk();
+            separation.tint_ref(*tint);
+        }
     }
 
     /// Write the necessary color spaces functions and ICC profiles to the
@@ -194,6 +221,17 @@ impl ColorSpaces {
                 .filter(Filter::FlateDecode);
         }
 
+        // Write the tint transforms of the spot colors.
+        for (spot, tint) in &self.spots {
+            chunk
+                .exponential_function(*tint)
+                .domain([0.0, 1.0])
+                .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+                .c0([0.0; 4])
+                .c1(spot.alternate)
+                .n(1.0);
+        }
+
         // Write the sRGB color space.
         if let Some(srgb) = self.srgb {
             chunk
@@ -339,6 +377,13 @@ impl PaintEncode for Color {
                 let [r, g, b, _] = ColorSpace::Srgb.encode(*self);
                 ctx.content.set_fill_color([r, g, b]);
             }
+            Color::Cmyk(Cmyk { spot: Some(spot), .. }) => {
+                let name = ctx.parent.colors.spot(*spot, &mut ctx.parent.alloc);
+                ctx.reset_fill_color_space();
+                let space = ColorSpaceOperand::Named(Name(name.as_bytes()));
+                ctx.content.set_fill_color_space(space);
+                ctx.content.set_fill_color([spot.tint]);
+            }
             Color::Cmyk(_) => {
                 ctx.reset_fill_color_space();
 
@@ -393,6 +438,13 @@ impl PaintEncode for Color {
                 let [r, g, b, _] = ColorSpace::Srgb.encode(*self);
                 ctx.content.set_stroke_color([r, g, b]);
             }
+            Color::Cmyk(Cmyk { spot: Some(spot), .. }) => {
+                let name = ctx.parent.colors.spot(*spot, &mut ctx.parent.alloc);
+                ctx.reset_stroke_color_space();
+                let space = ColorSpaceOperand::Named(Name(name.as_bytes()));
+                ctx.content.set_stroke_color_space(space);
+                ctx.content.set_stroke_color([spot.tint]);
+            }
             Color::Cmyk(_) => {
                 ctx.reset_stroke_color_space();
 
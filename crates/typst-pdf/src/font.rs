@@ -4,7 +4,8 @@ use std::sync::Arc;
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
 use pdf_writer::{Filter, Finish, Name, Rect, Str};
-use ttf_parser::{name_id, GlyphId, Tag};
+use ttf_parser::{name_id, GlyphId, Permissions, Tag};
+use typst::diag::{bail, StrResult};
 use typst::text::Font;
 use typst::util::SliceExt;
 use unicode_properties::{GeneralCategory, UnicodeGeneralCategory};
@@ -163,6 +164,23 @@ pub(crate) fn write_fonts(ctx: &mut PdfContext) {
     }
 }
 
+/// Check that all used fonts may be embedded into a PDF/A file.
+pub(crate) fn check_embedding(ctx: &PdfContext) -> StrResult<()> {
+    for font in ctx.font_map.items() {
+        let ttf = font.ttf();
+        let family = &font.info().family;
+        if ttf.permissions() == Some(Permissions::Restricted) {
+            bail!("font {family} does not permit embedding, which PDF/A requires");
+        }
+
+        if ttf.raw_face().table(CFF2).is_some() {
+            bail!("font {family} has CFF2 outlines, which PDF/A does not support");
+        }
+    }
+
+    Ok(())
+}
+
 /// Subset a font to the given glyphs.
 ///
 /// - For a font with TrueType outlines, this returns the whole OpenType font.
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 use std::io::Cursor;
+use std::sync::Arc;
 
+use ecow::eco_format;
 use image::{DynamicImage, GenericImageView, Rgba};
 use pdf_writer::{Chunk, Filter, Finish, Ref};
+use resvg::usvg::NodeExt;
+use resvg::{tiny_skia, usvg};
+use typst::diag::StrResult;
 use typst::util::Deferred;
 use typst::visualize::{
     ColorSpace, Image, ImageKind, RasterFormat, RasterImage, SvgImage,
@@ -14,7 +19,7 @@ use crate::{deflate, PdfContext};
 ///
 /// Also starts the deferred encoding of the image.
 #[comemo::memoize]
-pub fn deferred_image(image: Image) -> Deferred<EncodedImage> {
+pub fn deferred_image(image: Image) -> Deferred<StrResult<EncodedImage>> {
     Deferred::new(move || match image.kind() {
         ImageKind::Raster(raster) => {
             let raster = raster.clone();
@@ -25,18 +30,36 @@ pub fn deferred_image(image: Image) -> Deferred<EncodedImage> {
             let alpha =
                 raster.dynamic().color().has_alpha().then(|| encode_alpha(&raster));
 
-            EncodedImage::Raster { data, filter, has_color, width, height, icc, alpha }
+            Ok(EncodedImage::Raster {
+                data,
+                filter,
+                has_color,
+                width,
+                height,
+                icc,
+                alpha,
+            })
         }
-        ImageKind::Svg(svg) => EncodedImage::Svg(encode_svg(svg)),
+        ImageKind::Svg(svg) => encode_svg(svg).map(EncodedImage::Svg),
     })
 }
 
+/// The resolution multiplier for parts of SVGs that need to be rasterized.
+///
+/// SVG pixels are at 96 DPI, so this yields 384 DPI.
+const SVG_RASTER_SCALE: f32 = 4.0;
+
+/// The maximum number of pixels of a rasterized part of an SVG.
+///
+/// Large SVGs are rasterized at a lower resolution to stay within this limit.
+const SVG_RASTER_MAX_PIXELS: f32 = 4096.0 * 4096.0;
+
 /// Embed all used images into the PDF.
 #[tracing::instrument(skip_all)]
-pub(crate) fn write_images(ctx: &mut PdfContext) {
+pub(crate) fn write_images(ctx: &mut PdfContext) -> StrResult<()> {
     for (i, _) in ctx.image_map.items().enumerate() {
         let handle = ctx.image_deferred_map.get(&i).unwrap();
-        match handle.wait() {
+        match handle.wait().as_ref().map_err(Clone::clone)? {
             EncodedImage::Raster {
                 data,
                 filter,
@@ -105,6 +128,8 @@ pub(crate) fn write_images(ctx: &mut PdfContext) {
             }
         }
     }
+
+    Ok(())
 }
 
 /// Encode an image with a suitable filter and return the data, filter and
@@ -168,23 +193,115 @@ fn encode_alpha(raster: &RasterImage) -> (Vec<u8>, Filter) {
 ///
 /// The main XObject will have ID 1.
 #[tracing::instrument(skip_all)]
-fn encode_svg(svg: &SvgImage) -> Chunk {
+fn encode_svg(svg: &SvgImage) -> StrResult<Chunk> {
     let mut chunk = Chunk::new();
+    let mut result = Ok(());
 
     // Safety: We do not keep any references to tree nodes beyond the
     // scope of `with`.
     unsafe {
         svg.with(|tree| {
-            svg2pdf::convert_tree_into(
-                tree,
-                svg2pdf::Options::default(),
-                &mut chunk,
-                Ref::new(1),
-            );
+            let options = svg2pdf::Options::default();
+            if !svg.has_filters() {
+                svg2pdf::convert_tree_into(tree, options, &mut chunk, Ref::new(1));
+                return;
+            }
+
+            // Filters have no equivalent in PDF, so we rasterize the groups
+            // that use them and keep the rest as vector graphics.
+            let copy = usvg::Tree {
+                size: tree.size,
+                view_box: tree.view_box,
+                root: tree.root.make_deep_copy(),
+            };
+            result = rasterize_filters(&copy, &copy.root);
+            if result.is_ok() {
+                svg2pdf::convert_tree_into(&copy, options, &mut chunk, Ref::new(1));
+            }
         });
     }
 
-    chunk
+    result.map(|()| chunk)
+}
+
+/// Replace the groups with filters among the node's descendants with raster
+/// images of themselves.
+fn rasterize_filters(tree: &usvg::Tree, node: &usvg::Node) -> StrResult<()> {
+    for child in node.children().collect::<Vec<_>>() {
+        let filtered = matches!(
+            &*child.borrow(),
+            usvg::NodeKind::Group(group) if !group.filters.is_empty()
+        );
+        if !filtered {
+            rasterize_filters(tree, &child)?;
+            continue;
+        }
+
+        // The image covers the whole SVG, so it must undo the transforms that
+        // apply to the group's parent.
+        let view_box = tree.view_box;
+        let parent =
+            usvg::utils::view_box_to_transform(view_box.rect, view_box.aspect, tree.size)
+                .pre_concat(node.abs_transform());
+        if let Some(transform) = parent.invert() {
+            let image = usvg::Image {
+                id: String::new(),
+                transform,
+                visibility: usvg::Visibility::Visible,
+                view_box: usvg::ViewBox {
+                    rect: usvg::NonZeroRect::from_xywh(
+                        0.0,
+                        0.0,
+                        tree.size.width(),
+                        tree.size.height(),
+                    )
+                    .unwrap(),
+                    aspect: usvg::AspectRatio {
+                        align: usvg::Align::None,
+                        ..Default::default()
+                    },
+                },
+                rendering_mode: usvg::ImageRendering::OptimizeQuality,
+                kind: usvg::ImageKind::PNG(Arc::new(rasterize_group(tree, &child)?)),
+            };
+            child.insert_before(usvg::Node::new(usvg::NodeKind::Image(image)));
+        }
+
+        child.detach();
+    }
+
+    Ok(())
+}
+
+/// Rasterize a group of an SVG into a PNG that covers the whole SVG.
+#[tracing::instrument(skip_all)]
+fn rasterize_group(tree: &usvg::Tree, group: &usvg::Node) -> StrResult<Vec<u8>> {
+    let size = tree.size;
+    let scale = SVG_RASTER_SCALE
+        .min((SVG_RASTER_MAX_PIXELS / (size.width() * size.height())).sqrt());
+    let width = ((size.width() * scale).ceil() as u32).max(1);
+    let height = ((size.height() * scale).ceil() as u32).max(1);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or("failed to rasterize SVG with filters")?;
+
+    // Render the group on its own, with all transforms that apply to it.
+    let copy = group.make_deep_copy();
+    if let usvg::NodeKind::Group(inner) = &mut *copy.borrow_mut() {
+        inner.transform = group.abs_transform();
+    }
+    let root = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+    root.append(copy);
+
+    let single = usvg::Tree { size, view_box: tree.view_box, root };
+    let ts = tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height(),
+    );
+    resvg::Tree::from_usvg(&single).render(ts, &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|err| eco_format!("failed to encode rasterized SVG ({err})"))
 }
 
 /// A pre-encoded image.
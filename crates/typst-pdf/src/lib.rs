@@ -8,6 +8,7 @@ mod image;
 mod outline;
 mod page;
 mod pattern;
+mod tags;
 
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap};
@@ -18,6 +19,8 @@ use base64::Engine;
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::Direction;
 use pdf_writer::{Finish, Name, Pdf, Ref, TextStr};
+use typst::diag::{SourceDiagnostic, StrResult};
+use typst::eval::Tracer;
 use typst::foundations::Datetime;
 use typst::layout::{Abs, Dir, Em, Transform};
 use typst::model::Document;
@@ -32,6 +35,7 @@ use crate::gradient::PdfGradient;
 use crate::image::EncodedImage;
 use crate::page::Page;
 use crate::pattern::PdfPattern;
+use crate::tags::Tags;
 
 /// Export a document into a PDF file.
 ///
@@ -47,22 +51,68 @@ use crate::pattern::PdfPattern;
 /// The `timestamp`, if given, is expected to be the creation date of the
 /// document as a UTC datetime. It will only be used if `set document(date: ..)`
 /// is `auto`.
+///
+/// The `options` control how the PDF is written. Fails if the document cannot
+/// be represented within the requested [standard](PdfOptions::standard).
+///
+/// Warnings about content that cannot be represented faithfully, like SVG
+/// filters, are reported to the `tracer`.
 #[tracing::instrument(skip_all)]
 pub fn pdf(
     document: &Document,
     ident: Option<&str>,
     timestamp: Option<Datetime>,
-) -> Vec<u8> {
+    options: PdfOptions,
+    tracer: &mut Tracer,
+) -> StrResult<Vec<u8>> {
     let mut ctx = PdfContext::new(document);
+    ctx.tags = options.tagged.then(Tags::default);
+    ctx.standard = options.standard;
     page::construct_pages(&mut ctx, &document.pages);
+    for warning in std::mem::take(&mut ctx.warnings) {
+        tracer.warn(warning);
+    }
+    if ctx.standard.is_some() {
+        font::check_embedding(&ctx)?;
+        // The sRGB profile doubles as the output intent.
+        ctx.colors.srgb(&mut ctx.alloc);
+    }
     font::write_fonts(&mut ctx);
-    image::write_images(&mut ctx);
+    image::write_images(&mut ctx)?;
     gradient::write_gradients(&mut ctx);
     extg::write_external_graphics_states(&mut ctx);
     pattern::write_patterns(&mut ctx);
     page::write_page_tree(&mut ctx);
     write_catalog(&mut ctx, ident, timestamp);
-    ctx.pdf.finish()
+    Ok(ctx.pdf.finish())
+}
+
+/// Options for exporting a PDF.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfOptions {
+    /// Whether to tag the PDF with its logical structure for use with
+    /// assistive technology.
+    ///
+    /// Headings, paragraphs, figures, lists, and tables are recovered from the
+    /// laid-out pages, while decorative graphics are marked as artifacts.
+    /// Header cells of tables label the columns below them.
+    pub tagged: bool,
+    /// The standard the PDF shall conform to, if any.
+    ///
+    /// Export fails if the document cannot be represented within the
+    /// standard, e.g. because a font does not permit embedding.
+    pub standard: Option<PdfStandard>,
+}
+
+/// A standard that an exported PDF can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PdfStandard {
+    /// PDF/A-2b, for long-term archival.
+    ///
+    /// Embeds an sRGB output intent and identifies the file as PDF/A in its
+    /// XMP metadata. Vector images are passed through as-is and must thus
+    /// conform on their own.
+    A2b,
 }
 
 /// Context for exporting a whole PDF document.
@@ -108,13 +158,19 @@ struct PdfContext<'a> {
     /// Deduplicates images used across the document.
     image_map: Remapper<Image>,
     /// Handles to deferred image conversions.
-    image_deferred_map: HashMap<usize, Deferred<EncodedImage>>,
+    image_deferred_map: HashMap<usize, Deferred<StrResult<EncodedImage>>>,
     /// Deduplicates gradients used across the document.
     gradient_map: Remapper<PdfGradient>,
     /// Deduplicates patterns used across the document.
     pattern_map: Remapper<PdfPattern>,
     /// Deduplicates external graphics states used across the document.
     extg_map: Remapper<ExtGState>,
+    /// The logical structure of the document, if it is tagged.
+    tags: Option<Tags>,
+    /// The standard the document shall conform to.
+    standard: Option<PdfStandard>,
+    /// Warnings that occurred during export.
+    warnings: Vec<SourceDiagnostic>,
 }
 
 impl<'a> PdfContext<'a> {
@@ -142,6 +198,9 @@ impl<'a> PdfContext<'a> {
             gradient_map: Remapper::new(),
             pattern_map: Remapper::new(),
             extg_map: Remapper::new(),
+            tags: None,
+            standard: None,
+            warnings: vec![],
         }
     }
 }
@@ -167,6 +226,9 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     // Write the page labels.
     let page_labels = page::write_page_labels(ctx);
 
+    // Write the structure tree.
+    let struct_tree_root = tags::write_structure(ctx);
+
     // Write the document information.
     let mut info = ctx.pdf.document_info(ctx.alloc.bump());
     let mut xmp = XmpWriter::new();
@@ -246,6 +308,11 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     xmp.rendition_class(RenditionClass::Proof);
     xmp.pdf_version("1.7");
 
+    if ctx.standard == Some(PdfStandard::A2b) {
+        xmp.pdfa_part("2");
+        xmp.pdfa_conformance("B");
+    }
+
     let xmp_buf = xmp.finish(None);
     let meta_ref = ctx.alloc.bump();
     ctx.pdf
@@ -253,10 +320,19 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         .pair(Name(b"Type"), Name(b"Metadata"))
         .pair(Name(b"Subtype"), Name(b"XML"));
 
+    // PDF/A requires an output intent so that colors are reproducible.
+    let output_profile = ctx.standard.map(|_| ctx.colors.srgb(&mut ctx.alloc));
+
     // Write the document catalog.
     let mut catalog = ctx.pdf.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
-    catalog.viewer_preferences().direction(dir);
+    let mut preferences = catalog.viewer_preferences();
+    preferences.direction(dir);
+    if struct_tree_root.is_some() {
+        // Screen readers announce the title instead of the file name.
+        preferences.pair(Name(b"DisplayDocTitle"), true);
+    }
+    preferences.finish();
     catalog.metadata(meta_ref);
 
     // Insert the page labels.
@@ -275,6 +351,22 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     if let Some(lang) = lang {
         catalog.lang(TextStr(lang.as_str()));
     }
+
+    if let Some(root) = struct_tree_root {
+        catalog.pair(Name(b"StructTreeRoot"), root);
+        catalog.insert(Name(b"MarkInfo")).dict().pair(Name(b"Marked"), true);
+    }
+
+    if let Some(profile) = output_profile {
+        let mut intents = catalog.insert(Name(b"OutputIntents")).array();
+        let mut intent = intents.push().dict();
+        intent.pair(Name(b"Type"), Name(b"OutputIntent"));
+        intent.pair(Name(b"S"), Name(b"GTS_PDFA1"));
+        intent.pair(Name(b"OutputConditionIdentifier"), TextStr("sRGB"));
+        intent.pair(Name(b"RegistryName"), TextStr("http://www.color.org"));
+        intent.pair(Name(b"Info"), TextStr("sRGB IEC61966-2.1"));
+        intent.pair(Name(b"DestOutputProfile"), profile);
+    }
 }
 
 /// Compress data with the DEFLATE algorithm.
@@ -415,3 +507,175 @@ fn transform_to_array(ts: Transform) -> [f32; 6] {
         ts.ty.to_f32(),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use typst::foundations::Bytes;
+    use typst::layout::{Frame, FrameItem, Point, Size};
+    use typst::syntax::Span;
+    use typst::text::{Glyph, TextItem};
+    use typst::visualize::Color;
+
+    use super::*;
+
+    pub(crate) const REGULAR: &[u8] =
+        include_bytes!("../../../assets/fonts/LinLibertine_R.ttf");
+    const CFF: &[u8] = include_bytes!("../../../assets/fonts/NewCM10-Regular.otf");
+    const COLR: &[u8] =
+        include_bytes!("../../../assets/fonts/COLRv1StaticTestGlyphs-Regular.ttf");
+    const SVG: &[u8] = include_bytes!("../../../assets/fonts/TwitterColorEmoji.ttf");
+
+    #[test]
+    fn test_pdf_a2b_metadata_and_output_intent() {
+        let mut frame = page();
+        text(&mut frame, Point::with_y(Abs::pt(20.0)), REGULAR, "Archived");
+
+        let plain = export(&document(vec![frame.clone()]), PdfOptions::default());
+        let plain = plain.unwrap();
+        assert!(!contains(&plain, "pdfaid"));
+        assert!(!contains(&plain, "/OutputIntents"));
+
+        let archival = export(&document(vec![frame]), a2b()).unwrap();
+        assert!(contains(&archival, "<pdfaid:part>2</pdfaid:part>"));
+        assert!(contains(&archival, "<pdfaid:conformance>B</pdfaid:conformance>"));
+        assert!(contains(&archival, "/OutputIntents"));
+        assert!(contains(&archival, "/S /GTS_PDFA1"));
+        assert!(contains(&archival, "/DestOutputProfile"));
+    }
+
+    #[test]
+    fn test_pdf_a2b_rejects_restricted_font() {
+        // Set the font's embedding permissions to "restricted license".
+        let mut data = REGULAR.to_vec();
+        let record = table_record(&data, b"OS/2");
+        let os2 = u32::from_be_bytes(data[record + 8..record + 12].try_into().unwrap());
+        let fs_type = os2 as usize + 8;
+        data[fs_type..fs_type + 2].copy_from_slice(&2u16.to_be_bytes());
+        let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+
+        let mut frame = page();
+        text(&mut frame, Point::with_y(Abs::pt(20.0)), data, "Restricted");
+        let doc = document(vec![frame]);
+        assert!(export(&doc, PdfOptions::default()).is_ok());
+
+        let error = export(&doc, a2b()).unwrap_err();
+        assert_eq!(
+            error,
+            "font Linux Libertine does not permit embedding, which PDF/A requires"
+        );
+    }
+
+    #[test]
+    fn test_pdf_a2b_rejects_cff2_font() {
+        // Relabel the font's CFF outlines as CFF2 outlines.
+        let mut data = CFF.to_vec();
+        let record = table_record(&data, b"CFF ");
+        data[record..record + 4].copy_from_slice(b"CFF2");
+        let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+
+        let mut frame = page();
+        text(&mut frame, Point::with_y(Abs::pt(20.0)), data, "Variable");
+        let error = export(&document(vec![frame]), a2b()).unwrap_err();
+        assert_eq!(
+            error,
+            "font New Computer Modern has CFF2 outlines, which PDF/A does not support"
+        );
+    }
+
+    #[test]
+    fn test_pdf_color_glyphs_are_drawn_as_images() {
+        let mut frame = page();
+        text(&mut frame, Point::with_y(Abs::pt(20.0)), REGULAR, "Plain");
+        let plain = export(&document(vec![frame.clone()]), PdfOptions::default());
+        assert!(!contains(&plain.unwrap(), "/Im0"));
+
+        // A COLR glyph with a linear gradient, one with a rasterized sweep
+        // gradient and an SVG glyph.
+        text(&mut frame, Point::with_y(Abs::pt(40.0)), COLR, "\u{F0101}\u{F0201}");
+        text(&mut frame, Point::with_y(Abs::pt(60.0)), SVG, "🐪");
+        let color = export(&document(vec![frame]), PdfOptions::default()).unwrap();
+        assert!(contains(&color, "/Im0"));
+        assert_eq!(count(&color, "/Subtype /Form"), 3);
+
+        // The rasterized gradient and its alpha mask.
+        assert_eq!(count(&color, "/Subtype /Image"), 2);
+
+        // The fonts are still embedded for the invisible text layer.
+        assert_eq!(count(&color, "/FontFile2"), 3);
+    }
+
+    /// Export a document without an identifier or timestamp.
+    pub(crate) fn export(document: &Document, options: PdfOptions) -> StrResult<Vec<u8>> {
+        pdf(document, None, None, options, &mut Tracer::new())
+    }
+
+    /// Options for PDF/A-2b export.
+    fn a2b() -> PdfOptions {
+        PdfOptions {
+            standard: Some(PdfStandard::A2b),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the PDF contains the given text outside of compressed streams.
+    pub(crate) fn contains(pdf: &[u8], text: &str) -> bool {
+        count(pdf, text) > 0
+    }
+
+    /// How often the PDF contains the given text outside of compressed
+    /// streams.
+    pub(crate) fn count(pdf: &[u8], text: &str) -> usize {
+        pdf.windows(text.len()).filter(|w| *w == text.as_bytes()).count()
+    }
+
+    /// Create a document with the given pages.
+    pub(crate) fn document(pages: Vec<Frame>) -> Document {
+        Document { pages, ..Default::default() }
+    }
+
+    /// Create an empty page.
+    pub(crate) fn page() -> Frame {
+        Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)))
+    }
+
+    /// Add a run of 10pt text with its baseline start at a position.
+    ///
+    /// Each character is mapped to a glyph without any shaping.
+    pub(crate) fn text(frame: &mut Frame, pos: Point, font: &'static [u8], text: &str) {
+        let font = Font::new(Bytes::from_static(font), 0).unwrap();
+        let glyphs = text
+            .char_indices()
+            .filter_map(|(i, c)| {
+                let id = font.ttf().glyph_index(c)?.0;
+                Some(Glyph {
+                    id,
+                    x_advance: font.advance(id).unwrap_or_default(),
+                    x_offset: Em::zero(),
+                    range: i as u16..(i + c.len_utf8()) as u16,
+                    span: (Span::detached(), 0),
+                })
+            })
+            .collect();
+
+        let item = TextItem {
+            font,
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            glyphs,
+        };
+        frame.push(pos, FrameItem::Text(item));
+    }
+
+    /// The offset of a table's record in the table directory of an OpenType
+    /// font.
+    fn table_record(data: &[u8], tag: &[u8; 4]) -> usize {
+        let count = u16::from_be_bytes([data[4], data[5]]) as usize;
+        let index = data[12..12 + 16 * count]
+            .chunks(16)
+            .position(|record| &record[..4] == tag)
+            .unwrap();
+        12 + 16 * index
+    }
+}
@@ -1,49 +1,69 @@
 use std::collections::HashMap;
+use std::io::Read;
 use std::num::NonZeroUsize;
 
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::{
-    ActionType, AnnotationType, ColorSpaceOperand, LineCapStyle, LineJoinStyle,
-    NumberingStyle,
+    ActionType, AnnotationFlags, AnnotationType, ColorSpaceOperand, LineCapStyle,
+    LineJoinStyle, NumberingStyle, TextRenderingMode,
 };
 use pdf_writer::writers::PageLabel;
 use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use resvg::usvg::{self, roxmltree, TreeParsing, TreeWriting};
+use ttf_parser::GlyphId;
+use typst::diag::warning;
 use typst::introspection::Meta;
 use typst::layout::{
-    Abs, Em, Frame, FrameItem, GroupItem, PdfPageLabel, PdfPageLabelStyle, Point, Ratio,
-    Size, Transform,
+    Abs, Em, Frame, FrameItem, GroupItem, PdfPageBoxes, PdfPageLabel, PdfPageLabelStyle,
+    Point, Ratio, Size, Transform,
 };
 use typst::model::Destination;
+use typst::syntax::Span;
 use typst::text::{Font, TextItem};
 use typst::util::Numeric;
 use typst::visualize::{
-    FixedStroke, Geometry, Image, LineCap, LineJoin, Paint, Path, PathItem, Shape,
+    Color, FixedStroke, Geometry, Image, ImageKind, LineCap, LineJoin, Paint, Path,
+    PathItem, RasterFormat, Shape, VectorFormat,
 };
 
 use crate::color::PaintEncode;
 use crate::extg::ExtGState;
 use crate::image::deferred_image;
+use crate::tags::Tags;
 use crate::{deflate_memoized, AbsExt, EmExt, PdfContext};
 
 /// Construct page objects.
 #[tracing::instrument(skip_all)]
 pub(crate) fn construct_pages(ctx: &mut PdfContext, frames: &[Frame]) {
     for frame in frames {
-        let (page_ref, page) = construct_page(ctx, frame);
+        let (page_ref, page) = construct_page(ctx, frame, true);
         ctx.page_refs.push(page_ref);
         ctx.pages.push(page);
     }
 }
 
 /// Construct a page object.
+///
+/// If `tagged` is true and the document is tagged, the page's content is
+/// added to the document's structure tree.
 #[tracing::instrument(skip_all)]
-pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page) {
+pub(crate) fn construct_page(
+    ctx: &mut PdfContext,
+    frame: &Frame,
+    tagged: bool,
+) -> (Ref, Page) {
     let page_ref = ctx.alloc.bump();
+    let index = ctx.pages.len();
+    let tagged = tagged && ctx.tags.is_some();
+    if let Some(tags) = ctx.tags.as_mut().filter(|_| tagged) {
+        tags.start_page(index, frame);
+    }
 
     let mut ctx = PageContext {
         parent: ctx,
         page_ref,
         label: None,
+        boxes: None,
         uses_opacities: false,
         content: Content::new(),
         state: State::new(frame.size()),
@@ -51,6 +71,7 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page)
         bottom: 0.0,
         links: vec![],
         resources: HashMap::default(),
+        tagged,
     };
 
     let size = frame.size();
@@ -76,6 +97,7 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page)
         uses_opacities: ctx.uses_opacities,
         links: ctx.links,
         label: ctx.label,
+        boxes: ctx.boxes,
         resources: ctx.resources,
     };
 
@@ -147,22 +169,41 @@ fn write_page(ctx: &mut PdfContext, i: usize) {
     let page = &ctx.pages[i];
     let content_id = ctx.alloc.bump();
 
+    // PDF/A requires transparency groups to blend in the output intent's
+    // color space.
+    let group_space = ctx.standard.map(|_| ctx.colors.srgb(&mut ctx.alloc));
+
     let mut page_writer = ctx.pdf.page(page.id);
     page_writer.parent(ctx.page_tree_ref);
 
     let w = page.size.x.to_f32();
     let h = page.size.y.to_f32();
     page_writer.media_box(Rect::new(0.0, 0.0, w, h));
+
+    if let Some(boxes) = page.boxes {
+        let inset = |d: Abs| {
+            let d = d.to_f32();
+            Rect::new(d, d, w - d, h - d)
+        };
+        page_writer.bleed_box(inset(boxes.bleed));
+        page_writer.trim_box(inset(boxes.trim));
+    }
+
     page_writer.contents(content_id);
 
+    if ctx.tags.is_some() {
+        page_writer.pair(Name(b"StructParents"), i as i32);
+        page_writer.pair(Name(b"Tabs"), Name(b"S"));
+    }
+
     if page.uses_opacities {
-        page_writer
-            .group()
-            .transparency()
-            .isolated(false)
-            .knockout(false)
-            .color_space()
-            .srgb();
+        let mut group = page_writer.group();
+        let space = group.transparency().isolated(false).knockout(false).color_space();
+        if let Some(profile) = group_space {
+            space.icc_based(profile);
+        } else {
+            space.srgb();
+        }
     }
 
     let mut annotations = page_writer.annotations();
@@ -170,6 +211,10 @@ fn write_page(ctx: &mut PdfContext, i: usize) {
         let mut annotation = annotations.push();
         annotation.subtype(AnnotationType::Link).rect(*rect);
         annotation.border(0.0, 0.0, 0.0, None);
+        if ctx.standard.is_some() {
+            // PDF/A requires annotations to be printed.
+            annotation.flags(AnnotationFlags::PRINT);
+        }
 
         let pos = match dest {
             Destination::Url(uri) => {
@@ -265,6 +310,8 @@ pub struct Page {
     pub links: Vec<(Destination, Rect)>,
     /// The page's PDF label.
     pub label: Option<PdfPageLabel>,
+    /// The page's bleed and trim boxes.
+    pub boxes: Option<PdfPageBoxes>,
     /// The page's used resources
     pub resources: HashMap<PageResource, usize>,
 }
@@ -329,6 +376,7 @@ pub struct PageContext<'a, 'b> {
     pub(crate) parent: &'a mut PdfContext<'b>,
     page_ref: Ref,
     label: Option<PdfPageLabel>,
+    boxes: Option<PdfPageBoxes>,
     pub content: Content,
     state: State,
     saves: Vec<State>,
@@ -337,6 +385,8 @@ pub struct PageContext<'a, 'b> {
     links: Vec<(Destination, Rect)>,
     /// Keep track of the resources being used in the page.
     pub resources: HashMap<PageResource, usize>,
+    /// Whether the page's content is added to the structure tree.
+    tagged: bool,
 }
 
 /// A simulated graphics state used to deduplicate graphics state changes and
@@ -555,25 +605,43 @@ impl PageContext<'_, '_> {
     pub fn reset_stroke_color_space(&mut self) {
         self.state.stroke_space = None;
     }
+
+    /// The structure tree to add the page's content to, if any.
+    fn tags(&mut self) -> Option<&mut Tags> {
+        self.parent.tags.as_mut().filter(|_| self.tagged)
+    }
+
+    /// Start a marked-content sequence for tagged content or for an artifact
+    /// if the content has no tag.
+    fn begin_tagged(&mut self, tag: Option<(Name, i32)>) {
+        let Some((tag, mcid)) = tag else {
+            self.content.begin_marked_content(Name(b"Artifact"));
+            return;
+        };
+        let mut marked = self.content.begin_marked_content_with_properties(tag);
+        marked.properties().pair(Name(b"MCID"), mcid);
+        marked.finish();
+    }
 }
 
 /// Encode a frame into the content stream.
 fn write_frame(ctx: &mut PageContext, frame: &Frame) {
     for &(pos, ref item) in frame.items() {
-        let x = pos.x.to_f32();
-        let y = pos.y.to_f32();
-
         match item {
             FrameItem::Group(group) => write_group(ctx, pos, group),
             FrameItem::Text(text) => write_text(ctx, pos, text),
             FrameItem::Shape(shape, _) => write_shape(ctx, pos, shape),
-            FrameItem::Image(image, size, _) => write_image(ctx, x, y, image, *size),
+            FrameItem::Image(image, size, span) => {
+                write_image(ctx, pos, image, *size, *span)
+            }
             FrameItem::Meta(meta, size) => match meta {
                 Meta::Link(dest) => write_link(ctx, pos, dest, *size),
                 Meta::Elem(_) => {}
                 Meta::Hide => {}
                 Meta::PageNumbering(_) => {}
                 Meta::PdfPageLabel(label) => ctx.label = Some(label.clone()),
+                Meta::PdfPageBoxes(boxes) => ctx.boxes = Some(*boxes),
+                Meta::Role(_) => {}
             },
         }
     }
@@ -623,7 +691,22 @@ fn write_text(ctx: &mut PageContext, pos: Point, text: &TextItem) {
     ctx.set_fill(&text.fill, true, ctx.state.transforms(Size::zero(), pos));
     ctx.set_font(&text.font, text.size);
     ctx.set_opacities(None, Some(&text.fill));
+
+    let tag = ctx.tags().map(Tags::next);
+    if let Some(tag) = tag {
+        ctx.begin_tagged(tag);
+    }
+
+    // Instances of variable fonts and color glyphs can't be embedded as they
+    // are, so such glyphs are drawn directly over invisible, but still
+    // selectable text.
+    let drawn = !text.font.variations().is_empty()
+        || text.glyphs.iter().any(|g| is_color_glyph(&text.font, GlyphId(g.id)));
+
     ctx.content.begin_text();
+    if drawn {
+        ctx.content.set_text_rendering_mode(TextRenderingMode::Invisible);
+    }
 
     // Positiosn the text.
     ctx.content.set_text_matrix([1.0, 0.0, 0.0, -1.0, x, y]);
@@ -664,7 +747,224 @@ fn write_text(ctx: &mut PageContext, pos: Point, text: &TextItem) {
 
     items.finish();
     positioned.finish();
+    if drawn {
+        ctx.content.set_text_rendering_mode(TextRenderingMode::Fill);
+    }
     ctx.content.end_text();
+
+    if drawn {
+        write_glyphs(ctx, pos, text);
+    }
+
+    if tag.is_some() {
+        ctx.content.end_marked_content();
+    }
+}
+
+/// Draw the glyphs of a text run directly instead of through the embedded
+/// font.
+fn write_glyphs(ctx: &mut PageContext, pos: Point, text: &TextItem) {
+    let mut x = Abs::zero();
+    for glyph in &text.glyphs {
+        let id = GlyphId(glyph.id);
+        let at = pos + Point::with_x(x + glyph.x_offset.at(text.size));
+        write_svg_glyph(ctx, at, text, id)
+            .or_else(|| write_bitmap_glyph(ctx, at, text, id))
+            .or_else(|| write_colr_glyph(ctx, at, text, id))
+            .or_else(|| write_outline_glyph(ctx, at, text, id, &text.fill));
+        x += glyph.x_advance.at(text.size);
+    }
+}
+
+/// Draw a glyph defined by an SVG.
+fn write_svg_glyph(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    id: GlyphId,
+) -> Option<()> {
+    let image = convert_svg_glyph(&text.font, id.0)?;
+    let ascender = text.font.metrics().ascender.at(text.size);
+    write_glyph_image(ctx, pos - Point::with_y(ascender), &image, Size::splat(text.size));
+    Some(())
+}
+
+/// Draw a glyph defined by a bitmap.
+fn write_bitmap_glyph(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    id: GlyphId,
+) -> Option<()> {
+    let (image, x, y) = convert_bitmap_glyph(&text.font, id.0)?;
+    let h = text.size;
+    let w = (image.width() as f64 / image.height() as f64) * h;
+    let dx = (x / image.width() as f64) * h;
+    let dy = (y / image.height() as f64) * h;
+    write_glyph_image(ctx, pos + Point::new(dx, -h - dy), &image, Size::new(w, h));
+    Some(())
+}
+
+/// Draw a glyph defined by colored layers.
+fn write_colr_glyph(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    id: GlyphId,
+) -> Option<()> {
+    // Layers that take on the color of the text can't be filled with
+    // gradients or patterns.
+    let foreground = match &text.fill {
+        Paint::Solid(color) => *color,
+        _ => Color::BLACK,
+    };
+
+    let (image, bounds) = typst::text::colr_glyph_image(&text.font, id, foreground)?;
+    let scale = text.size / text.font.units_per_em();
+    let at = pos + Point::new(bounds.x_min as f64 * scale, -bounds.y_max as f64 * scale);
+    let size = Size::new(bounds.width() as f64 * scale, bounds.height() as f64 * scale);
+    write_glyph_image(ctx, at, &image, size);
+    Some(())
+}
+
+/// Draw a glyph defined by an outline with the given paint.
+fn write_outline_glyph(
+    ctx: &mut PageContext,
+    pos: Point,
+    text: &TextItem,
+    id: GlyphId,
+    paint: &Paint,
+) -> Option<()> {
+    ctx.set_fill(paint, false, ctx.state.transforms(Size::zero(), pos));
+    ctx.set_opacities(None, Some(paint));
+
+    let mut builder = OutlineBuilder {
+        content: &mut ctx.content,
+        x: pos.x.to_f32(),
+        y: pos.y.to_f32(),
+        scale: text.size.to_f32() / text.font.units_per_em() as f32,
+        last: (0.0, 0.0),
+    };
+
+    text.font.ttf().outline_glyph(id, &mut builder)?;
+    ctx.content.fill_nonzero();
+    Some(())
+}
+
+/// Whether a glyph has a colored representation that the embedded font can't
+/// express.
+fn is_color_glyph(font: &Font, id: GlyphId) -> bool {
+    let ttf = font.ttf();
+    ttf.is_color_glyph(id)
+        || ttf.glyph_svg_image(id).is_some()
+        || ttf.glyph_raster_image(id, u16::MAX).is_some()
+}
+
+/// Convert an SVG glyph into an image spanning the em square.
+#[comemo::memoize]
+fn convert_svg_glyph(font: &Font, id: u16) -> Option<Image> {
+    let mut data = font.ttf().glyph_svg_image(GlyphId(id))?.data;
+
+    // Decompress SVGZ.
+    let mut decoded = vec![];
+    if data.starts_with(&[0x1f, 0x8b]) {
+        let mut decoder = flate2::read::GzDecoder::new(data);
+        decoder.read_to_end(&mut decoded).ok()?;
+        data = &decoded;
+    }
+
+    let xml = std::str::from_utf8(data).ok()?;
+    let document = roxmltree::Document::parse(xml).ok()?;
+    let root = document.root_element();
+    if root.has_attribute("viewBox") {
+        return Image::new(data.to_vec().into(), VectorFormat::Svg.into(), None).ok();
+    }
+
+    // Glyphs are drawn in font units with the baseline at zero. Without a
+    // view box, show the em square starting at the ascender, unless the
+    // glyph is given a size.
+    let mut tree = usvg::Tree::from_xmltree(&document, &usvg::Options::default()).ok()?;
+    let upem = font.units_per_em() as f32;
+    let ascender = font.metrics().ascender.get() as f32 * upem;
+    let width = if root.has_attribute("width") { tree.size.width() } else { upem };
+    let height = if root.has_attribute("height") { tree.size.height() } else { upem };
+    tree.size = usvg::Size::from_wh(width, height)?;
+    tree.view_box = usvg::ViewBox {
+        rect: usvg::NonZeroRect::from_xywh(0.0, -ascender, width, height)?,
+        aspect: usvg::AspectRatio::default(),
+    };
+
+    let svg = tree.to_string(&usvg::XmlOptions::default());
+    Image::new(svg.into_bytes().into(), VectorFormat::Svg.into(), None).ok()
+}
+
+/// Convert a bitmap glyph into an image and its offset.
+#[comemo::memoize]
+fn convert_bitmap_glyph(font: &Font, id: u16) -> Option<(Image, f64, f64)> {
+    let raster = font.ttf().glyph_raster_image(GlyphId(id), u16::MAX)?;
+    if raster.format != ttf_parser::RasterImageFormat::PNG {
+        return None;
+    }
+    let image = Image::new(raster.data.into(), RasterFormat::Png.into(), None).ok()?;
+    Some((image, raster.x as f64, raster.y as f64))
+}
+
+/// Writes glyph outlines in font units into a content stream.
+struct OutlineBuilder<'a> {
+    content: &'a mut Content,
+    x: f32,
+    y: f32,
+    scale: f32,
+    last: (f32, f32),
+}
+
+impl OutlineBuilder<'_> {
+    /// Convert a point in font units into page coordinates.
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.x + x * self.scale, self.y - y * self.scale)
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.content.move_to(x, y);
+        self.last = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.content.line_to(x, y);
+        self.last = (x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Elevate the quadratic curve to a cubic one.
+        let (x0, y0) = self.last;
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        self.content.cubic_to(
+            x0 + 2.0 / 3.0 * (x1 - x0),
+            y0 + 2.0 / 3.0 * (y1 - y0),
+            x + 2.0 / 3.0 * (x1 - x),
+            y + 2.0 / 3.0 * (y1 - y),
+            x,
+            y,
+        );
+        self.last = (x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        self.content.cubic_to(x1, y1, x2, y2, x, y);
+        self.last = (x, y);
+    }
+
+    fn close(&mut self) {
+        self.content.close_path();
+    }
 }
 
 /// Encode a geometrical shape into the content stream.
@@ -694,6 +994,12 @@ fn write_shape(ctx: &mut PageContext, pos: Point, shape: &Shape) {
 
     ctx.set_opacities(stroke, shape.fill.as_ref());
 
+    // Shapes are decorative, so screen readers should skip them.
+    let artifact = ctx.tags().is_some();
+    if artifact {
+        ctx.content.begin_marked_content(Name(b"Artifact"));
+    }
+
     match shape.geometry {
         Geometry::Line(target) => {
             let dx = target.x.to_f32();
@@ -719,6 +1025,10 @@ fn write_shape(ctx: &mut PageContext, pos: Point, shape: &Shape) {
         (None, Some(_)) => ctx.content.stroke(),
         (Some(_), Some(_)) => ctx.content.fill_nonzero_and_stroke(),
     };
+
+    if artifact {
+        ctx.content.end_marked_content();
+    }
 }
 
 /// Encode a bezier path into the content stream.
@@ -745,20 +1055,34 @@ fn write_path(ctx: &mut PageContext, x: f32, y: f32, path: &Path) {
 }
 
 /// Encode a vector or raster image into the content stream.
-fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size) {
-    let index = ctx.parent.image_map.insert(image.clone());
-    ctx.parent
-        .image_deferred_map
-        .entry(index)
-        .or_insert_with(|| deferred_image(image.clone()));
+fn write_image(ctx: &mut PageContext, pos: Point, image: &Image, size: Size, span: Span) {
+    if let ImageKind::Svg(svg) = image.kind() {
+        if svg.has_filters() {
+            ctx.parent.warnings.push(warning!(
+                span,
+                "SVG filters cannot be exported as vector graphics";
+                hint: "the parts of the image that use filters are rasterized"
+            ));
+        }
+    }
 
-    let name = eco_format!("Im{index}");
+    let name = image_resource(ctx, image);
+    let x = pos.x.to_f32();
+    let y = pos.y.to_f32();
     let w = size.x.to_f32();
     let h = size.y.to_f32();
+
+    let tag = ctx.tags().map(Tags::next);
+
     ctx.content.save_state();
     ctx.content.transform([w, 0.0, 0.0, -h, x, y + h]);
 
-    if let Some(alt) = image.alt() {
+    if let Some(tag) = tag {
+        // The alternative description is part of the structure element.
+        ctx.begin_tagged(tag);
+        ctx.content.x_object(Name(name.as_bytes()));
+        ctx.content.end_marked_content();
+    } else if let Some(alt) = image.alt() {
         let mut image_span =
             ctx.content.begin_marked_content_with_properties(Name(b"Span"));
         let mut image_alt = image_span.properties();
@@ -772,9 +1096,36 @@ fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size)
         ctx.content.x_object(Name(name.as_bytes()));
     }
 
+    ctx.content.restore_state();
+}
+
+/// Encode an image that is part of a glyph into the content stream.
+///
+/// Unlike other images, it is not tagged since it's part of the text.
+fn write_glyph_image(ctx: &mut PageContext, pos: Point, image: &Image, size: Size) {
+    let name = image_resource(ctx, image);
+    let x = pos.x.to_f32();
+    let y = pos.y.to_f32();
+    let w = size.x.to_f32();
+    let h = size.y.to_f32();
+    ctx.content.save_state();
+    ctx.content.transform([w, 0.0, 0.0, -h, x, y + h]);
+    ctx.content.x_object(Name(name.as_bytes()));
+    ctx.content.restore_state();
+}
+
+/// Register an image with the page and return its resource name.
+fn image_resource(ctx: &mut PageContext, image: &Image) -> EcoString {
+    let index = ctx.parent.image_map.insert(image.clone());
+    ctx.parent
+        .image_deferred_map
+        .entry(index)
+        .or_insert_with(|| deferred_image(image.clone()));
+
+    let name = eco_format!("Im{index}");
     ctx.resources
         .insert(PageResource::new(ResourceKind::XObject, name.clone()), index);
-    ctx.content.restore_state();
+    name
 }
 
 /// Save a link for later writing in the annotations dictionary.
@@ -72,9 +72,12 @@ pub(crate) fn write_patterns(ctx: &mut PdfContext) {
         );
 
         resources_map.finish();
+        let offset = pattern.offset();
         tiling_pattern
             .matrix(transform_to_array(
-                transform.post_concat(Transform::scale(Ratio::one(), -Ratio::one())),
+                transform
+                    .pre_concat(Transform::translate(offset.x, offset.y))
+                    .post_concat(Transform::scale(Ratio::one(), -Ratio::one())),
             ))
             .filter(Filter::FlateDecode);
     }
@@ -115,7 +118,7 @@ fn register_pattern(
     };
 
     // Render the body.
-    let (_, content) = construct_page(ctx.parent, pattern.frame());
+    let (_, content) = construct_page(ctx.parent, pattern.frame(), false);
 
     let pdf_pattern = PdfPattern {
         transform,
@@ -0,0 +1,558 @@
+use std::ops::Range;
+
+use ecow::{eco_format, EcoString};
+use pdf_writer::{Finish, Name, Null, Ref, Str, TextStr};
+use typst::introspection::Location;
+use typst::layout::Frame;
+use typst::model::{LeafKind, StructChild, StructKind, StructNode};
+
+use crate::PdfContext;
+
+/// The logical structure of a tagged PDF.
+///
+/// The structure is [recovered](typst::model::structure) from the laid-out
+/// pages. Each text run and image becomes a marked-content sequence whose
+/// identifier is the index of the run or image on its page. Everything else is
+/// marked as an artifact, which screen readers skip.
+///
+/// Elements that continue on a later page, like a table broken across pages,
+/// remain a single structure element. The header rows a table repeats on later
+/// pages are artifacts, too.
+#[derive(Default)]
+pub(crate) struct Tags {
+    /// The top-level structure elements in reading order.
+    elems: Vec<StructElem>,
+    /// The tags of the current page's text runs and images, by index. Those
+    /// without a tag are artifacts.
+    leaves: Vec<Option<Name<'static>>>,
+    /// The index of the next text run or image on the current page.
+    next: usize,
+}
+
+/// A structure element, i.e. a logical unit of the document.
+struct StructElem {
+    /// What kind of unit this is.
+    kind: StructKind,
+    /// The element the unit stems from, if any.
+    location: Option<Location>,
+    /// The index of the page the unit starts on.
+    page: usize,
+    /// An alternative description of the unit.
+    alt: Option<EcoString>,
+    /// The unit's marked content and nested units in reading order.
+    kids: Vec<Kid>,
+}
+
+/// A part of a structure element.
+enum Kid {
+    /// A marked-content sequence with the given identifier on the page with
+    /// the given index.
+    Mcid(usize, i32),
+    /// A nested element.
+    Elem(StructElem),
+}
+
+impl Tags {
+    /// Start tagging the content of the page with the given index.
+    pub fn start_page(&mut self, page: usize, frame: &Frame) {
+        self.leaves.clear();
+        self.next = 0;
+        for node in typst::model::structure(frame) {
+            let elem = self.convert(&node, page);
+
+            // Elements have unique locations, so an element with a location
+            // we have seen on an earlier page continues there.
+            let Some(prev) =
+                self.elems.iter_mut().rev().find(|prev| {
+                    prev.location.is_some() && prev.location == elem.location
+                })
+            else {
+                self.elems.push(elem);
+                continue;
+            };
+
+            for kid in elem.kids {
+                prev.merge(kid, &mut self.leaves);
+            }
+        }
+    }
+
+    /// Tag the next text run or image of the page, returning the tag and
+    /// marked-content identifier to wrap it in or `None` if it is an artifact.
+    ///
+    /// Must be called for text runs and images in the order in which they
+    /// appear in the page's frames.
+    pub fn next(&mut self) -> Option<(Name<'static>, i32)> {
+        let index = self.next;
+        self.next += 1;
+        let tag = self.leaves.get(index).copied().unwrap_or(Some(Name(b"P")))?;
+        Some((tag, index as i32))
+    }
+
+    /// Convert a unit of a page into a structure element, registering the tags
+    /// of its text runs and images.
+    fn convert(&mut self, node: &StructNode, page: usize) -> StructElem {
+        let mut alt = None;
+        let mut kids = vec![];
+        for child in &node.children {
+            match child {
+                StructChild::Node(child) => {
+                    kids.push(Kid::Elem(self.convert(child, page)));
+                }
+                StructChild::Leaf(leaf) => {
+                    if let LeafKind::Image(image, _) = leaf.kind {
+                        alt = alt.or_else(|| image.alt().map(Into::into));
+                    }
+                    if self.leaves.len() <= leaf.index {
+                        self.leaves.resize(leaf.index + 1, Some(Name(b"P")));
+                    }
+                    self.leaves[leaf.index] = Some(tag(node.kind));
+                    kids.push(Kid::Mcid(page, leaf.index as i32));
+                }
+            }
+        }
+        StructElem {
+            kind: node.kind,
+            location: node.location,
+            page,
+            alt,
+            kids,
+        }
+    }
+}
+
+impl StructElem {
+    /// Whether the part of the element on a later page is a header row that
+    /// the element, a table, already contains.
+    fn repeats(&self, kid: &Kid) -> bool {
+        let Kid::Elem(row) = kid else { return false };
+        row.is_header_row()
+            && self
+                .kids
+                .iter()
+                .any(|kid| matches!(kid, Kid::Elem(other) if other.kind == row.kind))
+    }
+
+    /// Whether the element is a table row with header cells.
+    fn is_header_row(&self) -> bool {
+        matches!(self.kind, StructKind::TableRow(_))
+            && self.kids.iter().any(|kid| {
+                matches!(
+                    kid,
+                    Kid::Elem(StructElem {
+                        kind: StructKind::TableCell { header: true, .. },
+                        ..
+                    })
+                )
+            })
+    }
+
+    /// Add a part of the element from a later page. A nested element that
+    /// continues there, like a table row or a list item, is merged with its
+    /// previous part, while a repeated header row becomes an artifact.
+    fn merge(&mut self, kid: Kid, leaves: &mut [Option<Name<'static>>]) {
+        if self.repeats(&kid) {
+            artifact(leaves, &kid);
+            return;
+        }
+
+        let Kid::Elem(elem) = kid else {
+            self.kids.push(kid);
+            return;
+        };
+
+        let prev = self.kids.iter_mut().rev().find_map(|kid| match kid {
+            Kid::Elem(prev) if prev.continued_by(&elem) => Some(prev),
+            _ => None,
+        });
+
+        match prev {
+            Some(prev) => elem.kids.into_iter().for_each(|kid| prev.merge(kid, leaves)),
+            None => self.kids.push(Kid::Elem(elem)),
+        }
+    }
+
+    /// Whether the other element is the continuation of this one. Besides
+    /// elements, like a table in a figure, only units that identify their
+    /// part in the parent, like rows and cells, continue.
+    fn continued_by(&self, other: &StructElem) -> bool {
+        self.kind == other.kind
+            && self.location == other.location
+            && (self.location.is_some()
+                || matches!(
+                    self.kind,
+                    StructKind::ListItem(_)
+                        | StructKind::ListLabel
+                        | StructKind::ListBody
+                        | StructKind::TableRow(_)
+                        | StructKind::TableCell { .. }
+                ))
+    }
+}
+
+/// Mark the text runs and images of a part of an element as artifacts.
+fn artifact(leaves: &mut [Option<Name<'static>>], kid: &Kid) {
+    match kid {
+        Kid::Mcid(_, mcid) => leaves[*mcid as usize] = None,
+        Kid::Elem(elem) => elem.kids.iter().for_each(|kid| artifact(leaves, kid)),
+    }
+}
+
+/// The standard structure type of a kind of unit.
+fn tag(kind: StructKind) -> Name<'static> {
+    match kind {
+        StructKind::Heading(level) => match level.get() {
+            1 => Name(b"H1"),
+            2 => Name(b"H2"),
+            3 => Name(b"H3"),
+            4 => Name(b"H4"),
+            5 => Name(b"H5"),
+            _ => Name(b"H6"),
+        },
+        StructKind::Par => Name(b"P"),
+        StructKind::Figure => Name(b"Figure"),
+        StructKind::List => Name(b"L"),
+        StructKind::ListItem(_) => Name(b"LI"),
+        StructKind::ListLabel => Name(b"Lbl"),
+        StructKind::ListBody => Name(b"LBody"),
+        StructKind::Table => Name(b"Table"),
+        StructKind::TableRow(_) => Name(b"TR"),
+        StructKind::TableCell { header: true, .. } => Name(b"TH"),
+        StructKind::TableCell { header: false, .. } => Name(b"TD"),
+    }
+}
+
+/// Write the structure tree, returning the reference to its root.
+#[tracing::instrument(skip_all)]
+pub(crate) fn write_structure(ctx: &mut PdfContext) -> Option<Ref> {
+    let tags = ctx.tags.take()?;
+    let root_ref = ctx.alloc.bump();
+    let document_ref = ctx.alloc.bump();
+
+    let refs: Vec<Ref> = tags.elems.iter().map(|_| ctx.alloc.bump()).collect();
+
+    // Maps from each page's marked-content identifiers to their elements.
+    let mut parent_tree = vec![vec![]; ctx.pages.len()];
+    for (elem, &elem_ref) in tags.elems.iter().zip(&refs) {
+        write_elem(ctx, elem, elem_ref, document_ref, &[], 0, &mut parent_tree);
+    }
+
+    let mut document = ctx.pdf.indirect(document_ref).dict();
+    document.pair(Name(b"Type"), Name(b"StructElem"));
+    document.pair(Name(b"S"), Name(b"Document"));
+    document.pair(Name(b"P"), root_ref);
+    document.insert(Name(b"K")).array().items(refs.iter().copied());
+    document.finish();
+
+    let mut root = ctx.pdf.indirect(root_ref).dict();
+    root.pair(Name(b"Type"), Name(b"StructTreeRoot"));
+    root.pair(Name(b"K"), document_ref);
+
+    // The identifiers of artifacts have no parent, but the array must still
+    // be indexable by identifier.
+    let mut tree = root.insert(Name(b"ParentTree")).dict();
+    let mut nums = tree.insert(Name(b"Nums")).array();
+    for (i, parents) in parent_tree.iter().enumerate() {
+        nums.item(i as i32);
+        let mut array = nums.push().array();
+        for parent in parents {
+            match parent {
+                Some(parent) => array.item(*parent),
+                None => array.item(Null),
+            };
+        }
+    }
+    nums.finish();
+    tree.finish();
+
+    root.pair(Name(b"ParentTreeNextKey"), ctx.pages.len() as i32);
+    root.finish();
+
+    Some(root_ref)
+}
+
+/// The identifier of a table's header cell, its row, and the columns it spans.
+type HeaderCell = (EcoString, usize, Range<usize>);
+
+/// Write a structure element and its nested elements.
+///
+/// The header cells are those of the table the element is part of, if any,
+/// and the row is the table row it is part of.
+fn write_elem(
+    ctx: &mut PdfContext,
+    elem: &StructElem,
+    elem_ref: Ref,
+    parent: Ref,
+    headers: &[HeaderCell],
+    row: usize,
+    parent_tree: &mut [Vec<Option<Ref>>],
+) {
+    // Header cells are identified by their table and position, so that data
+    // cells can refer to the header cells of their columns.
+    let table_headers;
+    let headers = if elem.kind == StructKind::Table {
+        table_headers = header_cells(elem, elem_ref);
+        &table_headers
+    } else {
+        headers
+    };
+    let row = match elem.kind {
+        StructKind::TableRow(y) => y,
+        _ => row,
+    };
+
+    let mut dict = ctx.pdf.indirect(elem_ref).dict();
+    dict.pair(Name(b"Type"), Name(b"StructElem"));
+    dict.pair(Name(b"S"), tag(elem.kind));
+    dict.pair(Name(b"P"), parent);
+    dict.pair(Name(b"Pg"), ctx.page_refs[elem.page]);
+    if let Some(alt) = &elem.alt {
+        dict.pair(Name(b"Alt"), TextStr(alt.as_str()));
+    }
+    if let StructKind::TableCell { x, colspan, rowspan, header } = elem.kind {
+        let columns = x..x + colspan;
+        if header {
+            let id = headers.iter().find(|(_, y, range)| *y == row && *range == columns);
+            if let Some((id, ..)) = id {
+                dict.pair(Name(b"ID"), Str(id.as_bytes()));
+            }
+        }
+
+        let mut attrs = dict.insert(Name(b"A")).dict();
+        attrs.pair(Name(b"O"), Name(b"Table"));
+        if header {
+            // Header rows label the columns below them.
+            attrs.pair(Name(b"Scope"), Name(b"Column"));
+        } else {
+            let mut ids = attrs.insert(Name(b"Headers")).array();
+            for (id, _, range) in headers {
+                if range.start < columns.end && columns.start < range.end {
+                    ids.item(Str(id.as_bytes()));
+                }
+            }
+        }
+        if rowspan > 1 {
+            attrs.pair(Name(b"RowSpan"), rowspan as i32);
+        }
+        if colspan > 1 {
+            attrs.pair(Name(b"ColSpan"), colspan as i32);
+        }
+    }
+
+    let mut children = vec![];
+    let mut kids = dict.insert(Name(b"K")).array();
+    for kid in &elem.kids {
+        match kid {
+            &Kid::Mcid(page, mcid) => {
+                // Content on another page than the element's one needs a
+                // reference to its page.
+                if page == elem.page {
+                    kids.item(mcid);
+                } else {
+                    kids.push()
+                        .dict()
+                        .pair(Name(b"Type"), Name(b"MCR"))
+                        .pair(Name(b"Pg"), ctx.page_refs[page])
+                        .pair(Name(b"MCID"), mcid);
+                }
+
+                let parents = &mut parent_tree[page];
+                let index = mcid as usize;
+                if parents.len() <= index {
+                    parents.resize(index + 1, None);
+                }
+                parents[index] = Some(elem_ref);
+            }
+            Kid::Elem(child) => {
+                let child_ref = ctx.alloc.bump();
+                kids.item(child_ref);
+                children.push((child, child_ref));
+            }
+        }
+    }
+    kids.finish();
+    dict.finish();
+
+    for (child, child_ref) in children {
+        write_elem(ctx, child, child_ref, elem_ref, headers, row, parent_tree);
+    }
+}
+
+/// Assign identifiers to the header cells of a table.
+fn header_cells(table: &StructElem, table_ref: Ref) -> Vec<HeaderCell> {
+    let mut cells = vec![];
+    for kid in &table.kids {
+        let Kid::Elem(row) = kid else { continue };
+        let StructKind::TableRow(y) = row.kind else { continue };
+        for kid in &row.kids {
+            if let Kid::Elem(StructElem {
+                kind: StructKind::TableCell { x, colspan, header: true, .. },
+                ..
+            }) = kid
+            {
+                let id = eco_format!("table{}-{}-{}", table_ref.get(), y, x);
+                cells.push((id, y, *x..*x + *colspan));
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::foundations::{Content, NativeElement};
+    use typst::introspection::{Location, Meta};
+    use typst::layout::{Abs, FrameItem, Point, Size};
+    use typst::model::{FigureElem, HeadingElem, Role, TableElem};
+    use typst::text::TextElem;
+
+    use super::*;
+    use crate::tests::{contains, count, document, export, page, text, REGULAR};
+    use crate::PdfOptions;
+
+    #[test]
+    fn test_tagged_headings_and_paragraphs() {
+        let mut frame = page();
+        let heading = HeadingElem::new(TextElem::packed("Intro")).pack();
+        elem(&mut frame, heading, 1, Size::new(Abs::pt(200.0), Abs::pt(20.0)));
+        text(&mut frame, Point::with_y(Abs::pt(15.0)), REGULAR, "Intro");
+        text(&mut frame, Point::with_y(Abs::pt(40.0)), REGULAR, "Hello");
+        text(&mut frame, Point::with_y(Abs::pt(52.0)), REGULAR, "world.");
+
+        let pdf = export(&document(vec![frame]), tagged()).unwrap();
+        assert!(contains(&pdf, "/MarkInfo <<\n    /Marked true"));
+        assert_eq!(count(&pdf, "/S /H1"), 1);
+        assert_eq!(count(&pdf, "/S /P\n"), 1);
+        assert!(contains(&pdf, "/K [1 2]"));
+        assert!(contains(&pdf, "/Nums [0 ["));
+        assert!(!contains(&pdf, "null"));
+
+        let content = content(&pdf);
+        assert!(content.contains("/H1 <<\n  /MCID 0\n>> BDC"));
+        assert!(content.contains("/P <<\n  /MCID 2\n>> BDC"));
+    }
+
+    #[test]
+    fn test_tagged_table_across_pages() {
+        // The header repeats on the second page, where the apple's row
+        // continues.
+        let mut first = page();
+        table(&mut first);
+        cell(&mut first, (0, 0, 2, 1, true), (0.0, 0.0), "Fruit");
+        cell(&mut first, (0, 1, 1, 1, true), (0.0, 20.0), "Name");
+        cell(&mut first, (1, 1, 1, 1, true), (100.0, 20.0), "Color");
+        cell(&mut first, (0, 2, 1, 1, false), (0.0, 40.0), "Apple");
+        cell(&mut first, (1, 2, 1, 2, false), (100.0, 40.0), "Red");
+
+        let mut second = page();
+        table(&mut second);
+        cell(&mut second, (0, 0, 2, 1, true), (0.0, 0.0), "Fruit");
+        cell(&mut second, (0, 1, 1, 1, true), (0.0, 20.0), "Name");
+        cell(&mut second, (1, 1, 1, 1, true), (100.0, 20.0), "Color");
+        cell(&mut second, (0, 2, 1, 1, false), (0.0, 40.0), "(sliced)");
+        cell(&mut second, (0, 3, 1, 1, false), (0.0, 60.0), "Lime");
+
+        let pdf = export(&document(vec![first, second]), tagged()).unwrap();
+        assert_eq!(count(&pdf, "/S /Table"), 1);
+        assert_eq!(count(&pdf, "/S /TR"), 4);
+        assert_eq!(count(&pdf, "/S /TH"), 3);
+        // The apple's cell continues on the second page.
+        assert_eq!(count(&pdf, "/S /TD"), 3);
+        assert_eq!(count(&pdf, "/ColSpan 2"), 1);
+        assert_eq!(count(&pdf, "/RowSpan 2"), 1);
+
+        // Data cells refer to the header cells above them.
+        let ids = ids(&pdf);
+        assert_eq!(ids.len(), 3);
+        let apple = format!("/Headers [({}) ({})]", ids[0], ids[1]);
+        let red = format!("/Headers [({}) ({})]", ids[0], ids[2]);
+        assert_eq!(count(&pdf, &apple), 2);
+        assert_eq!(count(&pdf, &red), 1);
+
+        // The repeated header is an artifact without a parent.
+        assert!(contains(&pdf, "1 [null null null "));
+        assert_eq!(content(&pdf).matches("/Artifact BMC").count(), 3);
+    }
+
+    #[test]
+    fn test_tagged_figure_table_across_pages() {
+        let pages = [(1, "Apple"), (2, "Lime")].map(|(y, body)| {
+            let mut frame = page();
+            let size = frame.size();
+            elem(&mut frame, FigureElem::new(Content::empty()).pack(), 3, size);
+            table(&mut frame);
+            cell(&mut frame, (0, 0, 1, 1, true), (0.0, 0.0), "Fruit");
+            cell(&mut frame, (0, y, 1, 1, false), (0.0, 20.0), body);
+            frame
+        });
+
+        let pdf = export(&document(pages.into()), tagged()).unwrap();
+        assert_eq!(count(&pdf, "/S /Figure"), 1);
+        assert_eq!(count(&pdf, "/S /Table"), 1);
+        assert_eq!(count(&pdf, "/S /TH"), 1);
+        assert_eq!(count(&pdf, "/S /TD"), 2);
+        assert!(contains(&pdf, "1 [null "));
+    }
+
+    /// Options for tagged export.
+    fn tagged() -> PdfOptions {
+        PdfOptions { tagged: true, ..Default::default() }
+    }
+
+    /// Add an element with the given location hash that covers a region at
+    /// the top of the frame.
+    fn elem(frame: &mut Frame, mut elem: Content, hash: u128, size: Size) {
+        elem.set_location(Location { hash, disambiguator: 0, variant: 0 });
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(elem), size));
+    }
+
+    /// Add a table that covers the whole frame.
+    fn table(frame: &mut Frame) {
+        let size = frame.size();
+        elem(frame, TableElem::new(vec![]).pack(), 2, size);
+    }
+
+    /// Add a table cell with the given column, row, spans, and header flag at
+    /// a position.
+    fn cell(
+        frame: &mut Frame,
+        (x, y, colspan, rowspan, header): (usize, usize, usize, usize, bool),
+        (left, top): (f64, f64),
+        body: &str,
+    ) {
+        let role = Role::TableCell { x, y, colspan, rowspan, header };
+        let pos = Point::new(Abs::pt(left), Abs::pt(top));
+        let size = Size::new(Abs::pt(100.0 * colspan as f64), Abs::pt(20.0));
+        frame.push(pos, FrameItem::Meta(Meta::Role(role), size));
+        text(frame, pos + Point::with_y(Abs::pt(15.0)), REGULAR, body);
+    }
+
+    /// The identifiers of the structure elements in the PDF.
+    fn ids(pdf: &[u8]) -> Vec<String> {
+        let pdf = String::from_utf8_lossy(pdf);
+        pdf.split("/ID (")
+            .skip(1)
+            .map(|rest| rest[..rest.find(')').unwrap()].to_string())
+            .collect()
+    }
+
+    /// The decompressed content streams of the PDF.
+    fn content(pdf: &[u8]) -> String {
+        let mut content = String::new();
+        let mut rest = pdf;
+        while let Some(start) = find(rest, b"stream\n") {
+            rest = &rest[start + 7..];
+            let end = find(rest, b"\nendstream").unwrap();
+            if let Ok(data) = miniz_oxide::inflate::decompress_to_vec_zlib(&rest[..end]) {
+                content.push_str(&String::from_utf8_lossy(&data));
+            }
+            rest = &rest[end + 10..];
+        }
+        content
+    }
+
+    /// The position of a byte sequence in another.
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).position(|w| w == needle)
+    }
+}
@@ -166,6 +166,8 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
                 Meta::Elem(_) => {}
                 Meta::PageNumbering(_) => {}
                 Meta::PdfPageLabel(_) => {}
+                Meta::PdfPageBoxes(_) => {}
+                Meta::Role(_) => {}
                 Meta::Hide => {}
             },
         }
@@ -240,6 +242,7 @@ fn render_text(canvas: &mut sk::Pixmap, state: State, text: &TextItem) {
 
         render_svg_glyph(canvas, state, text, id)
             .or_else(|| render_bitmap_glyph(canvas, state, text, id))
+            .or_else(|| render_colr_glyph(canvas, state, text, id))
             .or_else(|| render_outline_glyph(canvas, state, text, id));
 
         x += glyph.x_advance.at(text.size).to_f32();
@@ -254,7 +257,7 @@ fn render_svg_glyph(
     id: GlyphId,
 ) -> Option<()> {
     let ts = &state.transform;
-    let mut data = text.font.ttf().glyph_svg_image(id)?;
+    let mut data = text.font.ttf().glyph_svg_image(id)?.data;
 
     // Decompress SVGZ.
     let mut decoded = vec![];
@@ -364,6 +367,27 @@ fn render_bitmap_glyph(
     )
 }
 
+/// Render a glyph defined by colored layers into the canvas.
+fn render_colr_glyph(
+    canvas: &mut sk::Pixmap,
+    state: State,
+    text: &TextItem,
+    id: GlyphId,
+) -> Option<()> {
+    // Layers that take on the color of the text can't be filled with
+    // gradients or patterns.
+    let foreground = match &text.fill {
+        Paint::Solid(color) => *color,
+        _ => Color::BLACK,
+    };
+
+    let (image, bounds) = typst::text::colr_glyph_image(&text.font, id, foreground)?;
+    let scale = text.size / text.font.units_per_em();
+    let at = Point::new(bounds.x_min as f64 * scale, -bounds.y_max as f64 * scale);
+    let size = Size::new(bounds.width() as f64 * scale, bounds.height() as f64 * scale);
+    render_image(canvas, state.pre_translate(at), &image, size)
+}
+
 /// Render an outline glyph into the canvas. This is the "normal" case.
 fn render_outline_glyph(
     canvas: &mut sk::Pixmap,
@@ -835,6 +859,7 @@ impl PaintSampler for GradientSampler<'_> {
 #[derive(Clone, Copy)]
 struct PatternSampler<'a> {
     size: Size,
+    offset: Size,
     transform_to_parent: sk::Transform,
     pixmap: &'a sk::Pixmap,
     pixel_per_pt: f32,
@@ -856,6 +881,7 @@ impl<'a> PatternSampler<'a> {
         Self {
             pixmap,
             size: (pattern.size() + pattern.spacing()) * state.pixel_per_pt as f64,
+            offset: pattern.offset(),
             transform_to_parent: fill_transform,
             pixel_per_pt: state.pixel_per_pt,
         }
@@ -869,10 +895,12 @@ impl PaintSampler for PatternSampler<'_> {
         let mut point = sk::Point { x: x as f32, y: y as f32 };
         self.transform_to_parent.map_point(&mut point);
 
-        let x =
-            (point.x * self.pixel_per_pt).rem_euclid(self.size.x.to_f32()).floor() as u32;
-        let y =
-            (point.y * self.pixel_per_pt).rem_euclid(self.size.y.to_f32()).floor() as u32;
+        let x = ((point.x - self.offset.x.to_f32()) * self.pixel_per_pt)
+            .rem_euclid(self.size.x.to_f32())
+            .floor() as u32;
+        let y = ((point.y - self.offset.y.to_f32()) * self.pixel_per_pt)
+            .rem_euclid(self.size.y.to_f32())
+            .floor() as u32;
 
         // Sample the pattern
         self.pixmap.pixel(x, y).unwrap()
@@ -978,12 +1006,14 @@ fn to_sk_paint<'a>(
             *pixmap = Some(Arc::new(canvas));
 
             // Create the shader
+            let offset = pattern.offset();
             sk_paint.shader = sk::Pattern::new(
                 pixmap.as_ref().unwrap().as_ref().as_ref(),
                 sk::SpreadMode::Repeat,
                 sk::FilterQuality::Nearest,
                 1.0,
                 fill_transform
+                    .pre_translate(offset.x.to_f32(), offset.y.to_f32())
                     .pre_scale(1.0 / state.pixel_per_pt, 1.0 / state.pixel_per_pt),
             );
         }
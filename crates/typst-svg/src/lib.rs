@@ -12,7 +12,7 @@ use typst::layout::{
     Size, Transform,
 };
 use typst::text::{Font, TextItem};
-use typst::util::hash128;
+use typst::util::{hash128, Numeric};
 use typst::visualize::{
     Color, FixedStroke, Geometry, Gradient, Image, ImageFormat, LineCap, LineJoin, Paint,
     Path, PathItem, Pattern, RasterFormat, RatioOrAngle, RelativeTo, Shape, VectorFormat,
@@ -1037,6 +1037,10 @@ impl SVGRenderer {
             self.xml.write_attribute("width", &size.x.to_pt());
             self.xml.write_attribute("height", &size.y.to_pt());
             self.xml.write_attribute("patternUnits", "userSpaceOnUse");
+            if !pattern.offset().is_zero() {
+                self.xml.write_attribute("x", &pattern.offset().x.to_pt());
+                self.xml.write_attribute("y", &pattern.offset().y.to_pt());
+            }
             self.xml.write_attribute_fmt(
                 "viewBox",
                 format_args!("0 0 {:.3} {:.3}", size.x.to_pt(), size.y.to_pt()),
@@ -1108,7 +1112,7 @@ fn convert_bitmap_glyph_to_image(font: &Font, id: GlyphId) -> Option<(Image, f64
 /// Convert an SVG glyph to an encoded image URL.
 #[comemo::memoize]
 fn convert_svg_glyph_to_base64_url(font: &Font, id: GlyphId) -> Option<EcoString> {
-    let mut data = font.ttf().glyph_svg_image(id)?;
+    let mut data = font.ttf().glyph_svg_image(id)?.data;
 
     // Decompress SVGZ.
     let mut decoded = vec![];
@@ -51,6 +51,9 @@ impl Tracer {
 #[comemo::track]
 impl Tracer {
     /// Push delayed errors.
+    ///
+    /// Delayed diagnostics may also be warnings. These are only reported if
+    /// they remain at the end of the introspection loop.
     pub fn delay(&mut self, errors: EcoVec<SourceDiagnostic>) {
         self.delayed.extend(errors);
     }
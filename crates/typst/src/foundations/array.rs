@@ -7,7 +7,7 @@ use ecow::{eco_format, EcoString, EcoVec};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use crate::diag::{At, SourceResult, StrResult};
+use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::eval::ops;
 use crate::foundations::{
@@ -625,6 +625,46 @@ impl Array {
         flat.into()
     }
 
+    /// Swaps the rows and columns of an array of arrays, such that the `i`th
+    /// inner array of the result contains the `i`th items of all inner arrays.
+    ///
+    /// All inner arrays must have the same length. This is useful to display
+    /// row-major data column by column, for example in a table.
+    ///
+    /// ```example
+    /// #let data = ((1, 2, 3), (4, 5, 6))
+    /// #table(
+    ///   columns: data.len(),
+    ///   ..data.transpose().flatten().map(str),
+    /// )
+    /// ```
+    #[func]
+    pub fn transpose(self) -> StrResult<Array> {
+        let rows = self
+            .into_iter()
+            .map(Value::cast::<Array>)
+            .collect::<StrResult<Vec<_>>>()?;
+
+        let Some(len) = rows.first().map(Array::len) else { return Ok(Array::new()) };
+        if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != len)
+        {
+            bail!(
+                "inner arrays must have the same length \
+                 (array at index {i} has length {}, but the first has length {len})",
+                row.len(),
+            );
+        }
+
+        let mut columns = vec![Array::with_capacity(rows.len()); len];
+        for row in rows {
+            for (column, item) in columns.iter_mut().zip(row) {
+                column.push(item);
+            }
+        }
+
+        Ok(columns.into_iter().map(IntoValue::into_value).collect())
+    }
+
     /// Return a new array with the same items, but in reverse order.
     #[func(title = "Reverse")]
     pub fn rev(self) -> Array {
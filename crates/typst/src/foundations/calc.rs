@@ -6,7 +6,7 @@ use std::ops::{Div, Rem};
 
 use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::eval::ops;
-use crate::foundations::{cast, func, IntoValue, Module, Scope, Value};
+use crate::foundations::{cast, func, Array, IntoValue, Module, Scope, Value};
 use crate::layout::{Angle, Fr, Length, Ratio};
 use crate::syntax::{Span, Spanned};
 
@@ -678,52 +678,63 @@ pub fn fract(
 
 /// Rounds a number to the nearest integer.
 ///
-/// Optionally, a number of decimal places can be specified.
+/// Optionally, a number of decimal places can be specified. If an array of
+/// numbers is given, each of its items is rounded.
 ///
 /// ```example
 /// #assert(calc.round(3.14) == 3)
 /// #assert(calc.round(3.5) == 4)
-/// #calc.round(3.1415, digits: 2)
+/// #calc.round(3.1415, digits: 2) \
+/// #calc.round((1.234, 2.5, 3), digits: 1)
 /// ```
 #[func]
 pub fn round(
-    /// The number to round.
-    value: Num,
+    /// The number or array of numbers to round.
+    value: Spanned<Nums>,
     /// The number of decimal places.
     #[named]
     #[default(0)]
     digits: i64,
-) -> Num {
-    match value {
-        Num::Int(n) if digits == 0 => Num::Int(n),
-        _ => {
-            let n = value.float();
-            let factor = 10.0_f64.powi(digits as i32);
-            Num::Float((n * factor).round() / factor)
-        }
-    }
+) -> SourceResult<Value> {
+    value
+        .v
+        .map(|value| match value {
+            Num::Int(n) if digits == 0 => Num::Int(n),
+            _ => {
+                let n = value.float();
+                let factor = 10.0_f64.powi(digits as i32);
+                Num::Float((n * factor).round() / factor)
+            }
+        })
+        .at(value.span)
 }
 
 /// Clamps a number between a minimum and maximum value.
 ///
+/// If an array of numbers is given, each of its items is clamped.
+///
 /// ```example
 /// #assert(calc.clamp(5, 0, 10) == 5)
 /// #assert(calc.clamp(5, 6, 10) == 6)
-/// #calc.clamp(5, 0, 4)
+/// #calc.clamp(5, 0, 4) \
+/// #calc.clamp((-4, 0.5, 12), 0, 10)
 /// ```
 #[func]
 pub fn clamp(
-    /// The number to clamp.
-    value: Num,
+    /// The number or array of numbers to clamp.
+    value: Spanned<Nums>,
     /// The inclusive minimum value.
     min: Num,
     /// The inclusive maximum value.
     max: Spanned<Num>,
-) -> SourceResult<Num> {
+) -> SourceResult<Value> {
     if max.v.float() < min.float() {
         bail!(max.span, "max must be greater than or equal to min")
     }
-    Ok(value.apply3(min, max.v, i64::clamp, f64::clamp))
+    value
+        .v
+        .map(|value| value.apply3(min, max.v, i64::clamp, f64::clamp))
+        .at(value.span)
 }
 
 /// Determines the minimum of a sequence of values.
@@ -961,6 +972,32 @@ cast! {
     v: f64 => Self::Float(v),
 }
 
+/// A number or an array of numbers, which functions apply to item by item.
+pub enum Nums {
+    Num(Num),
+    Array(Array),
+}
+
+impl Nums {
+    /// Apply a function to the number or to each item of the array.
+    fn map(self, f: impl Fn(Num) -> Num) -> StrResult<Value> {
+        match self {
+            Self::Num(num) => Ok(f(num).into_value()),
+            Self::Array(array) => array
+                .into_iter()
+                .map(|item| Ok(f(item.cast()?).into_value()))
+                .collect::<StrResult<Array>>()
+                .map(IntoValue::into_value),
+        }
+    }
+}
+
+cast! {
+    Nums,
+    v: Num => Self::Num(v),
+    v: Array => Self::Array(v),
+}
+
 /// A value that can be passed to a trigonometric function.
 pub enum AngleLike {
     Int(i64),
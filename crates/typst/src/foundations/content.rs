@@ -17,7 +17,7 @@ use crate::foundations::{
 };
 use crate::introspection::{Location, Meta, MetaElem};
 use crate::layout::{Align, AlignElem, Axes, Length, MoveElem, PadElem, Rel, Sides};
-use crate::model::{Destination, EmphElem, StrongElem};
+use crate::model::{Destination, EmphElem, Role, StrongElem};
 use crate::syntax::Span;
 use crate::text::UnderlineElem;
 use crate::util::fat;
@@ -483,6 +483,11 @@ impl Content {
         self.styled(MetaElem::set_data(smallvec![Meta::Link(dest)]))
     }
 
+    /// Mark the content with its role within a list or table.
+    pub fn tagged(self, role: Role) -> Self {
+        self.styled(MetaElem::set_data(smallvec![Meta::Role(role)]))
+    }
+
     /// Make the content linkable by `.linked(Destination::Location(loc))`.
     ///
     /// Should be used in combination with [`Location::variant`].
@@ -582,6 +587,25 @@ impl Content {
     pub fn location(&self) -> Option<Location> {
         self.0.location()
     }
+
+    /// The position at which the content was laid out. This is a dictionary
+    /// with the page number and the x, y coordinates, just like the one
+    /// returned by a location's [`position`]($location.position) method. It is
+    /// only available for content that has a [location]($content.location),
+    /// for other content it will be `{none}`.
+    ///
+    /// ```example
+    /// = Introduction
+    /// #locate(loc => {
+    ///   let heading = query(heading, loc).first()
+    ///   [The heading is #heading.position().y from the top.]
+    /// })
+    /// ```
+    #[func]
+    pub fn position(&self, engine: &mut Engine) -> Option<Dict> {
+        self.location()
+            .map(|location| engine.introspector.position(location).into())
+    }
 }
 
 impl Default for Content {
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, Sub};
 
 use ecow::{eco_format, EcoString, EcoVec};
@@ -12,6 +12,7 @@ use crate::engine::Engine;
 use crate::foundations::{
     cast, func, repr, scope, ty, Dict, Duration, Repr, Smart, Str, Value,
 };
+use crate::text::{Lang, Region};
 use crate::World;
 
 /// Represents a date, a time, or a combination of both.
@@ -111,8 +112,30 @@ use crate::World;
 /// will be stored as a plain date internally, meaning that you cannot use
 /// components such as `hour` or `minute`, which would only work on datetimes
 /// that have a specified time.
+///
+/// # Arithmetic and comparison
+/// You can add a [duration]($duration) to or subtract it from a datetime.
+/// Subtracting two datetimes yields the duration between them, and datetimes
+/// can be compared with operators like `{<}` and `{>=}`.
+///
+/// When a plain date is combined with a datetime that has a time, the date is
+/// treated as midnight at the start of that day. A plain time has no date, so
+/// it can only be compared with and subtracted from other plain times. This
+/// also means that a plain date is equal to a datetime at midnight of the same
+/// day.
+///
+/// ```example
+/// #let deadline = datetime(year: 2024, month: 5, day: 1)
+/// #let now = datetime(
+///   year: 2024, month: 4, day: 29,
+///   hour: 12, minute: 0, second: 0,
+/// )
+///
+/// #(now < deadline) \
+/// #(deadline - now).hours() hours left
+/// ```
 #[ty(scope)]
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy)]
 pub enum Datetime {
     /// Representation as a date.
     Date(time::Date),
@@ -317,21 +340,44 @@ impl Datetime {
     /// `[[year]-[month]-[day]]`. If you specified a time, it will be
     /// `[[hour]:[minute]:[second]]`. In the case of a datetime, it will be
     /// `[[year]-[month]-[day] [hour]:[minute]:[second]]`.
+    ///
+    /// If a `lang` is given, the date part of the default format follows that
+    /// language's conventions instead, for example `[[day].[month].[year]]`
+    /// for German. When a datetime is inserted into markup, the
+    /// [text language]($text.lang) and region are used automatically. An
+    /// explicit pattern always takes precedence.
+    ///
+    /// ```example
+    /// #let date = datetime(year: 2023, month: 10, day: 4)
+    /// #date.display(lang: "de") \
+    /// #set text(lang: "fr")
+    /// #date
+    /// ```
     #[func]
     pub fn display(
         &self,
         /// The format used to display the datetime.
         #[default]
         pattern: Smart<DisplayPattern>,
+        /// The language whose date format to use by default.
+        #[named]
+        #[default]
+        lang: Option<Lang>,
+        /// The region whose date format to use by default.
+        #[named]
+        #[default]
+        region: Option<Region>,
     ) -> StrResult<EcoString> {
         let pat = |s| format_description::parse_borrowed::<2>(s).unwrap();
+        let date_pat = lang
+            .and_then(|lang| lang.date_pattern(region))
+            .unwrap_or("[year]-[month]-[day]");
+        let datetime_pat = eco_format!("{date_pat} [hour]:[minute]:[second]");
         let result = match pattern {
             Smart::Auto => match self {
-                Self::Date(date) => date.format(&pat("[year]-[month]-[day]")),
+                Self::Date(date) => date.format(&pat(date_pat)),
                 Self::Time(time) => time.format(&pat("[hour]:[minute]:[second]")),
-                Self::Datetime(datetime) => {
-                    datetime.format(&pat("[year]-[month]-[day] [hour]:[minute]:[second]"))
-                }
+                Self::Datetime(datetime) => datetime.format(&pat(datetime_pat.as_str())),
             },
 
             Smart::Custom(DisplayPattern(_, format)) => match self {
@@ -441,12 +487,39 @@ impl Repr for Datetime {
     }
 }
 
+impl PartialEq for Datetime {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Datetime(a), Self::Datetime(b)) => a == b,
+            (Self::Date(a), Self::Date(b)) => a == b,
+            (Self::Time(a), Self::Time(b)) => a == b,
+            (Self::Date(a), Self::Datetime(b)) => a.midnight() == *b,
+            (Self::Datetime(a), Self::Date(b)) => *a == b.midnight(),
+            _ => false,
+        }
+    }
+}
+
+impl Hash for Datetime {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // A plain date hashes like the datetime at its midnight since the two
+        // are equal.
+        match self {
+            Self::Datetime(datetime) => datetime.hash(state),
+            Self::Date(date) => date.midnight().hash(state),
+            Self::Time(time) => time.hash(state),
+        }
+    }
+}
+
 impl PartialOrd for Datetime {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
             (Self::Datetime(a), Self::Datetime(b)) => a.partial_cmp(b),
             (Self::Date(a), Self::Date(b)) => a.partial_cmp(b),
             (Self::Time(a), Self::Time(b)) => a.partial_cmp(b),
+            (Self::Date(a), Self::Datetime(b)) => a.midnight().partial_cmp(b),
+            (Self::Datetime(a), Self::Date(b)) => a.partial_cmp(&b.midnight()),
             _ => None,
         }
     }
@@ -486,6 +559,8 @@ impl Sub for Datetime {
             (Self::Datetime(a), Self::Datetime(b)) => Ok((a - b).into()),
             (Self::Date(a), Self::Date(b)) => Ok((a - b).into()),
             (Self::Time(a), Self::Time(b)) => Ok((a - b).into()),
+            (Self::Date(a), Self::Datetime(b)) => Ok((a.midnight() - b).into()),
+            (Self::Datetime(a), Self::Date(b)) => Ok((a - b.midnight()).into()),
             (a, b) => bail!("cannot subtract {} from {}", b.kind(), a.kind()),
         }
     }
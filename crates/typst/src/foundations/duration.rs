@@ -4,7 +4,8 @@ use std::ops::{Add, Div, Mul, Neg, Sub};
 use ecow::{eco_format, EcoString};
 use time::ext::NumericalDuration;
 
-use crate::foundations::{func, repr, scope, ty, Repr};
+use crate::diag::{bail, StrResult};
+use crate::foundations::{func, repr, scope, ty, Repr, Smart, Str};
 
 /// Represents a positive or negative span of time.
 #[ty(scope)]
@@ -108,6 +109,201 @@ impl Duration {
     pub fn weeks(&self) -> f64 {
         self.seconds() / 604_800.0
     }
+
+    /// Displays the duration in a specified format.
+    ///
+    /// By default, the duration is displayed with its non-zero days, hours,
+    /// minutes, and seconds, like `{"1d 2h 30m"}`.
+    ///
+    /// A custom format can contain the components `[weeks]`, `[days]`,
+    /// `[hours]`, `[minutes]`, and `[seconds]`. The largest unit in the
+    /// format displays the whole number of that unit in the duration, while
+    /// smaller units only display what remains after the next larger unit in
+    /// the format. With the format `{"[hours]h [minutes]m"}`, 90 minutes are
+    /// thus displayed as `{"1h 30m"}`, but with `{"[minutes]m"}`, they are
+    /// displayed as `{"90m"}`. A component can be padded to two digits with
+    /// zeros by adding the `padding:zero` modifier, as in
+    /// `[minutes padding:zero]`. Use `[[` for a literal opening bracket.
+    ///
+    /// Negative durations are prefixed with a minus sign. Fractions of a
+    /// second are not displayed.
+    ///
+    /// ```example
+    /// #let shift = duration(hours: 7, minutes: 45)
+    /// #shift.display() \
+    /// #shift.display("[hours]:[minutes padding:zero]") \
+    /// #shift.display("[minutes] minutes")
+    /// ```
+    #[func]
+    pub fn display(
+        &self,
+        /// The format used to display the duration.
+        #[default]
+        pattern: Smart<Str>,
+    ) -> StrResult<EcoString> {
+        let total = self.0.whole_seconds().unsigned_abs();
+        let mut out = EcoString::new();
+        if self.0.is_negative() && total > 0 {
+            out.push('-');
+        }
+
+        let Smart::Custom(pattern) = pattern else {
+            let mut above = None;
+            let mut parts = vec![];
+            for unit in [Unit::Days, Unit::Hours, Unit::Minutes, Unit::Seconds] {
+                let value = unit.value(total, above);
+                if value != 0 {
+                    parts.push(eco_format!("{value}{}", unit.suffix()));
+                }
+                above = Some(unit);
+            }
+
+            if parts.is_empty() {
+                parts.push("0s".into());
+            }
+
+            out.push_str(&parts.join(" "));
+            return Ok(out);
+        };
+
+        let parts = parse_pattern(&pattern)?;
+        for part in &parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Unit(unit, padded) => {
+                    // Find the next larger unit that is part of the format.
+                    let above = parts
+                        .iter()
+                        .filter_map(|part| match part {
+                            Part::Unit(other, _) if other > unit => Some(*other),
+                            _ => None,
+                        })
+                        .min();
+
+                    let value = unit.value(total, above);
+                    if *padded {
+                        out.push_str(&eco_format!("{value:02}"));
+                    } else {
+                        out.push_str(&eco_format!("{value}"));
+                    }
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// A unit of time in a duration format.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum Unit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl Unit {
+    /// Parses the name of a unit in a duration format.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "seconds" => Self::Seconds,
+            "minutes" => Self::Minutes,
+            "hours" => Self::Hours,
+            "days" => Self::Days,
+            "weeks" => Self::Weeks,
+            _ => return None,
+        })
+    }
+
+    /// The number of seconds in this unit.
+    fn seconds(self) -> u64 {
+        match self {
+            Self::Seconds => 1,
+            Self::Minutes => 60,
+            Self::Hours => 3_600,
+            Self::Days => 86_400,
+            Self::Weeks => 604_800,
+        }
+    }
+
+    /// The short suffix used in the default format.
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Seconds => "s",
+            Self::Minutes => "m",
+            Self::Hours => "h",
+            Self::Days => "d",
+            Self::Weeks => "w",
+        }
+    }
+
+    /// The whole number of this unit in the given seconds that remains after
+    /// taking out the `above` unit, if any.
+    fn value(self, seconds: u64, above: Option<Self>) -> u64 {
+        let remaining = match above {
+            Some(above) => seconds % above.seconds(),
+            None => seconds,
+        };
+        remaining / self.seconds()
+    }
+}
+
+/// A piece of a duration format.
+enum Part {
+    /// Text that is displayed as is.
+    Literal(EcoString),
+    /// A unit and whether it is padded with zeros.
+    Unit(Unit, bool),
+}
+
+/// Parses a duration format into its pieces.
+fn parse_pattern(pattern: &str) -> StrResult<Vec<Part>> {
+    let mut parts = vec![];
+    let mut literal = EcoString::new();
+    let mut rest = pattern;
+
+    while let Some(i) = rest.find('[') {
+        literal.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        if let Some(after) = rest.strip_prefix('[') {
+            literal.push('[');
+            rest = after;
+            continue;
+        }
+
+        let end = rest.find(']').ok_or("unclosed bracket in duration format")?;
+        let mut words = rest[..end].split_whitespace();
+        rest = &rest[end + 1..];
+
+        let name = words.next().unwrap_or_default();
+        let unit = Unit::from_name(name).ok_or_else(|| {
+            eco_format!("invalid component `{name}` in duration format")
+        })?;
+
+        let mut padded = false;
+        for modifier in words {
+            match modifier {
+                "padding:zero" => padded = true,
+                "padding:none" => padded = false,
+                _ => bail!("invalid modifier `{modifier}` in duration format"),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(std::mem::take(&mut literal)));
+        }
+        parts.push(Part::Unit(unit, padded));
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+
+    Ok(parts)
 }
 
 impl Debug for Duration {
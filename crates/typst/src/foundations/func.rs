@@ -8,8 +8,8 @@ use once_cell::sync::Lazy;
 use crate::diag::{bail, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, repr, scope, ty, Args, CastInfo, Content, Element, IntoArgs, Scope, Selector,
-    Type, Value,
+    cast, repr, scope, ty, Args, CastInfo, Comparison, Content, Element, IntoArgs,
+    Scope, Selector, Str, Type, Value,
 };
 use crate::syntax::{ast, Span, SyntaxNode};
 use crate::util::Static;
@@ -361,6 +361,42 @@ impl Func {
 
         Ok(element.where_(fields))
     }
+
+    /// Returns a selector that filters for elements belonging to this function
+    /// whose field compares to the given value in the given way.
+    ///
+    /// Elements without the field never match. Ordering comparisons only
+    /// match if the field's value can be compared with the given one.
+    ///
+    /// ```example
+    /// #locate(loc => query(
+    ///   heading.compare("level", ">", 1),
+    ///   loc,
+    /// ).len())
+    ///
+    /// = Not this one
+    /// == But this one
+    /// === And this one
+    /// ```
+    #[func]
+    pub fn compare(
+        self,
+        /// The name of the field to compare.
+        field: Str,
+        /// The comparison to perform: One of `{"=="}`, `{"!="}`, `{"<"}`,
+        /// `{"<="}`, `{">"}`, or `{">="}`.
+        op: Comparison,
+        /// The value to compare the field to.
+        value: Value,
+    ) -> StrResult<Selector> {
+        let elem = self
+            .element()
+            .ok_or("`compare()` can only be called on element functions")?;
+        let field = elem.field_id(&field).ok_or_else(|| {
+            eco_format!("element `{}` does not have field `{}`", elem.name(), field)
+        })?;
+        Ok(Selector::Compare { elem, field, op, value })
+    }
 }
 
 impl Debug for Func {
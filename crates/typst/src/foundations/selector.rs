@@ -1,17 +1,20 @@
 use std::any::{Any, TypeId};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
-use ecow::{eco_format, EcoString, EcoVec};
+use ecow::{eco_format, eco_vec, EcoString, EcoVec};
 use smallvec::SmallVec;
 
 use crate::diag::{bail, StrResult};
 use crate::foundations::{
-    cast, func, repr, scope, ty, CastInfo, Content, Dict, Element, FromValue, Func,
-    Label, Reflect, Regex, Repr, Str, Type, Value,
+    cast, func, repr, scope, ty, Cast, CastInfo, Content, Dict, Element, FromValue,
+    Func, Label, Reflect, Regex, Repr, Str, Type, Value,
 };
 use crate::introspection::{Locatable, Location};
+use crate::layout::{Abs, Length, Point, Size};
 use crate::symbols::Symbol;
 use crate::text::TextElem;
+use crate::util::Numeric;
 
 /// A helper macro to create a field selector used in [`Selector::Elem`]
 ///
@@ -86,20 +89,51 @@ pub enum Selector {
     Elem(Element, Option<SmallVec<[(u8, Value); 1]>>),
     /// Matches the element at the specified location.
     Location(Location),
+    /// Matches elements of a specific type whose field compares to the value
+    /// in the given way. Elements without the field don't match.
+    Compare { elem: Element, field: u8, op: Comparison, value: Value },
     /// Matches elements with a specific label.
     Label(Label),
     /// Matches text elements through a regular expression.
     Regex(Regex),
+    /// Matches elements whose label matches a regular expression.
+    LabelRegex(Regex),
     /// Matches elements with a specific capability.
     Can(TypeId),
+    /// Matches all elements on the given page.
+    OnPage(NonZeroUsize),
+    /// Matches all elements on the given page whose positions lie within the
+    /// rectangle that starts at `origin` and spans `size`.
+    InRegion { page: NonZeroUsize, origin: Point, size: Size },
     /// Matches if any of the subselectors match.
     Or(EcoVec<Self>),
     /// Matches if all of the subselectors match.
     And(EcoVec<Self>),
-    /// Matches all matches of `selector` before `end`.
+    /// Matches if the subselector does not match.
+    Not(Arc<Self>),
+    /// Matches all matches of `selector` before the first match of `end` in
+    /// document order.
     Before { selector: Arc<Self>, end: Arc<Self>, inclusive: bool },
-    /// Matches all matches of `selector` after `start`.
+    /// Matches all matches of `selector` after the first match of `start` in
+    /// document order.
     After { selector: Arc<Self>, start: Arc<Self>, inclusive: bool },
+    /// Matches all matches of `selector` after `start` and before `end`.
+    Between {
+        selector: Arc<Self>,
+        start: Arc<Self>,
+        end: Arc<Self>,
+        inclusive_start: bool,
+        inclusive_end: bool,
+    },
+    /// Matches all matches of `selector` that are contained in a match of
+    /// `ancestor`.
+    ///
+    /// This is only supported in show rules, where the ancestors are known
+    /// during realization. It cannot be queried.
+    Within { selector: Arc<Self>, ancestor: Arc<Self> },
+    /// Matches the match of `selector` at the given index, in document order.
+    /// Negative indices count from the end.
+    Nth { selector: Arc<Self>, index: i64 },
 }
 
 impl Selector {
@@ -138,16 +172,30 @@ impl Selector {
                         .flat_map(|dict| dict.iter())
                         .all(|(id, value)| target.get(*id).as_ref() == Some(value))
             }
+            Self::Compare { elem, field, op, value } => {
+                target.func() == *elem
+                    && target.get(*field).map_or(false, |field| op.holds(&field, value))
+            }
             Self::Label(label) => target.label() == Some(*label),
+            Self::LabelRegex(regex) => {
+                target.label().map_or(false, |label| regex.is_match(label.as_str()))
+            }
             Self::Regex(regex) => target
                 .to::<TextElem>()
                 .map_or(false, |elem| regex.is_match(elem.text())),
             Self::Can(cap) => target.func().can_type_id(*cap),
             Self::Or(selectors) => selectors.iter().any(move |sel| sel.matches(target)),
             Self::And(selectors) => selectors.iter().all(move |sel| sel.matches(target)),
+            Self::Not(selector) => !selector.matches(target),
             Self::Location(location) => target.location() == Some(*location),
             // Not supported here.
-            Self::Before { .. } | Self::After { .. } => false,
+            Self::OnPage(_)
+            | Self::InRegion { .. }
+            | Self::Before { .. }
+            | Self::After { .. }
+            | Self::Between { .. }
+            | Self::Within { .. }
+            | Self::Nth { .. } => false,
         }
     }
 }
@@ -229,6 +277,95 @@ impl Selector {
             inclusive,
         }
     }
+
+    /// Returns a modified selector that will only match elements that are
+    /// contained in an element matching `ancestor`. This is only supported in
+    /// show rules.
+    ///
+    /// If the ancestor itself also matches the original selector, the show
+    /// rule applies to it, too.
+    ///
+    /// ```example
+    /// #show selector(raw).within(figure): set text(blue)
+    ///
+    /// `Outside` of a figure.
+    /// #figure(`Inside`, caption: [A figure])
+    /// ```
+    #[func]
+    pub fn within(
+        self,
+        /// The element in which the original selection must be contained.
+        ancestor: ShowableSelector,
+    ) -> Selector {
+        Self::Within {
+            selector: Arc::new(self),
+            ancestor: Arc::new(ancestor.0),
+        }
+    }
+
+    /// Returns a modified selector that will only match elements on the given
+    /// page.
+    ///
+    /// ```example
+    /// = Introduction
+    /// #pagebreak()
+    /// = Conclusion
+    ///
+    /// #locate(loc => query(
+    ///   selector(heading).on-page(2),
+    ///   loc,
+    /// ).first().body)
+    /// ```
+    #[func]
+    pub fn on_page(
+        self,
+        /// The page number, starting at one.
+        page: NonZeroUsize,
+    ) -> Selector {
+        Self::And(eco_vec![self, Self::OnPage(page)])
+    }
+
+    /// Returns a modified selector that will only match elements whose
+    /// positions lie within a rectangular region of a page. Points on the
+    /// region's border count as inside.
+    ///
+    /// All lengths are measured from the top-left corner of the page and must
+    /// be absolute, i.e. may not contain `em` units.
+    #[func]
+    pub fn in_region(
+        self,
+        /// The page number, starting at one.
+        page: NonZeroUsize,
+        /// The horizontal offset of the region's top-left corner.
+        #[named]
+        #[default]
+        x: Length,
+        /// The vertical offset of the region's top-left corner.
+        #[named]
+        #[default]
+        y: Length,
+        /// The width of the region. Defaults to extending to the right edge of
+        /// the page.
+        #[named]
+        width: Option<Length>,
+        /// The height of the region. Defaults to extending to the bottom edge
+        /// of the page.
+        #[named]
+        height: Option<Length>,
+    ) -> StrResult<Selector> {
+        let abs = |length: Length| -> StrResult<Abs> {
+            if !length.em.is_zero() {
+                bail!("region cannot be measured in em units");
+            }
+            Ok(length.abs)
+        };
+        let origin = Point::new(abs(x)?, abs(y)?);
+        let size = Size::new(
+            width.map_or(Ok(Abs::inf()), abs)?,
+            height.map_or(Ok(Abs::inf()), abs)?,
+        );
+        Ok(Self::And(eco_vec![self, Self::InRegion { page, origin, size }]))
+    }
 }
 
 impl From<Location> for Selector {
@@ -252,14 +389,31 @@ impl Repr for Selector {
                     elem.name().into()
                 }
             }
+            Self::Compare { elem, field, op, value } => eco_format!(
+                "{}.compare({}, {}, {})",
+                elem.name(),
+                elem.field_name(*field).unwrap().repr(),
+                op.as_str().repr(),
+                value.repr()
+            ),
             Self::Label(label) => label.repr(),
             Self::Regex(regex) => regex.repr(),
+            Self::LabelRegex(regex) => eco_format!("label({})", regex.repr()),
             Self::Can(cap) => eco_format!("{cap:?}"),
+            Self::OnPage(page) => eco_format!("page({page})"),
+            Self::InRegion { page, origin, size } => eco_format!(
+                "region({page}, x: {}, y: {}, width: {}, height: {})",
+                origin.x.repr(),
+                origin.y.repr(),
+                size.x.repr(),
+                size.y.repr()
+            ),
             Self::Or(selectors) | Self::And(selectors) => {
                 let function = if matches!(self, Self::Or(_)) { "or" } else { "and" };
                 let pieces: Vec<_> = selectors.iter().map(Selector::repr).collect();
                 eco_format!("{}{}", function, repr::pretty_array_like(&pieces, false))
             }
+            Self::Not(selector) => eco_format!("not({})", selector.repr()),
             Self::Location(loc) => loc.repr(),
             Self::Before { selector, end: split, inclusive }
             | Self::After { selector, start: split, inclusive } => {
@@ -274,6 +428,22 @@ impl Repr for Selector {
                     inclusive_arg
                 )
             }
+            Self::Between { selector, start, end, inclusive_start, inclusive_end } => {
+                let mut args = eco_format!("{}, {}", start.repr(), end.repr());
+                if !*inclusive_start {
+                    args.push_str(", inclusive-start: false");
+                }
+                if !*inclusive_end {
+                    args.push_str(", inclusive-end: false");
+                }
+                eco_format!("{}.between({})", selector.repr(), args)
+            }
+            Self::Within { selector, ancestor } => {
+                eco_format!("{}.within({})", selector.repr(), ancestor.repr())
+            }
+            Self::Nth { selector, index } => {
+                eco_format!("{}.nth({})", selector.repr(), index)
+            }
         }
     }
 }
@@ -290,6 +460,58 @@ cast! {
     location: Location => Self::Location(location),
 }
 
+/// How a [`Selector::Compare`] selector compares an element's field to its
+/// value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum Comparison {
+    /// The field is equal to the value.
+    #[string("==")]
+    Eq,
+    /// The field is not equal to the value.
+    #[string("!=")]
+    Neq,
+    /// The field is less than the value.
+    #[string("<")]
+    Lt,
+    /// The field is less than or equal to the value.
+    #[string("<=")]
+    Leq,
+    /// The field is greater than the value.
+    #[string(">")]
+    Gt,
+    /// The field is greater than or equal to the value.
+    #[string(">=")]
+    Geq,
+}
+
+impl Comparison {
+    /// Whether the comparison holds between the field and the value.
+    ///
+    /// Values that cannot be ordered never fulfill an ordering comparison.
+    pub fn holds(self, field: &Value, value: &Value) -> bool {
+        match self {
+            Self::Eq => field == value,
+            Self::Neq => field != value,
+            Self::Lt => field < value,
+            Self::Leq => field <= value,
+            Self::Gt => field > value,
+            Self::Geq => field >= value,
+        }
+    }
+
+    /// The operator as it is written in Typst.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Eq => "==",
+            Self::Neq => "!=",
+            Self::Lt => "<",
+            Self::Leq => "<=",
+            Self::Gt => ">",
+            Self::Geq => ">=",
+        }
+    }
+}
+
 /// A selector that can be used with `query`.
 ///
 /// Hopefully, this is made obsolete by a more powerful query mechanism in the
@@ -324,13 +546,16 @@ impl FromValue for LocatableSelector {
     fn from_value(value: Value) -> StrResult<Self> {
         fn validate(selector: &Selector) -> StrResult<()> {
             match selector {
-                Selector::Elem(elem, _) => {
+                Selector::Elem(elem, _) | Selector::Compare { elem, .. } => {
                     if !elem.can::<dyn Locatable>() {
                         Err(eco_format!("{} is not locatable", elem.name()))?
                     }
                 }
                 Selector::Location(_) => {}
                 Selector::Label(_) => {}
+                Selector::LabelRegex(_) => {}
+                Selector::OnPage(_) => {}
+                Selector::InRegion { .. } => {}
                 Selector::Regex(_) => bail!("text is not locatable"),
                 Selector::Can(_) => bail!("capability is not locatable"),
                 Selector::Or(list) | Selector::And(list) => {
@@ -338,12 +563,21 @@ impl FromValue for LocatableSelector {
                         validate(selector)?;
                     }
                 }
+                Selector::Not(selector) | Selector::Nth { selector, .. } => {
+                    validate(selector)?
+                }
                 Selector::Before { selector, end: split, .. }
                 | Selector::After { selector, start: split, .. } => {
                     for selector in [selector, split] {
                         validate(selector)?;
                     }
                 }
+                Selector::Between { selector, start, end, .. } => {
+                    for selector in [selector, start, end] {
+                        validate(selector)?;
+                    }
+                }
+                Selector::Within { .. } => bail!("within selector is not locatable"),
             }
             Ok(())
         }
@@ -409,12 +643,26 @@ impl FromValue for ShowableSelector {
                 Selector::Elem(_, _) => {}
                 Selector::Label(_) => {}
                 Selector::Regex(_) => {}
+                Selector::Within { selector, ancestor } => {
+                    validate(selector)?;
+                    match **ancestor {
+                        Selector::Elem(_, _) | Selector::Label(_) => {}
+                        _ => bail!("this selector cannot be used as an ancestor"),
+                    }
+                }
                 Selector::Or(_)
                 | Selector::And(_)
+                | Selector::Not(_)
+                | Selector::Nth { .. }
                 | Selector::Location(_)
+                | Selector::Compare { .. }
                 | Selector::Can(_)
+                | Selector::LabelRegex(_)
+                | Selector::OnPage(_)
+                | Selector::InRegion { .. }
                 | Selector::Before { .. }
-                | Selector::After { .. } => {
+                | Selector::After { .. }
+                | Selector::Between { .. } => {
                     bail!("this selector cannot be used with show")
                 }
             }
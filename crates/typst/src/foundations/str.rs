@@ -483,12 +483,24 @@ impl Str {
 
     /// Removes matches of a pattern from one or both sides of the string, once or
     /// repeatedly and returns the resulting string.
+    ///
+    /// Besides a string or regular expression, the pattern can be an array of
+    /// strings, in which case grapheme clusters contained in the array are
+    /// trimmed, or a function that receives each grapheme cluster and returns
+    /// whether it should be trimmed.
+    ///
+    /// ```example
+    /// #"--==abc==--".trim(("-", "="), at: start) \
+    /// #"2024abc".trim(c => c.match(regex("\d")) != none)
+    /// ```
     #[func]
     pub fn trim(
         &self,
+        /// The engine.
+        engine: &mut Engine,
         /// The pattern to search for.
         #[default]
-        pattern: Option<StrPattern>,
+        pattern: Option<TrimPattern>,
         /// Can be `start` or `end` to only trim the start or end of the string.
         /// If omitted, both sides are trimmed.
         #[named]
@@ -498,7 +510,7 @@ impl Str {
         #[named]
         #[default(true)]
         repeat: bool,
-    ) -> Str {
+    ) -> SourceResult<Str> {
         let mut start = matches!(at, Some(StrSide::Start) | None);
         let end = matches!(at, Some(StrSide::End) | None);
 
@@ -508,7 +520,17 @@ impl Str {
                 Some(StrSide::Start) => self.0.trim_start(),
                 Some(StrSide::End) => self.0.trim_end(),
             },
-            Some(StrPattern::Str(pat)) => {
+            Some(TrimPattern::Set(set)) => {
+                trim_clusters(self, start, end, repeat, |c| {
+                    Ok(set.iter().any(|s| s.as_str() == c))
+                })?
+            }
+            Some(TrimPattern::Func(func)) => {
+                trim_clusters(self, start, end, repeat, |c| {
+                    func.call(engine, [Str::from(c)])?.cast::<bool>().at(func.span())
+                })?
+            }
+            Some(TrimPattern::Pattern(StrPattern::Str(pat))) => {
                 let pat = pat.as_str();
                 let mut s = self.as_str();
                 if repeat {
@@ -528,7 +550,7 @@ impl Str {
                 }
                 s
             }
-            Some(StrPattern::Regex(re)) => {
+            Some(TrimPattern::Pattern(StrPattern::Regex(re))) => {
                 let s = self.as_str();
                 let mut last = 0;
                 let mut range = 0..s.len();
@@ -563,7 +585,58 @@ impl Str {
             }
         };
 
-        trimmed.into()
+        Ok(trimmed.into())
+    }
+
+    /// Pads the start of the string to the given width and returns the
+    /// resulting string.
+    ///
+    /// The width is measured in grapheme clusters. If the string is already at
+    /// least as wide, it is returned unchanged.
+    ///
+    /// ```example
+    /// #"7".pad-start(3, with: "0") \
+    /// #"1234".pad-start(3, with: "0")
+    /// ```
+    #[func]
+    pub fn pad_start(
+        &self,
+        /// The width to pad the string to.
+        width: usize,
+        /// The string to fill the space with. If it consists of multiple
+        /// grapheme clusters, it is repeated and cut off to fit.
+        #[named]
+        #[default(" ".into())]
+        with: Str,
+    ) -> StrResult<Str> {
+        let mut padded = padding(self, width, &with)?;
+        padded.push_str(self);
+        Ok(padded.into())
+    }
+
+    /// Pads the end of the string to the given width and returns the
+    /// resulting string.
+    ///
+    /// The width is measured in grapheme clusters. If the string is already at
+    /// least as wide, it is returned unchanged.
+    ///
+    /// ```example
+    /// #"Name".pad-end(8, with: ".") : Typst
+    /// ```
+    #[func]
+    pub fn pad_end(
+        &self,
+        /// The width to pad the string to.
+        width: usize,
+        /// The string to fill the space with. If it consists of multiple
+        /// grapheme clusters, it is repeated and cut off to fit.
+        #[named]
+        #[default(" ".into())]
+        with: Str,
+    ) -> StrResult<Str> {
+        let mut padded = self.0.clone();
+        padded.push_str(&padding(self, width, &with)?);
+        Ok(padded.into())
     }
 
     /// Splits a string at matches of a specified pattern and returns an array
@@ -840,6 +913,21 @@ fn string_is_empty() -> EcoString {
 ///
 /// The numbers 1 to 10.
 /// ```
+///
+/// When a show rule with a regex selector is given a function, the matched
+/// text passed to it has a `captures` field with an array of the capturing
+/// groups and a `named-captures` field with a dictionary of the named ones.
+/// This way, the replacement can be built from parts of the match.
+///
+/// ```example
+/// #show regex("v(\d+)\.(?<minor>\d+)"): it => box(
+///   fill: blue.lighten(80%),
+///   inset: 2pt,
+///   radius: 2pt,
+/// )[*#it.captures.first()* · #it.named-captures.minor]
+///
+/// Upgrade from v1.2 to v2.0.
+/// ```
 #[ty(scope)]
 #[derive(Debug, Clone)]
 pub struct Regex(regex::Regex);
@@ -921,6 +1009,81 @@ cast! {
     v: Regex => Self::Regex(v),
 }
 
+/// A pattern that can be trimmed from a string.
+pub enum TrimPattern {
+    /// A string or regular expression.
+    Pattern(StrPattern),
+    /// A set of grapheme clusters.
+    Set(Vec<Str>),
+    /// A predicate that decides for each grapheme cluster.
+    Func(Func),
+}
+
+cast! {
+    TrimPattern,
+    self => match self {
+        Self::Pattern(v) => v.into_value(),
+        Self::Set(v) => v.into_value(),
+        Self::Func(v) => v.into_value(),
+    },
+    v: StrPattern => Self::Pattern(v),
+    v: Array => Self::Set(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+    v: Func => Self::Func(v),
+}
+
+/// Trims grapheme clusters that match a predicate from the sides of a string.
+fn trim_clusters(
+    s: &str,
+    start: bool,
+    end: bool,
+    repeat: bool,
+    mut matches: impl FnMut(&str) -> SourceResult<bool>,
+) -> SourceResult<&str> {
+    let mut range = 0..s.len();
+
+    if start {
+        for (i, c) in s.grapheme_indices(true) {
+            if !matches(c)? {
+                break;
+            }
+            range.start = i + c.len();
+            if !repeat {
+                break;
+            }
+        }
+    }
+
+    if end {
+        for (i, c) in s[range.start..].grapheme_indices(true).rev() {
+            if !matches(c)? {
+                break;
+            }
+            range.end = range.start + i;
+            if !repeat {
+                break;
+            }
+        }
+    }
+
+    Ok(&s[range])
+}
+
+/// Creates the padding that brings a string to the given width in grapheme
+/// clusters.
+fn padding(s: &str, width: usize, with: &str) -> StrResult<EcoString> {
+    if with.is_empty() {
+        bail!("padding string must not be empty");
+    }
+
+    let mut fill = EcoString::new();
+    let missing = width.saturating_sub(s.graphemes(true).count());
+    for c in with.graphemes(true).cycle().take(missing) {
+        fill.push_str(c);
+    }
+
+    Ok(fill)
+}
+
 /// A side of a string.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum StrSide {
@@ -352,9 +352,13 @@ impl Recipe {
 
     /// Whether the recipe is applicable to the target.
     pub fn applicable(&self, target: &Content) -> bool {
-        self.selector
-            .as_ref()
-            .map_or(false, |selector| selector.matches(target))
+        match &self.selector {
+            // Scoped recipes are applied to their ancestors first, which then
+            // pass them on to their contents.
+            Some(Selector::Within { ancestor, .. }) => ancestor.matches(target),
+            Some(selector) => selector.matches(target),
+            None => false,
+        }
     }
 
     /// Apply the recipe to the given content.
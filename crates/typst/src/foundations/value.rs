@@ -14,12 +14,12 @@ use crate::eval::ops;
 use crate::foundations::{
     fields, repr, Args, Array, AutoValue, Bytes, CastInfo, Content, Datetime, Dict,
     Duration, FromValue, Func, IntoValue, Label, Module, NativeElement, NativeType,
-    NoneValue, Plugin, Reflect, Repr, Scope, Str, Styles, Type, Version,
+    NoneValue, Plugin, Reflect, Repr, Scope, Smart, Str, Styles, Type, Version,
 };
 use crate::layout::{Abs, Angle, Em, Fr, Length, Ratio, Rel};
 use crate::symbols::Symbol;
 use crate::syntax::{ast, Span};
-use crate::text::{RawElem, TextElem};
+use crate::text::{LocalizedElem, RawElem, TextElem};
 use crate::visualize::{Color, Gradient, Pattern};
 
 /// A computational value.
@@ -202,7 +202,15 @@ impl Value {
         match self {
             Self::None => Content::empty(),
             Self::Int(v) => TextElem::packed(repr::format_int_with_base(v, 10)),
+            // Only decimal fractions and dates depend on the text language.
+            Self::Float(v) if v.is_finite() && v.fract() != 0.0 => {
+                LocalizedElem::new(self).pack()
+            }
             Self::Float(v) => TextElem::packed(repr::format_float(v, None, "")),
+            Self::Datetime(v @ Datetime::Time(_)) => {
+                TextElem::packed(v.display(Smart::Auto, None, None).unwrap_or_default())
+            }
+            Self::Datetime(_) => LocalizedElem::new(self).pack(),
             Self::Str(v) => TextElem::packed(v),
             Self::Version(v) => TextElem::packed(eco_format!("{v}")),
             Self::Symbol(v) => TextElem::packed(v.get()),
@@ -231,8 +231,7 @@ impl Counter {
         let sequence = self.sequence(engine)?;
         let offset = engine
             .introspector
-            .query(&self.selector().before(location.into(), true))
-            .len();
+            .query_count(&self.selector().before(location.into(), true));
         let (mut at_state, at_page) = sequence[offset].clone();
         let (mut final_state, final_page) = sequence.last().unwrap().clone();
         if self.is_page() {
@@ -422,8 +421,7 @@ impl Counter {
         let sequence = self.sequence(engine)?;
         let offset = engine
             .introspector
-            .query(&self.selector().before(location.into(), true))
-            .len();
+            .query_count(&self.selector().before(location.into(), true));
         let (mut state, page) = sequence[offset].clone();
         if self.is_page() {
             let delta =
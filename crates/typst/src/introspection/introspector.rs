@@ -1,20 +1,23 @@
-use std::collections::{BTreeSet, HashMap};
-use std::fmt::{self, Debug, Formatter};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
 use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
 use comemo::Prehashed;
-use ecow::{eco_format, EcoVec};
+use ecow::{eco_format, EcoString, EcoVec};
 use indexmap::IndexMap;
+use serde::Serialize;
 use smallvec::SmallVec;
 
 use crate::diag::{bail, StrResult};
 use crate::foundations::{Content, Label, Repr, Selector};
 use crate::introspection::{Location, Meta};
-use crate::layout::{Frame, FrameItem, Point, Position, Transform};
+use crate::layout::{Abs, Bounds, Frame, FrameItem, Point, Position, Size, Transform};
 use crate::model::Numbering;
-use crate::util::NonZeroExt;
+use crate::util::{hash128, NonZeroExt};
 
 /// Can be queried for elements and their positions.
 #[derive(Clone)]
@@ -22,56 +25,241 @@ pub struct Introspector {
     /// The number of pages in the document.
     pages: usize,
     /// All introspectable elements.
-    elems: IndexMap<Location, (Prehashed<Content>, Position)>,
+    elems: IndexMap<Location, (Prehashed<Content>, Position, Bounds)>,
+    /// The index of each element's enclosing element, if any, in the same
+    /// order as `elems`.
+    parents: Vec<Option<usize>>,
     /// Maps labels to their indices in the element list. We use a smallvec such
     /// that if the label is unique, we don't need to allocate.
     labels: HashMap<Label, SmallVec<[usize; 1]>>,
-    /// The page numberings, indexed by page number minus 1.
+    /// The page numberings, indexed by page number minus 1. Pages without
+    /// numbering metadata have a `None` slot or are past the end.
     page_numberings: Vec<Option<Numbering>>,
     /// Caches queries done on the introspector. This is important because
     /// even if all top-level queries are distinct, they often have shared
     /// subqueries. Example: Individual counter queries with `before` that
     /// all depend on a global counter query.
     queries: QueryCache,
+    /// A hash of the introspected elements in document order, but not of
+    /// their positions. Determines whether the query cache survives a
+    /// rebuild.
+    content_hash: u128,
+    /// A hash of the introspected elements, positions, and page numberings.
+    hash: u128,
+    /// Whether the introspection data matches that of the previous layout
+    /// pass.
+    stable: bool,
 }
 
 impl Introspector {
+    /// Creates an introspector for multiple documents that are combined into
+    /// one, for example the individual papers of a proceedings volume.
+    ///
+    /// The pages of each part follow the pages of the previous parts, so page
+    /// numbers are offset by the page counts of all preceding parts. This
+    /// makes it possible to assemble a combined outline from the queried
+    /// elements and their positions.
+    pub fn merge<'a>(parts: impl IntoIterator<Item = &'a [Frame]>) -> Self {
+        let mut introspector = Self::default();
+        introspector.rebuild_from(parts.into_iter().flatten());
+        introspector
+    }
+
+    /// Creates an empty introspector with room for the given number of
+    /// elements and distinct labels.
+    ///
+    /// Documents typically have far fewer labels than elements, so the two
+    /// are reserved separately. Space for the page numberings is reserved
+    /// when rebuilding based on the number of pages.
+    pub fn with_capacity(elems: usize, labels: usize) -> Self {
+        Self {
+            elems: IndexMap::with_capacity(elems),
+            parents: Vec::with_capacity(elems),
+            labels: HashMap::with_capacity(labels),
+            ..Self::default()
+        }
+    }
+
+    /// Limits the number of query results the introspector caches.
+    ///
+    /// Once the limit is reached, the least recently used results are
+    /// dropped and recomputed if needed again. By default, the cache is
+    /// unbounded.
+    pub fn with_query_cache_limit(mut self, limit: usize) -> Self {
+        self.queries.limit = limit;
+        self.queries.clear();
+        self
+    }
+
     /// Applies new frames in-place, reusing the existing allocations.
+    ///
+    /// Cached query results are kept if the new frames contain exactly the
+    /// same elements (including their locations) in the same order as
+    /// before. The results of selectors that depend on positions or page
+    /// numbers, like `page` and `region` selectors, are never cached, so
+    /// these may change freely. As soon as any element is added, removed,
+    /// reordered, or has a different field value, all cached results are
+    /// discarded.
     #[tracing::instrument(skip_all)]
     pub fn rebuild(&mut self, frames: &[Frame]) {
-        self.pages = frames.len();
+        self.page_numberings.reserve(frames.len());
+        self.rebuild_from(frames);
+    }
+
+    /// Applies new frames in-place, re-extracting only the pages from the
+    /// first changed one onwards.
+    ///
+    /// The `frames` are the frames of all pages of the document and
+    /// `changed_pages` lists the indices of those that differ from the frames
+    /// the introspector was last built from. Since elements are ordered by
+    /// their position in the document, all pages after the first changed one
+    /// are extracted again, but the elements and labels on the preceding
+    /// pages are kept. Cached query results are kept under the same
+    /// conditions as for [`rebuild`](Self::rebuild).
+    #[tracing::instrument(skip_all)]
+    pub fn update(&mut self, changed_pages: &[usize], frames: &[Frame]) {
+        let first = changed_pages
+            .iter()
+            .copied()
+            .min()
+            .unwrap_or(usize::MAX)
+            .min(self.pages)
+            .min(frames.len());
+
+        // Drop everything extracted from the first changed page onwards.
+        let kept = self
+            .elems
+            .values()
+            .position(|(_, pos, _)| pos.page.get() > first)
+            .unwrap_or(self.elems.len());
+        self.elems.truncate(kept);
+        self.parents.truncate(kept);
+        self.labels.retain(|_, indices| {
+            indices.retain(|&mut index| index < kept);
+            !indices.is_empty()
+        });
+        self.page_numberings.truncate(first);
+        self.pages = first;
+
+        self.append(&frames[first..]);
+    }
+
+    /// Appends the frames of additional pages after the existing ones.
+    ///
+    /// Elements on the already extracted pages are kept as they are. Cached
+    /// query results are discarded if the new pages contain any locatable
+    /// elements.
+    #[tracing::instrument(skip_all)]
+    pub fn extend(&mut self, frames: &[Frame]) {
+        self.append(frames);
+    }
+
+    /// Applies the frames of consecutive pages in-place.
+    fn rebuild_from<'a>(&mut self, frames: impl IntoIterator<Item = &'a Frame>) {
+        self.pages = 0;
         self.elems.clear();
+        self.parents.clear();
         self.labels.clear();
         self.page_numberings.clear();
-        self.queries.clear();
+        self.append(frames);
+    }
+
+    /// Extracts the frames of consecutive pages after the existing ones.
+    fn append<'a>(&mut self, frames: impl IntoIterator<Item = &'a Frame>) {
+        for frame in frames {
+            self.pages += 1;
+            let page = NonZeroUsize::new(self.pages).unwrap();
+            self.extract(frame, page, Transform::identity(), None);
+        }
 
-        for (i, frame) in frames.iter().enumerate() {
-            let page = NonZeroUsize::new(1 + i).unwrap();
-            self.extract(frame, page, Transform::identity());
+        // Contents are prehashed, so this is cheap.
+        let content_hash = hash128(&self.all().collect::<Vec<_>>());
+        if content_hash != self.content_hash {
+            self.queries.clear();
+            self.content_hash = content_hash;
         }
+
+        self.hash = hash128(&(
+            self.pages,
+            self.elems.values().map(|(elem, pos, _)| (elem, pos)).collect::<Vec<_>>(),
+            &self.page_numberings,
+        ));
+    }
+
+    /// Discards all cached query results.
+    ///
+    /// This is never necessary for correctness: Rebuilding or extending the
+    /// introspector already discards the results whenever the elements or
+    /// their labels change. It is useful to free memory held by the cache
+    /// though.
+    pub fn clear_query_cache(&self) {
+        self.queries.clear();
+    }
+
+    /// Marks this introspector as stable if its data matches the given hash
+    /// of the previous pass's introspector.
+    pub fn settle(&mut self, previous: u128) {
+        self.stable = self.hash == previous;
+    }
+
+    /// Marks this introspector as stable unconditionally.
+    ///
+    /// This should be called once a layout pass converged, that is, once its
+    /// introspections all validated against this introspector.
+    pub fn stabilize(&mut self) {
+        self.stable = true;
+    }
+
+    /// A hash of the introspection data, for use with
+    /// [`settle`](Self::settle).
+    pub fn hash(&self) -> u128 {
+        self.hash
     }
 
     /// Extract metadata from a frame.
+    ///
+    /// The `parent` is the index of the element that encloses the frame, if
+    /// any. Elements whose metadata is in the same frame are siblings. An
+    /// element encloses the elements in the groups that follow its metadata
+    /// in the same frame, since an element's metadata is attached to the frame
+    /// of its content.
     #[tracing::instrument(skip_all)]
-    fn extract(&mut self, frame: &Frame, page: NonZeroUsize, ts: Transform) {
+    fn extract(
+        &mut self,
+        frame: &Frame,
+        page: NonZeroUsize,
+        ts: Transform,
+        parent: Option<usize>,
+    ) {
+        // The element that encloses the groups that follow.
+        let mut enclosing = parent;
         for (pos, item) in frame.items() {
             match item {
                 FrameItem::Group(group) => {
                     let ts = ts
                         .pre_concat(Transform::translate(pos.x, pos.y))
                         .pre_concat(group.transform);
-                    self.extract(&group.frame, page, ts);
+                    self.extract(&group.frame, page, ts, enclosing);
                 }
-                FrameItem::Meta(Meta::Elem(content), _)
-                    if !self.elems.contains_key(&content.location().unwrap()) =>
-                {
+                FrameItem::Meta(Meta::Elem(content), size) => {
+                    // The same element may be laid out more than once, e.g.
+                    // when a fragment is placed into multiple frames. We
+                    // keep only its first occurrence.
+                    let location = content.location().unwrap();
+                    if self.elems.contains_key(&location) {
+                        continue;
+                    }
+
+                    let bounds = Self::bounds_of(*pos, *size, page, ts);
+                    self.parents.push(parent);
+                    enclosing = Some(self.elems.len());
+
                     let pos = pos.transform(ts);
                     let content = Prehashed::new(content.clone());
-                    let ret = self.elems.insert(
-                        content.location().unwrap(),
-                        (content.clone(), Position { page, point: pos }),
+                    self.elems.insert(
+                        location,
+                        (content.clone(), Position { page, point: pos }, bounds),
                     );
-                    assert!(ret.is_none(), "duplicate locations");
 
                     // Build the label cache.
                     if let Some(label) = content.label() {
@@ -79,149 +267,574 @@ impl Introspector {
                     }
                 }
                 FrameItem::Meta(Meta::PageNumbering(numbering), _) => {
-                    self.page_numberings.push(numbering.clone());
+                    // Index by page so that pages without numbering don't
+                    // shift the numberings of later pages. If a page has
+                    // multiple numberings, the first one wins.
+                    let index = page.get() - 1;
+                    if self.page_numberings.len() <= index {
+                        self.page_numberings.resize(index, None);
+                        self.page_numberings.push(numbering.clone());
+                    }
                 }
                 _ => {}
             }
         }
     }
 
+    /// Whether the element at `index` is (transitively) contained in the one
+    /// at `ancestor`.
+    fn is_descendant(&self, index: usize, ancestor: usize) -> bool {
+        let mut current = self.parents[index];
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.parents[parent];
+        }
+        false
+    }
+
+    /// Compute the axis-aligned bounding box of an item with the given
+    /// position and size after applying the transform.
+    fn bounds_of(pos: Point, size: Size, page: NonZeroUsize, ts: Transform) -> Bounds {
+        let corners = [
+            pos,
+            pos + Point::with_x(size.x),
+            pos + Point::with_y(size.y),
+            pos + size.to_point(),
+        ]
+        .map(|corner| corner.transform(ts));
+        let min = corners.into_iter().reduce(Point::min).unwrap();
+        let max = corners.into_iter().reduce(Point::max).unwrap();
+        Bounds { page, point: min, size: (max - min).to_size() }
+    }
+
     /// Iterate over all locatable elements.
     pub fn all(&self) -> impl Iterator<Item = &Prehashed<Content>> + '_ {
-        self.elems.values().map(|(c, _)| c)
+        self.elems.values().map(|(c, _, _)| c)
     }
 
-    /// Get an element by its location.
-    fn get(&self, location: &Location) -> Option<&Prehashed<Content>> {
-        self.elems.get(location).map(|(elem, _)| elem)
+    /// Iterate over all locatable elements together with their positions.
+    ///
+    /// The elements are in document order, which is the same order in which
+    /// [`query`](Self::query) returns them.
+    pub fn all_with_positions(
+        &self,
+    ) -> impl Iterator<Item = (&Prehashed<Content>, &Position)> + '_ {
+        self.elems.values().map(|(elem, pos, _)| (elem, pos))
     }
 
-    /// Get the index of this element among all.
-    fn index(&self, elem: &Content) -> usize {
-        self.elems
-            .get_index_of(&elem.location().unwrap())
-            .unwrap_or(usize::MAX)
+    /// Query for all elements for which the predicate returns `true`, in
+    /// document order.
+    ///
+    /// This is useful for conditions that cannot be expressed as a
+    /// [`Selector`]. Because predicates can be neither hashed nor serialized,
+    /// this is only available from Rust, is not tracked, and bypasses the
+    /// query cache.
+    pub fn query_by(
+        &self,
+        predicate: impl Fn(&Content) -> bool,
+    ) -> EcoVec<Prehashed<Content>> {
+        self.all().filter(|elem| predicate(elem)).cloned().collect()
     }
 
-    /// Perform a binary search for `elem` among the `list`.
-    fn binary_search(
-        &self,
-        list: &[Prehashed<Content>],
-        elem: &Content,
-    ) -> Result<usize, usize> {
-        list.binary_search_by_key(&self.index(elem), |elem| self.index(elem))
+    /// Query for all matching elements and bundle them with their locations
+    /// and positions for serialization.
+    pub fn query_export(&self, selector: &Selector) -> Vec<QueryMatch> {
+        let matches = self.matches(selector);
+        matches
+            .indices
+            .iter()
+            .map(|&index| {
+                let (location, (elem, pos, _)) = self.elems.get_index(index).unwrap();
+                QueryMatch {
+                    element: elem.clone().into_inner(),
+                    location: eco_format!(
+                        "{:032x}-{}-{}",
+                        location.hash,
+                        location.disambiguator,
+                        location.variant
+                    ),
+                    page: pos.page,
+                    x: pos.point.x.to_pt(),
+                    y: pos.point.y.to_pt(),
+                }
+            })
+            .collect()
     }
-}
 
-#[comemo::track]
-impl Introspector {
-    /// Query for all matching elements.
-    pub fn query(&self, selector: &Selector) -> EcoVec<Prehashed<Content>> {
+    /// Iterate over the locations and positions of all locatable elements in
+    /// document order.
+    ///
+    /// This is useful for building an external index of the document without
+    /// looking up each position individually.
+    pub fn table(&self) -> impl Iterator<Item = (Location, Position)> + '_ {
+        self.elems.iter().map(|(&location, (_, position, _))| (location, *position))
+    }
+
+    /// Consumes the introspector and iterates over its elements, together
+    /// with their locations and positions, in document order.
+    ///
+    /// Unlike querying, this moves the elements out instead of cloning them.
+    pub fn into_elements(
+        self,
+    ) -> impl Iterator<Item = (Location, Prehashed<Content>, Position)> {
+        self.into_parts().0
+    }
+
+    /// Consumes the introspector and returns its elements (like
+    /// [`into_elements`](Self::into_elements)) and its page numberings,
+    /// indexed by page number minus 1.
+    pub fn into_parts(
+        self,
+    ) -> (
+        impl Iterator<Item = (Location, Prehashed<Content>, Position)>,
+        Vec<Option<Numbering>>,
+    ) {
+        let elems = self
+            .elems
+            .into_iter()
+            .map(|(location, (elem, position, _))| (location, elem, position));
+        (elems, self.page_numberings)
+    }
+
+    /// The number of locatable elements in the document.
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    /// Whether the document contains no locatable elements.
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
+    /// The number of locatable elements on the given page.
+    pub fn count_on_page(&self, page: NonZeroUsize) -> usize {
+        self.elems.values().filter(|(_, pos, _)| pos.page == page).count()
+    }
+
+    /// Iterate over all distinct labels in the document.
+    ///
+    /// The iteration order is arbitrary and may change between compilations,
+    /// so callers should sort the labels if they need a stable order.
+    pub fn labels(&self) -> impl Iterator<Item = &Label> + '_ {
+        self.labels.keys()
+    }
+
+    /// Get an element by its location.
+    fn get(&self, location: &Location) -> Option<&Prehashed<Content>> {
+        self.elems.get(location).map(|(elem, _, _)| elem)
+    }
+
+    /// Query for all matching elements, together with their indices.
+    ///
+    /// The indices are in ascending order, which makes it possible to
+    /// compare the positions of elements with plain integer comparisons
+    /// instead of looking up each element's index by its location.
+    fn matches(&self, selector: &Selector) -> Matches {
+        // Combinations of a single selector are equivalent to it and an empty
+        // `or` matches nothing, so these need neither computing nor caching.
+        match selector {
+            Selector::And(selectors) | Selector::Or(selectors)
+                if selectors.len() == 1 =>
+            {
+                return self.matches(&selectors[0]);
+            }
+            Selector::Or(selectors) if selectors.is_empty() => {
+                return Matches::default();
+            }
+            _ => {}
+        }
+
+        // The cache survives rebuilds in which only positions change, so the
+        // results of position-dependent selectors must not end up in it.
+        let cacheable = !is_positional(selector);
         let hash = crate::util::hash128(selector);
-        if let Some(output) = self.queries.get(hash) {
-            return output;
+        if cacheable {
+            if let Some(output) = self.queries.get(hash, selector) {
+                return output;
+            }
         }
 
         let output = match selector {
             Selector::Label(label) => self
                 .labels
                 .get(label)
-                .map(|indices| {
-                    indices.iter().map(|&index| self.elems[index].0.clone()).collect()
-                })
+                .map(|indices| self.collect(indices.iter().copied()))
                 .unwrap_or_default(),
-            Selector::Elem(..) | Selector::Regex(_) | Selector::Can(_) => {
-                self.all().filter(|elem| selector.matches(elem)).cloned().collect()
-            }
+            Selector::LabelRegex(regex) => self.collect(
+                self.labels
+                    .iter()
+                    .filter(|(label, _)| regex.is_match(label.as_str()))
+                    .flat_map(|(_, indices)| indices.iter().copied())
+                    .collect::<BTreeSet<usize>>(),
+            ),
+            Selector::Elem(..)
+            | Selector::Compare { .. }
+            | Selector::Regex(_)
+            | Selector::Can(_) => self.collect(
+                self.all()
+                    .enumerate()
+                    .filter(|(_, elem)| selector.matches(elem))
+                    .map(|(index, _)| index),
+            ),
             Selector::Location(location) => {
-                self.get(location).cloned().into_iter().collect()
+                self.collect(self.elems.get_index_of(location))
             }
+            Selector::OnPage(page) => self.collect(
+                self.elems
+                    .values()
+                    .enumerate()
+                    .filter(|(_, (_, pos, _))| pos.page == *page)
+                    .map(|(index, _)| index),
+            ),
+            Selector::InRegion { page, origin, size } => self.collect(
+                self.elems
+                    .values()
+                    .enumerate()
+                    .filter(|(_, (_, pos, _))| in_region(pos, *page, *origin, *size))
+                    .map(|(index, _)| index),
+            ),
             Selector::Before { selector, end, inclusive } => {
-                let mut list = self.query(selector);
-                if let Some(end) = self.query_first(end) {
-                    // Determine which elements are before `end`.
-                    let split = match self.binary_search(&list, &end) {
-                        // Element itself is contained.
-                        Ok(i) => i + *inclusive as usize,
-                        // Element itself is not contained.
-                        Err(i) => i,
-                    };
-                    list = list[..split].into();
+                let list = self.matches(selector);
+                match self.split_before(&list.indices, end, *inclusive) {
+                    Some(split) => list.slice(0..split),
+                    None => list,
                 }
-                list
             }
             Selector::After { selector, start, inclusive } => {
-                let mut list = self.query(selector);
-                if let Some(start) = self.query_first(start) {
-                    // Determine which elements are after `start`.
-                    let split = match self.binary_search(&list, &start) {
-                        // Element itself is contained.
-                        Ok(i) => i + !*inclusive as usize,
-                        // Element itself is not contained.
-                        Err(i) => i,
-                    };
-                    list = list[split..].into();
+                let list = self.matches(selector);
+                match self.split_after(&list.indices, start, *inclusive) {
+                    Some(split) => list.slice(split..list.indices.len()),
+                    None => list,
                 }
-                list
+            }
+            Selector::Between {
+                selector,
+                start,
+                end,
+                inclusive_start,
+                inclusive_end,
+            } => {
+                let list = self.matches(selector);
+                let len = list.indices.len();
+                let from = self.split_after(&list.indices, start, *inclusive_start);
+                let to = self.split_before(&list.indices, end, *inclusive_end);
+                let (from, to) = (from.unwrap_or(0), to.unwrap_or(len));
+                if from < to {
+                    list.slice(from..to)
+                } else {
+                    Matches::default()
+                }
+            }
+            // Like an empty conjunction, an empty `and` matches everything.
+            Selector::And(selectors) if selectors.is_empty() => {
+                self.collect(0..self.elems.len())
             }
             Selector::And(selectors) => {
                 let mut results: Vec<_> =
-                    selectors.iter().map(|sel| self.query(sel)).collect();
+                    selectors.iter().map(|sel| self.matches(sel)).collect();
 
                 // Extract the smallest result list and then keep only those
                 // elements in the smallest list that are also in all other
-                // lists.
-                results
+                // lists. Since every list is in document order, filtering
+                // any of them keeps the result in document order.
+                let smallest = results
                     .iter()
                     .enumerate()
-                    .min_by_key(|(_, vec)| vec.len())
+                    .min_by_key(|(_, list)| list.indices.len())
                     .map(|(i, _)| i)
                     .map(|i| results.swap_remove(i))
-                    .iter()
-                    .flatten()
-                    .filter(|candidate| {
-                        results
-                            .iter()
-                            .all(|other| self.binary_search(other, candidate).is_ok())
-                    })
-                    .cloned()
-                    .collect()
+                    .unwrap_or_default();
+
+                self.collect(smallest.indices.iter().copied().filter(|candidate| {
+                    results
+                        .iter()
+                        .all(|other| other.indices.binary_search(candidate).is_ok())
+                }))
             }
-            Selector::Or(selectors) => selectors
-                .iter()
-                .flat_map(|sel| self.query(sel))
-                .map(|elem| self.index(&elem))
-                .collect::<BTreeSet<usize>>()
-                .into_iter()
-                .map(|index| self.elems[index].0.clone())
-                .collect(),
+            Selector::Not(selector) => {
+                // All indices not in the (sorted) inner list, in order.
+                let excluded = self.matches(selector).indices;
+                let mut excluded = excluded.iter().peekable();
+                self.collect(
+                    (0..self.elems.len())
+                        .filter(|index| excluded.next_if_eq(&index).is_none()),
+                )
+            }
+            Selector::Nth { selector, index } => {
+                let list = self.matches(selector);
+                let len = list.indices.len() as i64;
+                let i = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&i) {
+                    list.slice(i as usize..i as usize + 1)
+                } else {
+                    Matches::default()
+                }
+            }
+            // Within selectors only scope show rules. Casting a selector for
+            // a query rejects them, so they never end up here.
+            Selector::Within { .. } => {
+                debug_assert!(false, "within selectors cannot be queried");
+                Matches::default()
+            }
+            // The set sorts and deduplicates the indices, so that the result
+            // is in document order rather than in the order of the selectors.
+            Selector::Or(selectors) => self.collect(
+                selectors
+                    .iter()
+                    .flat_map(|sel| self.matches(sel).indices)
+                    .collect::<BTreeSet<usize>>(),
+            ),
         };
 
-        self.queries.insert(hash, output.clone());
+        if cacheable {
+            self.queries.insert(hash, selector, output.clone());
+        }
+
         output
     }
 
+    /// Collect the elements at the given ascending indices.
+    fn collect(&self, indices: impl IntoIterator<Item = usize>) -> Matches {
+        let indices: EcoVec<usize> = indices.into_iter().collect();
+        debug_assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        let elems = indices.iter().map(|&index| self.elems[index].0.clone()).collect();
+        Matches { elems, indices }
+    }
+
+    /// The index of the first element that matches the selector.
+    fn first_index(&self, selector: &Selector) -> Option<usize> {
+        match selector {
+            Selector::Location(location) => self.elems.get_index_of(location),
+            Selector::Label(label) => {
+                self.labels.get(label).and_then(|indices| indices.first()).copied()
+            }
+            _ => self.matches(selector).indices.first().copied(),
+        }
+    }
+
+    /// Determine how many elements of the `list` come before `end`.
+    ///
+    /// Returns `None` if `end` does not match any element.
+    fn split_before(
+        &self,
+        list: &[usize],
+        end: &Selector,
+        inclusive: bool,
+    ) -> Option<usize> {
+        let end = self.first_index(end)?;
+        Some(match list.binary_search(&end) {
+            // Element itself is contained.
+            Ok(i) => i + inclusive as usize,
+            // Element itself is not contained.
+            Err(i) => i,
+        })
+    }
+
+    /// Determine at which index of the `list` the elements after `start`
+    /// begin.
+    ///
+    /// Returns `None` if `start` does not match any element.
+    fn split_after(
+        &self,
+        list: &[usize],
+        start: &Selector,
+        inclusive: bool,
+    ) -> Option<usize> {
+        let start = self.first_index(start)?;
+        Some(match list.binary_search(&start) {
+            // Element itself is contained.
+            Ok(i) => i + !inclusive as usize,
+            // Element itself is not contained.
+            Err(i) => i,
+        })
+    }
+}
+
+#[comemo::track]
+impl Introspector {
+    /// Query for all matching elements.
+    ///
+    /// The results are in document order. In particular, `or` and `and`
+    /// selectors do not preserve the order of their sub-selectors.
+    ///
+    /// [`Within`](Selector::Within) selectors are only supported in show
+    /// rules and must not be passed here.
+    pub fn query(&self, selector: &Selector) -> EcoVec<Prehashed<Content>> {
+        self.matches(selector).elems
+    }
+
+    /// Query for all matching elements together with their positions.
+    pub fn query_with_positions(
+        &self,
+        selector: &Selector,
+    ) -> EcoVec<(Prehashed<Content>, Position)> {
+        let matches = self.matches(selector);
+        matches
+            .elems
+            .iter()
+            .zip(&matches.indices)
+            .map(|(elem, &index)| (elem.clone(), self.elems[index].1))
+            .collect()
+    }
+
+    /// Count the elements that match the selector.
+    ///
+    /// For labels, locations, and simple element selectors, this counts the
+    /// matches without collecting them into a list. For `before` and `after`
+    /// selectors, this avoids materializing and caching a new list of
+    /// elements for each distinct boundary. This keeps location-dependent
+    /// counter and state lookups cheap, even if there are hundreds of them.
+    pub fn query_count(&self, selector: &Selector) -> usize {
+        match selector {
+            Selector::Label(label) => self.labels.get(label).map_or(0, |list| list.len()),
+            Selector::Location(location) => self.elems.contains_key(location) as usize,
+            Selector::Elem(..)
+            | Selector::Compare { .. }
+            | Selector::Regex(_)
+            | Selector::Can(_) => {
+                let hash = crate::util::hash128(selector);
+                match self.queries.get(hash, selector) {
+                    Some(output) => output.indices.len(),
+                    None => self.all().filter(|elem| selector.matches(elem)).count(),
+                }
+            }
+            Selector::Before { selector, end, inclusive } => {
+                let list = self.matches(selector).indices;
+                self.split_before(&list, end, *inclusive).unwrap_or(list.len())
+            }
+            Selector::After { selector, start, inclusive } => {
+                let list = self.matches(selector).indices;
+                list.len() - self.split_after(&list, start, *inclusive).unwrap_or(0)
+            }
+            _ => self.query(selector).len(),
+        }
+    }
+
     /// Query for the first element that matches the selector.
     pub fn query_first(&self, selector: &Selector) -> Option<Prehashed<Content>> {
         match selector {
             Selector::Location(location) => self.get(location).cloned(),
+            Selector::Label(label) => self
+                .labels
+                .get(label)
+                .and_then(|indices| indices.first())
+                .map(|&index| self.elems[index].0.clone()),
             _ => self.query(selector).first().cloned(),
         }
     }
 
-    /// Query for a unique element with the label.
-    pub fn query_label(&self, label: Label) -> StrResult<&Prehashed<Content>> {
-        let indices = self.labels.get(&label).ok_or_else(|| {
-            eco_format!("label `{}` does not exist in the document", label.repr())
-        })?;
+    /// Query for the last element matching the selector that is at or before
+    /// the given location in document order.
+    ///
+    /// This only binary searches the cached matches of the selector, so it
+    /// stays cheap when it is called once per page, as it is for running
+    /// headers that show the current chapter.
+    ///
+    /// Returns `None` if there is no such element or if the location does not
+    /// belong to any element.
+    pub fn query_last_before(
+        &self,
+        selector: &Selector,
+        location: Location,
+    ) -> Option<Prehashed<Content>> {
+        let target = self.elems.get_index_of(&location)?;
+        let matches = self.matches(selector);
+        let i = match matches.indices.binary_search(&target) {
+            Ok(i) => i,
+            Err(i) => i.checked_sub(1)?,
+        };
+        Some(matches.elems[i].clone())
+    }
+
+    /// Query for the first element matching the selector that is at or after
+    /// the given location in document order.
+    ///
+    /// Returns `None` if there is no such element or if the location does not
+    /// belong to any element.
+    pub fn query_first_after(
+        &self,
+        selector: &Selector,
+        location: Location,
+    ) -> Option<Prehashed<Content>> {
+        let target = self.elems.get_index_of(&location)?;
+        let matches = self.matches(selector);
+        let i = match matches.indices.binary_search(&target) {
+            Ok(i) | Err(i) => i,
+        };
+        matches.elems.get(i).cloned()
+    }
+
+    /// Query for the single element that matches the selector.
+    ///
+    /// Unlike [`query_first`](Self::query_first), which silently picks the
+    /// first match in document order, this fails if the selector is
+    /// ambiguous. This is useful to check the anchors of `before` and `after`
+    /// selectors, which are resolved to their first match.
+    pub fn query_first_strict(
+        &self,
+        selector: &Selector,
+    ) -> StrResult<Option<Prehashed<Content>>> {
+        let list = match selector {
+            Selector::Location(location) => return Ok(self.get(location).cloned()),
+            _ => self.query(selector),
+        };
+        if list.len() > 1 {
+            bail!("selector {} matches {} elements", selector.repr(), list.len());
+        }
+        Ok(list.first().cloned())
+    }
+
+    /// Query for the last element that matches the selector.
+    pub fn query_last(&self, selector: &Selector) -> Option<Prehashed<Content>> {
+        match selector {
+            Selector::Location(location) => self.get(location).cloned(),
+            Selector::Label(label) => self
+                .labels
+                .get(label)
+                .and_then(|indices| indices.last())
+                .map(|&index| self.elems[index].0.clone()),
+            Selector::Before { selector, end, inclusive } => {
+                let list = self.matches(selector);
+                let split = self
+                    .split_before(&list.indices, end, *inclusive)
+                    .unwrap_or(list.elems.len());
+                split.checked_sub(1).map(|i| list.elems[i].clone())
+            }
+            Selector::After { selector, start, inclusive } => {
+                let list = self.matches(selector);
+                let split =
+                    self.split_after(&list.indices, start, *inclusive).unwrap_or(0);
+                list.elems.get(split..).and_then(|rest| rest.last()).cloned()
+            }
+            _ => self.query(selector).last().cloned(),
+        }
+    }
 
+    /// Query for a unique element with the label.
+    pub fn query_label(&self, label: Label) -> Result<&Prehashed<Content>, LabelError> {
+        let indices = self.labels.get(&label).ok_or(LabelError::NotFound(label))?;
         if indices.len() > 1 {
-            bail!("label `{}` occurs multiple times in the document", label.repr());
+            return Err(LabelError::Ambiguous { label, count: indices.len() });
         }
 
         Ok(&self.elems[indices[0]].0)
     }
 
+    /// Query for all elements with the label, in document order.
+    ///
+    /// Unlike [`query_label`](Self::query_label), this doesn't fail if the
+    /// label occurs multiple times or not at all.
+    pub fn query_label_all(&self, label: Label) -> EcoVec<Prehashed<Content>> {
+        self.labels
+            .get(&label)
+            .map(|indices| {
+                indices.iter().map(|&index| self.elems[index].0.clone()).collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// The total number pages.
     pub fn pages(&self) -> NonZeroUsize {
         NonZeroUsize::new(self.pages).unwrap_or(NonZeroUsize::ONE)
@@ -229,12 +842,49 @@ impl Introspector {
 
     /// Gets the page numbering for the given location, if any.
     pub fn page_numbering(&self, location: Location) -> Option<&Numbering> {
-        let page = self.page(location);
+        self.numbering_for_page(self.page(location))
+    }
+
+    /// Gets the page numbering of the given page, or `None` if the page has
+    /// no numbering or doesn't exist.
+    pub fn numbering_for_page(&self, page: NonZeroUsize) -> Option<&Numbering> {
         self.page_numberings
             .get(page.get() - 1)
             .and_then(|slot| slot.as_ref())
     }
 
+    /// Get the element at the given location.
+    pub fn element_at(&self, location: Location) -> Option<&Prehashed<Content>> {
+        self.get(&location)
+    }
+
+    /// Query for the matching elements that are contained in the element at
+    /// the `ancestor` location, in document order.
+    ///
+    /// Containment is determined from the nesting of the laid-out frames: An
+    /// element is contained in another one if its metadata is in a group that
+    /// follows the other's metadata in the same frame, or in a group nested
+    /// therein. Only the parts of the ancestor on its first page are
+    /// considered.
+    pub fn query_within(
+        &self,
+        ancestor: Location,
+        selector: &Selector,
+    ) -> EcoVec<Prehashed<Content>> {
+        let Some(ancestor) = self.elems.get_index_of(&ancestor) else {
+            return EcoVec::new();
+        };
+
+        let matches = self.matches(selector);
+        matches
+            .indices
+            .iter()
+            .zip(&matches.elems)
+            .filter(|&(&index, _)| self.is_descendant(index, ancestor))
+            .map(|(_, elem)| elem.clone())
+            .collect()
+    }
+
     /// Find the page number for the given location.
     pub fn page(&self, location: Location) -> NonZeroUsize {
         self.position(location).page
@@ -244,9 +894,142 @@ impl Introspector {
     pub fn position(&self, location: Location) -> Position {
         self.elems
             .get(&location)
-            .map(|(_, loc)| *loc)
+            .map(|(_, loc, _)| *loc)
             .unwrap_or(Position { page: NonZeroUsize::ONE, point: Point::zero() })
     }
+
+    /// Find the bounding box of the element at the given location.
+    ///
+    /// The box's corner may differ from the element's
+    /// [position](Self::position) if the element is rotated or mirrored.
+    pub fn bounds(&self, location: Location) -> Bounds {
+        self.elems.get(&location).map(|(_, _, bounds)| *bounds).unwrap_or(Bounds {
+            page: NonZeroUsize::ONE,
+            point: Point::zero(),
+            size: Size::zero(),
+        })
+    }
+
+    /// Find the elements on the given page that are positioned at or near the
+    /// given point, for example to map a click in a viewer back to the
+    /// elements around it.
+    ///
+    /// An element is included if its recorded position is at most `radius`
+    /// away from the point. The elements are sorted by their distance to the
+    /// point, nearest first, and otherwise remain in document order.
+    pub fn at_point(
+        &self,
+        page: NonZeroUsize,
+        point: Point,
+        radius: Abs,
+    ) -> EcoVec<Prehashed<Content>> {
+        let mut found: Vec<_> = self
+            .elems
+            .values()
+            .filter(|(_, pos, _)| pos.page == page)
+            .map(|(elem, pos, _)| (elem, (pos.point - point).hypot()))
+            .filter(|(_, distance)| *distance <= radius)
+            .collect();
+        found.sort_by_key(|&(_, distance)| distance);
+        found.into_iter().map(|(elem, _)| elem.clone()).collect()
+    }
+
+    /// Compute, for each page, the bounding box that encloses the bounding
+    /// boxes of all elements matching the selector on that page.
+    ///
+    /// The result is sorted by page. Pages without matches are omitted.
+    pub fn query_bounds(&self, selector: &Selector) -> Vec<Bounds> {
+        let mut pages = BTreeMap::<NonZeroUsize, (Point, Point)>::new();
+        for &index in self.matches(selector).indices.iter() {
+            let bounds = self.elems[index].2;
+            let end = bounds.point + bounds.size.to_point();
+            pages
+                .entry(bounds.page)
+                .and_modify(|(min, max)| {
+                    *min = min.min(bounds.point);
+                    *max = max.max(end);
+                })
+                .or_insert((bounds.point, end));
+        }
+
+        pages
+            .into_iter()
+            .map(|(page, (min, max))| Bounds {
+                page,
+                point: min,
+                size: (max - min).to_size(),
+            })
+            .collect()
+    }
+
+    /// The first and last page on which elements matching the selector
+    /// appear, or `None` if there are no matches.
+    ///
+    /// The matches are scanned in full since document order does not imply
+    /// page order, e.g. for floating elements.
+    pub fn page_range(
+        &self,
+        selector: &Selector,
+    ) -> Option<(NonZeroUsize, NonZeroUsize)> {
+        self.matches(selector)
+            .indices
+            .iter()
+            .map(|&index| self.elems[index].1.page)
+            .fold(None, |range, page| match range {
+                Some((min, max)) => Some((page.min(min), page.max(max))),
+                None => Some((page, page)),
+            })
+    }
+
+    /// Find the element on the given page whose position is closest to the
+    /// given point.
+    ///
+    /// If multiple elements are equally close, the first one in document
+    /// order is returned.
+    pub fn nearest(
+        &self,
+        page: NonZeroUsize,
+        point: Point,
+    ) -> Option<&Prehashed<Content>> {
+        self.elems
+            .values()
+            .filter(|(_, pos, _)| pos.page == page)
+            .min_by_key(|(_, pos, _)| (pos.point - point).hypot())
+            .map(|(elem, _, _)| elem)
+    }
+
+    /// Find the elements on the given page whose positions lie within the
+    /// rectangle that starts at `origin` and spans `size`.
+    ///
+    /// Points on the rectangle's border count as inside. A rectangle with zero
+    /// or negative width or height contains no elements.
+    pub fn query_in_region(
+        &self,
+        page: NonZeroUsize,
+        origin: Point,
+        size: Size,
+    ) -> EcoVec<Prehashed<Content>> {
+        self.elems
+            .values()
+            .filter(|(_, pos, _)| in_region(pos, page, origin, size))
+            .map(|(elem, _, _)| elem.clone())
+            .collect()
+    }
+
+    /// Find all elements on the given page.
+    pub fn query_page(&self, page: NonZeroUsize) -> EcoVec<Prehashed<Content>> {
+        self.elems
+            .values()
+            .filter(|(_, pos, _)| pos.page == page)
+            .map(|(elem, _, _)| elem.clone())
+            .collect()
+    }
+
+    /// Whether the introspection data is stable, that is, whether the previous
+    /// layout pass didn't change it anymore.
+    pub fn stable(&self) -> bool {
+        self.stable
+    }
 }
 
 impl Default for Introspector {
@@ -254,9 +1037,13 @@ impl Default for Introspector {
         Self {
             pages: 0,
             elems: IndexMap::new(),
+            parents: vec![],
             labels: HashMap::new(),
             page_numberings: vec![],
             queries: QueryCache::default(),
+            content_hash: 0,
+            hash: 0,
+            stable: false,
         }
     }
 }
@@ -267,26 +1054,687 @@ impl Debug for Introspector {
     }
 }
 
+/// Whether the position lies within the rectangle on the given page that
+/// starts at `origin` and spans `size`, including its border.
+fn in_region(pos: &Position, page: NonZeroUsize, origin: Point, size: Size) -> bool {
+    if size.x <= Abs::zero() || size.y <= Abs::zero() {
+        return false;
+    }
+
+    let end = origin + size.to_point();
+    pos.page == page
+        && (origin.x..=end.x).contains(&pos.point.x)
+        && (origin.y..=end.y).contains(&pos.point.y)
+}
+
+/// Whether the selector's matches depend on the positions of elements and
+/// not just on the elements themselves.
+fn is_positional(selector: &Selector) -> bool {
+    match selector {
+        Selector::OnPage(_) | Selector::InRegion { .. } => true,
+        Selector::Or(selectors) | Selector::And(selectors) => {
+            selectors.iter().any(is_positional)
+        }
+        Selector::Not(selector) | Selector::Nth { selector, .. } => {
+            is_positional(selector)
+        }
+        Selector::Before { selector, end: anchor, .. }
+        | Selector::After { selector, start: anchor, .. }
+        | Selector::Within { selector, ancestor: anchor } => {
+            is_positional(selector) || is_positional(anchor)
+        }
+        Selector::Between { selector, start, end, .. } => {
+            is_positional(selector) || is_positional(start) || is_positional(end)
+        }
+        _ => false,
+    }
+}
+
+/// A query match with its location and position.
+///
+/// This is meant for exporting query results to external tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryMatch {
+    /// The matched element.
+    pub element: Content,
+    /// An identifier for the element's location that is unique within the
+    /// document.
+    pub location: EcoString,
+    /// The page the element is on, starting at 1.
+    pub page: NonZeroUsize,
+    /// The horizontal distance from the page's left edge, in points.
+    pub x: f64,
+    /// The vertical distance from the page's top edge, in points.
+    pub y: f64,
+}
+
+/// An error that occurred while querying for a unique label.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LabelError {
+    /// No element has the label.
+    NotFound(Label),
+    /// Multiple elements have the label.
+    Ambiguous {
+        /// The queried label.
+        label: Label,
+        /// How many elements have the label.
+        count: usize,
+    },
+}
+
+impl std::error::Error for LabelError {}
+
+impl Display for LabelError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound(label) => {
+                write!(f, "label `{}` does not exist in the document", label.repr())
+            }
+            Self::Ambiguous { label, .. } => {
+                let label = label.repr();
+                write!(f, "label `{label}` occurs multiple times in the document")
+            }
+        }
+    }
+}
+
+impl From<LabelError> for EcoString {
+    fn from(err: LabelError) -> Self {
+        eco_format!("{err}")
+    }
+}
+
+/// The elements matched by a query and their indices among all elements.
+///
+/// The matches are always in document order, i.e. the indices are strictly
+/// ascending, regardless of how the selector combines its sub-selectors. The
+/// `Before`, `After`, and `Between` selectors rely on this to binary search
+/// for their anchors.
+#[derive(Default, Clone)]
+struct Matches {
+    elems: EcoVec<Prehashed<Content>>,
+    indices: EcoVec<usize>,
+}
+
+impl Matches {
+    /// Extract a subrange of the matches.
+    fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            elems: self.elems[range.clone()].into(),
+            indices: self.indices[range].into(),
+        }
+    }
+}
+
 /// Caches queries.
-#[derive(Default)]
-struct QueryCache(RwLock<HashMap<u128, EcoVec<Prehashed<Content>>>>);
+///
+/// The cache is guarded by a read-write lock, so that the introspector stays
+/// `Sync` and can be queried from multiple threads at once. Cache hits only
+/// take the read lock.
+///
+/// Entries are keyed by the selector's hash, but also store the selector
+/// itself. A lookup only hits if the stored selector is equal to the queried
+/// one, so that a hash collision results in a cache miss instead of wrong
+/// query results.
+///
+/// Once the cache holds `limit` entries, the least recently used entry is
+/// evicted for each new one. Evicted queries are simply recomputed when they
+/// are needed again.
+struct QueryCache {
+    map: RwLock<HashMap<u128, CacheEntry>>,
+    limit: usize,
+    clock: AtomicU64,
+}
+
+/// An entry in the query cache.
+struct CacheEntry {
+    selector: Selector,
+    output: Matches,
+    /// The value of the cache's clock when the entry was last used.
+    used: AtomicU64,
+}
 
 impl QueryCache {
-    fn get(&self, hash: u128) -> Option<EcoVec<Prehashed<Content>>> {
-        self.0.read().unwrap().get(&hash).cloned()
+    fn get(&self, hash: u128, selector: &Selector) -> Option<Matches> {
+        let map = self.map.read().unwrap();
+        let entry = map.get(&hash).filter(|entry| entry.selector == *selector)?;
+        entry.used.store(self.tick(), Ordering::Relaxed);
+        Some(entry.output.clone())
     }
 
-    fn insert(&self, hash: u128, output: EcoVec<Prehashed<Content>>) {
-        self.0.write().unwrap().insert(hash, output);
+    fn insert(&self, hash: u128, selector: &Selector, output: Matches) {
+        if self.limit == 0 {
+            return;
+        }
+
+        let mut map = self.map.write().unwrap();
+        if map.len() >= self.limit && !map.contains_key(&hash) {
+            let lru = map
+                .iter()
+                .min_by_key(|(_, entry)| entry.used.load(Ordering::Relaxed))
+                .map(|(&hash, _)| hash);
+            if let Some(lru) = lru {
+                map.remove(&lru);
+            }
+        }
+
+        let used = AtomicU64::new(self.tick());
+        map.insert(hash, CacheEntry { selector: selector.clone(), output, used });
     }
 
-    fn clear(&mut self) {
-        self.0.get_mut().unwrap().clear();
+    fn clear(&self) {
+        self.map.write().unwrap().clear();
+    }
+
+    /// Advance the clock that determines how recently entries were used.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self {
+            map: RwLock::new(HashMap::new()),
+            limit: usize::MAX,
+            clock: AtomicU64::new(0),
+        }
     }
 }
 
 impl Clone for QueryCache {
     fn clone(&self) -> Self {
-        Self(RwLock::new(self.0.read().unwrap().clone()))
+        Self {
+            map: RwLock::new(self.map.read().unwrap().clone()),
+            limit: self.limit,
+            clock: AtomicU64::new(self.clock.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Clone for CacheEntry {
+    fn clone(&self) -> Self {
+        Self {
+            selector: self.selector.clone(),
+            output: self.output.clone(),
+            used: AtomicU64::new(self.used.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ecow::eco_vec;
+
+    use super::*;
+    use crate::foundations::{NativeElement, Value};
+    use crate::introspection::MetadataElem;
+    use crate::layout::GroupItem;
+
+    /// Build an introspector with one page containing metadata elements
+    /// with the given location hashes.
+    fn introspector(hashes: &[u128]) -> Introspector {
+        let mut frame = Frame::soft(Size::zero());
+        for &hash in hashes {
+            let mut elem = MetadataElem::new(Value::None).pack();
+            elem.set_location(location(hash));
+            frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(elem), Size::zero()));
+        }
+
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[frame]);
+        introspector
+    }
+
+    /// Build a page with metadata elements with the given location hashes
+    /// and labels.
+    fn labelled(elems: &[(u128, &str)]) -> Frame {
+        let mut frame = Frame::soft(Size::zero());
+        for &(hash, name) in elems {
+            let mut elem =
+                MetadataElem::new(Value::None).pack().labelled(Label::new(name));
+            elem.set_location(location(hash));
+            frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(elem), Size::zero()));
+        }
+        frame
+    }
+
+    fn location(hash: u128) -> Location {
+        Location { hash, disambiguator: 0, variant: 0 }
+    }
+
+    #[test]
+    fn test_before_and_after_with_missing_anchor() {
+        let introspector = introspector(&[1, 2, 3]);
+        let all = Arc::new(MetadataElem::elem().select());
+        let before = |hash, inclusive| Selector::Before {
+            selector: all.clone(),
+            end: Arc::new(Selector::Location(location(hash))),
+            inclusive,
+        };
+        let after = |hash, inclusive| Selector::After {
+            selector: all.clone(),
+            start: Arc::new(Selector::Location(location(hash))),
+            inclusive,
+        };
+        let count = |selector| introspector.query(&selector).len();
+
+        // A present anchor constrains the result.
+        assert_eq!(count(before(2, true)), 2);
+        assert_eq!(count(after(2, false)), 1);
+
+        // A missing anchor doesn't constrain the result at all.
+        assert_eq!(count(before(4, true)), 3);
+        assert_eq!(count(after(4, false)), 3);
+        assert_eq!(introspector.query_count(&before(4, false)), 3);
+    }
+
+    #[test]
+    fn test_query_within_group() {
+        let meta = |hash, size| {
+            let mut elem = MetadataElem::new(Value::None).pack();
+            elem.set_location(location(hash));
+            FrameItem::Meta(Meta::Elem(elem), size)
+        };
+
+        // Element 1 encloses the group with elements 2 and 3, which are
+        // siblings. Element 3 encloses the nested group with element 4.
+        // Element 5 is in the same frame as element 1 and thus not enclosed by
+        // it, even though it lies within its bounds.
+        let size = Size::splat(Abs::pt(10.0));
+        let mut innermost = Frame::soft(size);
+        innermost.push(Point::zero(), meta(4, size));
+        let mut inner = Frame::soft(size);
+        inner.push(Point::zero(), meta(2, size));
+        inner.push(Point::zero(), meta(3, size));
+        inner.push(Point::zero(), FrameItem::Group(GroupItem::new(innermost)));
+        let mut frame = Frame::soft(size);
+        frame.push(Point::zero(), meta(1, size));
+        frame.push(Point::zero(), FrameItem::Group(GroupItem::new(inner)));
+        frame.push(Point::zero(), meta(5, Size::zero()));
+
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[frame]);
+        let all = MetadataElem::elem().select();
+        let within = |hash| {
+            introspector
+                .query_within(location(hash), &all)
+                .iter()
+                .map(|elem| elem.location().unwrap().hash)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(within(1), [2, 3, 4]);
+        assert!(within(2).is_empty());
+        assert_eq!(within(3), [4]);
+        assert!(within(5).is_empty());
+        assert!(within(6).is_empty());
+    }
+
+    #[test]
+    fn test_or_and_and_are_in_document_order() {
+        let introspector = introspector(&[1, 2, 3, 4]);
+        let loc = |hash| Selector::Location(location(hash));
+        let hashes = |selector| {
+            introspector
+                .query(&selector)
+                .iter()
+                .map(|elem| elem.location().unwrap().hash)
+                .collect::<Vec<_>>()
+        };
+
+        // The order of the sub-selectors does not matter.
+        let or = Selector::Or(eco_vec![loc(3), loc(1), loc(4), loc(1)]);
+        assert_eq!(hashes(or.clone()), [1, 3, 4]);
+
+        // Neither does which sub-selector has the fewest matches.
+        let and = Selector::And(eco_vec![
+            Selector::Or(eco_vec![loc(4), loc(2), loc(3)]),
+            Selector::Or(eco_vec![loc(4), loc(3), loc(2), loc(1)]),
+        ]);
+        assert_eq!(hashes(and.clone()), [2, 3, 4]);
+
+        // Combinators that binary search rely on the document order.
+        let before = |selector| Selector::Before {
+            selector: Arc::new(selector),
+            end: Arc::new(loc(3)),
+            inclusive: false,
+        };
+        assert_eq!(hashes(before(or)), [1]);
+        assert_eq!(hashes(before(and)), [2]);
+    }
+
+    #[test]
+    fn test_lengths_and_labels() {
+        let mut introspector = introspector(&[1, 2]);
+        assert_eq!(introspector.len(), 2);
+        assert!(!introspector.is_empty());
+        assert_eq!(introspector.count_on_page(NonZeroUsize::ONE), 2);
+        assert_eq!(introspector.count_on_page(NonZeroUsize::new(2).unwrap()), 0);
+        assert_eq!(introspector.labels().count(), 0);
+
+        introspector.rebuild(&[]);
+        assert_eq!(introspector.len(), 0);
+        assert!(introspector.is_empty());
+
+        // Labels are distinct, even if multiple elements share one.
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b"), (3, "a")])]);
+        let mut labels: Vec<_> = introspector.labels().map(Label::as_str).collect();
+        labels.sort();
+        assert_eq!(labels, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_cached_label_query_after_rebuild() {
+        let label = Label::new("a");
+        let selector = Selector::Label(label);
+        let hashes = |introspector: &Introspector| {
+            introspector
+                .query(&selector)
+                .iter()
+                .map(|elem| elem.location().unwrap().hash)
+                .collect::<Vec<_>>()
+        };
+
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b")])]);
+        assert_eq!(hashes(&introspector), [1]);
+        assert!(introspector.query_label(label).is_ok());
+
+        // The label becomes ambiguous and the cached result must not survive.
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b"), (3, "a")])]);
+        assert_eq!(hashes(&introspector), [1, 3]);
+        assert!(matches!(
+            introspector.query_label(label),
+            Err(LabelError::Ambiguous { count: 2, .. }),
+        ));
+
+        // The label moves to a different element.
+        introspector.rebuild(&[labelled(&[(1, "b"), (2, "a")])]);
+        assert_eq!(hashes(&introspector), [2]);
+
+        introspector.clear_query_cache();
+        assert!(introspector.queries.map.read().unwrap().is_empty());
+        assert_eq!(hashes(&introspector), [2]);
+    }
+
+    #[test]
+    fn test_positional_query_after_rebuild() {
+        let page = |n| NonZeroUsize::new(n).unwrap();
+        let on_page = Selector::OnPage(page(2));
+        let b_on_page = Selector::And(eco_vec![
+            Selector::Label(Label::new("b")),
+            Selector::OnPage(page(2)),
+        ]);
+        let hashes = |introspector: &Introspector, selector| {
+            introspector
+                .query(selector)
+                .iter()
+                .map(|elem| elem.location().unwrap().hash)
+                .collect::<Vec<_>>()
+        };
+
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b")]), labelled(&[])]);
+        assert!(hashes(&introspector, &on_page).is_empty());
+        assert!(hashes(&introspector, &b_on_page).is_empty());
+        assert!(introspector.query_page(page(2)).is_empty());
+
+        // The second element moves to the next page, but the elements stay
+        // the same, so the cache survives the rebuild.
+        introspector.rebuild(&[labelled(&[(1, "a")]), labelled(&[(2, "b")])]);
+        assert_eq!(hashes(&introspector, &on_page), [2]);
+        assert_eq!(hashes(&introspector, &b_on_page), [2]);
+        assert_eq!(introspector.query_page(page(2)).len(), 1);
+    }
+
+    #[test]
+    fn test_page_range() {
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[
+            labelled(&[(1, "a")]),
+            labelled(&[(2, "b")]),
+            labelled(&[(3, "a"), (4, "b")]),
+        ]);
+
+        let page = |n| NonZeroUsize::new(n).unwrap();
+        let range = |name| introspector.page_range(&Selector::Label(Label::new(name)));
+        assert_eq!(range("a"), Some((page(1), page(3))));
+        assert_eq!(range("b"), Some((page(2), page(3))));
+        assert_eq!(range("c"), None);
+    }
+
+    #[test]
+    fn test_duplicate_locations_keep_first() {
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b")]), labelled(&[(1, "a")])]);
+        assert_eq!(introspector.len(), 2);
+        assert_eq!(introspector.position(location(1)).page, NonZeroUsize::ONE);
+        assert_eq!(introspector.query_label_all(Label::new("a")).len(), 1);
+    }
+
+    #[test]
+    fn test_single_and_empty_combinations() {
+        let introspector = introspector(&[1, 2, 3]);
+        let loc = Selector::Location(location(2));
+        let count = |selector| introspector.query(&selector).len();
+
+        assert_eq!(count(Selector::And(eco_vec![])), 3);
+        assert_eq!(count(Selector::Or(eco_vec![])), 0);
+        assert_eq!(count(Selector::And(eco_vec![loc.clone()])), 1);
+        assert_eq!(count(Selector::Or(eco_vec![loc.clone()])), 1);
+        assert_eq!(
+            count(Selector::Or(eco_vec![Selector::Or(eco_vec![loc.clone()]), loc])),
+            1,
+        );
+
+        // Empty `and` selectors constrain nothing when nested.
+        let before = Selector::Before {
+            selector: Arc::new(Selector::And(eco_vec![])),
+            end: Arc::new(Selector::Location(location(3))),
+            inclusive: false,
+        };
+        assert_eq!(count(before), 2);
+    }
+
+    #[test]
+    fn test_query_last_before_and_first_after() {
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b"), (3, "b"), (4, "a")])]);
+        let a = Selector::Label(Label::new("a"));
+        let hash = |elem: Option<Prehashed<Content>>| {
+            elem.map(|elem| elem.location().unwrap().hash)
+        };
+
+        // The location itself may match.
+        assert_eq!(hash(introspector.query_last_before(&a, location(1))), Some(1));
+        assert_eq!(hash(introspector.query_first_after(&a, location(4))), Some(4));
+
+        // Otherwise, the nearest match in the direction is found.
+        assert_eq!(hash(introspector.query_last_before(&a, location(3))), Some(1));
+        assert_eq!(hash(introspector.query_first_after(&a, location(2))), Some(4));
+
+        // There are no matches beyond the ends.
+        let b = Selector::Label(Label::new("b"));
+        assert_eq!(hash(introspector.query_last_before(&b, location(1))), None);
+        assert_eq!(hash(introspector.query_first_after(&b, location(4))), None);
+
+        // Unknown locations have no position in the document.
+        assert_eq!(hash(introspector.query_last_before(&a, location(5))), None);
+    }
+
+    #[test]
+    fn test_with_capacity_does_not_reallocate() {
+        let elems: Vec<_> =
+            (0..100u128).map(|hash| (hash, ["a", "b"][hash as usize % 2])).collect();
+        let mut introspector = Introspector::with_capacity(100, 2);
+        let capacities = |introspector: &Introspector| {
+            (
+                introspector.elems.capacity(),
+                introspector.parents.capacity(),
+                introspector.labels.capacity(),
+            )
+        };
+
+        let before = capacities(&introspector);
+        introspector.rebuild(&[labelled(&elems[..50]), labelled(&elems[50..])]);
+        assert_eq!(introspector.len(), 100);
+        assert_eq!(capacities(&introspector), before);
+        assert!(introspector.page_numberings.capacity() >= 2);
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let (elems, numberings) = introspector(&[1, 2]).into_parts();
+        let hashes: Vec<_> = elems.map(|(location, _, _)| location.hash).collect();
+        assert_eq!(hashes, [1, 2]);
+        assert!(numberings.is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_anchor() {
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[labelled(&[(1, "a"), (2, "b"), (3, "a"), (4, "b")])]);
+        let a = Selector::Label(Label::new("a"));
+        let before = Selector::Before {
+            selector: Arc::new(MetadataElem::elem().select()),
+            end: Arc::new(a.clone()),
+            inclusive: false,
+        };
+
+        // The anchor resolves to its first match.
+        assert!(introspector.query(&before).is_empty());
+        assert_eq!(
+            introspector.query_first(&a).unwrap().location(),
+            Some(location(1)),
+        );
+
+        // Unless strictness is requested.
+        assert_eq!(
+            introspector.query_first_strict(&a).unwrap_err(),
+            "selector <a> matches 2 elements",
+        );
+        assert!(introspector
+            .query_first_strict(&Selector::Label(Label::new("c")))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_update_changed_pages() {
+        let before =
+            [labelled(&[(1, "a")]), labelled(&[(2, "b")]), labelled(&[(3, "a")])];
+        let after = [labelled(&[(1, "a")]), labelled(&[(4, "a"), (2, "b")])];
+
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&before);
+        introspector.update(&[1], &after);
+
+        let mut expected = Introspector::default();
+        expected.rebuild(&after);
+        assert_eq!(introspector.hash(), expected.hash());
+
+        let a: Vec<_> = introspector
+            .query_label_all(Label::new("a"))
+            .iter()
+            .map(|elem| elem.location().unwrap().hash)
+            .collect();
+        assert_eq!(a, [1, 4]);
+        assert_eq!(introspector.position(location(4)).page.get(), 2);
+
+        // Without changes, nothing is extracted again.
+        introspector.update(&[], &after);
+        assert_eq!(introspector.hash(), expected.hash());
+    }
+
+    #[test]
+    fn test_introspector_is_send_and_sync() {
+        fn ensure_send_and_sync<T: Send + Sync>() {}
+        ensure_send_and_sync::<Introspector>();
+    }
+
+    #[test]
+    fn test_page_numberings_are_keyed_by_page() {
+        let numbering = |pattern: &str| Numbering::Pattern(pattern.parse().unwrap());
+        let page = |numberings: &[&str]| {
+            let mut frame = Frame::soft(Size::zero());
+            for &pattern in numberings {
+                let meta = Meta::PageNumbering(Some(numbering(pattern)));
+                frame.push(Point::zero(), FrameItem::Meta(meta, Size::zero()));
+            }
+            frame
+        };
+
+        // The second page has no numbering and the third one has two.
+        let mut introspector = Introspector::default();
+        introspector.rebuild(&[page(&["1"]), page(&[]), page(&["i", "a"])]);
+        assert_eq!(
+            introspector.page_numberings,
+            [Some(numbering("1")), None, Some(numbering("i"))],
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let numbering = |pattern: &str| Numbering::Pattern(pattern.parse().unwrap());
+        let page = |hash, pattern: Option<&str>| {
+            let mut frame = labelled(&[(hash, "a")]);
+            if let Some(pattern) = pattern {
+                let meta = Meta::PageNumbering(Some(numbering(pattern)));
+                frame.push(Point::zero(), FrameItem::Meta(meta, Size::zero()));
+            }
+            frame
+        };
+
+        // The first part has two pages, the second one has one.
+        let first = [page(1, Some("1")), page(2, None)];
+        let second = [page(3, Some("i"))];
+        let introspector = Introspector::merge([&first[..], &second[..]]);
+
+        assert_eq!(introspector.pages(), NonZeroUsize::new(3).unwrap());
+        assert_eq!(introspector.page(location(1)).get(), 1);
+        assert_eq!(introspector.page(location(2)).get(), 2);
+        assert_eq!(introspector.page(location(3)).get(), 3);
+        assert_eq!(introspector.query(&Selector::Label(Label::new("a"))).len(), 3);
+        assert_eq!(
+            introspector.page_numberings,
+            [Some(numbering("1")), None, Some(numbering("i"))],
+        );
+    }
+
+    #[test]
+    fn test_query_cache_hash_collision() {
+        let cache = QueryCache::default();
+        let a = Selector::Label(Label::new("a"));
+        let b = Selector::Label(Label::new("b"));
+        let matches = |index| Matches { elems: EcoVec::new(), indices: eco_vec![index] };
+
+        // Force both selectors onto the same hash.
+        cache.insert(0, &a, matches(1));
+        assert_eq!(cache.get(0, &a).unwrap().indices.as_slice(), [1]);
+        assert!(cache.get(0, &b).is_none());
+
+        cache.insert(0, &b, matches(2));
+        assert_eq!(cache.get(0, &b).unwrap().indices.as_slice(), [2]);
+        assert!(cache.get(0, &a).is_none());
+    }
+
+    #[test]
+    fn test_query_cache_limit() {
+        let cache = QueryCache { limit: 2, ..Default::default() };
+        let label = |name: &str| Selector::Label(Label::new(name));
+        let matches = |index| Matches { elems: EcoVec::new(), indices: eco_vec![index] };
+
+        cache.insert(1, &label("a"), matches(1));
+        cache.insert(2, &label("b"), matches(2));
+        assert!(cache.get(1, &label("a")).is_some());
+
+        // Evicts "b", which was used less recently than "a".
+        cache.insert(3, &label("c"), matches(3));
+        assert!(cache.get(2, &label("b")).is_none());
+        assert_eq!(cache.get(1, &label("a")).unwrap().indices.as_slice(), [1]);
+        assert_eq!(cache.get(3, &label("c")).unwrap().indices.as_slice(), [3]);
     }
 }
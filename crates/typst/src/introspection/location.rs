@@ -71,6 +71,27 @@ impl Location {
     pub fn page_numbering(self, engine: &mut Engine) -> Option<Numbering> {
         engine.introspector.page_numbering(self).cloned()
     }
+
+    /// Returns whether the document's layout has stabilized, that is, whether
+    /// the previous layout pass did not change the positions of any elements
+    /// anymore.
+    ///
+    /// Typst lays out a document multiple times until all introspections have
+    /// stabilized. Code running in the early passes may observe incomplete
+    /// information. This can be used to defer expensive computations or
+    /// user-visible output such as panics until the layout has settled.
+    ///
+    /// ```example
+    /// #locate(loc => if loc.stable() [
+    ///   Figures: #query(figure, loc).len()
+    /// ])
+    /// ```
+    ///
+    /// Note that observing this may cause one additional layout pass.
+    #[func]
+    pub fn stable(self, engine: &mut Engine) -> bool {
+        engine.introspector.stable()
+    }
 }
 
 impl Repr for Location {
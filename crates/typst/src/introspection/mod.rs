@@ -29,8 +29,8 @@ use crate::foundations::{
     cast, category, elem, ty, Behave, Behaviour, Category, Content, Repr, Scope,
     Unlabellable,
 };
-use crate::layout::PdfPageLabel;
-use crate::model::{Destination, Numbering};
+use crate::layout::{PdfPageBoxes, PdfPageLabel};
+use crate::model::{Destination, Numbering, Role};
 
 /// Interactions between document parts.
 ///
@@ -83,6 +83,10 @@ pub enum Meta {
     PageNumbering(Option<Numbering>),
     /// A PDF page label of the current page.
     PdfPageLabel(PdfPageLabel),
+    /// The bleed and trim boxes of the current page.
+    PdfPageBoxes(PdfPageBoxes),
+    /// The role of the content in the area within its list or table.
+    Role(Role),
     /// Indicates that content should be hidden. This variant doesn't appear
     /// in the final frames as it is removed alongside the content that should
     /// be hidden.
@@ -100,6 +104,8 @@ impl Debug for Meta {
             Self::Elem(content) => write!(f, "Elem({:?})", content.func()),
             Self::PageNumbering(value) => write!(f, "PageNumbering({value:?})"),
             Self::PdfPageLabel(label) => write!(f, "PdfPageLabel({label:?})"),
+            Self::PdfPageBoxes(boxes) => write!(f, "PdfPageBoxes({boxes:?})"),
+            Self::Role(role) => write!(f, "Role({role:?})"),
             Self::Hide => f.pad("Hide"),
         }
     }
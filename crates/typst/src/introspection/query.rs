@@ -61,6 +61,45 @@ use crate::introspection::Location;
 /// #lorem(15)
 /// ```
 ///
+/// # Running headers
+/// Headers that show the current chapter or, like in a dictionary, the last
+/// entry on the page need only a single element: The last one at or before
+/// the current location. Passing the current location as `before` returns just
+/// this element (or nothing if there is none). Unlike a query for
+/// `{selector(heading).before(loc)}`, this doesn't collect all previous
+/// headings on every page and thus stays fast in long documents.
+///
+/// The header comes before the page's body in the document, so in the header,
+/// you find the last element from the previous pages. The footer comes after
+/// the body, so there, you find the last element on the current page.
+///
+/// ```example
+/// >>> #set page(
+/// >>>   width: 240pt,
+/// >>>   height: 180pt,
+/// >>>   margin: (bottom: 35pt, rest: 15pt),
+/// >>> )
+/// #set page(footer: locate(loc => {
+///   let entry = query(
+///     heading,
+///     loc,
+///     before: loc,
+///   )
+///   if entry != () {
+///     align(right, emph(entry.first().body))
+///   }
+/// }))
+///
+/// = Aardvark
+/// #lorem(20)
+///
+/// = Abacus
+/// #lorem(20)
+///
+/// = Abbey
+/// #lorem(20)
+/// ```
+///
 /// # A word of caution { #caution }
 /// To resolve all your queries, Typst evaluates and layouts parts of the
 /// document multiple times. However, there is no guarantee that your queries
@@ -150,8 +189,21 @@ pub fn query(
     /// level of a module, the evaluation of the whole module and its exports
     /// could depend on the query's result.
     location: Location,
+    /// If given, only the last matching element at or before this location is
+    /// returned. The result then contains at most one element.
+    #[named]
+    before: Option<Location>,
 ) -> Array {
     let _ = location;
+    if let Some(before) = before {
+        return engine
+            .introspector
+            .query_last_before(&target.0, before)
+            .into_iter()
+            .map(|elem| Value::Content(elem.into_inner()))
+            .collect();
+    }
+
     let vec = engine.introspector.query(&target.0);
     vec.into_iter()
         .map(|elem| Value::Content(elem.into_inner()))
@@ -313,8 +313,7 @@ impl State {
         let sequence = self.sequence(engine)?;
         let offset = engine
             .introspector
-            .query(&self.selector().before(location.into(), true))
-            .len();
+            .query_count(&self.selector().before(location.into(), true));
         Ok(sequence[offset].clone())
     }
 
@@ -2,20 +2,22 @@ use std::num::NonZeroUsize;
 
 use crate::diag::SourceResult;
 use crate::engine::Engine;
-use crate::foundations::{elem, Behave, Behaviour, Content, StyleChain};
+use crate::foundations::{elem, scope, Behave, Behaviour, Content, Resolve, StyleChain};
 use crate::layout::{
-    Abs, Axes, Dir, Fragment, Frame, Layout, Length, Point, Ratio, Regions, Rel, Size,
+    Abs, Axes, Dir, FlowElem, Fragment, Frame, FrameItem, Layout, Length, Point, Ratio,
+    Regions, Rel, Size, Spacing, VElem,
 };
+use crate::realize::{realize_block, Scratch};
 use crate::text::TextElem;
 use crate::util::Numeric;
 
 /// Separates a region into multiple equally sized columns.
 ///
 /// The `column` function allows to separate the interior of any container into
-/// multiple columns. It will not equalize the height of the columns, instead,
-/// the columns will take up the height of their container or the remaining
-/// height on the page. The columns function can break across pages if
-/// necessary.
+/// multiple columns. By default, it will not equalize the height of the
+/// columns, instead, the columns will take up the height of their container or
+/// the remaining height on the page. The columns function can break across
+/// pages if necessary.
 ///
 /// If you need to insert columns across your whole document, you can use the
 /// [`{page}` function's `columns` parameter]($page.columns) instead.
@@ -40,7 +42,18 @@ use crate::util::Numeric;
 /// increasingly been used to solve a
 /// variety of problems.
 /// ```
-#[elem(Layout)]
+///
+/// # Balancing
+/// With [`balance`]($columns.balance) enabled, the content of the last region
+/// is distributed evenly across its columns instead of filling them one after
+/// another. This way, the last page of a newsletter or a two-column paper ends
+/// with columns of the same height.
+///
+/// # Spanning content
+/// Content wrapped in [`columns.span`]($columns.span) breaks out of the
+/// columns and takes up the full width. The columns before it are balanced to
+/// equal heights and the columns after it resume below.
+#[elem(scope, Layout)]
 pub struct ColumnsElem {
     /// The number of columns.
     #[positional]
@@ -52,6 +65,21 @@ pub struct ColumnsElem {
     #[default(Ratio::new(0.04).into())]
     pub gutter: Rel<Length>,
 
+    /// Whether to balance the columns in the last region such that they have
+    /// roughly the same height.
+    ///
+    /// All other regions are filled like without balancing. Column breaks are
+    /// respected. When set with a set rule, this also applies to the
+    /// [columns of the page]($page.columns).
+    ///
+    /// ```example
+    /// #columns(3, balance: true)[
+    ///   #lorem(30)
+    /// ]
+    /// ```
+    #[default(false)]
+    pub balance: bool,
+
     /// The content that should be layouted into the columns.
     #[required]
     pub body: Content,
@@ -73,6 +101,38 @@ impl Layout for ColumnsElem {
             return body.layout(engine, styles, regions);
         }
 
+        // Spanning content is only recognizable with the spacing around it
+        // once the body is realized into a flow.
+        let scratch = Scratch::default();
+        let (realized, shared) = realize_block(engine, &scratch, body, styles)?;
+
+        let mut runs = vec![];
+        split(&realized, &mut runs);
+        if !runs.iter().any(|run| matches!(run, Run::Span(_))) {
+            // Lay out the already realized flow instead of the body, so that
+            // the body isn't realized a second time.
+            return self.layout_rest(engine, shared, &realized, regions);
+        }
+
+        self.layout_runs(engine, shared, runs, regions)
+    }
+}
+
+#[scope]
+impl ColumnsElem {
+    #[elem]
+    type ColumnSpan;
+}
+
+impl ColumnsElem {
+    /// Layout content into the columns.
+    fn layout_columns(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        body: &Content,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
         // Determine the width of the gutter and each column.
         let columns = self.count(styles).get();
         let gutter = self.gutter(styles).relative_to(regions.base().x);
@@ -133,6 +193,340 @@ impl Layout for ColumnsElem {
 
         Ok(Fragment::frames(finished))
     }
+
+    /// Layout runs of column content that are interrupted by spanning content.
+    fn layout_runs(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        runs: Vec<Run>,
+        mut regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let mut finished = vec![];
+        let mut output = Frame::hard(Size::new(regions.size.x, Abs::zero()));
+        let mut height = regions.size.y;
+
+        // The flows of the runs discard weak spacing at their edges, so we
+        // apply the spacing between runs ourselves.
+        let mut below = Abs::zero();
+
+        let count = runs.len();
+        for (i, run) in runs.into_iter().enumerate() {
+            if let Run::Columns(children) = &run {
+                let base = regions.base().y;
+                let above = edge_spacing(children.iter(), styles, base);
+                if !output.is_empty() {
+                    let gap = below.max(above);
+                    output.size_mut().y += gap;
+                    regions.size.y -= gap;
+                }
+                below = edge_spacing(children.iter().rev(), styles, base);
+            } else if !output.is_empty() {
+                output.size_mut().y += below;
+                regions.size.y -= below;
+                below = Abs::zero();
+            }
+
+            let pod = Regions { expand: Axes::new(true, false), ..regions };
+            let frames = match run {
+                Run::Span(span) => span.layout(engine, styles, pod)?,
+                // The last run fills the remaining regions like normal
+                // columns would. In infinite regions, it is balanced since
+                // it would otherwise end up in the first column.
+                Run::Columns(children)
+                    if i + 1 == count && regions.size.y.is_finite() =>
+                {
+                    let body = Content::sequence(children);
+                    self.layout_rest(engine, styles, &body, regions)?
+                }
+                Run::Columns(children) => {
+                    let body = Content::sequence(children);
+                    self.layout_balanced(engine, styles, &body, pod)?
+                }
+            };
+
+            // Stack the first frame below what we already have. Every further
+            // frame starts a new region.
+            for (j, frame) in frames.into_iter().enumerate() {
+                if j > 0 {
+                    if regions.expand.y {
+                        output.size_mut().y = height;
+                    }
+                    finished.push(std::mem::replace(
+                        &mut output,
+                        Frame::hard(Size::new(regions.size.x, Abs::zero())),
+                    ));
+                    regions.next();
+                    height = regions.size.y;
+                }
+
+                let y = output.height();
+                output.size_mut().y += frame.height();
+                regions.size.y -= frame.height();
+                output.push_frame(Point::with_y(y), frame);
+            }
+        }
+
+        if regions.expand.y {
+            output.size_mut().y = height;
+        }
+        finished.push(output);
+
+        Ok(Fragment::frames(finished))
+    }
+
+    /// Layout column content that fills all remaining regions, balancing the
+    /// last one if requested.
+    fn layout_rest(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        body: &Content,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        if self.balance(styles) {
+            self.layout_balanced(engine, styles, body, regions)
+        } else {
+            self.layout_columns(engine, styles, body, regions)
+        }
+    }
+
+    /// Layout column content such that the columns in the last region it
+    /// needs have roughly the same height. All regions before are filled like
+    /// normal columns.
+    fn layout_balanced(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        body: &Content,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let columns = self.count(styles).get();
+        let gutter = self.gutter(styles).relative_to(regions.base().x);
+        let width = (regions.size.x - gutter * (columns - 1) as f64) / columns as f64;
+
+        // Measures the content in columns whose heights are given by region.
+        // The last height is repeated.
+        let mut measure = |heights: &[Abs]| -> SourceResult<Fragment> {
+            let Some((&last, heights)) = heights.split_last() else {
+                return Ok(Fragment::frames(vec![]));
+            };
+            let backlog: Vec<_> = heights
+                .iter()
+                .flat_map(|&height| std::iter::repeat(height).take(columns))
+                .chain(std::iter::repeat(last).take(columns))
+                .skip(1)
+                .collect();
+            let pod = Regions {
+                size: Size::new(width, heights.first().copied().unwrap_or(last)),
+                full: regions.full,
+                backlog: &backlog,
+                last: Some(last),
+                expand: Axes::new(true, false),
+                root: regions.root,
+            };
+            body.measure(engine, styles, pod)
+        };
+
+        // Determine how many regions the content needs without balancing.
+        let mut heights: Vec<Abs> = std::iter::once(regions.size.y)
+            .chain(regions.backlog.iter().copied())
+            .chain(regions.last)
+            .collect();
+        let natural = measure(&heights)?;
+        let count = ((natural.len() + columns - 1) / columns).max(1);
+        match regions.last {
+            _ if count <= heights.len() => heights.truncate(count),
+            Some(last) => heights.resize(count, last),
+            None => return self.layout_columns(engine, styles, body, regions),
+        }
+
+        // The content needs at least an equal share of the height of what ends
+        // up in its last region and at most the height of its tallest column
+        // there.
+        let rest = || natural.iter().skip((count - 1) * columns).map(Frame::height);
+        let mut lo = rest().sum::<Abs>() / columns as f64;
+        let mut hi = rest().fold(Abs::zero(), Abs::max);
+
+        // Whether the content fits into the same number of regions if the
+        // columns in the last one have the given height. Content that can't
+        // be broken, like a paragraph kept together to avoid orphans, may
+        // overflow its column instead of moving on, so we check where the
+        // content of the columns ends, too.
+        let mut fits = |height: Abs| -> SourceResult<bool> {
+            heights[count - 1] = height;
+            let fragment = measure(&heights)?;
+            Ok(fragment.len() <= count * columns
+                && fragment
+                    .iter()
+                    .skip((count - 1) * columns)
+                    .all(|frame| height.fits(bottom(frame))))
+        };
+
+        // Find the smallest height that fits.
+        let fitting = fits(hi)?;
+        while fitting && hi - lo > Abs::pt(1.0) {
+            let mid = (lo + hi) / 2.0;
+            if fits(mid)? {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        if !fitting {
+            return self.layout_columns(engine, styles, body, regions);
+        }
+
+        let mut backlog = heights[1..].to_vec();
+        let first = match backlog.last_mut() {
+            Some(last) => {
+                *last = hi;
+                regions.size.y
+            }
+            None => hi,
+        };
+
+        let pod = Regions {
+            size: Size::new(regions.size.x, first),
+            backlog: &backlog,
+            last: None,
+            ..regions
+        };
+
+        self.layout_columns(engine, styles, body, pod)
+    }
+}
+
+/// Content that spans all columns.
+///
+/// When used directly inside of [`columns`]($columns) or a page with multiple
+/// columns, the content interrupts the columns and takes up the full width.
+/// The columns before it are balanced to equal heights and the ones after it
+/// continue below it. This is useful for wide figures and titles in
+/// multi-column documents. Outside of columns, the content is laid out
+/// normally.
+///
+/// ```example
+/// #set page(columns: 2, height: 180pt)
+/// #columns.span(align(center)[
+///   *A Wide Title*
+/// ])
+///
+/// #lorem(20)
+///
+/// #columns.span(rect(width: 100%)[
+///   A figure spanning both columns.
+/// ])
+///
+/// #lorem(20)
+/// ```
+#[elem(name = "span", title = "Column Span", Layout)]
+pub struct ColumnSpan {
+    /// The content that spans all columns.
+    #[required]
+    pub body: Content,
+}
+
+impl Layout for ColumnSpan {
+    #[tracing::instrument(name = "ColumnSpan::layout", skip_all)]
+    fn layout(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        self.body().layout(engine, styles, regions)
+    }
+}
+
+/// A run of content inside of columns.
+enum Run {
+    /// Content that is distributed across the columns.
+    Columns(Vec<Content>),
+    /// Content that spans all columns.
+    Span(Content),
+}
+
+/// Split the content of columns into runs at spanning content.
+///
+/// Only spanning content that is a direct child of the columns' sequence or
+/// flow (possibly within set rules) interrupts the columns.
+fn split(content: &Content, runs: &mut Vec<Run>) {
+    if content.is::<ColumnSpan>() {
+        runs.push(Run::Span(content.clone()));
+    } else if let Some(children) = content.to_sequence() {
+        for child in children {
+            split(child, runs);
+        }
+    } else if let Some(flow) = content.to::<FlowElem>() {
+        for child in flow.children() {
+            split(child, runs);
+        }
+    } else if let Some((child, map)) = content.to_styled() {
+        let mut inner = vec![];
+        split(child, &mut inner);
+        if let [Run::Columns(_)] = inner.as_slice() {
+            push(runs, content.clone());
+            return;
+        }
+
+        for run in inner {
+            match run {
+                Run::Columns(children) => {
+                    push(runs, Content::sequence(children).styled_with_map(map.clone()))
+                }
+                Run::Span(span) => {
+                    runs.push(Run::Span(span.styled_with_map(map.clone())))
+                }
+            }
+        }
+    } else {
+        push(runs, content.clone());
+    }
+}
+
+/// The largest weak spacing at the edge of column content that the given
+/// children start with.
+fn edge_spacing<'a>(
+    children: impl Iterator<Item = &'a Content>,
+    styles: StyleChain,
+    base: Abs,
+) -> Abs {
+    let mut max = Abs::zero();
+    for child in children {
+        let (child, styles) = match child.to_styled() {
+            Some((elem, map)) => (elem, styles.chain(map)),
+            None => (child, styles),
+        };
+        let Some(v) = child.to::<VElem>() else { break };
+        if let (Spacing::Rel(rel), true) = (v.amount(), v.weakness(styles) > 0) {
+            max.set_max(rel.resolve(styles).relative_to(base));
+        }
+    }
+    max
+}
+
+/// Where the content of a frame ends, which may be below the frame itself if
+/// the content overflows it.
+fn bottom(frame: &Frame) -> Abs {
+    frame
+        .items()
+        .map(|(pos, item)| match item {
+            FrameItem::Group(group) => pos.y + group.frame.height(),
+            FrameItem::Image(_, size, _) => pos.y + size.y,
+            FrameItem::Text(_) | FrameItem::Shape(..) => pos.y,
+            FrameItem::Meta(..) => Abs::zero(),
+        })
+        .fold(Abs::zero(), Abs::max)
+}
+
+/// Add content to the last run of column content or start a new one.
+fn push(runs: &mut Vec<Run>, content: Content) {
+    match runs.last_mut() {
+        Some(Run::Columns(children)) => children.push(content),
+        _ => runs.push(Run::Columns(vec![content])),
+    }
 }
 
 /// Forces a column break.
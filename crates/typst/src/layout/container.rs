@@ -1,12 +1,14 @@
 use crate::diag::SourceResult;
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, AutoValue, Content, NativeElement, Resolve, Smart, StyleChain, Value,
+    cast, dict, elem, AutoValue, Content, NativeElement, Resolve, Smart, StyleChain,
+    Styles, Value,
 };
 use crate::layout::{
     Abs, Axes, Corners, Em, Fr, Fragment, FrameKind, Layout, Length, Ratio, Regions, Rel,
     Sides, Size, Spacing, VElem,
 };
+use crate::model::ParElem;
 use crate::util::Numeric;
 use crate::visualize::{clip_rect, Paint, Stroke};
 
@@ -121,6 +123,8 @@ impl Layout for BoxElem {
             Sizing::Auto => Smart::Auto,
             Sizing::Rel(rel) => Smart::Custom(rel),
             Sizing::Fr(_) => Smart::Custom(Ratio::one().into()),
+            // Bounds can only be specified for grid tracks.
+            Sizing::Bounded { .. } => Smart::Auto,
         };
 
         // Resolve the sizing to a concrete size.
@@ -138,10 +142,14 @@ impl Layout for BoxElem {
             body = body.padded(inset.map(|side| side.map(Length::from)));
         }
 
+        // The lines of a box are part of the surrounding line, so they aren't
+        // numbered separately.
+        let local = Styles::from(ParElem::set_line_numbering(None));
+
         // Select the appropriate base and expansion for the child depending
         // on whether it is automatically or relatively sized.
         let pod = Regions::one(size, expand);
-        let mut frame = body.layout(engine, styles, pod)?.into_frame();
+        let mut frame = body.layout(engine, styles.chain(&local), pod)?.into_frame();
 
         // Enforce correct size.
         *frame.size_mut() = expand.select(size, frame.size());
@@ -240,6 +248,11 @@ pub struct BlockElem {
 
     /// Whether the block can be broken and continue on the next page.
     ///
+    /// An unbreakable block moves to the next page as a whole if it doesn't
+    /// fit into the remaining space, even if it contains breakable content.
+    /// Only if a block without a fixed height is taller than a full page, it
+    /// is broken anyway as a last resort.
+    ///
     /// ```example
     /// #set page(height: 80pt)
     /// The following block will
@@ -364,13 +377,44 @@ impl Layout for BlockElem {
             .zip_map(regions.base(), |s, b| s.map(|v| v.relative_to(b)))
             .unwrap_or(regions.base());
 
-        // Layout the child.
-        let mut frames = if self.breakable(styles) {
+        // Layout the child. An unbreakable block is laid out into a single
+        // unbounded region, so that nested content can't break either. It is
+        // only measured first since it is laid out again if it is too large.
+        let mut unbroken = None;
+        let mut measured = None;
+        if !self.breakable(styles) {
+            let mut pod = Regions::one(size, expand);
+            if !expand.y {
+                pod.size.y = Abs::inf();
+            }
+
+            let mut frame = body.measure(engine, styles, pod)?.into_frame();
+            measured = Some(frame.width());
+
+            // Only break the block as a last resort if it is too large to fit
+            // into a full region. Otherwise, the measured frame is final.
+            let largest =
+                regions.iter().take(2).fold(regions.full, |max, size| max.max(size.y));
+            if expand.y || largest.fits(frame.height()) {
+                engine.locator.visit_frame(&frame);
+                *frame.size_mut() = expand.select(size, frame.size());
+                unbroken = Some(vec![frame]);
+            }
+        }
+
+        let mut frames = if let Some(frames) = unbroken {
+            frames
+        } else {
             // Measure to ensure frames for all regions have the same width.
+            // An unbreakable block that is too large was measured already.
             if sizing.x == Smart::Auto {
-                let pod = Regions::one(size, Axes::splat(false));
-                let frame = body.measure(engine, styles, pod)?.into_frame();
-                size.x = frame.width();
+                size.x = match measured {
+                    Some(width) => width,
+                    None => {
+                        let pod = Regions::one(size, Axes::splat(false));
+                        body.measure(engine, styles, pod)?.into_frame().width()
+                    }
+                };
                 expand.x = true;
             }
 
@@ -410,11 +454,6 @@ impl Layout for BlockElem {
                     expand.select(Size::new(size.x, height), frame.size());
             }
             frames
-        } else {
-            let pod = Regions::one(size, expand);
-            let mut frames = body.layout(engine, styles, pod)?.into_frames();
-            *frames[0].size_mut() = expand.select(size, frames[0].size());
-            frames
         };
 
         // Prepare fill and stroke.
@@ -472,6 +511,9 @@ pub enum Sizing {
     /// A track size specified as a fraction of the remaining free space in the
     /// parent.
     Fr(Fr),
+    /// A track that fits its cell's contents, but is at least `min` and at most
+    /// `max` large. Only supported for grid rows.
+    Bounded { min: Rel<Length>, max: Option<Rel<Length>> },
 }
 
 impl Sizing {
@@ -502,6 +544,7 @@ cast! {
         Self::Auto => Value::Auto,
         Self::Rel(rel) => rel.into_value(),
         Self::Fr(fr) => fr.into_value(),
+        Self::Bounded { min, max } => dict! { "min" => min, "max" => max }.into_value(),
     },
     _: AutoValue => Self::Auto,
     v: Rel<Length> => Self::Rel(v),
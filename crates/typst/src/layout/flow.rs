@@ -1,13 +1,13 @@
 use comemo::Prehashed;
 
-use crate::diag::{bail, SourceResult};
+use crate::diag::{bail, warning, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{elem, Content, NativeElement, Resolve, Smart, StyleChain};
 use crate::introspection::{Meta, MetaElem};
 use crate::layout::{
-    Abs, AlignElem, Axes, BlockElem, ColbreakElem, ColumnsElem, FixedAlign, Fr, Fragment,
-    Frame, FrameItem, Layout, PlaceElem, Point, Regions, Rel, Size, Spacing, VAlign,
-    VElem,
+    Abs, AlignElem, Axes, BlockElem, ColbreakElem, ColumnsElem, FixedAlign, FloatArea,
+    FloatPlacement, Fr, Fragment, Frame, FrameItem, Layout, PageElem, PlaceElem, Point,
+    Regions, Rel, Size, Spacing, VAlign, VElem,
 };
 use crate::model::{FootnoteElem, FootnoteEntry, ParElem};
 use crate::util::Numeric;
@@ -79,9 +79,11 @@ impl Layout for FlowElem {
                 layouter.layout_placed(engine, placed, styles)?;
             } else if child.can::<dyn Layout>() {
                 layouter.layout_multiple(engine, child, styles)?;
-            } else if child.is::<ColbreakElem>() {
-                if !layouter.regions.backlog.is_empty() || layouter.regions.last.is_some()
-                {
+            } else if let Some(colbreak) = child.to::<ColbreakElem>() {
+                // A weak column break is skipped in an empty column.
+                let more = !layouter.regions.backlog.is_empty()
+                    || layouter.regions.last.is_some();
+                if more && (!colbreak.weak(styles) || layouter.has_content()) {
                     layouter.finish_region(engine)?;
                 }
             } else {
@@ -112,8 +114,12 @@ struct FlowLayouter<'a> {
     items: Vec<FlowItem>,
     /// A queue of floats.
     pending_floats: Vec<FlowItem>,
+    /// Whether the current region is a page that only holds floats.
+    float_page: bool,
     /// Whether we have any footnotes in the current region.
     has_footnotes: bool,
+    /// The distance between the lines of the page's baseline grid, if any.
+    baseline_grid: Option<Abs>,
     /// Footnote configuration.
     footnote_config: FootnoteConfig,
     /// Finished frames for previous regions.
@@ -125,8 +131,13 @@ struct FootnoteConfig {
     separator: Content,
     clearance: Abs,
     gap: Abs,
+    span: bool,
 }
 
+/// How often columns with spanning footnotes are laid out at most to settle
+/// the space reserved for their footnotes.
+const MAX_FOOTNOTE_PASSES: usize = 3;
+
 /// A prepared item in a flow layout.
 #[derive(Debug)]
 enum FlowItem {
@@ -146,6 +157,7 @@ enum FlowItem {
         delta: Axes<Rel<Abs>>,
         float: bool,
         clearance: Abs,
+        placement: FloatPlacement,
     },
     /// A footnote frame (can also be the separator).
     Footnote(Frame),
@@ -180,11 +192,15 @@ impl<'a> FlowLayouter<'a> {
             last_was_par: false,
             items: vec![],
             pending_floats: vec![],
+            float_page: false,
             has_footnotes: false,
+            baseline_grid: PageElem::baseline_grid_in(styles)
+                .filter(|grid| root && *grid > Abs::zero()),
             footnote_config: FootnoteConfig {
                 separator: FootnoteEntry::separator_in(styles),
                 clearance: FootnoteEntry::clearance_in(styles),
                 gap: FootnoteEntry::gap_in(styles),
+                span: FootnoteEntry::spanning_in(styles),
             },
             finished: vec![],
         }
@@ -250,11 +266,16 @@ impl<'a> FlowLayouter<'a> {
             }
         }
 
+        let grid = self.baseline_grid.filter(|_| ParElem::snap_in(styles));
         for (i, frame) in lines.into_iter().enumerate() {
             if i > 0 {
                 self.layout_item(engine, FlowItem::Absolute(leading, true))?;
             }
 
+            if let Some(grid) = grid {
+                self.snap_to_grid(engine, &frame, grid)?;
+            }
+
             self.layout_item(
                 engine,
                 FlowItem::Frame { frame, align, sticky: false, movable: true },
@@ -265,6 +286,54 @@ impl<'a> FlowLayouter<'a> {
         Ok(())
     }
 
+    /// Insert spacing before a frame that is about to be laid out so that its
+    /// baseline lies on the baseline grid.
+    fn snap_to_grid(
+        &mut self,
+        engine: &mut Engine,
+        frame: &Frame,
+        grid: Abs,
+    ) -> SourceResult<()> {
+        let mut extra = self.grid_offset(frame, grid);
+        if !self.regions.size.y.fits(extra + frame.height()) && !self.regions.in_last() {
+            self.finish_region_for_content(engine)?;
+            extra = self.grid_offset(frame, grid);
+        }
+
+        if extra > Abs::zero() {
+            self.layout_item(engine, FlowItem::Absolute(extra, false))?;
+        }
+
+        Ok(())
+    }
+
+    /// How far a frame that is laid out next needs to be moved down for its
+    /// baseline to lie on the baseline grid.
+    fn grid_offset(&self, frame: &Frame, grid: Abs) -> Abs {
+        let mut y = Abs::zero();
+        for item in &self.items {
+            match item {
+                FlowItem::Absolute(v, _) => y += *v,
+                FlowItem::Frame { frame, .. } => y += frame.height(),
+                FlowItem::Placed {
+                    frame,
+                    float: true,
+                    y_align: Smart::Custom(Some(FixedAlign::Start)),
+                    ..
+                } => y += frame.height(),
+                _ => {}
+            }
+        }
+
+        let baseline = y + frame.baseline();
+        let snapped = grid * (baseline / grid).ceil();
+        if snapped.approx_eq(baseline) {
+            Abs::zero()
+        } else {
+            snapped - baseline
+        }
+    }
+
     /// Layout into a single region.
     #[tracing::instrument(name = "FlowLayouter::layout_single", skip_all)]
     fn layout_single(
@@ -301,7 +370,56 @@ impl<'a> FlowLayouter<'a> {
         });
         let y_align = alignment.map(|align| align.y().map(VAlign::fix));
         let frame = placed.layout(engine, styles, self.regions)?.into_frame();
-        let item = FlowItem::Placed { frame, x_align, y_align, delta, float, clearance };
+        let placement = match placed.placement(styles) {
+            Smart::Custom(placement) => placement,
+            Smart::Auto => match y_align {
+                Smart::Custom(Some(FixedAlign::Start)) => {
+                    FloatPlacement::only(FloatArea::Top)
+                }
+                Smart::Custom(Some(FixedAlign::End)) => {
+                    FloatPlacement::only(FloatArea::Bottom)
+                }
+                _ => FloatPlacement::auto(),
+            },
+        };
+
+        // A float that fits into none of its areas even in an empty region
+        // can't be placed properly. It overflows its preferred area instead.
+        if float {
+            let full = self
+                .regions
+                .backlog
+                .iter()
+                .chain(&self.regions.last)
+                .fold(self.regions.full, |max, &height| max.max(height));
+            let height = frame.height();
+            let fits = |area: &FloatArea| match area {
+                FloatArea::Top | FloatArea::Bottom => full.fits(height + clearance),
+                FloatArea::Page => full.fits(height),
+            };
+
+            if !placement.allow.iter().any(fits) {
+                let mut warning = warning!(
+                    placed.span(),
+                    "floating element does not fit into any allowed area";
+                    hint: "it is placed into its preferred area and overflows it"
+                );
+                if !placement.allows(FloatArea::Page) && fits(&FloatArea::Page) {
+                    warning.hint("allowing `page` would place it on a page of its own");
+                }
+                engine.tracer.warn(warning);
+            }
+        }
+
+        let item = FlowItem::Placed {
+            frame,
+            x_align,
+            y_align,
+            delta,
+            float,
+            clearance,
+            placement,
+        };
         self.layout_item(engine, item)
     }
 
@@ -312,18 +430,20 @@ impl<'a> FlowLayouter<'a> {
         block: &Content,
         styles: StyleChain,
     ) -> SourceResult<()> {
-        // Temporarily delegerate rootness to the columns.
+        // Temporarily delegerate rootness to the columns, unless their
+        // footnotes should span the full width below them.
         let is_root = self.root;
-        if is_root && block.is::<ColumnsElem>() {
+        let span = is_root && self.footnote_config.span && block.is::<ColumnsElem>();
+        if is_root && block.is::<ColumnsElem>() && !span {
             self.root = false;
             self.regions.root = true;
         }
 
         let mut notes = Vec::new();
 
-        if self.regions.is_full() {
-            // Skip directly if region is already full.
-            self.finish_region(engine)?;
+        if self.regions.is_full() || self.float_page {
+            // Skip directly if region is already full or only holds floats.
+            self.finish_region_for_content(engine)?;
         }
 
         // How to align the block.
@@ -338,20 +458,34 @@ impl<'a> FlowLayouter<'a> {
 
         // Layout the block itself.
         let sticky = BlockElem::sticky_in(styles);
-        let fragment = block.layout(engine, styles, self.regions)?;
+        let fragment = if span {
+            self.layout_spanning_columns(engine, block, styles)?
+        } else {
+            block.layout(engine, styles, self.regions)?
+        };
 
+        let start = self.finished.len();
         for (i, frame) in fragment.into_iter().enumerate() {
             // Find footnotes in the frame.
             if self.root {
                 find_footnotes(&mut notes, &frame);
             }
 
-            if i > 0 {
+            // Spanning footnotes may already have overflowed into the region
+            // of this frame.
+            while self.finished.len() < start + i {
                 self.finish_region(engine)?;
             }
 
             let item = FlowItem::Frame { frame, align, sticky, movable: false };
             self.layout_item(engine, item)?;
+
+            // Space for spanning footnotes was reserved, so we list them in
+            // the region they are referenced in.
+            if span {
+                self.handle_footnotes(engine, &mut notes, false, true)?;
+                notes.clear();
+            }
         }
 
         self.try_handle_footnotes(engine, notes)?;
@@ -370,14 +504,21 @@ impl<'a> FlowLayouter<'a> {
         engine: &mut Engine,
         mut item: FlowItem,
     ) -> SourceResult<()> {
+        // A page of floats ends as soon as other content arrives.
+        if self.float_page
+            && !matches!(
+                item,
+                FlowItem::Absolute(_, true)
+                    | FlowItem::Placed { float: true, .. }
+                    | FlowItem::Footnote(_)
+            )
+        {
+            self.finish_region_for_content(engine)?;
+        }
+
         match item {
             FlowItem::Absolute(v, weak) => {
-                if weak
-                    && !self
-                        .items
-                        .iter()
-                        .any(|item| matches!(item, FlowItem::Frame { .. }))
-                {
+                if weak && !self.has_content() {
                     return Ok(());
                 }
                 self.regions.size.y -= v
@@ -386,7 +527,7 @@ impl<'a> FlowLayouter<'a> {
             FlowItem::Frame { ref frame, movable, .. } => {
                 let height = frame.height();
                 if !self.regions.size.y.fits(height) && !self.regions.in_last() {
-                    self.finish_region(engine)?;
+                    self.finish_region_for_content(engine)?;
                 }
 
                 self.regions.size.y -= height;
@@ -396,7 +537,7 @@ impl<'a> FlowLayouter<'a> {
                     self.items.push(item);
                     if !self.handle_footnotes(engine, &mut notes, true, false)? {
                         let item = self.items.pop();
-                        self.finish_region(engine)?;
+                        self.finish_region_for_content(engine)?;
                         self.items.extend(item);
                         self.regions.size.y -= height;
                         self.handle_footnotes(engine, &mut notes, true, true)?;
@@ -410,31 +551,47 @@ impl<'a> FlowLayouter<'a> {
                 ref mut y_align,
                 float: true,
                 clearance,
+                ref placement,
                 ..
             } => {
-                // If the float doesn't fit, queue it for the next region.
-                if !self.regions.size.y.fits(frame.height() + clearance)
-                    && !self.regions.in_last()
-                {
+                // Floats are placed in order, so a float must not overtake
+                // one that is still queued. If the float doesn't fit into any
+                // of its areas, queue it for the next region.
+                let area = match self.pending_floats.is_empty() {
+                    true => self.select_float_area(frame.height(), clearance, placement),
+                    false => None,
+                };
+                let Some(area) = area else {
                     self.pending_floats.push(item);
                     return Ok(());
-                }
+                };
+
+                if area == FloatArea::Page {
+                    // Floats on a page of their own are stacked from the top.
+                    self.float_page = true;
+                    *y_align = Smart::Custom(Some(FixedAlign::Start));
+                } else {
+                    let align = match area {
+                        FloatArea::Bottom => FixedAlign::End,
+                        _ => FixedAlign::Start,
+                    };
+                    *y_align = Smart::Custom(Some(align));
 
-                // Select the closer placement, top or bottom.
-                if y_align.is_auto() {
-                    let ratio = (self.regions.size.y
-                        - (frame.height() + clearance) / 2.0)
-                        / self.regions.full;
-                    let better_align =
-                        if ratio <= 0.5 { FixedAlign::End } else { FixedAlign::Start };
-                    *y_align = Smart::Custom(Some(better_align));
-                }
+                    // Add some clearance so that the float doesn't touch the
+                    // main content.
+                    frame.size_mut().y += clearance;
+                    if align == FixedAlign::End {
+                        frame.translate(Point::with_y(clearance));
+                    }
 
-                // Add some clearance so that the float doesn't touch the main
-                // content.
-                frame.size_mut().y += clearance;
-                if *y_align == Smart::Custom(Some(FixedAlign::End)) {
-                    frame.translate(Point::with_y(clearance));
+                    // Top floats take up whole grid cells so that they don't
+                    // move the lines below them off the grid.
+                    if let Some(grid) = self.baseline_grid {
+                        if align == FixedAlign::Start {
+                            let height = frame.height();
+                            frame.size_mut().y = grid * (height / grid).ceil();
+                        }
+                    }
                 }
 
                 self.regions.size.y -= frame.height();
@@ -453,6 +610,69 @@ impl<'a> FlowLayouter<'a> {
         Ok(())
     }
 
+    /// Select the area a float goes into in the current region, if any.
+    fn select_float_area(
+        &self,
+        height: Abs,
+        clearance: Abs,
+        placement: &FloatPlacement,
+    ) -> Option<FloatArea> {
+        // Try the preferred area first. Without a preference, try the closer
+        // one of top and bottom first.
+        let mut areas = vec![];
+        match placement.prefer {
+            Smart::Custom(area) => areas.push(area),
+            Smart::Auto => {
+                let ratio = (self.regions.size.y - (height + clearance) / 2.0)
+                    / self.regions.full;
+                let closer = if ratio <= 0.5 {
+                    [FloatArea::Bottom, FloatArea::Top]
+                } else {
+                    [FloatArea::Top, FloatArea::Bottom]
+                };
+                areas.extend(closer.into_iter().filter(|&area| placement.allows(area)));
+            }
+        }
+
+        for &area in &placement.allow {
+            if !areas.contains(&area) {
+                areas.push(area);
+            }
+        }
+
+        let fits = |area: &FloatArea| match area {
+            FloatArea::Top | FloatArea::Bottom => {
+                self.regions.size.y.fits(height + clearance)
+            }
+            FloatArea::Page => !self.has_content() && self.regions.size.y.fits(height),
+        };
+
+        // Deferring doesn't help if the next region is no larger, unless the
+        // float waits for a region without other content.
+        match areas.iter().copied().find(fits) {
+            None if self.regions.in_last() => areas
+                .first()
+                .copied()
+                .filter(|&area| area != FloatArea::Page || !self.has_content()),
+            area => area,
+        }
+    }
+
+    /// Whether the current region contains any laid out blocks.
+    fn has_content(&self) -> bool {
+        self.items.iter().any(|item| matches!(item, FlowItem::Frame { .. }))
+    }
+
+    /// Finish the current region and any pages of floats that start after it,
+    /// so that other content can be laid out next.
+    fn finish_region_for_content(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        self.finish_region(engine)?;
+        while self.float_page {
+            self.finish_region(engine)?;
+        }
+        Ok(())
+    }
+
     /// Finish the frame for one region.
     fn finish_region(&mut self, engine: &mut Engine) -> SourceResult<()> {
         // Trim weak spacing.
@@ -578,6 +798,7 @@ impl<'a> FlowLayouter<'a> {
         self.regions.next();
         self.initial = self.regions.size;
         self.has_footnotes = false;
+        self.float_page = false;
 
         // Try to place floats.
         for item in std::mem::take(&mut self.pending_floats) {
@@ -694,6 +915,101 @@ impl FlowLayouter<'_> {
         Ok(true)
     }
 
+    /// Layout columns whose footnotes span the full width below them.
+    ///
+    /// Since the footnotes of a region are only known once the columns are
+    /// laid out, the columns are laid out again with the space that the
+    /// footnotes need reserved until the reservations settle.
+    fn layout_spanning_columns(
+        &mut self,
+        engine: &mut Engine,
+        block: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Fragment> {
+        let checkpoint = engine.locator.clone();
+        let mut fragment = block.layout(engine, styles, self.regions)?;
+        let mut reserved: Vec<Abs> = vec![];
+        let mut backlog = vec![];
+
+        for _ in 0..MAX_FOOTNOTE_PASSES {
+            // Footnotes may take up at most half of a region. The rest
+            // overflows into the next one.
+            let mut needed = vec![];
+            let mut carry = Abs::zero();
+            for (frame, size) in fragment.iter().zip(self.regions.iter()) {
+                let mut notes = vec![];
+                find_footnotes(&mut notes, frame);
+                let height = self.measure_footnotes(engine, &notes, carry)?;
+                let limit = size.y / 2.0;
+                needed.push(height.min(limit));
+                carry = (height - limit).max(Abs::zero());
+            }
+
+            if needed.len() == reserved.len()
+                && needed.iter().zip(&reserved).all(|(a, b)| a.approx_eq(*b))
+            {
+                break;
+            }
+
+            reserved = needed;
+            let reserve = |i: usize| reserved.get(i).copied().unwrap_or_default();
+            let len = self.regions.backlog.len().max(reserved.len().saturating_sub(1));
+            backlog.clear();
+            backlog.extend(
+                self.regions
+                    .iter()
+                    .skip(1)
+                    .take(len)
+                    .enumerate()
+                    .map(|(i, size)| size.y - reserve(i + 1)),
+            );
+
+            let mut pod = self.regions;
+            pod.size.y -= reserve(0);
+            pod.backlog = &backlog;
+
+            *engine.locator = checkpoint.clone();
+            fragment = block.layout(engine, styles, pod)?;
+        }
+
+        Ok(fragment)
+    }
+
+    /// Measure the height that the entries of the notes, including the
+    /// separator, take up when listed at the full width of the flow.
+    ///
+    /// The `carry` is the height of entries that overflowed from the previous
+    /// region. They are listed below a separator of their own.
+    fn measure_footnotes(
+        &self,
+        engine: &mut Engine,
+        notes: &[FootnoteElem],
+        carry: Abs,
+    ) -> SourceResult<Abs> {
+        let mut notes = notes.iter().filter(|note| !note.is_ref()).peekable();
+        if notes.peek().is_none() && carry <= Abs::zero() {
+            return Ok(Abs::zero());
+        }
+
+        let checkpoint = engine.locator.clone();
+        let size = Size::new(self.regions.size.x, Abs::inf());
+        let pod = Regions::one(size, Axes::new(self.regions.expand.x, false));
+        let separator = &self.footnote_config.separator;
+        let mut height =
+            separator.layout(engine, self.styles, pod)?.into_frame().height()
+                + self.footnote_config.clearance
+                + carry;
+
+        for note in notes {
+            let entry = FootnoteEntry::new(note.clone()).pack();
+            let frame = entry.layout(engine, self.styles, pod)?.into_frame();
+            height += self.footnote_config.gap + frame.height();
+        }
+
+        *engine.locator = checkpoint;
+        Ok(height)
+    }
+
     /// Layout and save the footnote separator, typically a line.
     #[tracing::instrument(skip_all)]
     fn layout_footnote_separator(&mut self, engine: &mut Engine) -> SourceResult<()> {
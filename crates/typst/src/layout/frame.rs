@@ -547,6 +547,17 @@ impl From<Position> for Dict {
     }
 }
 
+/// The physical extent of an element in a document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Bounds {
+    /// The page, starting at 1.
+    pub page: NonZeroUsize,
+    /// The top left corner of the element's bounding box on the page.
+    pub point: Point,
+    /// The size of the bounding box. Zero for elements without an extent.
+    pub size: Size,
+}
+
 /// Specification for a PDF page label.
 #[derive(Debug, Clone, PartialEq, Hash, Default)]
 pub struct PdfPageLabel {
@@ -569,6 +580,20 @@ impl Repr for PdfPageLabel {
     }
 }
 
+/// The print production boxes of a PDF page.
+///
+/// Both boxes are given as insets from the edges of the page's frame, which
+/// becomes the PDF media box.
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+pub struct PdfPageBoxes {
+    /// The inset of the bleed box, which is the region to which the page's
+    /// contents are clipped in a production environment.
+    pub bleed: Abs,
+    /// The inset of the trim box, which describes the finished dimensions of
+    /// the page after trimming.
+    pub trim: Abs,
+}
+
 /// A PDF page label number style.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum PdfPageLabelStyle {
@@ -5,15 +5,17 @@ use smallvec::{smallvec, SmallVec};
 use crate::diag::{bail, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Array, Content, NativeElement, Resolve, StyleChain, Value,
+    cast, elem, scope, Array, Content, Dict, NativeElement, Resolve, Show, Smart,
+    StyleChain, Styles, Synthesize, Value,
 };
 use crate::layout::{
-    Abs, Axes, Dir, Fr, Fragment, Frame, Layout, Length, Point, Regions, Rel, Size,
+    Abs, Axes, Axis, Dir, Fr, Fragment, Frame, Layout, Length, Point, Regions, Rel, Size,
     Sizing,
 };
 use crate::syntax::Span;
 use crate::text::TextElem;
-use crate::util::Numeric;
+use crate::util::{NonZeroExt, Numeric};
+use crate::visualize::Path;
 
 /// Arranges content in a grid.
 ///
@@ -41,6 +43,12 @@ use crate::util::Numeric;
 ///   with a fraction of `{1fr}`, they will each take up half of the remaining
 ///   space.
 ///
+/// - A dictionary with `min` and/or `max` keys (e.g. `{(min: 2cm)}`): Only
+///   available for rows. The row will be sized to fit its contents like an
+///   `{auto}` row, but it will be at least `min` and at most `max` tall. Cell
+///   content that doesn't fit into the maximum height is clipped. Such a row
+///   never breaks across pages.
+///
 /// To specify a single track, the array can be omitted in favor of a single
 /// value. To specify multiple `{auto}` tracks, enter the number of tracks
 /// instead of an array. For example, `columns:` `{3}` is equivalent to
@@ -81,7 +89,25 @@ use crate::util::Numeric;
 ///   ..range(25).map(str)
 /// )
 /// ```
-#[elem(Layout)]
+///
+/// # Headers and footers
+/// The first child of a grid can be a [header]($grid.header). Its cells occupy
+/// one or more complete rows at the top of the grid and are, by default,
+/// repeated at the top of each region when the grid breaks across pages.
+/// Likewise, the last child can be a [footer]($grid.footer), which is repeated
+/// at the bottom of each region.
+///
+/// ```example
+/// >>> #set page(height: 100pt)
+/// #grid(
+///   columns: 2,
+///   gutter: 4pt,
+///   grid.header([*Key*], [*Value*]),
+///   ..range(12).map(str),
+///   grid.footer([_Key_], [_Value_]),
+/// )
+/// ```
+#[elem(scope, Layout)]
 pub struct GridElem {
     /// The column sizes.
     ///
@@ -118,13 +144,43 @@ pub struct GridElem {
     #[borrowed]
     pub row_gutter: TrackSizings,
 
+    /// The direction in which the columns are laid out.
+    ///
+    /// By default, the columns follow the [text direction]($text.dir), so
+    /// that the first column is on the right in right-to-left text. Setting
+    /// this to `{ltr}` or `{rtl}` overrides it, for instance for a grid with
+    /// left-to-right contents in a right-to-left document.
+    ///
+    /// ```example
+    /// #grid(
+    ///   columns: 3,
+    ///   gutter: 6pt,
+    ///   dir: rtl,
+    ///   [First], [Second], [Third],
+    /// )
+    /// ```
+    pub dir: Smart<Dir>,
+
     /// The contents of the grid cells.
     ///
-    /// The cells are populated in row-major order.
+    /// The cells are populated in row-major order. Wrap a child in a
+    /// [cell]($grid.cell) to make it span multiple columns or rows.
     #[variadic]
     pub children: Vec<Content>,
 }
 
+#[scope]
+impl GridElem {
+    #[elem]
+    type GridHeader;
+
+    #[elem]
+    type GridFooter;
+
+    #[elem]
+    type GridCell;
+}
+
 impl Layout for GridElem {
     #[tracing::instrument(name = "GridElem::layout", skip_all)]
     fn layout(
@@ -137,14 +193,42 @@ impl Layout for GridElem {
         let rows = self.rows(styles);
         let column_gutter = self.column_gutter(styles);
         let row_gutter = self.row_gutter(styles);
+        let dir = resolve_grid_dir(self.dir(styles), styles, self.span())?;
+
+        let grid = CellGrid::resolve(
+            self.children(),
+            columns.0.len(),
+            |child| {
+                child
+                    .to::<GridHeader>()
+                    .map(|header| (header.children().as_slice(), header.repeat(styles)))
+            },
+            |child| {
+                child
+                    .to::<GridFooter>()
+                    .map(|footer| (footer.children().as_slice(), footer.repeat(styles)))
+            },
+            |child| match child.to::<GridCell>() {
+                Some(cell) => Cell {
+                    body: child.clone(),
+                    colspan: cell.colspan(styles),
+                    rowspan: cell.rowspan(styles),
+                },
+                None => {
+                    Cell::new(GridCell::new(child.clone()).spanned(child.span()).pack())
+                }
+            },
+        )?
+        .with_continued(GridCell::set_continued(true).into());
 
         // Prepare grid layout by unifying content and gutter tracks.
         let layouter = GridLayouter::new(
             Axes::new(&columns.0, &rows.0),
             Axes::new(&column_gutter.0, &row_gutter.0),
-            &self.children,
+            &grid,
             regions,
             styles,
+            dir,
             self.span(),
         );
 
@@ -153,6 +237,384 @@ impl Layout for GridElem {
     }
 }
 
+/// Determine the direction of a grid's columns, which follows the text
+/// direction unless overridden.
+pub(crate) fn resolve_grid_dir(
+    dir: Smart<Dir>,
+    styles: StyleChain,
+    span: Span,
+) -> SourceResult<Dir> {
+    let dir = dir.unwrap_or_else(|| TextElem::dir_in(styles));
+    if dir.axis() != Axis::X {
+        bail!(span, "grid direction must be horizontal");
+    }
+    Ok(dir)
+}
+
+/// A repeatable grid header.
+///
+/// The cells of a header are placed at the top of the grid and occupy as many
+/// complete rows as they need. If the header consists of fewer cells than its
+/// rows have columns, its last row is filled up with empty cells. A header can
+/// only be placed as the first child of a grid.
+///
+/// ```example
+/// >>> #set page(height: 120pt)
+/// #grid(
+///   columns: 3,
+///   row-gutter: 6pt,
+///   grid.header(
+///     [*Name*], [*Start*], [*End*],
+///     [], [_(date)_], [_(date)_],
+///   ),
+///   ..range(21).map(str),
+/// )
+/// ```
+#[elem(name = "header", title = "Grid Header")]
+pub struct GridHeader {
+    /// Whether the header should be repeated at the top of each region the
+    /// grid breaks into.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the header, in row-major order.
+    #[variadic]
+    pub children: Vec<Content>,
+}
+
+/// A repeatable grid footer.
+///
+/// The cells of a footer are placed at the bottom of the grid, starting in a
+/// fresh row, and occupy as many complete rows as they need. By default, the
+/// footer is repeated at the bottom of each region the grid breaks into. It
+/// can only be placed as the last child of a grid.
+///
+/// ```example
+/// >>> #set page(height: 120pt)
+/// #grid(
+///   columns: 2,
+///   row-gutter: 6pt,
+///   grid.header([*Item*], [*Count*]),
+///   ..range(16).map(str),
+///   grid.footer([_Item_], [_Count_]),
+/// )
+/// ```
+#[elem(name = "footer", title = "Grid Footer")]
+pub struct GridFooter {
+    /// Whether the footer should be repeated at the bottom of each region the
+    /// grid breaks into.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the footer, in row-major order.
+    #[variadic]
+    pub children: Vec<Content>,
+}
+
+/// A cell in the grid.
+///
+/// Each child of a grid is laid out as a cell, so show rules on cells apply
+/// to all of them. Wrapping a child in a cell explicitly allows it to span
+/// multiple columns or rows. A cell is placed at the next position where it
+/// fits without overlapping previously placed cells.
+///
+/// ```example
+/// #set rect(width: 100%, height: 100%, fill: luma(230))
+/// #grid(
+///   columns: 3,
+///   rows: 24pt,
+///   gutter: 3pt,
+///   grid.cell(colspan: 2, rect[Two columns]),
+///   grid.cell(rowspan: 2, rect[Two rows]),
+///   rect[A], rect[B],
+/// )
+/// ```
+#[elem(name = "cell", title = "Grid Cell", Synthesize, Show)]
+pub struct GridCell {
+    /// The cell's body.
+    #[required]
+    pub body: Content,
+
+    /// The number of columns spanned by the cell.
+    #[default(NonZeroUsize::ONE)]
+    pub colspan: NonZeroUsize,
+
+    /// The number of rows spanned by the cell.
+    ///
+    /// If the last spanned row is `{auto}`-sized, it grows such that the cell's
+    /// content fits into the spanned rows. A cell spanning rows that break
+    /// across pages breaks along with them.
+    #[default(NonZeroUsize::ONE)]
+    pub rowspan: NonZeroUsize,
+
+    /// Whether the cell is part of a header that is repeated at the top of a
+    /// region after the first one.
+    ///
+    /// This is set automatically. Show rules can match on it to customize the
+    /// repeated header, for instance to mark it as continued.
+    ///
+    /// ```example
+    /// >>> #set page(height: 100pt)
+    /// #show grid.cell.where(continued: true): it => {
+    ///   it.body + [ _(continued)_]
+    /// }
+    ///
+    /// #grid(
+    ///   columns: 2,
+    ///   gutter: 4pt,
+    ///   grid.header(grid.cell(colspan: 2)[*Readings*]),
+    ///   ..range(16).map(str),
+    /// )
+    /// ```
+    #[default(false)]
+    pub continued: bool,
+}
+
+impl Synthesize for GridCell {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        self.push_continued(self.continued(styles));
+        Ok(())
+    }
+}
+
+impl Show for GridCell {
+    fn show(&self, _: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(self.body().clone())
+    }
+}
+
+/// Details about the header of a grid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Header {
+    /// The number of content rows occupied by the header.
+    pub rows: usize,
+    /// Whether the header is repeated at the top of each region.
+    pub repeat: bool,
+}
+
+/// Details about the footer of a grid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Footer {
+    /// The content row in which the footer starts. The footer occupies all
+    /// rows from there on.
+    pub start: usize,
+    /// Whether the footer is repeated at the bottom of each region.
+    pub repeat: bool,
+}
+
+/// A cell of a grid, which may span multiple columns and rows.
+#[derive(Debug, Clone)]
+pub struct Cell {
+    /// The cell's content.
+    pub body: Content,
+    /// The number of columns spanned by the cell.
+    pub colspan: NonZeroUsize,
+    /// The number of rows spanned by the cell.
+    pub rowspan: NonZeroUsize,
+}
+
+impl Cell {
+    /// Create a cell that spans a single column and row.
+    pub fn new(body: Content) -> Self {
+        Self { body, colspan: NonZeroUsize::ONE, rowspan: NonZeroUsize::ONE }
+    }
+}
+
+/// The cells of a grid, placed at their positions.
+#[derive(Debug, Clone)]
+pub struct CellGrid {
+    /// The grid's positions in row-major order. A position is `None` if no cell
+    /// covers it.
+    entries: Vec<Option<Entry>>,
+    /// The number of content columns.
+    cols: usize,
+    /// The header of the grid, if any.
+    header: Option<Header>,
+    /// The footer of the grid, if any.
+    footer: Option<Footer>,
+    /// Styles applied to the cells of a header that is repeated in a region
+    /// after the first one.
+    continued: Styles,
+}
+
+/// A position in a grid.
+#[derive(Debug, Clone)]
+enum Entry {
+    /// A cell starts at the position.
+    Cell(Cell),
+    /// The position is covered by the cell that starts at the given index.
+    Merged(usize),
+}
+
+impl CellGrid {
+    /// Place cells that each span a single column and row in row-major order.
+    pub fn new(cells: Vec<Content>, cols: usize) -> Self {
+        let entries =
+            cells.into_iter().map(|body| Some(Entry::Cell(Cell::new(body)))).collect();
+        Self {
+            entries,
+            cols: cols.max(1),
+            header: None,
+            footer: None,
+            continued: Styles::new(),
+        }
+    }
+
+    /// Place the children of a grid, splitting off an optional header and
+    /// footer.
+    ///
+    /// The `header` and `footer` functions should return the cells and whether
+    /// they repeat if the given child is a header or footer, respectively. Both
+    /// occupy as many complete rows as their cells need, such that the
+    /// following cells start in a fresh row. The `cell` function turns a
+    /// child into a cell.
+    pub fn resolve<'c>(
+        children: &'c [Content],
+        cols: usize,
+        header: impl Fn(&'c Content) -> Option<(&'c [Content], bool)>,
+        footer: impl Fn(&'c Content) -> Option<(&'c [Content], bool)>,
+        cell: impl Fn(&'c Content) -> Cell,
+    ) -> SourceResult<Self> {
+        let mut grid = Self {
+            entries: vec![],
+            cols: cols.max(1),
+            header: None,
+            footer: None,
+            continued: Styles::new(),
+        };
+        let mut cursor = 0;
+
+        for (i, child) in children.iter().enumerate() {
+            if let Some((header_cells, repeat)) = header(child) {
+                if i > 0 {
+                    bail!(child.span(), "header must be the first child");
+                }
+
+                for child in header_cells {
+                    cursor = grid.place(cell(child), child.span(), cursor)?;
+                }
+
+                let rows = grid.rows();
+                cursor = rows * grid.cols;
+                grid.header = (rows > 0).then_some(Header { rows, repeat });
+            } else if let Some((footer_cells, repeat)) = footer(child) {
+                if i + 1 < children.len() {
+                    bail!(child.span(), "footer must be the last child");
+                }
+
+                let start = grid.rows();
+                cursor = start * grid.cols;
+                for child in footer_cells {
+                    cursor = grid.place(cell(child), child.span(), cursor)?;
+                }
+
+                grid.footer = (grid.rows() > start).then_some(Footer { start, repeat });
+            } else {
+                cursor = grid.place(cell(child), child.span(), cursor)?;
+            }
+        }
+
+        Ok(grid)
+    }
+
+    /// Set the styles applied to the cells of a header that is repeated in a
+    /// region after the first one.
+    pub fn with_continued(mut self, styles: Styles) -> Self {
+        self.continued = styles;
+        self
+    }
+
+    /// Place a cell at the first position from the cursor on where it fits
+    /// and return the position after it.
+    fn place(&mut self, cell: Cell, span: Span, cursor: usize) -> SourceResult<usize> {
+        let colspan = cell.colspan.get();
+        let rowspan = cell.rowspan.get();
+        if colspan > self.cols {
+            bail!(span, "cell's colspan would exceed the available columns");
+        }
+
+        let mut index = cursor;
+        while !self.fits(index, colspan, rowspan) {
+            index += 1;
+        }
+
+        let (x, y) = (index % self.cols, index / self.cols);
+        let len = (y + rowspan - 1) * self.cols + x + colspan;
+        if self.entries.len() < len {
+            self.entries.resize(len, None);
+        }
+
+        for dy in 0..rowspan {
+            for dx in 0..colspan {
+                self.entries[(y + dy) * self.cols + x + dx] = Some(Entry::Merged(index));
+            }
+        }
+
+        self.entries[index] = Some(Entry::Cell(cell));
+        Ok(index + 1)
+    }
+
+    /// Whether a cell with the given spans fits at the position without
+    /// leaving the grid horizontally or overlapping other cells.
+    fn fits(&self, index: usize, colspan: usize, rowspan: usize) -> bool {
+        let (x, y) = (index % self.cols, index / self.cols);
+        x + colspan <= self.cols
+            && (y..y + rowspan).all(|y| {
+                (x..x + colspan).all(|x| {
+                    self.entries.get(y * self.cols + x).map_or(true, Option::is_none)
+                })
+            })
+    }
+
+    /// The number of content columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of content rows needed to place all cells.
+    pub fn rows(&self) -> usize {
+        (self.entries.len() + self.cols - 1) / self.cols
+    }
+
+    /// The header of the grid, if any.
+    pub fn header(&self) -> Option<Header> {
+        self.header
+    }
+
+    /// The footer of the grid, if any.
+    pub fn footer(&self) -> Option<Footer> {
+        self.footer
+    }
+
+    /// The cell that starts in column `x` and row `y`, if any.
+    pub fn cell(&self, x: usize, y: usize) -> Option<&Cell> {
+        match self.entries.get(y * self.cols + x)? {
+            Some(Entry::Cell(cell)) => Some(cell),
+            _ => None,
+        }
+    }
+
+    /// The column and row in which the cell covering column `x` and row `y`
+    /// starts, if any cell covers it.
+    pub fn parent(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match self.entries.get(y * self.cols + x)?.as_ref()? {
+            Entry::Cell(_) => Some((x, y)),
+            &Entry::Merged(index) => Some((index % self.cols, index / self.cols)),
+        }
+    }
+
+    /// The cells of the grid along with the column and row they start in.
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut Cell)> {
+        let cols = self.cols;
+        self.entries.iter_mut().enumerate().filter_map(move |(i, entry)| {
+            match entry {
+                Some(Entry::Cell(cell)) => Some((i % cols, i / cols, cell)),
+                _ => None,
+            }
+        })
+    }
+}
+
 /// Track sizing definitions.
 #[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
 pub struct TrackSizings(pub SmallVec<[Sizing; 4]>);
@@ -161,14 +623,31 @@ cast! {
     TrackSizings,
     self => self.0.into_value(),
     sizing: Sizing => Self(smallvec![sizing]),
+    bounds: Dict => Self(smallvec![bounded(bounds)?]),
     count: NonZeroUsize => Self(smallvec![Sizing::Auto; count.get()]),
-    values: Array => Self(values.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
+    values: Array => Self(values.into_iter().map(track).collect::<StrResult<_>>()?),
+}
+
+/// Cast a single track size, which may also be a dictionary of bounds.
+fn track(value: Value) -> StrResult<Sizing> {
+    match value {
+        Value::Dict(dict) => bounded(dict),
+        value => value.cast(),
+    }
+}
+
+/// Cast a dictionary with `min` and `max` keys into a bounded track size.
+fn bounded(mut dict: Dict) -> StrResult<Sizing> {
+    let min = dict.take("min").ok().map(Value::cast).transpose()?;
+    let max = dict.take("max").ok().map(Value::cast).transpose()?;
+    dict.finish(&["min", "max"])?;
+    Ok(Sizing::Bounded { min: min.unwrap_or_default(), max: max.flatten() })
 }
 
 /// Performs grid layout.
 pub struct GridLayouter<'a> {
     /// The grid cells.
-    cells: &'a [Content],
+    grid: &'a CellGrid,
     /// Whether this is an RTL grid.
     is_rtl: bool,
     /// Whether this grid has gutters.
@@ -193,6 +672,24 @@ pub struct GridLayouter<'a> {
     initial: Size,
     /// Frames for finished regions.
     finished: Vec<Frame>,
+    /// The header of the grid, if any.
+    header: Option<Header>,
+    /// The height of the header when it was last laid out. If the header
+    /// repeats, this space isn't available to rows in follow-up regions.
+    header_height: Abs,
+    /// The number of header rows at the start of `lrows`.
+    header_rows: usize,
+    /// Whether the header is currently being laid out as a repetition after
+    /// the first region.
+    continued: bool,
+    /// The footer of the grid, if any.
+    footer: Option<Footer>,
+    /// The height of the footer. If the footer repeats, this space is reserved
+    /// at the bottom of each region.
+    footer_height: Abs,
+    /// The cells spanning multiple rows, which are laid out once all regions
+    /// are finished.
+    rowspans: Vec<Rowspan<'a>>,
     /// The span of the grid element.
     span: Span,
 }
@@ -206,6 +703,45 @@ pub struct GridLayout {
     pub cols: Vec<Abs>,
     /// The heights of the resulting rows segments, by region.
     pub rows: Vec<Vec<RowPiece>>,
+    /// For each track position, the index of the position at which the cell
+    /// covering it starts.
+    parents: Vec<usize>,
+    /// Whether the columns are laid out from right to left.
+    rtl: bool,
+}
+
+impl GridLayout {
+    /// The column and row at which the cell covering column `x` and row `y`
+    /// starts. Both are track indices, i.e. they include gutter tracks.
+    ///
+    /// For positions that aren't covered by a cell spanning multiple tracks,
+    /// this is the position itself.
+    pub fn parent(&self, x: usize, y: usize) -> (usize, usize) {
+        let c = self.cols.len();
+        let index = self.parents[y * c + x];
+        (index % c, index / c)
+    }
+
+    /// The logical column and row of the cell covering column `x` and row
+    /// `y`. Both are track indices, i.e. they include gutter tracks.
+    ///
+    /// Other than [`parent`](Self::parent), this counts columns in the
+    /// grid's direction, so in RTL, the first column is the rightmost one.
+    pub fn logical(&self, x: usize, y: usize) -> (usize, usize) {
+        let (px, py) = self.parent(x, y);
+        if !self.rtl {
+            return (px, py);
+        }
+
+        // In RTL, a cell logically starts at its rightmost track.
+        let c = self.cols.len();
+        let mut end = px;
+        while end + 1 < c && self.parent(end + 1, y) == (px, py) {
+            end += 1;
+        }
+
+        (c - 1 - end, py)
+    }
 }
 
 /// Details about a resulting row piece.
@@ -217,6 +753,18 @@ pub struct RowPiece {
     pub y: usize,
 }
 
+/// A cell spanning multiple rows.
+struct Rowspan<'a> {
+    /// The cell.
+    cell: &'a Cell,
+    /// The column track of the cell's left edge.
+    x: usize,
+    /// The first row track spanned by the cell.
+    y: usize,
+    /// The last row track spanned by the cell.
+    end: usize,
+}
+
 /// Produced by initial row layout, auto and relative rows are already finished,
 /// fractional rows not yet.
 enum Row {
@@ -233,9 +781,10 @@ impl<'a> GridLayouter<'a> {
     pub fn new(
         tracks: Axes<&[Sizing]>,
         gutter: Axes<&[Sizing]>,
-        cells: &'a [Content],
+        grid: &'a CellGrid,
         regions: Regions<'a>,
         styles: StyleChain<'a>,
+        dir: Dir,
         span: Span,
     ) -> Self {
         let mut cols = vec![];
@@ -246,12 +795,7 @@ impl<'a> GridLayouter<'a> {
 
         // Number of content rows: At least as many as given, but also at least
         // as many as needed to place each item.
-        let r = {
-            let len = cells.len();
-            let given = tracks.y.len();
-            let needed = len / c + (len % c).clamp(0, 1);
-            given.max(needed)
-        };
+        let r = tracks.y.len().max(grid.rows());
 
         let has_gutter = gutter.any(|tracks| !tracks.is_empty());
         let auto = Sizing::Auto;
@@ -283,7 +827,7 @@ impl<'a> GridLayouter<'a> {
         }
 
         // Reverse for RTL.
-        let is_rtl = TextElem::dir_in(styles) == Dir::RTL;
+        let is_rtl = dir == Dir::RTL;
         if is_rtl {
             cols.reverse();
         }
@@ -293,8 +837,8 @@ impl<'a> GridLayouter<'a> {
         let mut regions = regions;
         regions.expand = Axes::new(true, false);
 
-        Self {
-            cells,
+        let mut layouter = Self {
+            grid,
             is_rtl,
             has_gutter,
             rows,
@@ -307,18 +851,58 @@ impl<'a> GridLayouter<'a> {
             lrows: vec![],
             initial: regions.size,
             finished: vec![],
+            header: grid.header(),
+            header_height: Abs::zero(),
+            header_rows: 0,
+            continued: false,
+            footer: grid.footer(),
+            footer_height: Abs::zero(),
+            rowspans: vec![],
             span,
+        };
+
+        // Collect the cells spanning multiple rows.
+        for y in 0..layouter.rows.len() {
+            for x in 0..layouter.cols.len() {
+                if let Some(cell) = layouter.cell(x, y) {
+                    if cell.rowspan.get() > 1 {
+                        let end = y + layouter.span_tracks(cell.rowspan) - 1;
+                        layouter.rowspans.push(Rowspan { cell, x, y, end });
+                    }
+                }
+            }
         }
+
+        layouter
     }
 
     /// Determines the columns sizes and then layouts the grid row-by-row.
     pub fn layout(mut self, engine: &mut Engine) -> SourceResult<GridLayout> {
         self.measure_columns(engine)?;
 
-        for y in 0..self.rows.len() {
+        // A repeated header is laid out upfront such that we know how much
+        // space it takes away from the rows in follow-up regions.
+        let start = if self.repeats_header() {
+            self.layout_header(engine)?;
+            self.header_end()
+        } else {
+            0
+        };
+
+        // A repeated footer is measured upfront and its space is reserved at
+        // the bottom of each region.
+        let end = if self.repeats_footer() {
+            self.footer_height = self.measure_footer(engine)?;
+            self.regions.size.y -= self.footer_height;
+            self.footer_start()
+        } else {
+            self.rows.len()
+        };
+
+        for y in start..end {
             // Skip to next region if current one is full, but only for content
             // rows, not for gutter rows.
-            if self.regions.is_full() && (!self.has_gutter || y % 2 == 0) {
+            if self.is_full() && (!self.has_gutter || y % 2 == 0) {
                 self.finish_region(engine)?;
             }
 
@@ -326,15 +910,26 @@ impl<'a> GridLayouter<'a> {
                 Sizing::Auto => self.layout_auto_row(engine, y)?,
                 Sizing::Rel(v) => self.layout_relative_row(engine, v, y)?,
                 Sizing::Fr(v) => self.lrows.push(Row::Fr(v, y)),
+                Sizing::Bounded { min, max } => {
+                    self.layout_bounded_row(engine, min, max, y)?
+                }
             }
         }
 
-        self.finish_region(engine)?;
+        if self.repeats_footer() {
+            self.layout_footer(engine)?;
+        }
+
+        self.finish_region_internal(engine)?;
+        self.layout_rowspans(engine)?;
 
+        let parents = self.parents();
         Ok(GridLayout {
             fragment: Fragment::frames(self.finished),
             cols: self.rcols,
             rows: self.rrows,
+            parents,
+            rtl: self.is_rtl,
         })
     }
 
@@ -359,6 +954,10 @@ impl<'a> GridLayouter<'a> {
                     rel += resolved;
                 }
                 Sizing::Fr(v) => fr += v,
+                Sizing::Bounded { .. } => bail!(
+                    self.span,
+                    "minimum and maximum sizes are only supported for rows"
+                ),
             }
         }
 
@@ -402,31 +1001,97 @@ impl<'a> GridLayouter<'a> {
 
             let mut resolved = Abs::zero();
             for y in 0..self.rows.len() {
-                if let Some(cell) = self.cell(x, y) {
-                    // For relative rows, we can already resolve the correct
-                    // base and for auto and fr we could only guess anyway.
-                    let height = match self.rows[y] {
-                        Sizing::Rel(v) => {
-                            v.resolve(self.styles).relative_to(self.regions.base().y)
-                        }
-                        _ => self.regions.base().y,
-                    };
-
-                    let size = Size::new(available, height);
-                    let pod = Regions::one(size, Axes::splat(false));
-                    let frame = cell.measure(engine, self.styles, pod)?.into_frame();
-                    resolved.set_max(frame.width());
-                }
+                // Cells spanning multiple columns are considered below.
+                let Some(cell) = self.cell(x, y).filter(|cell| cell.colspan.get() == 1)
+                else {
+                    continue;
+                };
+
+                // For relative rows, we can already resolve the correct
+                // base and for auto and fr we could only guess anyway.
+                let height = match self.rows[y] {
+                    Sizing::Rel(v) => {
+                        v.resolve(self.styles).relative_to(self.regions.base().y)
+                    }
+                    _ => self.regions.base().y,
+                };
+
+                let size = Size::new(available, height);
+                let pod = Regions::one(size, Axes::splat(false));
+                let frame = cell.body.measure(engine, self.styles, pod)?.into_frame();
+                resolved.set_max(frame.width());
             }
 
             self.rcols[x] = resolved;
-            auto += resolved;
             count += 1;
         }
 
+        self.measure_spanning_columns(engine, available)?;
+
+        for (&col, &rcol) in self.cols.iter().zip(&self.rcols) {
+            if col == Sizing::Auto {
+                auto += rcol;
+            }
+        }
+
         Ok((auto, count))
     }
 
+    /// Grow auto columns such that cells spanning multiple columns fit.
+    ///
+    /// The space a cell needs beyond the current size of its columns is
+    /// distributed evenly among the auto columns it spans. Cells spanning a
+    /// fractional column are skipped as that column will take up any remaining
+    /// space anyway.
+    fn measure_spanning_columns(
+        &mut self,
+        engine: &mut Engine,
+        available: Abs,
+    ) -> SourceResult<()> {
+        for y in 0..self.rows.len() {
+            for x in 0..self.cols.len() {
+                let Some(cell) = self.cell(x, y).filter(|cell| cell.colspan.get() > 1)
+                else {
+                    continue;
+                };
+
+                let tracks = x..x + self.span_tracks(cell.colspan);
+                let cols = &self.cols[tracks.clone()];
+                let count = cols.iter().filter(|&&col| col == Sizing::Auto).count();
+                if count == 0 || cols.iter().any(|col| matches!(col, Sizing::Fr(_))) {
+                    continue;
+                }
+
+                // The cell may use the space of its fixed-size columns in
+                // addition to the space available to auto columns.
+                let mut fixed = Abs::zero();
+                for t in tracks.clone() {
+                    if self.cols[t] != Sizing::Auto {
+                        fixed += self.rcols[t];
+                    }
+                }
+
+                let size = Size::new(available + fixed, self.regions.base().y);
+                let pod = Regions::one(size, Axes::splat(false));
+                let frame = cell.body.measure(engine, self.styles, pod)?.into_frame();
+                let current: Abs = self.rcols[tracks.clone()].iter().sum();
+                let excess = frame.width() - current;
+                if excess <= Abs::zero() {
+                    continue;
+                }
+
+                let share = excess / count as f64;
+                for t in tracks {
+                    if self.cols[t] == Sizing::Auto {
+                        self.rcols[t] += share;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Distribute remaining space to fractional columns.
     fn grow_fractional_columns(&mut self, remaining: Abs, fr: Fr) {
         if fr.is_zero() {
@@ -486,6 +1151,18 @@ impl<'a> GridLayouter<'a> {
             }
         };
 
+        // Grow the row such that cells spanning multiple rows down to it fit.
+        let mut extra = self.measure_rowspans(engine, y)?;
+        if self.regions.size.y.is_finite() {
+            extra.set_min(self.regions.size.y);
+        }
+        if extra > Abs::zero() {
+            match resolved.first_mut() {
+                Some(first) => first.set_max(extra),
+                None => resolved.push(extra),
+            }
+        }
+
         // Nothing to layout.
         if resolved.is_empty() {
             return Ok(());
@@ -501,8 +1178,9 @@ impl<'a> GridLayouter<'a> {
         // Expand all but the last region.
         // Skip the first region if the space is eaten up by an fr row.
         let len = resolved.len();
+        let mut backlog = vec![];
         for (region, target) in self
-            .regions
+            .row_regions(&mut backlog)
             .iter()
             .zip(&mut resolved[..len - 1])
             .skip(self.lrows.iter().any(|row| matches!(row, Row::Fr(..))) as usize)
@@ -532,34 +1210,34 @@ impl<'a> GridLayouter<'a> {
         can_skip: bool,
     ) -> SourceResult<Option<Vec<Abs>>> {
         let mut resolved: Vec<Abs> = vec![];
-
-        for (x, &rcol) in self.rcols.iter().enumerate() {
-            if let Some(cell) = self.cell(x, y) {
-                let mut pod = self.regions;
-                pod.size.x = rcol;
-
-                let frames = cell.measure(engine, self.styles, pod)?.into_frames();
-
-                // Skip the first region if one cell in it is empty. Then,
-                // remeasure.
-                if let [first, rest @ ..] = frames.as_slice() {
-                    if can_skip
-                        && first.is_empty()
-                        && rest.iter().any(|frame| !frame.is_empty())
-                    {
-                        return Ok(None);
-                    }
-                }
-
-                let mut sizes = frames.iter().map(|frame| frame.height());
-                for (target, size) in resolved.iter_mut().zip(&mut sizes) {
-                    target.set_max(size);
+        let mut backlog = vec![];
+        let regions = self.row_regions(&mut backlog);
+
+        for (_, width, cell) in self.row_cells(y) {
+            let mut pod = regions;
+            pod.size.x = width;
+
+            let frames = cell.body.measure(engine, self.styles, pod)?.into_frames();
+
+            // Skip the first region if one cell in it is empty. Then,
+            // remeasure.
+            if let [first, rest @ ..] = frames.as_slice() {
+                if can_skip
+                    && first.is_empty()
+                    && rest.iter().any(|frame| !frame.is_empty())
+                {
+                    return Ok(None);
                 }
+            }
 
-                // New heights are maximal by virtue of being new. Note that
-                // this extend only uses the rest of the sizes iterator.
-                resolved.extend(sizes);
+            let mut sizes = frames.iter().map(|frame| frame.height());
+            for (target, size) in resolved.iter_mut().zip(&mut sizes) {
+                target.set_max(size);
             }
+
+            // New heights are maximal by virtue of being new. Note that
+            // this extend only uses the rest of the sizes iterator.
+            resolved.extend(sizes);
         }
 
         Ok(Some(resolved))
@@ -574,11 +1252,36 @@ impl<'a> GridLayouter<'a> {
         y: usize,
     ) -> SourceResult<()> {
         let resolved = v.resolve(self.styles).relative_to(self.regions.base().y);
-        let frame = self.layout_single_row(engine, resolved, y)?;
+        self.layout_fixed_row(engine, resolved, y)
+    }
+
+    /// Layout a row that fits its contents within a minimum and an optional
+    /// maximum height. Like a relative row, such a row cannot break across
+    /// multiple regions.
+    fn layout_bounded_row(
+        &mut self,
+        engine: &mut Engine,
+        min: Rel<Length>,
+        max: Option<Rel<Length>>,
+        y: usize,
+    ) -> SourceResult<()> {
+        let resolved = self.measure_bounded_row(engine, min, max, y)?;
+        self.layout_fixed_row(engine, resolved, y)
+    }
+
+    /// Layout a row whose height is already known and push it into the first
+    /// region it fits into.
+    fn layout_fixed_row(
+        &mut self,
+        engine: &mut Engine,
+        height: Abs,
+        y: usize,
+    ) -> SourceResult<()> {
+        let frame = self.layout_single_row(engine, height, y)?;
 
         // Skip to fitting region.
         let height = frame.height();
-        while !self.regions.size.y.fits(height) && !self.regions.in_last() {
+        while !self.regions.size.y.fits(height) && !self.in_last() {
             self.finish_region(engine)?;
 
             // Don't skip multiple regions for gutter and don't push a row.
@@ -604,20 +1307,19 @@ impl<'a> GridLayouter<'a> {
         }
 
         let mut output = Frame::soft(Size::new(self.width, height));
-        let mut pos = Point::zero();
 
-        for (x, &rcol) in self.rcols.iter().enumerate() {
-            if let Some(cell) = self.cell(x, y) {
-                let size = Size::new(rcol, height);
-                let mut pod = Regions::one(size, Axes::splat(true));
-                if self.rows[y] == Sizing::Auto {
-                    pod.full = self.regions.full;
-                }
-                let frame = cell.layout(engine, self.styles, pod)?.into_frame();
-                output.push_frame(pos, frame);
+        for (dx, width, cell) in self.row_cells(y) {
+            let size = Size::new(width, height);
+            let mut pod = Regions::one(size, Axes::splat(true));
+            if self.rows[y] == Sizing::Auto {
+                pod.full = self.regions.full;
             }
-
-            pos.x += rcol;
+            let mut frame =
+                self.body(cell).layout(engine, self.styles, pod)?.into_frame();
+            if let Sizing::Bounded { max: Some(_), .. } = self.rows[y] {
+                frame.clip(Path::rect(frame.size()));
+            }
+            output.push_frame(Point::with_x(dx), frame);
         }
 
         Ok(output)
@@ -643,19 +1345,14 @@ impl<'a> GridLayouter<'a> {
         pod.backlog = &heights[1..];
 
         // Layout the row.
-        let mut pos = Point::zero();
-        for (x, &rcol) in self.rcols.iter().enumerate() {
-            if let Some(cell) = self.cell(x, y) {
-                pod.size.x = rcol;
+        for (dx, width, cell) in self.row_cells(y) {
+            pod.size.x = width;
 
-                // Push the layouted frames into the individual output frames.
-                let fragment = cell.layout(engine, self.styles, pod)?;
-                for (output, frame) in outputs.iter_mut().zip(fragment) {
-                    output.push_frame(pos, frame);
-                }
+            // Push the layouted frames into the individual output frames.
+            let fragment = cell.body.layout(engine, self.styles, pod)?;
+            for (output, frame) in outputs.iter_mut().zip(fragment) {
+                output.push_frame(Point::with_x(dx), frame);
             }
-
-            pos.x += rcol;
         }
 
         Ok(Fragment::frames(outputs))
@@ -667,8 +1364,203 @@ impl<'a> GridLayouter<'a> {
         self.lrows.push(Row::Frame(frame, y));
     }
 
-    /// Finish rows for one region.
+    /// Layout the header rows into the current region.
+    ///
+    /// In contrast to other rows, header rows never break across regions.
+    fn layout_header(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        // The header is continued if rows below it were laid out before.
+        let end = self.header_end();
+        self.continued = self.rrows.iter().flatten().any(|piece| piece.y >= end);
+
+        let mut height = Abs::zero();
+        for y in 0..end {
+            let resolved = self.measure_repeated_row(engine, y)?;
+            let frame = self.layout_single_row(engine, resolved, y)?;
+            height += frame.height();
+            self.push_row(frame, y);
+        }
+
+        self.continued = false;
+        self.header_height = height;
+        self.header_rows = self.lrows.len();
+
+        Ok(())
+    }
+
+    /// Layout the footer rows into the current region, using the space
+    /// reserved for them.
+    ///
+    /// Like header rows, footer rows never break across regions.
+    fn layout_footer(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        self.regions.size.y += self.footer_height;
+        for y in self.footer_start()..self.rows.len() {
+            let resolved = self.measure_repeated_row(engine, y)?;
+            let frame = self.layout_single_row(engine, resolved, y)?;
+            self.push_row(frame, y);
+        }
+        Ok(())
+    }
+
+    /// Measure the height of the footer rows.
+    fn measure_footer(&mut self, engine: &mut Engine) -> SourceResult<Abs> {
+        let mut height = Abs::zero();
+        for y in self.footer_start()..self.rows.len() {
+            height += self.measure_repeated_row(engine, y)?;
+        }
+        Ok(height)
+    }
+
+    /// Measure the height of a header or footer row.
+    fn measure_repeated_row(&self, engine: &mut Engine, y: usize) -> SourceResult<Abs> {
+        Ok(match self.rows[y] {
+            Sizing::Rel(v) => v.resolve(self.styles).relative_to(self.regions.base().y),
+            Sizing::Auto | Sizing::Fr(_) => self
+                .measure_single_row(engine, y)?
+                .max(self.measure_rowspans(engine, y)?),
+            Sizing::Bounded { min, max } => {
+                self.measure_bounded_row(engine, min, max, y)?
+            }
+        })
+    }
+
+    /// Measure the height of a row's contents if it is laid out into a single
+    /// region.
+    fn measure_single_row(&self, engine: &mut Engine, y: usize) -> SourceResult<Abs> {
+        let mut resolved = Abs::zero();
+        for (_, width, cell) in self.row_cells(y) {
+            let size = Size::new(width, self.regions.base().y);
+            let pod = Regions::one(size, Axes::new(true, false));
+            let frame = self.body(cell).measure(engine, self.styles, pod)?.into_frame();
+            resolved.set_max(frame.height());
+        }
+        Ok(resolved)
+    }
+
+    /// Measure the height of a row's contents and clamp it to the row's bounds.
+    fn measure_bounded_row(
+        &self,
+        engine: &mut Engine,
+        min: Rel<Length>,
+        max: Option<Rel<Length>>,
+        y: usize,
+    ) -> SourceResult<Abs> {
+        let base = self.regions.base().y;
+        let mut resolved = self
+            .measure_single_row(engine, y)?
+            .max(min.resolve(self.styles).relative_to(base));
+        if let Some(max) = max {
+            resolved.set_min(max.resolve(self.styles).relative_to(base));
+        }
+        Ok(resolved)
+    }
+
+    /// Whether the grid has a header that repeats in each region.
+    fn repeats_header(&self) -> bool {
+        self.header.map_or(false, |header| header.repeat)
+    }
+
+    /// The index of the first row after the header, including the gutter row
+    /// directly below it. Zero if there is no header.
+    fn header_end(&self) -> usize {
+        self.header.map_or(0, |header| {
+            let end = if self.has_gutter { 2 * header.rows } else { header.rows };
+            end.min(self.rows.len())
+        })
+    }
+
+    /// Whether the grid has a footer that repeats in each region.
+    fn repeats_footer(&self) -> bool {
+        self.footer.map_or(false, |footer| footer.repeat)
+    }
+
+    /// The index of the first footer row. The number of rows if there is no
+    /// footer.
+    fn footer_start(&self) -> usize {
+        self.footer.map_or(self.rows.len(), |footer| {
+            let start = if self.has_gutter { 2 * footer.start } else { footer.start };
+            start.min(self.rows.len())
+        })
+    }
+
+    /// Whether the row is part of a header or footer that is repeated in each
+    /// region. Such rows never break across regions.
+    fn is_repeated(&self, y: usize) -> bool {
+        (self.repeats_header() && y < self.header_end())
+            || (self.repeats_footer() && y >= self.footer_start())
+    }
+
+    /// The regions available to rows. If the header and footer repeat, they
+    /// take away space in each follow-up region.
+    fn row_regions<'b>(&self, backlog: &'b mut Vec<Abs>) -> Regions<'b>
+    where
+        'a: 'b,
+    {
+        let mut regions: Regions<'b> = self.regions;
+        let mut height = Abs::zero();
+        if self.repeats_header() {
+            height += self.header_height;
+        }
+        if self.repeats_footer() {
+            height += self.footer_height;
+        }
+        if !height.is_zero() {
+            let shrink = |h: Abs| (h - height).max(Abs::zero());
+            backlog.extend(self.regions.backlog.iter().map(|&h| shrink(h)));
+            regions.backlog = backlog.as_slice();
+            regions.last = self.regions.last.map(shrink);
+        }
+        regions
+    }
+
+    /// Whether the current region is the last usable one. A region that only
+    /// contains the header counts as unused.
+    fn in_last(&self) -> bool {
+        let mut regions = self.regions;
+        if self.lrows.len() == self.header_rows {
+            regions.size.y = self.initial.y;
+        }
+        regions.in_last()
+    }
+
+    /// Whether the current region is full and a region break is called for.
+    fn is_full(&self) -> bool {
+        Abs::zero().fits(self.regions.size.y) && !self.in_last()
+    }
+
+    /// Finish rows for one region and repeat the header in the next one, if
+    /// necessary.
     fn finish_region(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        // Don't leave a header behind at the end of a region without any rows
+        // below it. If there's a usable region, lay it out there instead.
+        if self.header_rows > 0
+            && self.lrows.len() == self.header_rows
+            && !self.in_last()
+        {
+            self.lrows.clear();
+            self.regions.size.y = self.initial.y;
+        }
+
+        // Close off the rows with the footer, unless the region ended up
+        // empty.
+        if self.repeats_footer() && !self.lrows.is_empty() {
+            self.layout_footer(engine)?;
+        }
+
+        self.finish_region_internal(engine)?;
+
+        if self.repeats_header() {
+            self.layout_header(engine)?;
+        }
+
+        if self.repeats_footer() {
+            self.regions.size.y -= self.footer_height;
+        }
+
+        Ok(())
+    }
+
+    /// Finish rows for one region.
+    fn finish_region_internal(&mut self, engine: &mut Engine) -> SourceResult<()> {
         // Determine the height of existing rows in the region.
         let mut used = Abs::zero();
         let mut fr = Fr::zero();
@@ -712,34 +1604,215 @@ impl<'a> GridLayouter<'a> {
         self.rrows.push(rrows);
         self.regions.next();
         self.initial = self.regions.size;
+        self.header_rows = 0;
+
+        Ok(())
+    }
+
+    /// Measure how much height row `y` needs such that the cells spanning
+    /// multiple rows down to it fit into their rows.
+    fn measure_rowspans(&self, engine: &mut Engine, y: usize) -> SourceResult<Abs> {
+        let mut extra = Abs::zero();
+        for rowspan in self.rowspans.iter().filter(|rowspan| rowspan.end == y) {
+            // The cell may use the space of its rows in earlier regions and
+            // all of the space that is left in the current one.
+            let mut heights = self.spanned_heights(rowspan.y, y);
+            let current = heights.pop().unwrap_or_default();
+            heights.push(current + self.regions.size.y);
+
+            let width = self.span_width(rowspan.x, rowspan.cell);
+            let size = Size::new(width, heights[0]);
+            let mut pod = Regions::one(size, Axes::new(true, false));
+            pod.full = self.regions.full;
+            pod.backlog = &heights[1..];
+
+            let frames =
+                self.body(rowspan.cell).measure(engine, self.styles, pod)?.into_frames();
+            if let Some(frame) = frames.get(heights.len() - 1) {
+                extra.set_max(frame.height() - current);
+            }
+        }
+        Ok(extra)
+    }
+
+    /// The summed heights of the rows from `start` up to (excluding) `y` that
+    /// are already laid out, by region. The last entry is for the current
+    /// region.
+    fn spanned_heights(&self, start: usize, y: usize) -> Vec<Abs> {
+        let current: Vec<_> = self
+            .lrows
+            .iter()
+            .map(|row| match row {
+                Row::Frame(frame, row) => (*row, frame.height()),
+                Row::Fr(_, row) => (*row, Abs::zero()),
+            })
+            .collect();
+
+        let previous = self.rrows.iter().rev().map(|pieces| {
+            pieces.iter().map(|piece| (piece.y, piece.height)).collect::<Vec<_>>()
+        });
+
+        // Rows in a repeated header or footer are never spread across regions.
+        let repeated = self.is_repeated(start);
+
+        let mut heights: Vec<Abs> = vec![];
+        for (i, rows) in std::iter::once(current).chain(previous).enumerate() {
+            let mut spanned = rows
+                .iter()
+                .filter(|(row, _)| (start..y).contains(row))
+                .map(|&(_, height)| height)
+                .peekable();
+
+            // Since the spanned rows are contiguous, we're done once a region
+            // doesn't contain any of them.
+            if i > 0 && spanned.peek().is_none() {
+                break;
+            }
+
+            heights.push(spanned.sum());
+
+            if repeated {
+                break;
+            }
+        }
+
+        heights.reverse();
+        heights
+    }
+
+    /// Layout the cells spanning multiple rows into the regions their rows
+    /// ended up in.
+    fn layout_rowspans(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        for rowspan in std::mem::take(&mut self.rowspans) {
+            let dx: Abs = self.rcols[..rowspan.x].iter().sum();
+            let width = self.span_width(rowspan.x, rowspan.cell);
+
+            // The index of each region containing some of the spanned rows
+            // along with the offset and summed height of those rows.
+            let mut parts = vec![];
+            for (i, pieces) in self.rrows.iter().enumerate() {
+                let mut dy = Abs::zero();
+                let mut part: Option<(Abs, Abs)> = None;
+                for piece in pieces {
+                    if (rowspan.y..=rowspan.end).contains(&piece.y) {
+                        part.get_or_insert((dy, Abs::zero())).1 += piece.height;
+                    }
+                    dy += piece.height;
+                }
+                if let Some((dy, height)) = part {
+                    parts.push((i, dy, height));
+                }
+            }
+
+            // A cell in a repeated header or footer is laid out anew in each
+            // region, any other cell breaks across the regions of its rows.
+            let repeated = self.is_repeated(rowspan.y);
+            let chunk = if repeated { 1 } else { parts.len().max(1) };
+
+            for (k, group) in parts.chunks(chunk).enumerate() {
+                let heights: Vec<Abs> = group.iter().map(|&(_, _, h)| h).collect();
+                let size = Size::new(width, heights[0]);
+                let mut pod = Regions::one(size, Axes::splat(true));
+                pod.backlog = &heights[1..];
+
+                self.continued = k > 0 && rowspan.y < self.header_end();
+                let body = self.body(rowspan.cell);
+                self.continued = false;
+
+                let fragment = body.layout(engine, self.styles, pod)?;
+                for (&(i, dy, _), frame) in group.iter().zip(fragment) {
+                    self.finished[i].push_frame(Point::new(dx, dy), frame);
+                }
+            }
+        }
 
         Ok(())
     }
 
-    /// Get the content of the cell in column `x` and row `y`.
+    /// For each track position, the index of the position at which the cell
+    /// covering it starts.
+    fn parents(&self) -> Vec<usize> {
+        let c = self.cols.len();
+        let mut parents: Vec<usize> = (0..c * self.rows.len()).collect();
+        for y in 0..self.rows.len() {
+            for x in 0..c {
+                let Some(cell) = self.cell(x, y) else { continue };
+                for dy in 0..self.span_tracks(cell.rowspan) {
+                    for dx in 0..self.span_tracks(cell.colspan) {
+                        parents[(y + dy) * c + x + dx] = y * c + x;
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    /// The content to lay out for a cell.
+    fn body(&self, cell: &Cell) -> Content {
+        if self.continued {
+            cell.body.clone().styled_with_map(self.grid.continued.clone())
+        } else {
+            cell.body.clone()
+        }
+    }
+
+    /// The cells that start in row `y` and span only that row, along with
+    /// their horizontal offset and width.
+    fn row_cells(&self, y: usize) -> Vec<(Abs, Abs, &'a Cell)> {
+        let mut cells = vec![];
+        let mut dx = Abs::zero();
+        for (x, &rcol) in self.rcols.iter().enumerate() {
+            if let Some(cell) = self.cell(x, y).filter(|cell| cell.rowspan.get() == 1) {
+                cells.push((dx, self.span_width(x, cell), cell));
+            }
+            dx += rcol;
+        }
+        cells
+    }
+
+    /// The width of a cell whose left edge is in column `x`.
+    fn span_width(&self, x: usize, cell: &Cell) -> Abs {
+        self.rcols[x..x + self.span_tracks(cell.colspan)].iter().sum()
+    }
+
+    /// The number of tracks covered when spanning `n` columns or rows,
+    /// including the gutter tracks in between.
+    fn span_tracks(&self, n: NonZeroUsize) -> usize {
+        if self.has_gutter {
+            2 * n.get() - 1
+        } else {
+            n.get()
+        }
+    }
+
+    /// Get the cell whose left edge is in column `x` and which starts in row
+    /// `y`.
     ///
-    /// Returns `None` if it's a gutter cell.
+    /// Returns `None` if it's a gutter cell or covered by another cell.
     #[track_caller]
-    fn cell(&self, mut x: usize, y: usize) -> Option<&'a Content> {
+    fn cell(&self, x: usize, y: usize) -> Option<&'a Cell> {
         assert!(x < self.cols.len());
         assert!(y < self.rows.len());
 
-        // Columns are reorder, but the cell slice is not.
-        if self.is_rtl {
-            x = self.cols.len() - 1 - x;
-        }
-
-        if self.has_gutter {
-            // Even columns and rows are children, odd ones are gutter.
-            if x % 2 == 0 && y % 2 == 0 {
-                let c = 1 + self.cols.len() / 2;
-                self.cells.get((y / 2) * c + x / 2)
-            } else {
-                None
+        // Even columns and rows are children, odd ones are gutter.
+        let (x, y) = if self.has_gutter {
+            if x % 2 == 1 || y % 2 == 1 {
+                return None;
             }
+            (x / 2, y / 2)
         } else {
-            let c = self.cols.len();
-            self.cells.get(y * c + x)
+            (x, y)
+        };
+
+        // Columns are reordered, but the grid is not. In RTL, the left edge of
+        // a cell is in its last column.
+        if !self.is_rtl {
+            return self.grid.cell(x, y);
         }
+
+        let last = self.grid.cols() - 1 - x;
+        let (px, py) = self.grid.parent(last, y)?;
+        let cell = self.grid.cell(px, py)?;
+        (py == y && px + cell.colspan.get() - 1 == last).then_some(cell)
     }
 }
@@ -8,7 +8,7 @@ use once_cell::sync::Lazy;
 
 use super::Preparation;
 use crate::syntax::link_prefix;
-use crate::text::{Lang, TextElem};
+use crate::text::{Hyphenation, Lang, TextElem};
 
 /// Generated by the following command:
 ///
@@ -143,14 +143,26 @@ pub(super) fn breakpoints<'a>(
             }
 
             let end = last + word.len();
-            let mut offset = last;
 
-            // Determine the language to hyphenate this word in.
-            let Some(lang) = lang_at(p, last) else { break 'hyphenate };
+            // Use custom patterns or the bundled ones for the word's
+            // language.
+            let custom = hyphenation_at(p, last).and_then(|h| h.hyphenate(word));
+            let offsets = match custom {
+                Some(offsets) => offsets,
+                None => {
+                    let Some(lang) = lang_at(p, last) else { break 'hyphenate };
+                    let mut offset = 0;
+                    hypher::hyphenate(word, lang)
+                        .map(|syllable| {
+                            offset += syllable.len();
+                            offset
+                        })
+                        .collect()
+                }
+            };
 
-            for syllable in hypher::hyphenate(word, lang) {
+            for offset in offsets.into_iter().map(|i| last + i) {
                 // Don't hyphenate after the final syllable.
-                offset += syllable.len();
                 if offset == end {
                     continue;
                 }
@@ -163,7 +175,7 @@ pub(super) fn breakpoints<'a>(
 
                 // Filter out forbidden hyphenation opportunities.
                 if matches!(
-                    syllable.chars().next_back().map(|c| lb.get(c)),
+                    text[..offset].chars().next_back().map(|c| lb.get(c)),
                     Some(LineBreak::Glue | LineBreak::WordJoiner | LineBreak::ZWJ)
                 ) {
                     continue;
@@ -249,6 +261,12 @@ fn hyphenate_at(p: &Preparation, offset: usize) -> bool {
         .unwrap_or(false)
 }
 
+/// The custom hyphenation patterns at the given offset.
+fn hyphenation_at(p: &Preparation, offset: usize) -> Option<Hyphenation> {
+    let shaped = p.find(offset)?.text()?;
+    TextElem::hyphenation_in(shaped.styles)
+}
+
 /// The text language at the given offset.
 fn lang_at(p: &Preparation, offset: usize) -> Option<hypher::Lang> {
     let lang = p.lang.or_else(|| {
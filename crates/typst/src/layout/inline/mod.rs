@@ -1,31 +1,33 @@
 mod linebreak;
 mod shaping;
 
+use std::num::NonZeroUsize;
+
 use comemo::{Prehashed, Tracked, TrackedMut};
 use unicode_bidi::{BidiInfo, Level as BidiLevel};
 use unicode_script::{Script, UnicodeScript};
 
 use self::linebreak::{breakpoints, Breakpoint};
 use self::shaping::{
-    is_gb_style, is_of_cj_script, shape, ShapedGlyph, ShapedText, BEGIN_PUNCT_PAT,
-    END_PUNCT_PAT,
+    check_number_features, is_gb_style, is_of_cj_script, shape, ShapedGlyph, ShapedText,
+    BEGIN_PUNCT_PAT, END_PUNCT_PAT,
 };
 use crate::diag::{bail, SourceResult};
 use crate::engine::{Engine, Route};
 use crate::eval::Tracer;
-use crate::foundations::{Content, Resolve, Smart, StyleChain};
-use crate::introspection::{Introspector, Locator, MetaElem};
+use crate::foundations::{Content, NativeElement, Resolve, Smart, StyleChain, Styles};
+use crate::introspection::{Introspector, Location, Locator, Meta, MetaElem};
 use crate::layout::{
-    Abs, AlignElem, Axes, BoxElem, Dir, Em, FixedAlign, Fr, Fragment, Frame, HElem,
-    Layout, Point, Regions, Size, Sizing, Spacing,
+    Abs, AlignElem, Axes, BoxElem, Dir, Em, FixedAlign, Fr, Fragment, Frame, FrameItem,
+    HElem, Layout, PageElem, Point, Ratio, Regions, Size, Sizing, Spacing, Transform,
 };
 use crate::math::EquationElem;
-use crate::model::{Linebreaks, ParElem};
+use crate::model::{LineNumbering, Linebreaks, Microtype, ParElem, ParLine};
 use crate::syntax::Span;
 use crate::text::{
     Lang, LinebreakElem, SmartQuoteElem, SmartQuoter, SmartQuotes, SpaceElem, TextElem,
 };
-use crate::util::Numeric;
+use crate::util::{hash128, Numeric};
 use crate::World;
 
 /// Layouts content inline.
@@ -133,6 +135,12 @@ struct Preparation<'a> {
     linebreaks: Smart<Linebreaks>,
     /// The text size.
     size: Abs,
+    /// How to number the paragraph's lines.
+    line_numbering: Option<LineNumbering>,
+    /// Microtypographic refinements.
+    microtype: Microtype,
+    /// The paragraph's styles.
+    styles: StyleChain<'a>,
 }
 
 impl<'a> Preparation<'a> {
@@ -384,6 +392,19 @@ impl<'a> Line<'a> {
         self.items().filter_map(Item::text).map(|s| s.shrinkability()).sum()
     }
 
+    /// The width of the line's text.
+    fn text_width(&self) -> Abs {
+        self.items().filter_map(Item::text).map(|s| s.width).sum()
+    }
+
+    /// How much the line can stretch or shrink by scaling its glyphs.
+    fn expandability(&self, expansion: Ratio) -> Abs {
+        if !self.justify {
+            return Abs::zero();
+        }
+        expansion.of(self.text_width())
+    }
+
     /// The sum of fractions in the line.
     fn fr(&self) -> Fr {
         self.items()
@@ -555,7 +576,13 @@ fn prepare<'a>(
         let end = cursor + segment.len();
         match segment {
             Segment::Text(_) => {
+                let start = items.len();
                 shape_range(&mut items, engine, &bidi, cursor..end, &spans, styles);
+                for item in &items[start..] {
+                    if let Some(text) = item.text() {
+                        check_number_features(engine, text);
+                    }
+                }
             }
             Segment::Spacing(spacing) => match spacing {
                 Spacing::Rel(v) => {
@@ -611,6 +638,9 @@ fn prepare<'a>(
         leading: ParElem::leading_in(styles),
         linebreaks: ParElem::linebreaks_in(styles),
         size: TextElem::size_in(styles),
+        line_numbering: ParElem::line_numbering_in(styles),
+        microtype: ParElem::microtype_in(styles),
+        styles,
     })
 }
 
@@ -872,14 +902,19 @@ fn linebreak_optimized<'a>(
             let attempt = line(engine, p, start..end, breakpoint);
 
             // Determine how much the line's spaces would need to be stretched
-            // to make it the desired width.
-            let delta = width - attempt.width;
+            // to make it the desired width. Glyphs that protrude into the
+            // margins don't take up any of the width.
+            let mut delta = width - attempt.width;
+            if p.microtype.protrusion {
+                let (left, right) = hanging(p, &reorder(&attempt).0);
+                delta += left + right;
+            }
             // Determine how much stretch are permitted.
             let adjust = if delta >= Abs::zero() {
                 attempt.stretchability()
             } else {
                 attempt.shrinkability()
-            };
+            } + attempt.expandability(p.microtype.expansion);
             // Ideally, the ratio should between -1.0 and 1.0, but sometimes a value above 1.0
             // is possible, in which case the line is underfull.
             let mut ratio = delta / adjust;
@@ -1173,10 +1208,15 @@ fn finalize(
     };
 
     // Stack the lines into one frame per region.
-    let mut frames: Vec<Frame> = lines
-        .iter()
-        .map(|line| commit(engine, p, line, width, region.y))
-        .collect::<SourceResult<_>>()?;
+    let mut frames = vec![];
+    let mut counter = LineCounter::default();
+    for line in lines {
+        let mut frame = commit(engine, p, line, width, region.y)?;
+        if let Some(numbering) = &p.line_numbering {
+            number_line(engine, p, numbering, &mut counter, &mut frame)?;
+        }
+        frames.push(frame);
+    }
 
     // Prevent orphans.
     if frames.len() >= 2 && !frames[1].is_empty() {
@@ -1196,6 +1236,100 @@ fn finalize(
     Ok(Fragment::frames(frames))
 }
 
+/// Mark a line's frame as a numbered line and add its number in the margin.
+fn number_line(
+    engine: &mut Engine,
+    p: &Preparation,
+    numbering: &LineNumbering,
+    counter: &mut LineCounter,
+    frame: &mut Frame,
+) -> SourceResult<()> {
+    // The location must not depend on the number, which is only known once
+    // the previous layout iteration has positioned all lines.
+    let location = engine.locator.locate(hash128(&ParLine::elem()));
+    let number = counter.number(engine.introspector, numbering, location);
+
+    let mut elem = ParLine::new(number).pack();
+    elem.set_location(location);
+    frame.prepend(Point::zero(), FrameItem::Meta(Meta::Elem(elem), Size::zero()));
+
+    if !numbering.shows(number) {
+        return Ok(());
+    }
+
+    // Lay out the number without line numbering, so that it doesn't number
+    // its own line.
+    let local = Styles::from(ParElem::set_line_numbering(None));
+    let styles = p.styles.chain(&local);
+    let pod = Regions::one(Size::splat(Abs::inf()), Axes::splat(false));
+    let label = numbering.numbering.apply(engine, &[number])?.display();
+    let label = label.layout(engine, styles, pod)?.into_frame();
+
+    // Place the number in the page margin at the line's start edge, so that
+    // the numbers of indented lines line up with the others. The line's
+    // position on the page is known from the previous layout iteration.
+    let position = engine.introspector.position(location);
+    let (size, margin) = PageElem::resolve_margins(p.styles, position.page);
+    let gap = Em::one().at(p.size);
+    let x = match TextElem::dir_in(p.styles) {
+        Dir::RTL => size.x - margin.right + gap,
+        _ => margin.left - gap - label.width(),
+    };
+    let y = frame.baseline() - label.baseline();
+    frame.push_frame(Point::new(x - position.point.x, y), label);
+
+    Ok(())
+}
+
+/// Numbers the lines of a paragraph.
+///
+/// Only the first line's number is determined from the lines that precede it
+/// in the document. The lines of a paragraph directly follow each other in
+/// document order, so the later ones count up from there.
+#[derive(Default)]
+struct LineCounter {
+    /// The index of the next line among all lines in the document.
+    next: Option<usize>,
+    /// The page of the previous line and the index of the first line on it.
+    page: Option<(NonZeroUsize, usize)>,
+}
+
+impl LineCounter {
+    /// Determine the number of the line at the given location.
+    fn number(
+        &mut self,
+        introspector: Tracked<Introspector>,
+        numbering: &LineNumbering,
+        location: Location,
+    ) -> usize {
+        let selector = ParLine::elem().select();
+        let index = self.next.unwrap_or_else(|| {
+            introspector.query_count(&selector.clone().before(location.into(), false))
+        });
+        self.next = Some(index + 1);
+
+        if !numbering.restart {
+            return numbering.start + index;
+        }
+
+        // Lines are in document order, so the lines on earlier pages come
+        // first.
+        let page = introspector.page(location);
+        let first = match self.page {
+            Some((prev, first)) if prev == page => first,
+            _ => {
+                let lines = introspector.query(&selector);
+                let before = &lines[..index.min(lines.len())];
+                before.partition_point(|line| {
+                    introspector.page(line.location().unwrap()) < page
+                })
+            }
+        };
+        self.page = Some((page, first));
+        numbering.start + index - first
+    }
+}
+
 /// Merge two line frames
 fn merge(first: &mut Frame, second: Frame, leading: Abs) {
     let offset = first.height() + leading;
@@ -1221,32 +1355,10 @@ fn commit(
         offset += p.hang;
     }
 
-    // Handle hanging punctuation to the left.
-    if let Some(Item::Text(text)) = reordered.first() {
-        if let Some(glyph) = text.glyphs.first() {
-            if !text.dir.is_positive()
-                && TextElem::overhang_in(text.styles)
-                && (reordered.len() > 1 || text.glyphs.len() > 1)
-            {
-                let amount = overhang(glyph.c) * glyph.x_advance.at(text.size);
-                offset -= amount;
-                remaining += amount;
-            }
-        }
-    }
-
-    // Handle hanging punctuation to the right.
-    if let Some(Item::Text(text)) = reordered.last() {
-        if let Some(glyph) = text.glyphs.last() {
-            if text.dir.is_positive()
-                && TextElem::overhang_in(text.styles)
-                && (reordered.len() > 1 || text.glyphs.len() > 1)
-            {
-                let amount = overhang(glyph.c) * glyph.x_advance.at(text.size);
-                remaining += amount;
-            }
-        }
-    }
+    // Handle hanging punctuation and protrusion.
+    let (left, right) = hanging(p, &reordered);
+    offset -= left;
+    remaining += left + right;
 
     // Determine how much additional space is needed.
     // The justicication_ratio is for the first step justification,
@@ -1259,6 +1371,18 @@ fn commit(
 
     let shrink = line.shrinkability();
     let stretch = line.stretchability();
+
+    // Let the glyphs take up a part of the adjustment by scaling them
+    // horizontally, in proportion to how much they can be scaled.
+    let mut expansion = Abs::zero();
+    let expand = line.expandability(p.microtype.expansion);
+    if expand > Abs::zero() && fr.is_zero() {
+        let adjust = if remaining < Abs::zero() { shrink } else { stretch };
+        expansion = (remaining * (expand / (expand + adjust))).max(-expand).min(expand);
+        remaining -= expansion;
+    }
+
+    let before = remaining;
     if remaining < Abs::zero() && shrink > Abs::zero() {
         // Attempt to reduce the length of the line, using shrinkability.
         justification_ratio = (remaining / shrink).max(-1.0);
@@ -1278,6 +1402,13 @@ fn commit(
         }
     }
 
+    // The justified text is scaled as a whole, including the space that was
+    // added to or removed from it.
+    let mut scale = 1.0;
+    if !expansion.is_zero() {
+        scale += expansion / (line.text_width() + before - remaining);
+    }
+
     let mut top = Abs::zero();
     let mut bottom = Abs::zero();
 
@@ -1309,8 +1440,12 @@ fn commit(
                 }
             }
             Item::Text(shaped) => {
-                let frame =
+                let mut frame =
                     shaped.build(engine, justification_ratio, extra_justification);
+                if scale != 1.0 {
+                    frame.transform(Transform::scale(Ratio::new(scale), Ratio::one()));
+                    frame.size_mut().x *= scale;
+                }
                 push(&mut offset, frame);
             }
             Item::Frame(frame) | Item::Meta(frame) => {
@@ -1378,6 +1513,36 @@ fn reorder<'a>(line: &'a Line<'a>) -> (Vec<&Item<'a>>, bool) {
     (reordered, starts_rtl)
 }
 
+/// How far the outer glyphs of a line, given in visual order, hang into the
+/// left and right margins.
+fn hanging(p: &Preparation, reordered: &[&Item]) -> (Abs, Abs) {
+    let hang = |item: Option<&&Item>, left: bool| {
+        let Some(Item::Text(text)) = item else { return Abs::zero() };
+        let glyph = if left { text.glyphs.first() } else { text.glyphs.last() };
+        let Some(glyph) = glyph else { return Abs::zero() };
+
+        // Never let a lone glyph hang.
+        if reordered.len() == 1 && text.glyphs.len() == 1 {
+            return Abs::zero();
+        }
+
+        // Punctuation hangs only into the end margin, while protruding
+        // quotes hang into both.
+        let end = left != text.dir.is_positive();
+        let mut amount = 0.0;
+        if end && (p.microtype.protrusion || TextElem::overhang_in(text.styles)) {
+            amount = overhang(glyph.c);
+        }
+        if p.microtype.protrusion {
+            amount = amount.max(protrusion(glyph.c));
+        }
+
+        amount * glyph.x_advance.at(text.size)
+    };
+
+    (hang(reordered.first(), true), hang(reordered.last(), false))
+}
+
 /// How much a character should hang into the end margin.
 ///
 /// For more discussion, see:
@@ -1398,3 +1563,21 @@ fn overhang(c: char) -> f64 {
         _ => 0.0,
     }
 }
+
+/// How much a character should protrude into either margin when protrusion
+/// is enabled, in addition to the punctuation that hangs into the end margin.
+fn protrusion(c: char) -> f64 {
+    match c {
+        // Quotes.
+        '"' | '\'' | '‘' | '’' | '‚' | '“' | '”' | '„' => 0.5,
+        '«' | '»' | '‹' | '›' => 0.3,
+
+        // Brackets.
+        '(' | ')' | '[' | ']' => 0.05,
+
+        // Other punctuation at the end of sentences.
+        '!' | '?' => 0.2,
+
+        _ => 0.0,
+    }
+}
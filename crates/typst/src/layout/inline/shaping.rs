@@ -5,17 +5,19 @@ use std::str::FromStr;
 
 use az::SaturatingAs;
 use ecow::EcoString;
-use rustybuzz::{Tag, UnicodeBuffer};
+use rustybuzz::UnicodeBuffer;
+use ttf_parser::Tag;
 use unicode_script::{Script, UnicodeScript};
 
 use super::SpanMapper;
+use crate::diag::warning;
 use crate::engine::Engine;
-use crate::foundations::StyleChain;
+use crate::foundations::{Smart, StyleChain};
 use crate::layout::{Abs, Dir, Em, Frame, FrameItem, Point, Size};
 use crate::syntax::Span;
 use crate::text::{
-    decorate, families, features, variant, Font, FontVariant, Glyph, Lang, Region,
-    TextElem, TextItem,
+    decorate, families, features, variant, Font, FontVariant, Glyph, Lang, NumberType,
+    NumberWidth, Region, TextElem, TextItem,
 };
 use crate::util::SliceExt;
 use crate::World;
@@ -436,8 +438,9 @@ impl<'a> ShapedText<'a> {
             .chain(fallback_func.iter().map(|f| f()))
             .flatten();
 
+        let axes = TextElem::axes_in(self.styles).coords();
         chain.find_map(|id| {
-            let font = world.font(id)?;
+            let font = world.font(id)?.with_variations(&axes);
             let ttf = font.ttf();
             let glyph_id = ttf.glyph_index('-')?;
             let x_advance = font.to_em(ttf.glyph_hor_advance(glyph_id)?);
@@ -562,6 +565,7 @@ struct ShapingContext<'a, 'v> {
     size: Abs,
     variant: FontVariant,
     features: Vec<rustybuzz::Feature>,
+    axes: Vec<(Tag, f64)>,
     fallback: bool,
     dir: Dir,
 }
@@ -588,6 +592,7 @@ pub(super) fn shape<'a>(
         styles,
         variant: variant(styles),
         features: features(styles),
+        axes: TextElem::axes_in(styles).coords(),
         fallback: TextElem::fallback_in(styles),
         dir,
     };
@@ -618,6 +623,57 @@ pub(super) fn shape<'a>(
     }
 }
 
+/// Warn about digits in the shaped text whose font doesn't support the
+/// requested number type or width.
+pub(super) fn check_number_features(engine: &mut Engine, text: &ShapedText) {
+    let mut requested = vec![];
+    match TextElem::number_type_in(text.styles) {
+        Smart::Auto => {}
+        Smart::Custom(NumberType::Lining) => requested.push((b"lnum", "lining numbers")),
+        Smart::Custom(NumberType::OldStyle) => {
+            requested.push((b"onum", "old-style numbers"))
+        }
+    }
+
+    match TextElem::number_width_in(text.styles) {
+        Smart::Auto => {}
+        Smart::Custom(NumberWidth::Proportional) => {
+            requested.push((b"pnum", "proportional numbers"))
+        }
+        Smart::Custom(NumberWidth::Tabular) => {
+            requested.push((b"tnum", "tabular numbers"))
+        }
+    }
+
+    if requested.is_empty() {
+        return;
+    }
+
+    for glyph in text.glyphs.iter().filter(|glyph| glyph.c.is_ascii_digit()) {
+        for &(tag, name) in &requested {
+            if !has_feature(&glyph.font, Tag::from_bytes(tag)) {
+                engine.tracer.warn(warning!(
+                    glyph.span.0,
+                    "font \"{}\" does not support {}",
+                    glyph.font.info().family,
+                    name;
+                    hint: "try using a font that has the `{}` feature",
+                    std::str::from_utf8(tag).unwrap()
+                ));
+            }
+        }
+    }
+}
+
+/// Whether the font has an OpenType layout feature with the given tag.
+fn has_feature(font: &Font, tag: Tag) -> bool {
+    let tables = font.ttf().tables();
+    [tables.gsub, tables.gpos]
+        .into_iter()
+        .flatten()
+        .any(|table| table.features.find(tag).is_some())
+}
+
 /// Shape text with font fallback using the `families` iterator.
 fn shape_segment<'a>(
     ctx: &mut ShapingContext,
@@ -636,6 +692,7 @@ fn shape_segment<'a>(
     let mut selection = families.find_map(|family| {
         book.select(family, ctx.variant)
             .and_then(|id| world.font(id))
+            .map(|font| font.with_variations(&ctx.axes))
             .filter(|font| !ctx.used.contains(font))
     });
 
@@ -645,6 +702,7 @@ fn shape_segment<'a>(
         selection = book
             .select_fallback(first, ctx.variant, text)
             .and_then(|id| world.font(id))
+            .map(|font| font.with_variations(&ctx.axes))
             .filter(|font| !ctx.used.contains(font));
     }
 
@@ -1,7 +1,7 @@
 use crate::diag::SourceResult;
 use crate::engine::Engine;
-use crate::foundations::{dict, func, Content, Dict, StyleChain, Styles};
-use crate::layout::{Abs, Axes, Layout, Regions, Size};
+use crate::foundations::{dict, func, Content, Dict, Resolve, StyleChain, Styles};
+use crate::layout::{Abs, Axes, Layout, Length, Regions, Size};
 
 /// Measures the layouted size of content.
 ///
@@ -37,6 +37,21 @@ use crate::layout::{Abs, Axes, Layout, Regions, Size};
 /// #thing[Welcome]
 /// ```
 ///
+/// To find out how tall content becomes when it is wrapped to a specific
+/// width, pass that width as the `width` argument. Paragraphs and other
+/// breakable content will then wrap just like they would in a container of
+/// that width.
+///
+/// ```example
+/// #style(styles => {
+///   let body = lorem(20)
+///   let narrow = measure(body, styles, width: 3cm)
+///   let wide = measure(body, styles, width: 6cm)
+///   [Narrow: #narrow.height \
+///    Wide: #wide.height]
+/// })
+/// ```
+///
 /// The measure function returns a dictionary with the entries `width` and
 /// `height`, both of type [`length`]($length).
 #[func]
@@ -47,9 +62,17 @@ pub fn measure(
     content: Content,
     /// The styles with which to layout the content.
     styles: Styles,
+    /// The width available to the content. If given, the content is laid out
+    /// as if it were placed into a container of this width. The measured
+    /// width may still be smaller than the given one if the content doesn't
+    /// need all of it.
+    #[named]
+    #[default]
+    width: Option<Length>,
 ) -> SourceResult<Dict> {
-    let pod = Regions::one(Axes::splat(Abs::inf()), Axes::splat(false));
     let styles = StyleChain::new(&styles);
+    let width = width.map_or(Abs::inf(), |width| width.resolve(styles));
+    let pod = Regions::one(Size::new(width, Abs::inf()), Axes::splat(false));
     let frame = content.measure(engine, styles, pod)?.into_frame();
     let Size { x, y } = frame.size();
     Ok(dict! { "width" => x, "height" => y })
@@ -3,23 +3,26 @@ use std::num::NonZeroUsize;
 use std::ptr;
 use std::str::FromStr;
 
+use comemo::Prehashed;
+
 use crate::diag::{bail, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, AutoValue, Cast, Content, Dict, Fold, Func, NativeElement, Resolve,
-    Smart, StyleChain, Value,
+    cast, dict, elem, func, scope, AutoValue, Cast, Content, Dict, Fold, Func,
+    NativeElement, Resolve, Smart, StyleChain, Styles, Value,
 };
 use crate::introspection::{Counter, CounterKey, ManualPageCounter, Meta};
 use crate::layout::{
-    Abs, Align, AlignElem, Axes, ColumnsElem, Dir, Fragment, Frame, HAlign, Layout,
-    Length, Point, Ratio, Regions, Rel, Sides, Size, VAlign,
+    Abs, Align, AlignElem, Angle, Axes, ColumnsElem, Dir, FlowElem, Fragment, Frame,
+    FrameItem, HAlign, Layout, Length, PdfPageBoxes, Point, Ratio, Regions, Rel, Sides,
+    Size, Transform, VAlign,
 };
 
-use crate::model::Numbering;
-use crate::syntax::Spanned;
+use crate::model::{FootnoteEntry, Numbering};
+use crate::syntax::{Span, Spanned};
 use crate::text::TextElem;
 use crate::util::{NonZeroExt, Numeric, Scalar};
-use crate::visualize::Paint;
+use crate::visualize::{Color, FixedStroke, Geometry, Paint, Path};
 
 /// Layouts its child onto one or multiple pages.
 ///
@@ -40,7 +43,7 @@ use crate::visualize::Paint;
 ///
 /// There you go, US friends!
 /// ```
-#[elem]
+#[elem(scope)]
 pub struct PageElem {
     /// A standard paper size to set width and height.
     #[external]
@@ -173,6 +176,39 @@ pub struct PageElem {
     #[default(NonZeroUsize::ONE)]
     pub columns: NonZeroUsize,
 
+    /// The distance between the lines of a baseline grid that the lines of
+    /// paragraphs snap to.
+    ///
+    /// With a baseline grid, the baselines of the body text are moved down to
+    /// the next multiple of the grid distance, measured from the top of the
+    /// page's content area. This way, the lines in facing columns and on both
+    /// sides of a sheet line up, even if headings, figures, or equations
+    /// between them have arbitrary heights. For the best results, the grid
+    /// distance should match the distance between two lines of body text, so
+    /// that no additional space is needed between them.
+    ///
+    /// Top floats take up whole grid cells. Paragraphs can opt out of the
+    /// grid with their [`snap`]($par.snap) property.
+    ///
+    /// ```example
+    /// #set page(
+    ///   height: 120pt,
+    ///   columns: 2,
+    ///   baseline-grid: 14pt,
+    /// )
+    /// #set par(leading: 14pt - 1em)
+    ///
+    /// = Grid
+    /// The lines in both columns
+    /// line up.
+    /// #colbreak()
+    /// #text(1.2em)[Larger text]
+    /// is followed by lines on
+    /// the grid again.
+    /// ```
+    #[resolve]
+    pub baseline_grid: Option<Length>,
+
     /// The page's background color.
     ///
     /// This instructs the printer to color the complete page with the given
@@ -188,6 +224,52 @@ pub struct PageElem {
     #[borrowed]
     pub fill: Option<Paint>,
 
+    /// How far the page's fill and background extend beyond its edges.
+    ///
+    /// For professional printing, pages are typically printed on larger
+    /// sheets and then trimmed to their final size. To avoid white slivers at
+    /// the edges after trimming, colors and images that should reach the
+    /// edge of the page must extend a bit beyond it. This area is called the
+    /// _bleed._
+    ///
+    /// The page's `width` and `height` remain the dimensions of the finished
+    /// page. The exported page grows by the bleed on all sides and, in PDF,
+    /// the finished page and bleed area are marked with a trim box and a
+    /// bleed box, respectively. The [fill]($page.fill) and
+    /// [background]($page.background) extend into the bleed area.
+    ///
+    /// ```example
+    /// #set page(
+    ///   width: 4cm,
+    ///   height: 3cm,
+    ///   bleed: 3mm,
+    ///   fill: aqua,
+    /// )
+    ///
+    /// Full bleed!
+    /// ```
+    #[resolve]
+    pub bleed: Length,
+
+    /// Whether to draw crop marks outside of the bleed area.
+    ///
+    /// Crop marks indicate where the printed sheet should be trimmed. When
+    /// enabled, the exported page additionally grows by the space needed for
+    /// the marks.
+    ///
+    /// ```example
+    /// #set page(
+    ///   width: 4cm,
+    ///   height: 3cm,
+    ///   bleed: 3mm,
+    ///   crop-marks: true,
+    /// )
+    ///
+    /// Trim here.
+    /// ```
+    #[default(false)]
+    pub crop_marks: bool,
+
     /// How to [number]($numbering) the pages.
     ///
     /// If an explicit `footer` (or `header` for top-aligned numbering) is
@@ -320,6 +402,45 @@ pub struct PageElem {
     #[borrowed]
     pub foreground: Option<Content>,
 
+    /// A faded watermark in the page's background.
+    ///
+    /// The watermark is placed above the page's [fill]($page.fill), but below
+    /// its [background]($page.background) and body, so it never affects the
+    /// layout of the page's content.
+    ///
+    /// This can either be content, which is then displayed centered along the
+    /// page's diagonal, or a dictionary with the following keys:
+    ///
+    /// - `body`: The content of the watermark.
+    /// - `angle`: How to rotate the watermark. If set to `{auto}` (the
+    ///   default), it is rotated along the diagonal from the bottom left to
+    ///   the top right corner of the page.
+    /// - `opacity`: The opacity of the watermark's solid colors. Defaults to
+    ///   `{20%}`.
+    /// - `repeat`: Whether to tile the watermark across the whole page instead
+    ///   of displaying it once in the center. Defaults to `{false}`.
+    ///
+    /// ```example
+    /// #set page(watermark: text(40pt)[*DRAFT*])
+    ///
+    /// = Preliminary results
+    /// The numbers in this report
+    /// have not been reviewed yet.
+    /// ```
+    ///
+    /// ```example
+    /// #set page(watermark: (
+    ///   body: [Confidential],
+    ///   angle: -30deg,
+    ///   opacity: 30%,
+    ///   repeat: true,
+    /// ))
+    ///
+    /// Do not distribute.
+    /// ```
+    #[borrowed]
+    pub watermark: Option<Watermark>,
+
     /// The contents of the page(s).
     ///
     /// Multiple pages will be created if the content does not fit on a single
@@ -333,25 +454,63 @@ pub struct PageElem {
     pub clear_to: Option<Parity>,
 }
 
+#[scope]
 impl PageElem {
-    /// A document can consist of multiple `PageElem`s, one per run of pages
-    /// with equal properties (not one per actual output page!). The `number` is
-    /// the physical page number of the first page of this run. It is mutated
-    /// while we post-process the pages in this function. This function returns
-    /// a fragment consisting of multiple frames, one per output page of this
-    /// page run.
-    #[tracing::instrument(skip_all)]
-    pub fn layout(
-        &self,
-        engine: &mut Engine,
-        styles: StyleChain,
-        page_counter: &mut ManualPageCounter,
-        extend_to: Option<Parity>,
-    ) -> SourceResult<Fragment> {
-        tracing::info!("Page layout");
+    /// Retrieves the geometry of pages in the given styles.
+    ///
+    /// Returns a dictionary with the keys `width` and `height` for the page's
+    /// dimensions, `margin` with the resolved `top`, `right`, `bottom`, and
+    /// `left` margins, and `inner` with the `width` and `height` of the area
+    /// within the margins. Dimensions that are `{auto}` in the active
+    /// [set rules]($styling/#set-rules) because the page fits its content
+    /// are `{auto}` here as well. For two-sided margins, `left` and `right`
+    /// refer to the margins of odd pages.
+    ///
+    /// This lets you adapt content to the page size without having to
+    /// [`layout`]($layout) it first.
+    ///
+    /// ```example
+    /// #set page(width: 120pt, height: 80pt, margin: 10pt)
+    ///
+    /// #style(styles => {
+    ///   let geometry = page.geometry(styles)
+    ///   [Text area: #geometry.inner.width]
+    /// })
+    /// ```
+    #[func]
+    pub fn geometry(
+        /// The styles to resolve the geometry in, as provided by the
+        /// [`style`]($style) function.
+        styles: Styles,
+    ) -> Dict {
+        let styles = StyleChain::new(&styles);
+        let (size, margin) = PageElem::new(Content::empty()).resolve_geometry(styles);
+        let finite =
+            |abs: Abs| if abs.is_finite() { Smart::Custom(abs) } else { Smart::Auto };
+        let inner = size - margin.sum_by_axis();
+        dict! {
+            "width" => finite(size.x),
+            "height" => finite(size.y),
+            "margin" => dict! {
+                "top" => margin.top,
+                "right" => margin.right,
+                "bottom" => margin.bottom,
+                "left" => margin.left,
+            },
+            "inner" => dict! {
+                "width" => finite(inner.x),
+                "height" => finite(inner.y),
+            },
+        }
+    }
+}
 
-        // When one of the lengths is infinite the page fits its content along
-        // that axis.
+impl PageElem {
+    /// Resolve the page's size and margins.
+    ///
+    /// When one of the lengths is infinite the page fits its content along
+    /// that axis.
+    fn resolve_geometry(&self, styles: StyleChain) -> (Size, Sides<Abs>) {
         let width = self.width(styles).unwrap_or(Abs::inf());
         let height = self.height(styles).unwrap_or(Abs::inf());
         let mut size = Size::new(width, height);
@@ -364,29 +523,81 @@ impl PageElem {
             min = Paper::A4.width();
         }
 
-        // Determine the margins.
         let default = Rel::<Length>::from((2.5 / 21.0) * min);
-        let margin = self.margin(styles);
-        let two_sided = margin.two_sided.unwrap_or(false);
-        let margin = margin
+        let margin = self
+            .margin(styles)
             .sides
             .map(|side| side.and_then(Smart::as_custom).unwrap_or(default))
             .resolve(styles)
             .relative_to(size);
 
+        (size, margin)
+    }
+
+    /// Resolve the page's binding.
+    fn resolve_binding(&self, styles: StyleChain) -> Binding {
+        self.binding(styles)
+            .unwrap_or_else(|| match TextElem::dir_in(styles) {
+                Dir::LTR => Binding::Left,
+                _ => Binding::Right,
+            })
+    }
+
+    /// Resolve the size and margins of the page with the given physical
+    /// number, with the left and right margin swapped if the page is
+    /// two-sided.
+    pub(crate) fn resolve_margins(
+        styles: StyleChain,
+        number: NonZeroUsize,
+    ) -> (Size, Sides<Abs>) {
+        let elem = Self::new(Content::empty());
+        let (size, mut margin) = elem.resolve_geometry(styles);
+        let two_sided = elem.margin(styles).two_sided.unwrap_or(false);
+        if two_sided && elem.resolve_binding(styles).swap(number) {
+            std::mem::swap(&mut margin.left, &mut margin.right);
+        }
+        (size, margin)
+    }
+
+    /// A document can consist of multiple `PageElem`s, one per run of pages
+    /// with equal properties (not one per actual output page!). The `number` is
+    /// the physical page number of the first page of this run. It is mutated
+    /// while we post-process the pages in this function. This function returns
+    /// a fragment consisting of multiple frames, one per output page of this
+    /// page run.
+    #[tracing::instrument(skip_all)]
+    pub fn layout(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        page_counter: &mut ManualPageCounter,
+        extend_to: Option<Parity>,
+    ) -> SourceResult<Fragment> {
+        tracing::info!("Page layout");
+
+        // Determine the size and margins.
+        let (size, margin) = self.resolve_geometry(styles);
+        let two_sided = self.margin(styles).two_sided.unwrap_or(false);
+
         // Determine the binding.
-        let binding =
-            self.binding(styles)
-                .unwrap_or_else(|| match TextElem::dir_in(styles) {
-                    Dir::LTR => Binding::Left,
-                    _ => Binding::Right,
-                });
+        let binding = self.resolve_binding(styles);
 
         // Realize columns.
         let mut child = self.body().clone();
         let columns = self.columns(styles);
         if columns.get() > 1 {
             child = ColumnsElem::new(child).with_count(columns).pack();
+
+            // Let a flow lay out the columns so that it can list their
+            // footnotes below them.
+            if FootnoteEntry::spanning_in(styles) {
+                child = FlowElem::new(vec![Prehashed::new(child)]).pack();
+            }
+        }
+
+        // Let the flows inside of the page know about the baseline grid.
+        if let Some(grid) = self.baseline_grid(styles) {
+            child = child.styled(PageElem::set_baseline_grid(Some(grid.into())));
         }
 
         let area = size - margin.sum_by_axis();
@@ -407,8 +618,11 @@ impl PageElem {
         }
 
         let fill = self.fill(styles);
+        let bleed = self.bleed(styles);
+        let crop_marks = self.crop_marks(styles);
         let foreground = Cow::Borrowed(self.foreground(styles));
         let background = Cow::Borrowed(self.background(styles));
+        let watermark = self.watermark(styles);
         let header_ascent = self.header_ascent(styles);
         let footer_descent = self.footer_descent(styles);
         let numbering = self.numbering(styles);
@@ -487,6 +701,10 @@ impl PageElem {
                     pos = Point::new(margin.left, size.y - margin.bottom + descent);
                     area = Size::new(pw, margin.bottom - descent);
                     align = Align::TOP;
+                } else if ptr::eq(marginal, &background) {
+                    pos = Point::splat(-bleed);
+                    area = size + Size::splat(2.0 * bleed);
+                    align = HAlign::Center + VAlign::Horizon;
                 } else {
                     pos = Point::zero();
                     area = size;
@@ -507,8 +725,21 @@ impl PageElem {
                 }
             }
 
+            if let Some(watermark) = watermark {
+                let layer = watermark.layout(engine, styles, size)?;
+                frame.prepend_frame(Point::zero(), layer);
+            }
+
             if let Some(fill) = fill {
-                frame.fill(fill.clone());
+                let shape = Geometry::Rect(size + Size::splat(2.0 * bleed))
+                    .filled(fill.clone());
+                let pos = Point::splat(-bleed);
+                frame.prepend(pos, FrameItem::Shape(shape, Span::detached()));
+            }
+
+            // Extend the page by the bleed and crop marks.
+            if !bleed.is_zero() || crop_marks {
+                realize_bleed(frame, bleed, crop_marks);
             }
 
             page_counter.visit(engine, frame)?;
@@ -527,6 +758,202 @@ impl PageElem {
     }
 }
 
+/// A faded watermark in a page's background.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Watermark {
+    /// The content of the watermark.
+    pub body: Content,
+    /// The rotation of the watermark. `Auto` rotates it along the diagonal.
+    pub angle: Smart<Angle>,
+    /// The opacity of the watermark's solid colors.
+    pub opacity: Ratio,
+    /// Whether to tile the watermark across the page.
+    pub repeat: bool,
+}
+
+impl Watermark {
+    /// Create a centered watermark with default settings.
+    pub fn new(body: Content) -> Self {
+        Self {
+            body,
+            angle: Smart::Auto,
+            opacity: Ratio::new(0.2),
+            repeat: false,
+        }
+    }
+
+    /// Layout the watermark into a layer of the page's size.
+    fn layout(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        size: Size,
+    ) -> SourceResult<Frame> {
+        let pod = Regions::one(size, Axes::splat(false));
+        let mut body = self.body.layout(engine, styles, pod)?.into_frame();
+        fade(&mut body, self.opacity.get() as f32);
+
+        let angle = self
+            .angle
+            .unwrap_or_else(|| Angle::rad(-(size.y.to_raw().atan2(size.x.to_raw()))));
+
+        // The rotated watermark covers the page if it covers a square with
+        // the page's diagonal around the page's center.
+        let diagonal = size.to_point().hypot();
+        let mut tiles = Frame::soft(Size::splat(diagonal));
+        if self.repeat {
+            let gap = body.height().max(Abs::pt(12.0));
+            let step = body.size() + Size::splat(gap);
+            let cols = (diagonal / step.x).ceil() as usize + 1;
+            let rows = (diagonal / step.y).ceil() as usize + 1;
+            for row in 0..rows {
+                // Offset every other row to create a staggered pattern.
+                let shift = if row % 2 == 1 { step.x / 2.0 } else { Abs::zero() };
+                for col in 0..cols {
+                    let pos = Point::new(
+                        step.x * col as f64 - shift,
+                        step.y * row as f64,
+                    );
+                    tiles.push_frame(pos, body.clone());
+                }
+            }
+        } else {
+            let pos = (tiles.size() - body.size()).to_point() / 2.0;
+            tiles.push_frame(pos, body);
+        }
+
+        let center = tiles.size().to_point() / 2.0;
+        tiles.transform(
+            Transform::translate(center.x, center.y)
+                .pre_concat(Transform::rotate(angle))
+                .pre_concat(Transform::translate(-center.x, -center.y)),
+        );
+
+        let mut layer = Frame::soft(size);
+        layer.push_frame((size.to_point() - tiles.size().to_point()) / 2.0, tiles);
+        layer.clip(Path::rect(size));
+        Ok(layer)
+    }
+}
+
+cast! {
+    Watermark,
+    self => dict! {
+        "body" => self.body,
+        "angle" => self.angle,
+        "opacity" => self.opacity,
+        "repeat" => self.repeat,
+    }.into_value(),
+    body: Content => Self::new(body),
+    mut dict: Dict => {
+        let mut watermark = Self::new(dict.take("body")?.cast()?);
+        if let Ok(angle) = dict.take("angle") {
+            watermark.angle = angle.cast()?;
+        }
+        if let Ok(opacity) = dict.take("opacity") {
+            watermark.opacity = opacity.cast()?;
+        }
+        if let Ok(repeat) = dict.take("repeat") {
+            watermark.repeat = repeat.cast()?;
+        }
+        dict.finish(&["body", "angle", "opacity", "repeat"])?;
+        watermark
+    },
+}
+
+/// Scale the opacity of the solid colors in a frame.
+fn fade(frame: &mut Frame, opacity: f32) {
+    let items: Vec<_> = frame.items().cloned().collect();
+    frame.clear();
+    for (pos, item) in items {
+        let item = match item {
+            FrameItem::Group(mut group) => {
+                fade(&mut group.frame, opacity);
+                FrameItem::Group(group)
+            }
+            FrameItem::Text(mut text) => {
+                text.fill = fade_paint(text.fill, opacity);
+                FrameItem::Text(text)
+            }
+            FrameItem::Shape(mut shape, span) => {
+                shape.fill = shape.fill.map(|fill| fade_paint(fill, opacity));
+                if let Some(stroke) = &mut shape.stroke {
+                    stroke.paint = fade_paint(stroke.paint.clone(), opacity);
+                }
+                FrameItem::Shape(shape, span)
+            }
+            item => item,
+        };
+        frame.push(pos, item);
+    }
+}
+
+/// Scale the opacity of a paint if it is a solid color.
+fn fade_paint(paint: Paint, opacity: f32) -> Paint {
+    match paint {
+        Paint::Solid(color) => {
+            // Luma and CMYK colors have no alpha channel.
+            let color = match color {
+                Color::Luma(_) | Color::Cmyk(_) => color.to_rgb(),
+                _ => color,
+            };
+            let alpha = color.alpha().unwrap_or(1.0);
+            Paint::Solid(color.with_alpha(alpha * opacity))
+        }
+        paint => paint,
+    }
+}
+
+/// Grow a page frame by its bleed and draw crop marks around it.
+fn realize_bleed(frame: &mut Frame, bleed: Abs, crop_marks: bool) {
+    // The distance between the bleed area and the start of the crop marks
+    // and the length of the marks.
+    let mark_offset = Abs::pt(3.0);
+    let mark_length = Abs::pt(18.0);
+
+    let size = frame.size();
+    let mut trim = bleed;
+    if crop_marks {
+        trim += mark_offset + mark_length;
+    }
+
+    frame.set_size(size + Size::splat(2.0 * trim));
+    frame.translate(Point::splat(trim));
+
+    if crop_marks {
+        let stroke = FixedStroke {
+            paint: Color::BLACK.into(),
+            thickness: Abs::pt(0.25),
+            ..FixedStroke::default()
+        };
+
+        let horizontal = Geometry::Line(Point::with_x(mark_length));
+        let vertical = Geometry::Line(Point::with_y(mark_length));
+        let (left, top) = (trim, trim);
+        let (right, bottom) = (trim + size.x, trim + size.y);
+        let outset = bleed + mark_offset;
+        for (x, y, mark_x, mark_y) in [
+            (left, top, Abs::zero(), Abs::zero()),
+            (right, top, right + outset, Abs::zero()),
+            (left, bottom, Abs::zero(), bottom + outset),
+            (right, bottom, right + outset, bottom + outset),
+        ] {
+            for (pos, geometry) in [
+                (Point::new(mark_x, y), &horizontal),
+                (Point::new(x, mark_y), &vertical),
+            ] {
+                let shape = geometry.clone().stroked(stroke.clone());
+                frame.push(pos, FrameItem::Shape(shape, Span::detached()));
+            }
+        }
+    }
+
+    frame.push_positionless_meta(Meta::PdfPageBoxes(PdfPageBoxes {
+        bleed: trim - bleed,
+        trim,
+    }));
+}
+
 /// Specification of the page's margins.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Margin {
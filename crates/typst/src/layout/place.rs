@@ -1,9 +1,12 @@
 use crate::diag::{bail, At, Hint, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    elem, Behave, Behaviour, Content, NativeElement, Smart, StyleChain,
+    cast, dict, elem, Behave, Behaviour, Content, Dict, Element, NativeElement, Repr,
+    Smart, StyleChain,
+};
+use crate::layout::{
+    Align, Axes, Em, Fragment, Layout, Length, PageElem, Regions, Rel, VAlign,
 };
-use crate::layout::{Align, Axes, Em, Fragment, Layout, Length, Regions, Rel, VAlign};
 
 /// Places content at an absolute position.
 ///
@@ -30,7 +33,8 @@ pub struct PlaceElem {
     /// Relative to which position in the parent container to place the content.
     ///
     /// Cannot be `{auto}` if `float` is `{false}` and must be either
-    /// `{auto}`, `{top}`, or `{bottom}` if `float` is `{true}`.
+    /// `{auto}`, `{top}`, or `{bottom}` if `float` is `{true}`, unless an
+    /// explicit [`placement`]($place.placement) is given.
     ///
     /// When an axis of the page is `{auto}` sized, all alignments relative to
     /// that axis will be ignored, instead, the item will be placed in the
@@ -62,6 +66,41 @@ pub struct PlaceElem {
     /// ```
     pub float: bool,
 
+    /// Which areas of the page a floating element may be placed in.
+    ///
+    /// When set to `{auto}`, the areas follow from the alignment: A float
+    /// aligned to the `{top}` or `{bottom}` only goes into that area, while an
+    /// `{auto}`-aligned float picks whichever of the two is closer. Otherwise,
+    /// this takes precedence over the vertical alignment and can either be a
+    /// single area or a dictionary with the following keys:
+    ///
+    /// - `prefer`: The area to try first. If set to `{auto}` (the default),
+    ///   the closer one of `{top}` and `{bottom}` is tried first.
+    /// - `allow`: An array of the areas the float may be placed in. Defaults
+    ///   to `{(top, bottom)}`.
+    ///
+    /// The possible areas are `{top}` and `{bottom}` of a page as well as
+    /// `{page}`, which places the float on a page that only holds floats. A
+    /// float that fits into none of its allowed areas in the current region
+    /// is deferred to the next one. Floats are always placed in the order in
+    /// which they appear in the document, so a deferred float also defers all
+    /// floats after it.
+    ///
+    /// ```example
+    /// #set page(height: 200pt)
+    /// #let note(body) = place(
+    ///   float: true,
+    ///   placement: (prefer: bottom, allow: (top, bottom, page)),
+    ///   rect(width: 100%, body),
+    /// )
+    ///
+    /// #lorem(10)
+    /// #note[Bottom]
+    /// #note(rect(height: 135pt)[Own page])
+    /// #lorem(30)
+    /// ```
+    pub placement: Smart<FloatPlacement>,
+
     /// The amount of clearance the placed element has in a floating layout.
     #[default(Em::new(1.5).into())]
     #[resolve]
@@ -100,7 +139,10 @@ impl Layout for PlaceElem {
         let float = self.float(styles);
         let alignment = self.alignment(styles);
 
+        // An explicit float placement determines the vertical position, so
+        // only the horizontal alignment matters in that case.
         if float
+            && self.placement(styles).is_auto()
             && alignment
                 .map_or(false, |align| matches!(align.y(), None | Some(VAlign::Horizon)))
         {
@@ -122,6 +164,96 @@ impl Layout for PlaceElem {
     }
 }
 
+/// An area of a page that floating elements can be placed in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FloatArea {
+    /// The top of the page, above the main flow content.
+    Top,
+    /// The bottom of the page, below the main flow content.
+    Bottom,
+    /// A page of its own that only holds floats.
+    Page,
+}
+
+cast! {
+    FloatArea,
+    self => match self {
+        Self::Top => Align::TOP.into_value(),
+        Self::Bottom => Align::BOTTOM.into_value(),
+        Self::Page => PageElem::elem().into_value(),
+    },
+    align: Align => match align {
+        Align::V(VAlign::Top) => Self::Top,
+        Align::V(VAlign::Bottom) => Self::Bottom,
+        v => bail!("expected `top`, `bottom`, or `page`, found {}", v.repr()),
+    },
+    elem: Element => match elem == PageElem::elem() {
+        true => Self::Page,
+        false => bail!("expected `top`, `bottom`, or `page`, found {}", elem.name()),
+    },
+}
+
+/// Where a floating element may be placed.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct FloatPlacement {
+    /// The area to try first. `Auto` tries the closer one of top and bottom.
+    pub prefer: Smart<FloatArea>,
+    /// The areas the float may be placed in.
+    pub allow: Vec<FloatArea>,
+}
+
+impl FloatPlacement {
+    /// Allow both the top and bottom, preferring the closer one.
+    pub fn auto() -> Self {
+        Self {
+            prefer: Smart::Auto,
+            allow: vec![FloatArea::Top, FloatArea::Bottom],
+        }
+    }
+
+    /// Allow only the given area.
+    pub fn only(area: FloatArea) -> Self {
+        Self { prefer: Smart::Custom(area), allow: vec![area] }
+    }
+
+    /// Whether the float may be placed in the given area.
+    pub fn allows(&self, area: FloatArea) -> bool {
+        self.allow.contains(&area)
+    }
+}
+
+cast! {
+    FloatPlacement,
+    self => if self.allow.len() == 1 && self.prefer == Smart::Custom(self.allow[0]) {
+        self.allow[0].into_value()
+    } else {
+        dict! {
+            "prefer" => self.prefer,
+            "allow" => self.allow,
+        }.into_value()
+    },
+    area: FloatArea => Self::only(area),
+    mut dict: Dict => {
+        let mut placement = Self::auto();
+        if let Ok(prefer) = dict.take("prefer") {
+            placement.prefer = prefer.cast()?;
+        }
+        if let Ok(allow) = dict.take("allow") {
+            placement.allow = allow.cast()?;
+        }
+        dict.finish(&["prefer", "allow"])?;
+        if placement.allow.is_empty() {
+            bail!("float placement must allow at least one area");
+        }
+        if let Smart::Custom(prefer) = placement.prefer {
+            if !placement.allows(prefer) {
+                bail!("preferred float area must also be allowed");
+            }
+        }
+        placement
+    },
+}
+
 impl Behave for PlaceElem {
     fn behaviour(&self) -> Behaviour {
         Behaviour::Ignorant
@@ -2,8 +2,8 @@ use crate::diag::SourceResult;
 use crate::engine::Engine;
 use crate::foundations::{elem, Content, Resolve, StyleChain};
 use crate::layout::{
-    Abs, Align, Angle, Axes, FixedAlign, Fragment, HAlign, Layout, Length, Ratio,
-    Regions, Rel, VAlign,
+    Abs, Align, Angle, Axes, FixedAlign, Fragment, HAlign, Layout, Length, Point, Ratio,
+    Regions, Rel, Size, VAlign,
 };
 
 /// Moves content without affecting layout.
@@ -98,6 +98,33 @@ pub struct RotateElem {
     #[default(HAlign::Center + VAlign::Horizon)]
     pub origin: Align,
 
+    /// Whether the rotation impacts the layout.
+    ///
+    /// If set to `{false}`, the rotated content retains the bounding box of
+    /// the original content. If set to `{true}`, the bounding box takes the
+    /// rotation into account and the surrounding layout makes room for the
+    /// rotated content. The body is then laid out as if the available space
+    /// was rotated, too, so that a quarter-turned body can extend along the
+    /// height of the container.
+    ///
+    /// This is useful for vertical labels in narrow table columns. The
+    /// rotated label's position within its cell is determined by the cell's
+    /// [alignment]($table.align).
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 3,
+    ///   align: (x, y) => if y == 0 { bottom } else { center },
+    ///   table.header(
+    ///     ..([Quantity], [Price per unit], [Total])
+    ///       .map(rotate.with(-90deg, reflow: true))
+    ///   ),
+    ///   [2], [€1.50], [€3.00],
+    /// )
+    /// ```
+    #[default(false)]
+    pub reflow: bool,
+
     /// The content to rotate.
     #[required]
     pub body: Content,
@@ -111,16 +138,37 @@ impl Layout for RotateElem {
         styles: StyleChain,
         regions: Regions,
     ) -> SourceResult<Fragment> {
-        let pod = Regions::one(regions.base(), Axes::splat(false));
+        let angle = self.angle(styles);
+        let reflow = self.reflow(styles);
+
+        // When reflowing, the body may use the space that it covers after
+        // rotation.
+        let base = if reflow {
+            rotated_size(regions.base(), angle)
+        } else {
+            regions.base()
+        };
+
+        let pod = Regions::one(base, Axes::splat(false));
         let mut frame = self.body().layout(engine, styles, pod)?.into_frame();
         let Axes { x, y } = self
             .origin(styles)
             .resolve(styles)
             .zip_map(frame.size(), FixedAlign::position);
         let ts = Transform::translate(x, y)
-            .pre_concat(Transform::rotate(self.angle(styles)))
+            .pre_concat(Transform::rotate(angle))
             .pre_concat(Transform::translate(-x, -y));
-        frame.transform(ts);
+
+        if reflow {
+            // Move the rotated content into the frame's new bounding box.
+            let (offset, size) = bounding_box(frame.size(), ts);
+            frame.transform(ts);
+            frame.translate(offset);
+            frame.set_size(size);
+        } else {
+            frame.transform(ts);
+        }
+
         Ok(Fragment::frame(frame))
     }
 }
@@ -190,6 +238,52 @@ impl Layout for ScaleElem {
     }
 }
 
+/// The size of the smallest axis-aligned box containing a box of the given size
+/// rotated by the angle.
+fn rotated_size(size: Size, angle: Angle) -> Size {
+    // Snap tiny factors to zero so that an infinite side doesn't spill into
+    // the other axis at quarter turns due to rounding.
+    let factor = |v: f64| if v.abs() < 1e-9 { 0.0 } else { v.abs() };
+    let part = |length: Abs, factor: f64| {
+        if factor == 0.0 {
+            Abs::zero()
+        } else {
+            length * factor
+        }
+    };
+
+    let sin = factor(angle.sin());
+    let cos = factor(angle.cos());
+    Size::new(
+        part(size.x, cos) + part(size.y, sin),
+        part(size.x, sin) + part(size.y, cos),
+    )
+}
+
+/// Computes the axis-aligned bounding box of a box of the given size after
+/// transformation.
+///
+/// Returns the offset by which the transformed box must be moved to start at
+/// the origin and the size of the bounding box.
+fn bounding_box(size: Size, ts: Transform) -> (Point, Size) {
+    let corners = [
+        Point::zero(),
+        Point::with_x(size.x),
+        Point::with_y(size.y),
+        size.to_point(),
+    ]
+    .map(|corner| corner.transform(ts));
+
+    let mut min = corners[0];
+    let mut max = corners[0];
+    for corner in &corners[1..] {
+        min = min.min(*corner);
+        max = max.max(*corner);
+    }
+
+    (-min, (max - min).to_size())
+}
+
 /// A scale-skew-translate transformation.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Transform {
@@ -62,7 +62,7 @@ use std::ops::Range;
 use comemo::{Prehashed, Track, Tracked, Validate};
 use ecow::{EcoString, EcoVec};
 
-use crate::diag::{warning, FileResult, SourceDiagnostic, SourceResult};
+use crate::diag::{warning, FileResult, Severity, SourceDiagnostic, SourceResult};
 use crate::engine::{Engine, Route};
 use crate::eval::Tracer;
 use crate::foundations::{
@@ -121,6 +121,10 @@ fn typeset(
         // Clear delayed errors.
         tracer.delayed();
 
+        // Take the previous introspector so that it can be rebuilt in place,
+        // keeping its query cache if the introspected elements didn't change.
+        let mut introspector = std::mem::take(&mut document.introspector);
+        let previous = introspector.hash();
         let constraint = <Introspector as Validate>::Constraint::new();
         let mut locator = Locator::new();
         let mut engine = Engine {
@@ -128,16 +132,24 @@ fn typeset(
             route: Route::default(),
             tracer: tracer.track_mut(),
             locator: &mut locator,
-            introspector: document.introspector.track_with(&constraint),
+            introspector: introspector.track_with(&constraint),
         };
 
         // Layout!
         document = content.layout_root(&mut engine, styles)?;
-        document.introspector.rebuild(&document.pages);
+        introspector.rebuild(&document.pages);
+        introspector.settle(previous);
+        document.introspector = introspector;
         iter += 1;
 
         if document.introspector.validate(&constraint) {
-            break;
+            // The layout converged, so the introspection data is final. Code
+            // that observed the layout as unstable needs one more pass to see
+            // that it has settled.
+            document.introspector.stabilize();
+            if document.introspector.validate(&constraint) {
+                break;
+            }
         }
 
         if iter >= 5 {
@@ -149,10 +161,16 @@ fn typeset(
         }
     }
 
-    // Promote delayed errors.
-    let delayed = tracer.delayed();
-    if !delayed.is_empty() {
-        return Err(delayed);
+    // Promote delayed errors and warnings.
+    let (errors, warnings): (EcoVec<_>, EcoVec<_>) = tracer
+        .delayed()
+        .into_iter()
+        .partition(|diag| diag.severity == Severity::Error);
+    for warning in warnings {
+        tracer.warn(warning);
+    }
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
     Ok(document)
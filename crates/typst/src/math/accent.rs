@@ -3,7 +3,7 @@ use unicode_math_class::MathClass;
 
 use crate::diag::{bail, SourceResult};
 use crate::foundations::{cast, elem, Content, NativeElement, Value};
-use crate::layout::{Abs, Em, Frame, Point, Size};
+use crate::layout::{Abs, Em, Frame, Point, Ratio, Size, Transform};
 use crate::math::{
     FrameFragment, GlyphFragment, LayoutMath, MathContext, MathFragment, Scaled,
 };
@@ -79,7 +79,30 @@ impl LayoutMath for AccentElem {
         let Accent(c) = self.accent();
         let glyph = GlyphFragment::new(ctx, *c, self.span());
         let short_fall = ACCENT_SHORT_FALL.scaled(ctx);
-        let variant = glyph.stretch_horizontal(ctx, base.width(), short_fall);
+        let mut variant = glyph.stretch_horizontal(ctx, base.width(), short_fall);
+
+        // If the font provides neither a wide enough variant nor an assembly,
+        // fall back to scaling stretchable accents horizontally. Combining
+        // accents often have no advance, so we go by their ink instead.
+        let short_target = base.width() - short_fall;
+        let ink = variant
+            .id
+            .filter(|_| is_stretchable(*c))
+            .and_then(|id| ctx.ttf.glyph_bounding_box(id));
+        if let Some(ink) = ink {
+            let min = ink.x_min.scaled(ctx);
+            let width = ink.x_max.scaled(ctx) - min;
+            if width > Abs::zero() && width < short_target {
+                let ratio = short_target / width;
+                variant.frame.transform(
+                    Transform::translate(-min * ratio, Abs::zero())
+                        .pre_concat(Transform::scale(Ratio::new(ratio), Ratio::one())),
+                );
+                variant.frame.size_mut().x = short_target;
+                variant.id = None;
+            }
+        }
+
         let accent = variant.frame;
         let accent_attach = match variant.id {
             Some(id) => attachment(ctx, id, variant.italics_correction),
@@ -125,6 +148,24 @@ fn attachment(ctx: &MathContext, id: GlyphId, italics_correction: Abs) -> Abs {
         })
 }
 
+/// Whether the accent may be widened to cover its base.
+fn is_stretchable(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0302}'
+            | '\u{0303}'
+            | '\u{0304}'
+            | '\u{0305}'
+            | '\u{0306}'
+            | '\u{030c}'
+            | '\u{20d0}'
+            | '\u{20d1}'
+            | '\u{20d6}'
+            | '\u{20d7}'
+            | '\u{20e1}'
+    )
+}
+
 /// An accent character.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Accent(char);
@@ -186,6 +186,7 @@ pub fn module() -> Module {
     math.define_func::<floor>();
     math.define_func::<ceil>();
     math.define_func::<round>();
+    math.define_func::<pmod>();
     math.define_func::<sqrt>();
     math.define_func::<upright>();
     math.define_func::<bold>();
@@ -2,9 +2,11 @@ use ecow::EcoString;
 use unicode_math_class::MathClass;
 
 use crate::diag::SourceResult;
-use crate::foundations::{elem, Content, NativeElement, Scope};
+use crate::foundations::{elem, func, Content, NativeElement, Scope};
 use crate::layout::HElem;
-use crate::math::{FrameFragment, LayoutMath, Limits, MathContext, MathStyleElem, THIN};
+use crate::math::{
+    ClassElem, FrameFragment, LayoutMath, Limits, MathContext, MathStyleElem, QUAD, THIN,
+};
 use crate::text::TextElem;
 
 /// A text operator in an equation.
@@ -22,6 +24,9 @@ use crate::text::TextElem;
 /// `gcd`, `hom`, `id`, `im`, `inf`, `ker`, `lg`, `lim`, `liminf`, `limsup`,
 /// `ln`, `log`, `max`, `min`, `mod`, `Pr`, `sec`, `sech`, `sin`, `sinc`,
 /// `sinh`, `sup`, `tan`, `tanh`, `tg` and `tr`.
+///
+/// For modular arithmetic, there is also `bmod`, a `mod` operator that is
+/// spaced like a binary operator, and the [`pmod`]($math.pmod) function.
 #[elem(title = "Text Operator", LayoutMath)]
 pub struct OpElem {
     /// The operator's text.
@@ -50,6 +55,33 @@ impl LayoutMath for OpElem {
     }
 }
 
+/// Displays a modulus in parentheses, as in a congruence.
+///
+/// Following TeX conventions, the parenthesized modulus is separated by a quad
+/// from what precedes it.
+///
+/// ```example
+/// $ a equiv b pmod(n) $
+/// $a equiv b pmod(n)$ for some $n$.
+/// ```
+#[func(title = "Parenthesized Modulus")]
+pub fn pmod(
+    /// The modulus.
+    body: Content,
+) -> Content {
+    HElem::new(QUAD.into()).with_weak(true).pack()
+        + TextElem::packed('(')
+        + modulo()
+        + HElem::new((THIN * 2.0).into()).pack()
+        + body
+        + TextElem::packed(')')
+}
+
+/// The `mod` text operator.
+fn modulo() -> Content {
+    OpElem::new(TextElem::packed("mod")).pack()
+}
+
 macro_rules! ops {
     ($($name:ident $(: $value:literal)? $(($tts:tt))?),* $(,)?) => {
         pub(super) fn define(math: &mut Scope) {
@@ -69,6 +101,8 @@ macro_rules! ops {
             };
             math.define("dif", dif('d'));
             math.define("Dif", dif('D'));
+
+            math.define("bmod", ClassElem::new(MathClass::Binary, modulo()).pack());
         }
     };
     (@name $name:ident) => { stringify!($name) };
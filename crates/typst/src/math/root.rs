@@ -87,15 +87,28 @@ fn layout(
     let gap = gap.max((sqrt.height() - thickness - radicand.height() + gap) / 2.0);
 
     let sqrt_ascent = radicand.ascent() + gap + thickness;
-    let descent = sqrt.height() - sqrt_ascent;
     let inner_ascent = sqrt_ascent + extra_ascender;
 
+    // If the font's largest radical variant or assembly is still too short,
+    // the radicand must not hang out below the surd.
+    let descent = (sqrt.height() - sqrt_ascent).max(radicand.descent());
+
+    let mut index_offset = kern_before;
     let mut sqrt_offset = Abs::zero();
     let mut shift_up = Abs::zero();
     let mut ascent = inner_ascent;
 
     if let Some(index) = &index {
+        // The kern after the degree is typically negative such that the
+        // index tucks into the surd. For narrow indices, this can move the
+        // surd to the left of the index's origin, so we shift both to the
+        // right such that nothing protrudes to the left of the frame.
         sqrt_offset = kern_before + index.width() + kern_after;
+        if sqrt_offset < Abs::zero() {
+            index_offset -= sqrt_offset;
+            sqrt_offset = Abs::zero();
+        }
+
         // The formula below for how much raise the index by comes from
         // the TeXbook, page 360, in the definition of `\root`.
         // However, the `+ index.descent()` part is different from TeX.
@@ -121,7 +134,7 @@ fn layout(
     frame.set_baseline(ascent);
 
     if let Some(index) = index {
-        let index_pos = Point::new(kern_before, ascent - index.ascent() - shift_up);
+        let index_pos = Point::new(index_offset, ascent - index.ascent() - shift_up);
         frame.push_frame(index_pos, index);
     }
 
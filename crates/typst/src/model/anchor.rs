@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use crate::diag::SourceResult;
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Behave, Behaviour, Content, NativeElement, Show, Smart, StyleChain,
+    Synthesize,
+};
+use crate::introspection::{Counter, CounterKey, Locatable};
+use crate::model::{Numbering, NumberingPattern, Refable, Supplement};
+use crate::text::{Lang, LocalName, Region, TextElem};
+use crate::util::option_eq;
+
+/// An invisible marker at a specific point in the text.
+///
+/// Anchors let you refer to an arbitrary position in running text instead of
+/// a whole element like a heading or figure. Attach a [label]($label) to an
+/// anchor to [link]($link) to it or to [reference]($ref) it. A reference to
+/// an anchor displays the page the anchor ended up on.
+///
+/// Like any other locatable element, an anchor's position can also be
+/// retrieved through [`query`]($query) and
+/// [`location.position`]($location.position).
+///
+/// # Example
+/// ```example
+/// #set page(numbering: "1")
+///
+/// The phrase marked on @phrase
+/// is important.
+///
+/// #pagebreak()
+/// This is the
+/// #anchor() <phrase>
+/// phrase in question.
+/// ```
+#[elem(Locatable, Synthesize, Behave, Show, LocalName, Refable)]
+pub struct AnchorElem {
+    /// A supplement for references to the anchor.
+    ///
+    /// By default, this is the localized word for "page". If a function is
+    /// specified, it is passed the referenced anchor and should return
+    /// content.
+    ///
+    /// ```example
+    /// #set page(numbering: "i")
+    /// #set anchor(supplement: [p.])
+    ///
+    /// #anchor() <here>
+    /// See @here.
+    /// ```
+    pub supplement: Smart<Option<Supplement>>,
+
+    /// The numbering of the page the anchor is on.
+    #[synthesized]
+    pub numbering: Option<Numbering>,
+}
+
+impl Synthesize for AnchorElem {
+    fn synthesize(
+        &mut self,
+        engine: &mut Engine,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let supplement = match self.supplement(styles) {
+            Smart::Auto => TextElem::packed(Self::local_name_in(styles)),
+            Smart::Custom(None) => Content::empty(),
+            Smart::Custom(Some(supplement)) => {
+                supplement.resolve(engine, [self.clone()])?
+            }
+        };
+
+        let numbering = self
+            .location()
+            .and_then(|location| engine.introspector.page_numbering(location).cloned())
+            .unwrap_or_else(|| NumberingPattern::from_str("1").unwrap().into());
+
+        self.push_supplement(Smart::Custom(Some(Supplement::Content(supplement))));
+        self.push_numbering(Some(numbering));
+
+        Ok(())
+    }
+}
+
+impl Show for AnchorElem {
+    fn show(&self, _: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
+
+impl Behave for AnchorElem {
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Invisible
+    }
+}
+
+impl Refable for AnchorElem {
+    fn supplement(&self) -> Content {
+        // After synthesis, this should always be custom content.
+        match self.supplement(StyleChain::default()) {
+            Smart::Custom(Some(Supplement::Content(content))) => content,
+            _ => Content::empty(),
+        }
+    }
+
+    fn counter(&self) -> Counter {
+        Counter::new(CounterKey::Page)
+    }
+
+    fn numbering(&self) -> Option<Numbering> {
+        self.numbering().clone()
+    }
+}
+
+impl LocalName for AnchorElem {
+    fn local_name(lang: Lang, region: Option<Region>) -> &'static str {
+        match lang {
+            Lang::ALBANIAN => "Faqe",
+            Lang::ARABIC => "صفحة",
+            Lang::BOKMÅL => "Side",
+            Lang::CHINESE if option_eq(region, "TW") => "頁",
+            Lang::CHINESE => "页",
+            Lang::CZECH => "Strana",
+            Lang::DANISH => "Side",
+            Lang::DUTCH => "Pagina",
+            Lang::ESTONIAN => "Lehekülg",
+            Lang::FILIPINO => "Pahina",
+            Lang::FINNISH => "Sivu",
+            Lang::FRENCH => "Page",
+            Lang::GERMAN => "Seite",
+            Lang::GREEK => "Σελίδα",
+            Lang::HUNGARIAN => "Oldal",
+            Lang::ITALIAN => "Pagina",
+            Lang::NYNORSK => "Side",
+            Lang::POLISH => "Strona",
+            Lang::PORTUGUESE => "Página",
+            Lang::ROMANIAN => "Pagina",
+            Lang::RUSSIAN => "Страница",
+            Lang::SLOVENIAN => "Stran",
+            Lang::SPANISH => "Página",
+            Lang::SWEDISH => "Sida",
+            Lang::TURKISH => "Sayfa",
+            Lang::UKRAINIAN => "Сторінка",
+            Lang::VIETNAMESE => "Trang",
+            Lang::JAPANESE => "ページ",
+            Lang::ENGLISH | _ => "Page",
+        }
+    }
+}
@@ -5,11 +5,12 @@ use crate::engine::Engine;
 use crate::foundations::{
     cast, elem, scope, Array, Content, Fold, NativeElement, Smart, StyleChain,
 };
+use crate::introspection::Locatable;
 use crate::layout::{
-    Align, Axes, BlockElem, Em, Fragment, GridLayouter, HAlign, Layout, Length, Regions,
-    Sizing, Spacing, VAlign,
+    Align, Axes, BlockElem, CellGrid, Em, Fragment, GridLayouter, HAlign, Layout,
+    Length, Regions, Sizing, Spacing, VAlign,
 };
-use crate::model::{Numbering, NumberingPattern, ParElem};
+use crate::model::{Numbering, NumberingPattern, ParElem, Role};
 use crate::text::TextElem;
 
 /// A numbered list.
@@ -68,7 +69,7 @@ use crate::text::TextElem;
 /// Enumeration items can contain multiple paragraphs and other block-level
 /// content. All content that is indented more than an item's marker becomes
 /// part of that item.
-#[elem(scope, title = "Numbered List", Layout)]
+#[elem(scope, title = "Numbered List", Locatable, Layout)]
 pub struct EnumElem {
     /// If this is `{false}`, the items are spaced apart with
     /// [enum spacing]($enum.spacing). If it is `{true}`, they use normal
@@ -237,7 +238,7 @@ impl Layout for EnumElem {
         // relation to the item it refers to.
         let number_align = self.number_align(styles);
 
-        for item in self.children() {
+        for (i, item) in self.children().iter().enumerate() {
             number = item.number(styles).unwrap_or(number);
 
             let resolved = if full {
@@ -255,17 +256,26 @@ impl Layout for EnumElem {
             };
 
             // Disable overhang as a workaround to end-aligned dots glitching
-            // and decreasing spacing between numbers and items.
-            let resolved =
-                resolved.aligned(number_align).styled(TextElem::set_overhang(false));
+            // and decreasing spacing between numbers and items. The number
+            // shares its line with the item, so it isn't numbered as a line.
+            let resolved = resolved
+                .aligned(number_align)
+                .styled(TextElem::set_overhang(false))
+                .styled(ParElem::set_line_numbering(None));
 
             cells.push(Content::empty());
-            cells.push(resolved);
+            cells.push(resolved.tagged(Role::ListLabel(i)));
             cells.push(Content::empty());
-            cells.push(item.body().clone().styled(Self::set_parents(Parent(number))));
+            cells.push(
+                item.body()
+                    .clone()
+                    .styled(Self::set_parents(Parent(number)))
+                    .tagged(Role::ListBody(i)),
+            );
             number = number.saturating_add(1);
         }
 
+        let grid = CellGrid::new(cells, 4);
         let layouter = GridLayouter::new(
             Axes::with_x(&[
                 Sizing::Rel(indent.into()),
@@ -274,9 +284,10 @@ impl Layout for EnumElem {
                 Sizing::Auto,
             ]),
             Axes::with_y(&[gutter.into()]),
-            &cells,
+            &grid,
             regions,
             styles,
+            TextElem::dir_in(styles),
             self.span(),
         );
 
@@ -13,7 +13,9 @@ use crate::foundations::{
 use crate::introspection::{
     Count, Counter, CounterKey, CounterUpdate, Locatable, Location,
 };
-use crate::layout::{Align, BlockElem, Em, HAlign, Length, PlaceElem, VAlign, VElem};
+use crate::layout::{
+    Align, BlockElem, Em, FloatPlacement, Length, PlaceElem, VAlign, VElem,
+};
 use crate::model::{Numbering, NumberingPattern, Outlinable, Refable, Supplement};
 use crate::syntax::Spanned;
 use crate::text::{Lang, Region, TextElem};
@@ -113,6 +115,10 @@ pub struct FigureElem {
     ///   is closer.
     /// - `{top}`: The figure floats to the top of the page.
     /// - `{bottom}`: The figure floats to the bottom of the page.
+    /// - `{page}`: The figure floats onto a page that only holds floats.
+    /// - A dictionary with `prefer` and `allow` keys to choose from several
+    ///   areas, as described for the [`placement`]($place.placement)
+    ///   argument of the `place` function.
     ///
     /// The gap between the main flow content and the floating figure is
     /// controlled by the [`clearance`]($place.clearance) argument on the
@@ -129,7 +135,18 @@ pub struct FigureElem {
     /// )
     /// #lorem(60)
     /// ```
-    pub placement: Option<Smart<VAlign>>,
+    ///
+    /// ```example
+    /// #set page(height: 200pt)
+    ///
+    /// #figure(
+    ///   placement: (prefer: top, allow: (top, page)),
+    ///   caption: [A tall glacier],
+    ///   image("glacier.jpg", height: 120pt),
+    /// )
+    /// #lorem(60)
+    /// ```
+    pub placement: Option<Smart<FloatPlacement>>,
 
     /// The figure's caption.
     pub caption: Option<FigureCaption>,
@@ -321,10 +338,11 @@ impl Show for FigureElem {
             .aligned(Align::CENTER);
 
         // Wrap in a float.
-        if let Some(align) = self.placement(styles) {
+        if let Some(placement) = self.placement(styles) {
             realized = PlaceElem::new(realized)
                 .with_float(true)
-                .with_alignment(align.map(|align| HAlign::Center + align))
+                .with_alignment(Smart::Auto)
+                .with_placement(placement)
                 .pack();
         }
 
@@ -4,12 +4,12 @@ use std::str::FromStr;
 use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, scope, Content, Finalize, Label, NativeElement, Show, Smart, StyleChain,
-    Synthesize,
+    cast, elem, scope, select_where, Cast, Content, Finalize, Label, NativeElement,
+    Selector, Show, Smart, StyleChain, Synthesize,
 };
 use crate::introspection::{Count, Counter, CounterUpdate, Locatable, Location};
 use crate::layout::{Abs, Em, HElem, Length, Ratio};
-use crate::model::{Destination, Numbering, NumberingPattern, ParElem};
+use crate::model::{Destination, HeadingElem, Numbering, NumberingPattern, ParElem};
 use crate::text::{SuperElem, TextElem, TextSize};
 use crate::util::NonZeroExt;
 use crate::visualize::{LineElem, Stroke};
@@ -54,9 +54,8 @@ pub struct FootnoteElem {
     /// How to number footnotes.
     ///
     /// By default, the footnote numbering continues throughout your document.
-    /// If you prefer per-page footnote numbering, you can reset the footnote
-    /// [counter]($counter) in the page [header]($page.header). In the future,
-    /// there might be a simpler way to achieve this.
+    /// To restart it on each page or chapter, use the [`reset`]($footnote.reset)
+    /// parameter.
     ///
     /// ```example
     /// #set footnote(numbering: "*")
@@ -69,6 +68,25 @@ pub struct FootnoteElem {
     #[default(Numbering::Pattern(NumberingPattern::from_str("1").unwrap()))]
     pub numbering: Numbering,
 
+    /// When to restart the footnote numbering.
+    ///
+    /// Because the page a footnote ends up on is only known after layout, the
+    /// restarted numbers are resolved during introspection. References to a
+    /// footnote display the same number as the footnote itself.
+    ///
+    /// ```example
+    /// #set page(height: 100pt)
+    /// #set footnote(reset: "per-page")
+    ///
+    /// Footnotes:
+    /// #footnote[One],
+    /// #footnote[Two]
+    /// #pagebreak()
+    /// Restarted: #footnote[One again]
+    /// ```
+    #[default(FootnoteReset::Continuous)]
+    pub reset: FootnoteReset,
+
     /// The content to put into the footnote. Can also be the label of another
     /// footnote this one should point to.
     #[required]
@@ -118,11 +136,41 @@ impl FootnoteElem {
             _ => Ok(self.location().unwrap()),
         }
     }
+
+    /// Displays the number of the footnote declared at the given location.
+    ///
+    /// The count restarts according to the reset scope of the declaration.
+    fn display_number(
+        engine: &mut Engine,
+        location: Location,
+        numbering: &Numbering,
+    ) -> SourceResult<Content> {
+        let counter = Counter::of(Self::elem());
+        let mut state = counter.at(engine, location)?;
+
+        let reset = engine
+            .introspector
+            .query_first(&Selector::Location(location))
+            .and_then(|elem| {
+                elem.to::<Self>().map(|note| note.reset(StyleChain::default()))
+            })
+            .unwrap_or(FootnoteReset::Continuous);
+
+        if let Some(boundary) = reset.boundary(engine, location) {
+            let offset = counter.at(engine, boundary)?.first();
+            if let Some(first) = state.0.first_mut() {
+                *first = first.saturating_sub(offset);
+            }
+        }
+
+        state.display(engine, numbering)
+    }
 }
 
 impl Synthesize for FootnoteElem {
     fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
         self.push_numbering(self.numbering(styles).clone());
+        self.push_reset(self.reset(styles));
         Ok(())
     }
 }
@@ -133,8 +181,7 @@ impl Show for FootnoteElem {
         Ok(engine.delayed(|engine| {
             let loc = self.declaration_location(engine).at(self.span())?;
             let numbering = self.numbering(styles);
-            let counter = Counter::of(Self::elem());
-            let num = counter.at(engine, loc)?.display(engine, numbering)?;
+            let num = Self::display_number(engine, loc, numbering)?;
             let sup = SuperElem::new(num).pack();
             let loc = loc.variant(1);
             // Add zero-width weak spacing to make the footnote "sticky".
@@ -149,6 +196,48 @@ impl Count for FootnoteElem {
     }
 }
 
+/// When to restart the numbering of footnotes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum FootnoteReset {
+    /// Number footnotes continuously throughout the document.
+    Continuous,
+    /// Restart the numbering on each page.
+    PerPage,
+    /// Restart the numbering with each top-level heading.
+    PerChapter,
+}
+
+impl FootnoteReset {
+    /// Finds the location whose footnote count should be subtracted from the
+    /// count of the footnote at the given location.
+    fn boundary(self, engine: &Engine, location: Location) -> Option<Location> {
+        let introspector = engine.introspector;
+        match self {
+            Self::Continuous => None,
+            Self::PerPage => {
+                // Binary search for the first footnote on this page among the
+                // footnotes before this one.
+                let selector = FootnoteElem::elem().select();
+                let page = introspector.page(location);
+                let notes = introspector.query(&selector);
+                let index = introspector
+                    .query_count(&selector.before(location.into(), false))
+                    .min(notes.len());
+                let first = notes[..index].partition_point(|elem| {
+                    introspector.page(elem.location().unwrap()) < page
+                });
+                first.checked_sub(1).and_then(|i| notes[i].location())
+            }
+            Self::PerChapter => introspector
+                .query_last_before(
+                    &select_where!(HeadingElem, Level => NonZeroUsize::ONE),
+                    location,
+                )
+                .and_then(|elem| elem.location()),
+        }
+    }
+}
+
 /// The body of a footnote can be either some content or a label referencing
 /// another footnote.
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -267,6 +356,27 @@ pub struct FootnoteEntry {
     /// ```
     #[default(Em::new(1.0).into())]
     pub indent: Length,
+
+    /// Whether the footnotes of multi-column layouts span the full width
+    /// below the columns.
+    ///
+    /// By default, each column lists the footnotes referenced within it at its
+    /// bottom, below a separator of its own. If this is enabled, the
+    /// footnotes of all columns are instead listed together below the
+    /// columns, and space for them is reserved on the page they are
+    /// referenced on. Footnotes that take up more than half of the page
+    /// continue on the next one.
+    ///
+    /// ```example
+    /// #set page(columns: 2, height: 100pt)
+    /// #set footnote.entry(spanning: true)
+    ///
+    /// Footnotes #footnote[Spanning]
+    /// below #colbreak()
+    /// both columns. #footnote[Wide]
+    /// ```
+    #[default(false)]
+    pub spanning: bool,
 }
 
 impl Show for FootnoteEntry {
@@ -275,7 +385,6 @@ impl Show for FootnoteEntry {
         let number_gap = Em::new(0.05);
         let default = StyleChain::default();
         let numbering = note.numbering(default);
-        let counter = Counter::of(FootnoteElem::elem());
         let Some(loc) = note.location() else {
             bail!(
                 self.span(), "footnote entry must have a location";
@@ -283,7 +392,7 @@ impl Show for FootnoteEntry {
             );
         };
 
-        let num = counter.at(engine, loc)?.display(engine, numbering)?;
+        let num = FootnoteElem::display_number(engine, loc, numbering)?;
         let sup = SuperElem::new(num)
             .pack()
             .linked(Destination::Location(loc))
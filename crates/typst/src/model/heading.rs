@@ -1,10 +1,12 @@
 use std::num::NonZeroUsize;
 
+use comemo::Prehashed;
+
 use crate::diag::SourceResult;
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Content, Finalize, NativeElement, Show, Smart, StyleChain, Styles,
-    Synthesize,
+    cast, elem, func, scope, select_where, Content, Finalize, Func, NativeElement,
+    Selector, Show, Smart, StyleChain, Styles, Synthesize,
 };
 use crate::introspection::{Count, Counter, CounterUpdate, Locatable};
 use crate::layout::{BlockElem, Em, HElem, VElem};
@@ -43,7 +45,21 @@ use crate::util::{option_eq, NonZeroExt};
 /// Headings have dedicated syntax: They can be created by starting a line with
 /// one or multiple equals signs, followed by a space. The number of equals
 /// signs determines the heading's logical nesting depth.
-#[elem(Locatable, Synthesize, Count, Show, Finalize, LocalName, Refable, Outlinable)]
+///
+/// # Running heads
+/// To show the title of the current chapter or section in a page's header, use
+/// [`heading.current`]($heading.current).
+#[elem(
+    scope,
+    Locatable,
+    Synthesize,
+    Count,
+    Show,
+    Finalize,
+    LocalName,
+    Refable,
+    Outlinable
+)]
 pub struct HeadingElem {
     /// The logical nesting depth of the heading, starting from one.
     #[default(NonZeroUsize::ONE)]
@@ -126,6 +142,43 @@ pub struct HeadingElem {
     pub body: Content,
 }
 
+#[scope]
+impl HeadingElem {
+    /// Displays the heading that is current on the page where this is placed.
+    ///
+    /// If a heading of the given level starts on the page, that heading is
+    /// current. Otherwise, it is the last heading of that level on a previous
+    /// page. A heading of a higher level ends the sections of the lower
+    /// levels, so a new chapter without sections has no current section. If
+    /// there is no current heading, nothing is displayed. This makes the
+    /// function well-suited for running heads in page headers.
+    ///
+    /// ```example
+    /// >>> #set page(width: 200pt, height: 120pt)
+    /// #set page(header: heading.current(it => emph(it.body)))
+    ///
+    /// = Introduction
+    /// #lorem(15)
+    ///
+    /// = Methods
+    /// #lorem(30)
+    /// ```
+    #[func]
+    pub fn current(
+        /// A function which receives the current heading and can return
+        /// arbitrary content which is then displayed. If this is omitted, the
+        /// heading's body is displayed.
+        #[default]
+        func: Option<Func>,
+        /// The level of the headings to consider.
+        #[named]
+        #[default(NonZeroUsize::ONE)]
+        level: NonZeroUsize,
+    ) -> Content {
+        CurrentHeadingElem::new(level, func).pack()
+    }
+}
+
 impl Synthesize for HeadingElem {
     fn synthesize(
         &mut self,
@@ -243,6 +296,69 @@ impl Outlinable for HeadingElem {
     }
 }
 
+/// Executes a display of the current heading.
+#[elem(Locatable, Show)]
+struct CurrentHeadingElem {
+    /// The level of the headings to consider.
+    #[required]
+    level: NonZeroUsize,
+
+    /// The function to display the heading with.
+    #[required]
+    func: Option<Func>,
+}
+
+impl Show for CurrentHeadingElem {
+    #[tracing::instrument(name = "CurrentHeadingElem::show", skip_all)]
+    fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(engine.delayed(|engine| {
+            let introspector = engine.introspector;
+            let location = self.location().unwrap();
+            let page = introspector.page(location);
+            let on_page = |elem: &Prehashed<Content>| {
+                introspector.page(elem.location().unwrap()) == page
+            };
+            let up_to = |level: usize| {
+                Selector::Or(
+                    (1..=level)
+                        .map(|level| {
+                            let level = NonZeroUsize::new(level).unwrap();
+                            select_where!(HeadingElem, Level => level)
+                        })
+                        .collect(),
+                )
+            };
+
+            // Prefer the first heading on this page. The page header precedes
+            // the page's body, so such a heading comes after this element.
+            let level = self.level().get();
+            let selector = select_where!(HeadingElem, Level => *self.level());
+            let mut current =
+                introspector.query_first_after(&selector, location).filter(on_page);
+
+            // Otherwise, the last heading before is current, unless a heading
+            // of a higher level started since then and ended its section.
+            if current.is_none() {
+                let ended = level > 1
+                    && introspector
+                        .query_first_after(&up_to(level - 1), location)
+                        .is_some_and(|elem| on_page(&elem));
+                if !ended {
+                    current = introspector
+                        .query_last_before(&up_to(level), location)
+                        .filter(|elem| selector.matches(elem));
+                }
+            }
+
+            let Some(elem) = current else { return Ok(Content::empty()) };
+            Ok(match self.func() {
+                Some(func) => func.call(engine, [elem.into_inner()])?.display(),
+                None => elem.to::<HeadingElem>().unwrap().body().clone(),
+            })
+        }))
+    }
+}
+
 impl LocalName for HeadingElem {
     fn local_name(lang: Lang, region: Option<Region>) -> &'static str {
         match lang {
@@ -1,12 +1,14 @@
-use ecow::{eco_format, EcoString};
+use ecow::{eco_format, eco_vec, EcoString};
 
-use crate::diag::{At, SourceResult};
+use crate::diag::{At, SourceDiagnostic, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Content, Label, NativeElement, Repr, Show, Smart, StyleChain,
+    cast, elem, Cast, Content, Label, NativeElement, Repr, Selector, Show, Smart,
+    StyleChain,
 };
 use crate::introspection::Location;
 use crate::layout::Position;
+use crate::syntax::Span;
 use crate::text::{Hyphenate, TextElem};
 
 /// Links to a URL or a location in the document.
@@ -79,6 +81,24 @@ pub struct LinkElem {
         _ => args.expect("body")?,
     })]
     pub body: Content,
+
+    /// How to report a link whose destination does not exist in the document,
+    /// for instance because it points to a label that isn't attached to any
+    /// element.
+    ///
+    /// By default, a link to a label that doesn't exist is an error, while a
+    /// link to a [location]($location) that doesn't exist is kept as is. Set
+    /// this to `{"error"}` or `{"warning"}` to report both kinds of dangling
+    /// links with that severity. While drafting a document, `{"warning"}` lets
+    /// you still compile it. In this case, a warning is emitted and the body is
+    /// shown without a link. This setting also applies to
+    /// [references]($ref) to nonexistent labels.
+    ///
+    /// ```example
+    /// #set link(dangling: "warning")
+    /// See #link(<appendix>)[the appendix] and @results.
+    /// ```
+    pub dangling: Smart<Dangling>,
 }
 
 impl LinkElem {
@@ -91,12 +111,34 @@ impl LinkElem {
 
 impl Show for LinkElem {
     #[tracing::instrument(name = "LinkElem::show", skip(self, engine))]
-    fn show(&self, engine: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+    fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
         let body = self.body().clone();
+        let dangling = self.dangling(styles);
         let linked = match self.dest() {
+            LinkTarget::Dest(Destination::Location(location)) => match dangling {
+                Smart::Auto => body.linked(Destination::Location(*location)),
+                Smart::Custom(dangling) => engine
+                    .delayed(|engine| {
+                        let selector = Selector::Location(*location);
+                        if engine.introspector.query_first(&selector).is_none() {
+                            dangling.report(
+                                engine,
+                                self.span(),
+                                "link destination does not exist in the document".into(),
+                            )?;
+                            return Ok(None);
+                        }
+                        Ok(Some(body.clone().linked(Destination::Location(*location))))
+                    })
+                    .unwrap_or(body),
+            },
             LinkTarget::Dest(dest) => body.linked(dest.clone()),
             LinkTarget::Label(label) => engine
                 .delayed(|engine| {
+                    let dangling = dangling.unwrap_or(Dangling::Error);
+                    if dangling.check_label(engine, *label, self.span())? {
+                        return Ok(None);
+                    }
                     let elem = engine.introspector.query_label(*label).at(self.span())?;
                     let dest = Destination::Location(elem.location().unwrap());
                     Ok(Some(body.clone().linked(dest)))
@@ -117,6 +159,55 @@ fn body_from_url(url: &EcoString) -> Content {
     TextElem::packed(if shorter { text.into() } else { url.clone() })
 }
 
+/// How to report links and references whose destination does not exist.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum Dangling {
+    /// Dangling links are compile errors.
+    Error,
+    /// Dangling links produce warnings.
+    Warning,
+}
+
+impl Dangling {
+    /// Reports a dangling destination with the configured severity.
+    ///
+    /// Returns an error for [`Dangling::Error`]. Otherwise, the warning is
+    /// delayed until the end of the introspection loop, such that destinations
+    /// that only appear in later iterations don't produce spurious warnings.
+    pub fn report(
+        self,
+        engine: &mut Engine,
+        span: Span,
+        message: EcoString,
+    ) -> SourceResult<()> {
+        match self {
+            Self::Error => Err(eco_vec![SourceDiagnostic::error(span, message)]),
+            Self::Warning => {
+                engine.tracer.delay(eco_vec![SourceDiagnostic::warning(span, message)]);
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks whether the label is missing from the document and reports it in
+    /// that case. Returns whether the label is missing.
+    pub fn check_label(
+        self,
+        engine: &mut Engine,
+        label: Label,
+        span: Span,
+    ) -> SourceResult<bool> {
+        if engine.introspector.query_first(&Selector::Label(label)).is_some() {
+            return Ok(false);
+        }
+
+        let message =
+            eco_format!("label `{}` does not exist in the document", label.repr());
+        self.report(engine, span, message)?;
+        Ok(true)
+    }
+}
+
 /// A target where a link can go.
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum LinkTarget {
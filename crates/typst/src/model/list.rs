@@ -4,11 +4,12 @@ use crate::foundations::{
     cast, elem, scope, Array, Content, Fold, Func, NativeElement, Smart, StyleChain,
     Value,
 };
+use crate::introspection::Locatable;
 use crate::layout::{
-    Axes, BlockElem, Em, Fragment, GridLayouter, HAlign, Layout, Length, Regions, Sizing,
-    Spacing, VAlign,
+    Axes, BlockElem, CellGrid, Em, Fragment, GridLayouter, HAlign, Layout, Length,
+    Regions, Sizing, Spacing, VAlign,
 };
-use crate::model::ParElem;
+use crate::model::{ParElem, Role};
 use crate::text::TextElem;
 
 /// A bullet list.
@@ -42,7 +43,7 @@ use crate::text::TextElem;
 /// followed by a space to create a list item. A list item can contain multiple
 /// paragraphs and other block-level content. All content that is indented
 /// more than an item's marker becomes part of that item.
-#[elem(scope, title = "Bullet List", Layout)]
+#[elem(scope, title = "Bullet List", Locatable, Layout)]
 pub struct ListElem {
     /// If this is `{false}`, the items are spaced apart with
     /// [list spacing]($list.spacing). If it is `{true}`, they use normal
@@ -149,16 +150,24 @@ impl Layout for ListElem {
             .marker(styles)
             .resolve(engine, depth)?
             // avoid '#set align' interference with the list
-            .aligned(HAlign::Start + VAlign::Top);
+            .aligned(HAlign::Start + VAlign::Top)
+            // the marker shares its line with the item's first line
+            .styled(ParElem::set_line_numbering(None));
 
         let mut cells = vec![];
-        for item in self.children() {
+        for (i, item) in self.children().iter().enumerate() {
             cells.push(Content::empty());
-            cells.push(marker.clone());
+            cells.push(marker.clone().tagged(Role::ListLabel(i)));
             cells.push(Content::empty());
-            cells.push(item.body().clone().styled(Self::set_depth(Depth)));
+            cells.push(
+                item.body()
+                    .clone()
+                    .styled(Self::set_depth(Depth))
+                    .tagged(Role::ListBody(i)),
+            );
         }
 
+        let grid = CellGrid::new(cells, 4);
         let layouter = GridLayouter::new(
             Axes::with_x(&[
                 Sizing::Rel(indent.into()),
@@ -167,9 +176,10 @@ impl Layout for ListElem {
                 Sizing::Auto,
             ]),
             Axes::with_y(&[gutter.into()]),
-            &cells,
+            &grid,
             regions,
             styles,
+            TextElem::dir_in(styles),
             self.span(),
         );
 
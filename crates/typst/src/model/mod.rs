@@ -1,5 +1,6 @@
 //! Structuring elements that define the document model.
 
+mod anchor;
 mod bibliography;
 mod cite;
 mod document;
@@ -11,6 +12,7 @@ mod footnote;
 mod heading;
 mod link;
 mod list;
+mod numbered;
 #[path = "numbering.rs"]
 mod numbering_;
 mod outline;
@@ -18,9 +20,11 @@ mod par;
 mod quote;
 mod reference;
 mod strong;
+mod structure;
 mod table;
 mod terms;
 
+pub use self::anchor::*;
 pub use self::bibliography::*;
 pub use self::cite::*;
 pub use self::document::*;
@@ -31,12 +35,14 @@ pub use self::footnote::*;
 pub use self::heading::*;
 pub use self::link::*;
 pub use self::list::*;
+pub use self::numbered::*;
 pub use self::numbering_::*;
 pub use self::outline::*;
 pub use self::par::*;
 pub use self::quote::*;
 pub use self::reference::*;
 pub use self::strong::*;
+pub use self::structure::*;
 pub use self::table::*;
 pub use self::terms::*;
 
@@ -56,9 +62,11 @@ pub fn define(global: &mut Scope) {
     global.define_elem::<DocumentElem>();
     global.define_elem::<RefElem>();
     global.define_elem::<LinkElem>();
+    global.define_elem::<AnchorElem>();
     global.define_elem::<OutlineElem>();
     global.define_elem::<HeadingElem>();
     global.define_elem::<FigureElem>();
+    global.define_elem::<NumberedElem>();
     global.define_elem::<FootnoteElem>();
     global.define_elem::<QuoteElem>();
     global.define_elem::<CiteElem>();
@@ -0,0 +1,238 @@
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
+use ecow::EcoString;
+
+use crate::diag::SourceResult;
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, select_where, Content, NativeElement, Show, Smart, StyleChain, Synthesize,
+};
+use crate::introspection::{Count, Counter, CounterKey, CounterUpdate, Locatable};
+use crate::layout::BlockElem;
+use crate::model::{
+    Numbering, NumberingPattern, Outlinable, Refable, StrongElem, Supplement,
+};
+use crate::text::{SpaceElem, TextElem};
+use crate::util::NonZeroExt;
+
+/// A numbered block of a custom kind, like a theorem, definition or example.
+///
+/// All numbered blocks of the same `kind` share a counter. Like headings and
+/// figures, they can be [referenced]($ref) when they have a label and listed
+/// in an [outline]($outline) by setting its `target` to
+/// `{numbered.where(kind: ..)}`. This makes it possible to define a custom
+/// environment with a single line of code.
+///
+/// # Example
+/// ```example
+/// #let theorem = numbered.with(
+///   "theorem",
+///   supplement: [Theorem],
+/// )
+///
+/// #outline(
+///   title: [List of Theorems],
+///   target: numbered.where(kind: "theorem"),
+/// )
+///
+/// #theorem(title: [Pythagoras])[
+///   $a^2 + b^2 = c^2$
+/// ] <pythagoras>
+///
+/// By @pythagoras, the diagonal
+/// of a unit square is $sqrt(2)$.
+/// ```
+///
+/// # Styling
+/// A numbered block displays its supplement, number and title in bold,
+/// followed by its body. For a different look, you can write a show rule
+/// that uses the block's `supplement`, `counter`, `numbering`, and `title`
+/// fields:
+///
+/// ```example
+/// #let example = numbered.with(
+///   "example",
+///   supplement: [Example],
+/// )
+///
+/// #show numbered.where(kind: "example"): it => block(
+///   inset: 8pt,
+///   stroke: (left: 2pt + gray),
+///   [_#it.supplement #it.counter.display(it.numbering)._ #it.body],
+/// )
+///
+/// #example[Two plus two is four.]
+/// ```
+#[elem(Locatable, Synthesize, Count, Show, Refable, Outlinable)]
+pub struct NumberedElem {
+    /// The kind of the numbered block.
+    ///
+    /// All numbered blocks of the same kind share a common counter.
+    #[required]
+    pub kind: EcoString,
+
+    /// The block's title, displayed in parentheses after its number.
+    pub title: Option<Content>,
+
+    /// The block's supplement, displayed before its number and in references.
+    ///
+    /// If set to `{auto}`, the block's kind with its first letter capitalized
+    /// is used. If a function is specified, it is passed the numbered block
+    /// and should return content.
+    pub supplement: Smart<Option<Supplement>>,
+
+    /// How to number the block. Accepts a
+    /// [numbering pattern or function]($numbering).
+    ///
+    /// ```example
+    /// #set numbered(numbering: "I")
+    /// #numbered("lemma", supplement: [Lemma])[Lemmas are useful.]
+    /// ```
+    #[default(Some(NumberingPattern::from_str("1").unwrap().into()))]
+    pub numbering: Option<Numbering>,
+
+    /// Whether the block should appear in an [outline]($outline).
+    #[default(true)]
+    pub outlined: bool,
+
+    /// The content of the block.
+    #[required]
+    pub body: Content,
+
+    /// Convenience field to get access to the counter for this kind of block.
+    ///
+    /// This is `{counter(numbered.where(kind: kind))}`.
+    #[synthesized]
+    pub counter: Option<Counter>,
+}
+
+impl NumberedElem {
+    /// The counter shared by all numbered blocks of the given kind.
+    fn counter_of(kind: &EcoString) -> Counter {
+        Counter::new(CounterKey::Selector(select_where!(Self, Kind => kind.clone())))
+    }
+
+    /// The supplement after synthesis.
+    fn resolved_supplement(&self) -> Content {
+        match self.supplement(StyleChain::default()) {
+            Smart::Custom(Some(Supplement::Content(content))) => content,
+            _ => Content::empty(),
+        }
+    }
+}
+
+impl Synthesize for NumberedElem {
+    fn synthesize(
+        &mut self,
+        engine: &mut Engine,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let supplement = match self.supplement(styles) {
+            Smart::Auto => TextElem::packed(capitalize(self.kind())),
+            Smart::Custom(None) => Content::empty(),
+            Smart::Custom(Some(supplement)) => {
+                supplement.resolve(engine, [self.clone()])?
+            }
+        };
+
+        self.push_title(self.title(styles));
+        self.push_supplement(Smart::Custom(Some(Supplement::Content(supplement))));
+        self.push_numbering(self.numbering(styles));
+        self.push_outlined(self.outlined(styles));
+        self.push_counter(Some(Self::counter_of(self.kind())));
+
+        Ok(())
+    }
+}
+
+impl Show for NumberedElem {
+    #[tracing::instrument(name = "NumberedElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let mut head = self.resolved_supplement();
+        if let Some(numbering) = self.numbering(styles) {
+            if !head.is_empty() {
+                head += TextElem::packed('\u{a0}');
+            }
+            head += Self::counter_of(self.kind())
+                .display(Some(numbering), false)
+                .spanned(self.span());
+        }
+
+        if let Some(title) = self.title(styles) {
+            if !head.is_empty() {
+                head += SpaceElem::new().pack();
+            }
+            head += TextElem::packed('(') + title + TextElem::packed(')');
+        }
+
+        let mut realized = self.body().clone();
+        if !head.is_empty() {
+            head += TextElem::packed('.');
+            realized = StrongElem::new(head).pack() + SpaceElem::new().pack() + realized;
+        }
+
+        Ok(BlockElem::new().with_body(Some(realized)).pack())
+    }
+}
+
+impl Count for NumberedElem {
+    fn update(&self) -> Option<CounterUpdate> {
+        self.numbering(StyleChain::default())
+            .is_some()
+            .then(|| CounterUpdate::Step(NonZeroUsize::ONE))
+    }
+}
+
+impl Refable for NumberedElem {
+    fn supplement(&self) -> Content {
+        // After synthesis, this should always be custom content.
+        self.resolved_supplement()
+    }
+
+    fn counter(&self) -> Counter {
+        Self::counter_of(self.kind())
+    }
+
+    fn numbering(&self) -> Option<Numbering> {
+        self.numbering(StyleChain::default())
+    }
+}
+
+impl Outlinable for NumberedElem {
+    fn outline(&self, engine: &mut Engine) -> SourceResult<Option<Content>> {
+        let default = StyleChain::default();
+        if !self.outlined(default) {
+            return Ok(None);
+        }
+
+        let mut realized = self.resolved_supplement();
+        if let Some(numbering) = self.numbering(default) {
+            let numbers = Self::counter_of(self.kind())
+                .at(engine, self.location().unwrap())?
+                .display(engine, &numbering)?;
+            if !realized.is_empty() {
+                realized += TextElem::packed('\u{a0}');
+            }
+            realized += numbers;
+        }
+
+        if let Some(title) = self.title(default) {
+            if !realized.is_empty() {
+                realized += TextElem::packed(": ");
+            }
+            realized += title;
+        }
+
+        Ok(Some(realized))
+    }
+}
+
+/// Capitalize the first letter of a kind to use it as the default supplement.
+fn capitalize(kind: &str) -> EcoString {
+    let mut chars = kind.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => EcoString::new(),
+    }
+}
@@ -4,7 +4,7 @@ use std::str::FromStr;
 use crate::diag::{bail, At, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, scope, select_where, Content, Finalize, Func, LocatableSelector,
+    cast, elem, scope, select_where, Cast, Content, Finalize, Func, LocatableSelector,
     NativeElement, Show, Smart, StyleChain,
 };
 use crate::introspection::{Counter, CounterKey, Locatable};
@@ -38,7 +38,9 @@ use crate::util::{option_eq, NonZeroExt};
 /// all figures containing images by setting `target` to `{figure.where(kind:
 /// image)}`. We could have also set it to just `figure`, but then the list
 /// would also include figures containing tables or other material. For more
-/// details on the `where` selector, [see here]($function.where).
+/// details on the `where` selector, [see here]($function.where). Custom
+/// environments like theorems can be listed by targeting a
+/// [`numbered`]($numbered) kind, e.g. `{numbered.where(kind: "theorem")}`.
 ///
 /// ```example
 /// #outline(
@@ -57,7 +59,7 @@ use crate::util::{option_eq, NonZeroExt};
 /// `title` and `indent` parameters. If desired, however, it is possible to have
 /// more control over the outline's look and style through the
 /// [`outline.entry`]($outline.entry) element.
-#[elem(scope, keywords = ["Table of Contents"], Show, Finalize, LocalName)]
+#[elem(scope, keywords = ["Table of Contents"], Locatable, Show, Finalize, LocalName)]
 pub struct OutlineElem {
     /// The title of the outline.
     ///
@@ -177,6 +179,22 @@ pub struct OutlineElem {
     /// ```
     #[default(Some(RepeatElem::new(TextElem::packed(".")).pack()))]
     pub fill: Option<Content>,
+
+    /// When to display the page numbers of the outline's entries.
+    ///
+    /// In short documents, page numbers in the outline are often just noise.
+    /// This option allows hiding them based on where the entries end up in
+    /// the document. When an entry's page number is hidden, its fill is
+    /// omitted as well.
+    ///
+    /// ```example
+    /// #outline(page-numbers: "multiple-pages")
+    ///
+    /// = Introduction
+    /// = Conclusion
+    /// ```
+    #[default(OutlinePageNumbers::Always)]
+    pub page_numbers: OutlinePageNumbers,
 }
 
 #[scope]
@@ -203,13 +221,34 @@ impl Show for OutlineElem {
 
         let mut ancestors: Vec<&Content> = vec![];
         let elems = engine.introspector.query(&self.target(styles).0);
+        // The pages of the outline and its entries are only looked up when
+        // they decide whether page numbers are shown.
+        let page_numbers = self.page_numbers(styles);
+        let single_page = page_numbers == OutlinePageNumbers::MultiplePages && {
+            let mut pages = elems
+                .iter()
+                .filter_map(|elem| elem.location())
+                .map(|loc| engine.introspector.page(loc));
+            let first = pages.next();
+            pages.all(|page| Some(page) == first)
+        };
 
         for elem in &elems {
+            let show_page = match page_numbers {
+                OutlinePageNumbers::Always => true,
+                OutlinePageNumbers::OtherPages => {
+                    let page = |loc| engine.introspector.page(loc);
+                    elem.location().map(page) != self.location().map(page)
+                }
+                OutlinePageNumbers::MultiplePages => !single_page,
+            };
+
             let Some(entry) = OutlineEntry::from_outlinable(
                 engine,
                 self.span(),
                 elem.clone().into_inner(),
-                self.fill(styles),
+                self.fill(styles).filter(|_| show_page),
+                show_page,
             )?
             else {
                 continue;
@@ -245,6 +284,18 @@ impl Show for OutlineElem {
     }
 }
 
+/// When to display the page numbers of an outline's entries.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum OutlinePageNumbers {
+    /// Always display page numbers.
+    Always,
+    /// Hide the page numbers of entries that are on the same page as the
+    /// outline itself.
+    OtherPages,
+    /// Hide all page numbers if all entries are on the same page.
+    MultiplePages,
+}
+
 impl Finalize for OutlineElem {
     fn finalize(&self, realized: Content, _: StyleChain) -> Content {
         realized
@@ -455,12 +506,14 @@ impl OutlineEntry {
     /// Generates an OutlineEntry from the given element, if possible (errors if
     /// the element does not implement `Outlinable`). If the element should not
     /// be outlined (e.g. heading with 'outlined: false'), does not generate an
-    /// entry instance (returns `Ok(None)`).
+    /// entry instance (returns `Ok(None)`). If `show_page` is false, the
+    /// entry's page number is left empty.
     fn from_outlinable(
         engine: &mut Engine,
         span: Span,
         elem: Content,
         fill: Option<Content>,
+        show_page: bool,
     ) -> SourceResult<Option<Self>> {
         let Some(outlinable) = elem.with::<dyn Outlinable>() else {
             bail!(span, "cannot outline {}", elem.func().name());
@@ -470,6 +523,16 @@ impl OutlineEntry {
             return Ok(None);
         };
 
+        if !show_page {
+            return Ok(Some(Self::new(
+                outlinable.level(),
+                elem,
+                body,
+                fill,
+                Content::empty(),
+            )));
+        }
+
         let location = elem.location().unwrap();
         let page_numbering = engine
             .introspector
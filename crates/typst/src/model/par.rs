@@ -1,12 +1,18 @@
+use std::num::NonZeroUsize;
+
 use comemo::Prehashed;
+use smallvec::smallvec;
 
-use crate::diag::SourceResult;
+use crate::diag::{bail, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    elem, Args, Cast, Construct, Content, NativeElement, Set, Smart, StyleChain,
-    Unlabellable,
+    cast, dict, elem, scope, Args, Cast, Construct, Content, Dict, NativeElement, Set,
+    Smart, StyleChain, Unlabellable,
 };
-use crate::layout::{Em, Fragment, Length, Size};
+use crate::introspection::{Count, CounterState, CounterUpdate, Locatable};
+use crate::layout::{Em, Fragment, Length, Ratio, Size};
+use crate::model::Numbering;
+use crate::util::NonZeroExt;
 
 /// Arranges text, spacing and inline-level elements into a paragraph.
 ///
@@ -33,7 +39,7 @@ use crate::layout::{Em, Fragment, Length, Size};
 /// let $a$ be the smallest of the
 /// three integers. Then, we ...
 /// ```
-#[elem(title = "Paragraph", Construct)]
+#[elem(scope, title = "Paragraph", Construct)]
 pub struct ParElem {
     /// The spacing between lines.
     #[resolve]
@@ -96,6 +102,86 @@ pub struct ParElem {
     #[resolve]
     pub hanging_indent: Length,
 
+    /// How to number the lines of paragraphs in the margin.
+    ///
+    /// Line numbers are counted continuously across paragraphs and placed
+    /// in the page margin next to the start edge of each line. The lines
+    /// within boxes and list markers aren't numbered. This can either be a
+    /// [numbering pattern or function]($numbering) or a dictionary with the
+    /// following keys:
+    ///
+    /// - `numbering`: How to display the line numbers.
+    /// - `start`: The number of the first line. Defaults to `{1}`.
+    /// - `step`: Only lines whose number is a multiple of the step are
+    ///   labelled, but all lines are counted. Defaults to `{1}`.
+    /// - `restart`: Whether to start counting from the start again on every
+    ///   page. Defaults to `{false}`.
+    ///
+    /// Every numbered line is represented by a [`par.line`]($par.line)
+    /// element, which can be used in queries and with the
+    /// [`counter`]($counter) function.
+    ///
+    /// ```example
+    /// #set page(margin: (left: 36pt))
+    /// #set par(line-numbering: (
+    ///   numbering: "1",
+    ///   step: 2,
+    /// ))
+    ///
+    /// Typst numbers every line of
+    /// this paragraph, but only every
+    /// second number is shown in the
+    /// margin.
+    /// ```
+    #[ghost]
+    pub line_numbering: Option<LineNumbering>,
+
+    /// Microtypographic refinements for the paragraph's lines.
+    ///
+    /// This is a dictionary with the following keys:
+    ///
+    /// - `protrusion`: Whether punctuation and quotes at the start and end
+    ///   of lines should protrude into the margins, so that the edges of the
+    ///   text look optically straight. Unlike the text's
+    ///   [`overhang`]($text.overhang), protrusion is also considered when
+    ///   choosing line breaks. Defaults to `{false}`.
+    /// - `expansion`: By how much the glyphs of a justified line may at most
+    ///   be stretched or compressed horizontally, in addition to adjusting
+    ///   the spacing. This gives the optimized line breaking more freedom
+    ///   and evens out the word spacing. Defaults to `{0%}`.
+    ///
+    /// ```example
+    /// #set par(
+    ///   justify: true,
+    ///   microtype: (protrusion: true, expansion: 2%),
+    /// )
+    ///
+    /// "This is a quote," she said,
+    /// "and it hangs into the margin
+    /// just like its final comma."
+    /// ```
+    #[ghost]
+    pub microtype: Microtype,
+
+    /// Whether the paragraph's lines snap to the page's
+    /// [baseline grid]($page.baseline-grid).
+    ///
+    /// This can be turned off for paragraphs whose lines shouldn't be
+    /// spread out to fit the grid, for instance ones with smaller text.
+    ///
+    /// ```example
+    /// #set page(height: 100pt, baseline-grid: 14pt)
+    /// #set par(leading: 14pt - 1em)
+    ///
+    /// On the grid.
+    ///
+    /// #set par(snap: false)
+    /// #text(0.8em)[Small text with its own line spacing.]
+    /// ```
+    #[ghost]
+    #[default(true)]
+    pub snap: bool,
+
     /// The contents of the paragraph.
     #[external]
     #[required]
@@ -122,6 +208,12 @@ impl Construct for ParElem {
     }
 }
 
+#[scope]
+impl ParElem {
+    #[elem]
+    type ParLine;
+}
+
 impl ParElem {
     /// Layout the paragraph into a collection of lines.
     #[tracing::instrument(name = "ParElement::layout", skip_all)]
@@ -156,6 +248,123 @@ pub enum Linebreaks {
     Optimized,
 }
 
+/// How the lines of paragraphs are numbered.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct LineNumbering {
+    /// How to display the line numbers.
+    pub numbering: Numbering,
+    /// The number of the first line.
+    pub start: usize,
+    /// Only multiples of this are displayed.
+    pub step: NonZeroUsize,
+    /// Whether to restart the numbering on every page.
+    pub restart: bool,
+}
+
+impl LineNumbering {
+    /// Create a line numbering with default settings.
+    pub fn new(numbering: Numbering) -> Self {
+        Self {
+            numbering,
+            start: 1,
+            step: NonZeroUsize::ONE,
+            restart: false,
+        }
+    }
+
+    /// Whether the line with the given number should be labelled.
+    pub fn shows(&self, number: usize) -> bool {
+        number % self.step.get() == 0
+    }
+}
+
+cast! {
+    LineNumbering,
+    self => dict! {
+        "numbering" => self.numbering,
+        "start" => self.start,
+        "step" => self.step,
+        "restart" => self.restart,
+    }.into_value(),
+    numbering: Numbering => Self::new(numbering),
+    mut dict: Dict => {
+        let mut numbering = Self::new(dict.take("numbering")?.cast()?);
+        if let Ok(start) = dict.take("start") {
+            numbering.start = start.cast()?;
+        }
+        if let Ok(step) = dict.take("step") {
+            numbering.step = step.cast()?;
+        }
+        if let Ok(restart) = dict.take("restart") {
+            numbering.restart = restart.cast()?;
+        }
+        dict.finish(&["numbering", "start", "step", "restart"])?;
+        numbering
+    },
+}
+
+/// Microtypographic refinements for paragraphs.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Hash)]
+pub struct Microtype {
+    /// Whether characters at the edges of lines protrude into the margins.
+    pub protrusion: bool,
+    /// How much glyphs may at most be scaled horizontally.
+    pub expansion: Ratio,
+}
+
+cast! {
+    Microtype,
+    self => dict! {
+        "protrusion" => self.protrusion,
+        "expansion" => self.expansion,
+    }.into_value(),
+    mut dict: Dict => {
+        let mut microtype = Self::default();
+        if let Ok(protrusion) = dict.take("protrusion") {
+            microtype.protrusion = protrusion.cast()?;
+        }
+        if let Ok(expansion) = dict.take("expansion") {
+            let expansion: Ratio = expansion.cast()?;
+            if expansion.get() < 0.0 || expansion.get() > 0.1 {
+                bail!("expansion must be between 0% and 10%");
+            }
+            microtype.expansion = expansion;
+        }
+        dict.finish(&["protrusion", "expansion"])?;
+        microtype
+    },
+}
+
+/// A numbered line of a paragraph.
+///
+/// This element is produced automatically for every line laid out while
+/// [line numbering]($par.line-numbering) is enabled. It can be queried and
+/// counted to find out about the numbered lines in the document.
+///
+/// ```example
+/// #set par(line-numbering: "1")
+/// The quick brown fox jumps over
+/// the lazy dog.
+///
+/// #locate(loc => [
+///   This document has
+///   #query(par.line, loc).len()
+///   numbered lines.
+/// ])
+/// ```
+#[elem(name = "line", title = "Paragraph Line", Locatable, Count)]
+pub struct ParLine {
+    /// The line's number.
+    #[required]
+    pub number: usize,
+}
+
+impl Count for ParLine {
+    fn update(&self) -> Option<CounterUpdate> {
+        Some(CounterUpdate::Set(CounterState(smallvec![*self.number()])))
+    }
+}
+
 /// A paragraph break.
 ///
 /// This starts a new paragraph. Especially useful when used within code like
@@ -1,15 +1,17 @@
+use comemo::Prehashed;
 use ecow::eco_format;
 
 use crate::diag::{bail, At, Hint, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Content, Func, IntoValue, Label, NativeElement, Show, Smart, StyleChain,
-    Synthesize,
+    cast, elem, Content, Func, IntoValue, Label, NativeElement, Repr, Show, Smart,
+    StyleChain, Synthesize,
 };
 use crate::introspection::{Counter, Locatable};
 use crate::math::EquationElem;
 use crate::model::{
-    BibliographyElem, CiteElem, Destination, Figurable, FootnoteElem, Numbering,
+    BibliographyElem, CiteElem, Dangling, Destination, Figurable, FootnoteElem, LinkElem,
+    Numbering, TableElem,
 };
 use crate::text::TextElem;
 
@@ -149,6 +151,7 @@ impl Synthesize for RefElem {
         let target = *self.target();
         if !BibliographyElem::has(engine, target) {
             if let Ok(elem) = engine.introspector.query_label(target).cloned() {
+                let elem = resolve_table_figure(engine, elem);
                 self.push_element(Some(elem.into_inner()));
                 return Ok(());
             }
@@ -163,28 +166,35 @@ impl Show for RefElem {
     fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
         Ok(engine.delayed(|engine| {
             let target = *self.target();
-            let elem = engine.introspector.query_label(target);
             let span = self.span();
 
             if BibliographyElem::has(engine, target) {
-                if elem.is_ok() {
+                if engine.introspector.query_label(target).is_ok() {
                     bail!(span, "label occurs in the document and its bibliography");
                 }
 
                 return Ok(self.to_citation(engine, styles)?.spanned(span).pack());
             }
 
-            let elem = elem.at(span)?;
+            // With a lenient setting, show the label instead of failing.
+            let dangling = LinkElem::dangling_in(styles).unwrap_or(Dangling::Error);
+            if dangling.check_label(engine, target, span)? {
+                return Ok(TextElem::packed(target.repr()));
+            }
+
+            let elem = engine.introspector.query_label(target).at(span)?;
 
             if elem.func() == FootnoteElem::elem() {
                 return Ok(FootnoteElem::with_label(target).spanned(span).pack());
             }
 
-            let elem = elem.clone();
+            let elem = resolve_table_figure(engine, elem.clone());
             let refable = elem
                 .with::<dyn Refable>()
                 .ok_or_else(|| {
-                    if elem.can::<dyn Figurable>() {
+                    if elem.func() == TableElem::elem() {
+                        "cannot reference table directly, try giving it a caption".into()
+                    } else if elem.can::<dyn Figurable>() {
                         eco_format!(
                             "cannot reference {} directly, try putting it into a figure",
                             elem.func().name()
@@ -236,6 +246,20 @@ impl Show for RefElem {
     }
 }
 
+/// Resolves a captioned table to the figure it is wrapped in, which is what
+/// references to it refer to.
+///
+/// Only tables with a caption are wrapped in a figure. Other tables are
+/// returned as is, so that referencing them fails with a hint to add one.
+fn resolve_table_figure(engine: &Engine, elem: Prehashed<Content>) -> Prehashed<Content> {
+    match elem.location() {
+        Some(location) if elem.func() == TableElem::elem() => {
+            TableElem::figure(engine, location).unwrap_or(elem)
+        }
+        _ => elem,
+    }
+}
+
 impl RefElem {
     /// Turn the reference into a citation.
     pub fn to_citation(
@@ -0,0 +1,498 @@
+use std::num::NonZeroUsize;
+
+use crate::foundations::{Content, StyleChain};
+use crate::introspection::{Location, Meta};
+use crate::layout::{Abs, Frame, FrameItem, Point, Size, Transform};
+use crate::model::{
+    Destination, EnumElem, FigureElem, HeadingElem, ListElem, TableElem, TermsElem,
+};
+use crate::text::TextItem;
+use crate::visualize::Image;
+
+/// The vertical distance between two baselines, relative to the font size,
+/// above which two lines are considered to belong to different paragraphs.
+///
+/// With the default leading and paragraph spacing, lines within a paragraph
+/// are about 1.65em apart while paragraphs are about 2.2em apart.
+const PAR_BREAK: f64 = 1.9;
+
+/// The role of laid-out content within its closest surrounding list or table.
+///
+/// Roles are attached to frames as [metadata](Meta::Role) so that exporters
+/// can recover the structure of lists and tables from the laid-out pages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Role {
+    /// The marker or term of the list item with the given index.
+    ListLabel(usize),
+    /// The body or description of the list item with the given index.
+    ListBody(usize),
+    /// The table cell in the given column and row, spanning the given number
+    /// of columns and rows. Whether it is part of the table's header.
+    TableCell { x: usize, y: usize, colspan: usize, rowspan: usize, header: bool },
+}
+
+/// A logical unit of a laid-out page.
+#[derive(Debug, Clone)]
+pub struct StructNode<'a> {
+    /// What kind of unit this is.
+    pub kind: StructKind,
+    /// The element the unit stems from, if any.
+    pub location: Option<Location>,
+    /// The unit's parts in reading order.
+    pub children: Vec<StructChild<'a>>,
+}
+
+/// A part of a logical unit.
+#[derive(Debug, Clone)]
+pub enum StructChild<'a> {
+    /// A nested unit.
+    Node(StructNode<'a>),
+    /// Laid-out content that belongs directly to the unit.
+    Leaf(StructLeaf<'a>),
+}
+
+/// A text run or image that belongs to a logical unit.
+#[derive(Debug, Clone)]
+pub struct StructLeaf<'a> {
+    /// The index of the leaf among all text runs and images of the page, in
+    /// the order in which they appear in the frames.
+    pub index: usize,
+    /// The start of the baseline for text and the top-left corner for images,
+    /// in page coordinates.
+    pub pos: Point,
+    /// The link the leaf is part of, if any.
+    pub link: Option<&'a Destination>,
+    /// The laid-out content.
+    pub kind: LeafKind<'a>,
+}
+
+/// The laid-out content of a leaf.
+#[derive(Debug, Copy, Clone)]
+pub enum LeafKind<'a> {
+    /// A run of text.
+    Text(&'a TextItem),
+    /// An image with its size.
+    Image(&'a Image, Size),
+}
+
+/// The kind of a logical unit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StructKind {
+    /// A heading of the given level. Contains its text directly.
+    Heading(NonZeroUsize),
+    /// A paragraph. Contains its text directly.
+    Par,
+    /// A figure. Contains its images directly.
+    Figure,
+    /// A bullet, numbered, or term list.
+    List,
+    /// The item of a list with the given index.
+    ListItem(usize),
+    /// The marker or term of a list item.
+    ListLabel,
+    /// The body or description of a list item.
+    ListBody,
+    /// A table.
+    Table,
+    /// The row of a table with the given index.
+    TableRow(usize),
+    /// The cell of a table row in the given column, spanning the given number
+    /// of columns and rows. Header cells label the cells below them.
+    TableCell { x: usize, colspan: usize, rowspan: usize, header: bool },
+}
+
+/// Recover the logical structure of a laid-out page.
+///
+/// Headings, figures, lists, and tables are identified by the metadata in the
+/// frames, with lists and tables relying on [roles](Role) to tell their items
+/// and cells apart. Text outside of headings is grouped into paragraphs based
+/// on the spacing between its lines and images outside of figures become
+/// figures of their own.
+pub fn structure(frame: &Frame) -> Vec<StructNode<'_>> {
+    let mut builder = Builder::default();
+    builder.visit(frame, Transform::identity());
+
+    // Text and images always end up in a paragraph or figure, so there are no
+    // leaves at the top level.
+    builder
+        .roots
+        .into_iter()
+        .filter_map(|child| match child {
+            StructChild::Node(node) => Some(node),
+            StructChild::Leaf(_) => None,
+        })
+        .collect()
+}
+
+/// Builds the structure of a page while walking through its frames.
+#[derive(Default)]
+struct Builder<'a> {
+    /// The top-level units so far.
+    roots: Vec<StructChild<'a>>,
+    /// The regions of the frames that are currently being visited.
+    regions: Vec<Region<'a>>,
+    /// The number of leaves so far.
+    leaves: usize,
+}
+
+/// A region of a page that gives the content within it a meaning.
+struct Region<'a> {
+    min: Point,
+    max: Point,
+    kind: RegionKind<'a>,
+}
+
+/// The meaning of a region.
+enum RegionKind<'a> {
+    Elem(StructKind, Location),
+    Role(Role),
+    Link(&'a Destination),
+}
+
+impl<'a> Builder<'a> {
+    /// Visit the items of a frame with the given transform to page
+    /// coordinates.
+    ///
+    /// Metadata applies to the items that follow it in the same frame and to
+    /// nested frames, so a frame's regions are discarded once it is done.
+    fn visit(&mut self, frame: &'a Frame, ts: Transform) {
+        let len = self.regions.len();
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    let ts = ts
+                        .pre_concat(Transform::translate(pos.x, pos.y))
+                        .pre_concat(group.transform);
+                    self.visit(&group.frame, ts);
+                }
+                FrameItem::Text(text) => {
+                    self.leaf(pos.transform(ts), LeafKind::Text(text));
+                }
+                FrameItem::Image(image, size, _) => {
+                    self.leaf(pos.transform(ts), LeafKind::Image(image, *size));
+                }
+                FrameItem::Meta(meta, size) => {
+                    let kind = match meta {
+                        Meta::Elem(elem) => match (elem_kind(elem), elem.location()) {
+                            (Some(kind), Some(location)) => {
+                                RegionKind::Elem(kind, location)
+                            }
+                            _ => continue,
+                        },
+                        Meta::Role(role) => RegionKind::Role(*role),
+                        Meta::Link(dest) => RegionKind::Link(dest),
+                        _ => continue,
+                    };
+                    self.regions.push(Region::new(*pos, *size, ts, kind));
+                }
+                FrameItem::Shape(..) => {}
+            }
+        }
+        self.regions.truncate(len);
+    }
+
+    /// Add a text run or image at a position on the page.
+    fn leaf(&mut self, pos: Point, kind: LeafKind<'a>) {
+        let mut path: Vec<(StructKind, Option<Location>)> = vec![];
+        let mut link = None;
+
+        // Metadata of outer elements precedes that of inner ones, so the
+        // regions containing the leaf form a path from the top-level unit to
+        // the leaf's unit. Elements occur once per frame they produce, so we
+        // skip the ones we have already seen.
+        for region in self.regions.iter().filter(|region| region.contains(pos)) {
+            match region.kind {
+                RegionKind::Elem(kind, location) => {
+                    if !path.iter().any(|&(_, other)| other == Some(location)) {
+                        path.push((kind, Some(location)));
+                    }
+                }
+                RegionKind::Role(role) => {
+                    // A role only applies if its list or table is the
+                    // innermost unit so far.
+                    let (container, outer, inner) = match role {
+                        Role::ListLabel(index) => (
+                            StructKind::List,
+                            StructKind::ListItem(index),
+                            StructKind::ListLabel,
+                        ),
+                        Role::ListBody(index) => (
+                            StructKind::List,
+                            StructKind::ListItem(index),
+                            StructKind::ListBody,
+                        ),
+                        Role::TableCell { x, y, colspan, rowspan, header } => (
+                            StructKind::Table,
+                            StructKind::TableRow(y),
+                            StructKind::TableCell { x, colspan, rowspan, header },
+                        ),
+                    };
+                    if path.last().map(|&(kind, _)| kind) != Some(container) {
+                        continue;
+                    }
+                    path.push((outer, None));
+                    path.push((inner, None));
+                }
+                RegionKind::Link(dest) => link = Some(dest),
+            }
+        }
+
+        let leaf = StructLeaf { index: self.leaves, pos, link, kind };
+        self.leaves += 1;
+        insert(&mut self.roots, None, &path, leaf);
+    }
+}
+
+impl Region<'_> {
+    /// Create a region from an item's position and size in a frame with the
+    /// given transform to page coordinates.
+    fn new(pos: Point, size: Size, ts: Transform, kind: RegionKind) -> Region {
+        let a = pos.transform(ts);
+        let b = (pos + size.to_point()).transform(ts);
+        Region { min: a.min(b), max: a.max(b), kind }
+    }
+
+    /// Whether the point lies within the region.
+    ///
+    /// Text that starts right at the end of a region, like the punctuation
+    /// after a link, lies outside of it.
+    fn contains(&self, point: Point) -> bool {
+        point.x.fits(self.min.x)
+            && point.y.fits(self.min.y)
+            && !point.x.fits(self.max.x)
+            && self.max.y.fits(point.y)
+    }
+}
+
+/// The kind of unit an element with a location forms, if any.
+fn elem_kind(elem: &Content) -> Option<StructKind> {
+    if let Some(heading) = elem.to::<HeadingElem>() {
+        Some(StructKind::Heading(heading.level(StyleChain::default())))
+    } else if elem.is::<FigureElem>() {
+        Some(StructKind::Figure)
+    } else if elem.is::<ListElem>() || elem.is::<EnumElem>() || elem.is::<TermsElem>() {
+        Some(StructKind::List)
+    } else if elem.is::<TableElem>() {
+        Some(StructKind::Table)
+    } else {
+        None
+    }
+}
+
+/// Insert a leaf into the unit at the end of the path, starting from the
+/// given children of a unit of the given kind.
+fn insert<'a>(
+    children: &mut Vec<StructChild<'a>>,
+    parent: Option<StructKind>,
+    path: &[(StructKind, Option<Location>)],
+    leaf: StructLeaf<'a>,
+) {
+    if let Some((&(kind, location), rest)) = path.split_first() {
+        // An element's content may be interrupted by other content, for
+        // example by a line number in the margin, so we look for the
+        // element's unit among all children.
+        let existing = children.iter().rposition(|child| {
+            matches!(child, StructChild::Node(node)
+                if node.kind == kind && node.location == location)
+        });
+        let i = existing.unwrap_or_else(|| {
+            children.push(StructChild::Node(StructNode {
+                kind,
+                location,
+                children: vec![],
+            }));
+            children.len() - 1
+        });
+        let StructChild::Node(node) = &mut children[i] else { unreachable!() };
+        insert(&mut node.children, Some(kind), rest, leaf);
+        return;
+    }
+
+    match (leaf.kind, parent) {
+        (LeafKind::Text(_), Some(StructKind::Heading(_)))
+        | (LeafKind::Image(..), Some(StructKind::Figure)) => {
+            children.push(StructChild::Leaf(leaf));
+        }
+        (LeafKind::Text(text), _) => match children.last_mut() {
+            Some(StructChild::Node(node))
+                if node.kind == StructKind::Par && !breaks(node, leaf.pos, text.size) =>
+            {
+                node.children.push(StructChild::Leaf(leaf));
+            }
+            _ => children.push(StructChild::Node(StructNode {
+                kind: StructKind::Par,
+                location: None,
+                children: vec![StructChild::Leaf(leaf)],
+            })),
+        },
+        (LeafKind::Image(..), _) => children.push(StructChild::Node(StructNode {
+            kind: StructKind::Figure,
+            location: None,
+            children: vec![StructChild::Leaf(leaf)],
+        })),
+    }
+}
+
+/// Whether text with a baseline at the point starts a new paragraph after
+/// the given one.
+fn breaks(par: &StructNode, pos: Point, size: Abs) -> bool {
+    let last = par.children.iter().rev().find_map(|child| match child {
+        StructChild::Leaf(StructLeaf { pos, kind: LeafKind::Text(text), .. }) => {
+            Some((pos.y, text.size))
+        }
+        _ => None,
+    });
+
+    let Some((y, prev)) = last else { return true };
+    let size = size.max(prev);
+    let gap = pos.y - y;
+    gap > PAR_BREAK * size || gap < -size
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::foundations::{Bytes, NativeElement};
+    use crate::layout::FrameKind;
+    use crate::model::ListItem;
+    use crate::text::{Font, Lang, TextElem};
+    use crate::util::NonZeroExt;
+    use crate::visualize::Color;
+
+    use super::*;
+
+    const FONT: &[u8] = include_bytes!("../../../../assets/fonts/LinLibertine_R.ttf");
+
+    #[test]
+    fn test_structure_paragraphs_and_headings() {
+        let mut frame = page();
+        let heading = HeadingElem::new(TextElem::packed("Intro")).pack();
+        elem(&mut frame, heading, Size::new(Abs::pt(200.0), Abs::pt(20.0)));
+        text(&mut frame, (0.0, 15.0), "Intro");
+        text(&mut frame, (0.0, 40.0), "First");
+        text(&mut frame, (0.0, 52.0), "line.");
+        text(&mut frame, (0.0, 80.0), "Second.");
+
+        let nodes = structure(&frame);
+        let kinds: Vec<_> = nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(
+            kinds,
+            [StructKind::Heading(NonZeroUsize::ONE), StructKind::Par, StructKind::Par]
+        );
+        assert_eq!(leaves(&nodes[0]), [0]);
+        assert_eq!(leaves(&nodes[1]), [1, 2]);
+        assert_eq!(leaves(&nodes[2]), [3]);
+    }
+
+    #[test]
+    fn test_structure_list_items() {
+        let mut frame = page();
+        let list = ListElem::new(vec![ListItem::new(Content::empty())]).pack();
+        let size = frame.size();
+        elem(&mut frame, list, size);
+        role(&mut frame, Role::ListLabel(0), (0.0, 0.0));
+        text(&mut frame, (0.0, 15.0), "•");
+        role(&mut frame, Role::ListBody(0), (20.0, 0.0));
+        text(&mut frame, (20.0, 15.0), "Item");
+
+        let nodes = structure(&frame);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].kind, StructKind::List);
+        let [StructChild::Node(item)] = nodes[0].children.as_slice() else { panic!() };
+        assert_eq!(item.kind, StructKind::ListItem(0));
+        let kinds: Vec<_> = item.children.iter().map(kind).collect();
+        assert_eq!(kinds, [Some(StructKind::ListLabel), Some(StructKind::ListBody)]);
+    }
+
+    #[test]
+    fn test_structure_roles_outside_of_container() {
+        // A table cell's role has no effect outside of a table.
+        let mut frame = page();
+        let cell = Role::TableCell { x: 0, y: 0, colspan: 1, rowspan: 1, header: true };
+        role(&mut frame, cell, (0.0, 0.0));
+        text(&mut frame, (0.0, 15.0), "Loose");
+
+        let nodes = structure(&frame);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].kind, StructKind::Par);
+    }
+
+    #[test]
+    fn test_structure_links_and_groups() {
+        // Text in a nested frame lies in page coordinates, and text right
+        // after a link is outside of it.
+        let mut frame = page();
+        let dest = Destination::Url("https://typst.app".into());
+        let size = Size::new(Abs::pt(30.0), Abs::pt(20.0));
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Link(dest), size));
+
+        let mut inner =
+            Frame::new(Size::new(Abs::pt(100.0), Abs::pt(20.0)), FrameKind::Hard);
+        text(&mut inner, (0.0, 15.0), "Link");
+        text(&mut inner, (30.0, 15.0), ".");
+        frame.push_frame(Point::with_y(Abs::pt(5.0)), inner);
+
+        let nodes = structure(&frame);
+        let [StructChild::Leaf(link), StructChild::Leaf(after)] =
+            nodes[0].children.as_slice()
+        else {
+            panic!()
+        };
+        assert_eq!(link.pos, Point::with_y(Abs::pt(20.0)));
+        assert!(matches!(link.link, Some(Destination::Url(_))));
+        assert!(after.link.is_none());
+    }
+
+    /// Create an empty page.
+    fn page() -> Frame {
+        Frame::soft(Size::new(Abs::pt(200.0), Abs::pt(200.0)))
+    }
+
+    /// Add an element that covers a region at the top of the frame.
+    fn elem(frame: &mut Frame, mut elem: Content, size: Size) {
+        elem.set_location(Location {
+            hash: crate::util::hash128(&elem),
+            disambiguator: 0,
+            variant: 0,
+        });
+        frame.push(Point::zero(), FrameItem::Meta(Meta::Elem(elem), size));
+    }
+
+    /// Add a role to a region of 20pt by 20pt at a position.
+    fn role(frame: &mut Frame, role: Role, (x, y): (f64, f64)) {
+        let pos = Point::new(Abs::pt(x), Abs::pt(y));
+        let size = Size::splat(Abs::pt(20.0));
+        frame.push(pos, FrameItem::Meta(Meta::Role(role), size));
+    }
+
+    /// Add a run of 10pt text with its baseline start at a position.
+    fn text(frame: &mut Frame, (x, y): (f64, f64), text: &str) {
+        let item = TextItem {
+            font: Font::new(Bytes::from_static(FONT), 0).unwrap(),
+            size: Abs::pt(10.0),
+            fill: Color::BLACK.into(),
+            lang: Lang::ENGLISH,
+            text: text.into(),
+            glyphs: vec![],
+        };
+        frame.push(Point::new(Abs::pt(x), Abs::pt(y)), FrameItem::Text(item));
+    }
+
+    /// The indices of the leaves within a unit and its nested units.
+    fn leaves(node: &StructNode) -> Vec<usize> {
+        node.children
+            .iter()
+            .flat_map(|child| match child {
+                StructChild::Node(node) => leaves(node),
+                StructChild::Leaf(leaf) => vec![leaf.index],
+            })
+            .collect()
+    }
+
+    /// The kind of a child if it is a unit.
+    fn kind(child: &StructChild) -> Option<StructKind> {
+        match child {
+            StructChild::Node(node) => Some(node.kind),
+            StructChild::Leaf(_) => None,
+        }
+    }
+}
@@ -1,15 +1,22 @@
+use std::num::NonZeroUsize;
+
+use comemo::Prehashed;
+
 use crate::diag::{At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    elem, Array, CastInfo, Content, FromValue, Func, IntoValue, NativeElement, Reflect,
-    Smart, StyleChain, Value,
+    elem, scope, Array, CastInfo, Content, FromValue, Func, Guard, IntoValue,
+    NativeElement, Reflect, Show, Smart, StyleChain, Synthesize, Value,
 };
+use crate::introspection::{Locatable, Location};
 use crate::layout::{
-    Abs, Align, AlignElem, Axes, Fragment, FrameItem, GridLayouter, Layout, Length,
-    Point, Regions, Rel, Sides, Size, TrackSizings,
+    resolve_grid_dir, Abs, Align, AlignElem, Axes, Cell, CellGrid, Dir, Fragment,
+    FrameItem, GridLayouter, Layout, Length, Point, Regions, Rel, Sides, Size,
+    TrackSizings,
 };
-use crate::model::Figurable;
+use crate::model::{Figurable, FigureCaption, FigureElem, FigureKind, Role};
 use crate::text::{Lang, LocalName, Region};
+use crate::util::NonZeroExt;
 use crate::visualize::{Geometry, Paint, Stroke};
 
 /// A table of items.
@@ -20,8 +27,8 @@ use crate::visualize::{Geometry, Paint, Stroke};
 /// the [grid documentation]($grid) for more information on how to size the
 /// table tracks.
 ///
-/// To give a table a caption and make it [referenceable]($ref), put it into a
-/// [figure]($figure).
+/// To give a table a caption and make it [referenceable]($ref), either put it
+/// into a [figure]($figure) or give it a [caption]($table.caption) directly.
 ///
 /// # Example
 /// ```example
@@ -42,7 +49,34 @@ use crate::visualize::{Geometry, Paint, Stroke};
 ///   [$a$: edge length]
 /// )
 /// ```
-#[elem(Layout, LocalName, Figurable)]
+///
+/// # Headers and footers
+/// The first child of a table can be a [header]($table.header), which is
+/// repeated at the top of each page when the table breaks across pages. A
+/// header may consist of multiple rows, which are repeated together. The last
+/// child can be a [footer]($table.footer), which is repeated at the bottom of
+/// each page.
+///
+/// To mark the repeated headers on follow-up pages as continued, use a show
+/// rule on [cells]($table.cell) that are [continued]($table.cell.continued).
+///
+/// ```example
+/// >>> #set page(height: 120pt)
+/// #show table.cell.where(continued: true): it => {
+///   it.body + [ _(cont.)_]
+/// }
+///
+/// #table(
+///   columns: 3,
+///   table.header(
+///     table.cell(colspan: 3)[*Prices*],
+///     [*Name*], [*Net*], [*Gross*],
+///   ),
+///   ..range(18).map(str),
+///   table.footer(table.cell(colspan: 3)[_All prices in €_]),
+/// )
+/// ```
+#[elem(scope, Locatable, Show, Layout, LocalName, Figurable)]
 pub struct TableElem {
     /// The column sizes. See the [grid documentation]($grid) for more
     /// information on track sizing.
@@ -74,6 +108,23 @@ pub struct TableElem {
     #[borrowed]
     pub row_gutter: TrackSizings,
 
+    /// The direction in which the columns are laid out. See the
+    /// [grid documentation]($grid.dir) for more information.
+    ///
+    /// The column indices passed to [`fill`]($table.fill) and
+    /// [`align`]($table.align) functions always count in this direction.
+    ///
+    /// ```example
+    /// #set text(dir: rtl)
+    /// #table(
+    ///   columns: 2,
+    ///   dir: ltr,
+    ///   fill: (col, _) => if col == 0 { luma(230) },
+    ///   [Name], [Value],
+    /// )
+    /// ```
+    pub dir: Smart<Dir>,
+
     /// How to fill the cells.
     ///
     /// This can be a color or a function that returns a color. The function is
@@ -150,11 +201,79 @@ pub struct TableElem {
     #[default(Sides::splat(Abs::pt(5.0).into()))]
     pub inset: Sides<Option<Rel<Length>>>,
 
+    /// The table's caption.
+    ///
+    /// A table with a caption is automatically wrapped in a
+    /// [figure]($figure) of kind `table`. It is thus numbered with the
+    /// other table figures and appears in their [outline]($outline). A
+    /// [reference]($ref) to a captioned table's label refers to that
+    /// figure.
+    ///
+    /// ```example
+    /// #outline(title: none, target: figure.where(kind: table))
+    ///
+    /// #table(
+    ///   columns: 2,
+    ///   caption: [Planets],
+    ///   [Mercury], [Venus],
+    /// ) <planets>
+    ///
+    /// See @planets.
+    /// ```
+    pub caption: Option<FigureCaption>,
+
     /// The contents of the table cells.
+    ///
+    /// Wrap a child in a [cell]($table.cell) to make it span multiple columns
+    /// or rows.
     #[variadic]
     pub children: Vec<Content>,
 }
 
+#[scope]
+impl TableElem {
+    #[elem]
+    type TableHeader;
+
+    #[elem]
+    type TableFooter;
+
+    #[elem]
+    type TableCell;
+}
+
+impl TableElem {
+    /// Finds the figure that a captioned table at the given location was
+    /// wrapped in.
+    pub fn figure(engine: &Engine, location: Location) -> Option<Prehashed<Content>> {
+        engine
+            .introspector
+            .query(&FigureElem::elem().select().after(location.into(), false))
+            .into_iter()
+            .find(|figure| {
+                figure
+                    .to::<FigureElem>()
+                    .is_some_and(|figure| figure.body().location() == Some(location))
+            })
+    }
+}
+
+impl Show for TableElem {
+    #[tracing::instrument(name = "TableElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let realized = self.clone().pack().guarded(Guard::Base(Self::elem()));
+        let Some(caption) = self.caption(styles) else {
+            return Ok(realized);
+        };
+
+        Ok(FigureElem::new(realized)
+            .with_caption(Some(caption))
+            .with_kind(Smart::Custom(FigureKind::Elem(Self::elem())))
+            .spanned(self.span())
+            .pack())
+    }
+}
+
 impl Layout for TableElem {
     #[tracing::instrument(name = "TableElem::layout", skip_all)]
     fn layout(
@@ -169,39 +288,72 @@ impl Layout for TableElem {
         let rows = self.rows(styles);
         let column_gutter = self.column_gutter(styles);
         let row_gutter = self.row_gutter(styles);
+        let dir = resolve_grid_dir(self.dir(styles), styles, self.span())?;
 
         let tracks = Axes::new(columns.0.as_slice(), rows.0.as_slice());
         let gutter = Axes::new(column_gutter.0.as_slice(), row_gutter.0.as_slice());
-        let cols = tracks.x.len().max(1);
-        let cells: Vec<_> = self
-            .children()
-            .iter()
-            .enumerate()
-            .map(|(i, child)| {
-                let mut child = child.clone().padded(inset);
-
-                let x = i % cols;
-                let y = i / cols;
-                if let Smart::Custom(alignment) = align.resolve(engine, x, y)? {
-                    child = child.styled(AlignElem::set_alignment(alignment));
+        let mut grid = CellGrid::resolve(
+            self.children(),
+            tracks.x.len(),
+            |child| {
+                child
+                    .to::<TableHeader>()
+                    .map(|header| (header.children().as_slice(), header.repeat(styles)))
+            },
+            |child| {
+                child
+                    .to::<TableFooter>()
+                    .map(|footer| (footer.children().as_slice(), footer.repeat(styles)))
+            },
+            |child| match child.to::<TableCell>() {
+                Some(cell) => Cell {
+                    body: child.clone(),
+                    colspan: cell.colspan(styles),
+                    rowspan: cell.rowspan(styles),
+                },
+                None => {
+                    Cell::new(TableCell::new(child.clone()).spanned(child.span()).pack())
                 }
-
-                Ok(child)
-            })
-            .collect::<SourceResult<_>>()?;
+            },
+        )?
+        .with_continued(TableCell::set_continued(true).into());
+
+        let header_rows = grid.header().map_or(0, |header| header.rows);
+        for (x, y, cell) in grid.cells_mut() {
+            let mut body = cell.body.clone().padded(inset);
+            if let Smart::Custom(alignment) = align.resolve(engine, x, y)? {
+                body = body.styled(AlignElem::set_alignment(alignment));
+            }
+            cell.body = body.tagged(Role::TableCell {
+                x,
+                y,
+                colspan: cell.colspan.get(),
+                rowspan: cell.rowspan.get(),
+                header: y < header_rows,
+            });
+        }
 
         let fill = self.fill(styles);
         let stroke = self.stroke(styles).map(Stroke::unwrap_or_default);
 
         // Prepare grid layout by unifying content and gutter tracks.
-        let layouter =
-            GridLayouter::new(tracks, gutter, &cells, regions, styles, self.span());
+        let layouter = GridLayouter::new(
+            tracks,
+            gutter,
+            &grid,
+            regions,
+            styles,
+            dir,
+            self.span(),
+        );
 
         // Measure the columns and layout the grid row-by-row.
         let mut layout = layouter.layout(engine)?;
+        let mut fragment =
+            std::mem::replace(&mut layout.fragment, Fragment::frames(vec![]));
 
         // Add lines and backgrounds.
-        for (frame, rows) in layout.fragment.iter_mut().zip(&layout.rows) {
+        for (frame, rows) in fragment.iter_mut().zip(&layout.rows) {
             if layout.cols.is_empty() || rows.is_empty() {
                 continue;
             }
@@ -211,33 +363,57 @@ impl Layout for TableElem {
                 let thickness = stroke.thickness;
                 let half = thickness / 2.0;
 
-                // Render horizontal lines.
-                for offset in points(rows.iter().map(|piece| piece.height)) {
-                    let target = Point::with_x(frame.width() + thickness);
-                    let hline = Geometry::Line(target).stroked(stroke.clone());
-                    frame.prepend(
-                        Point::new(-half, offset),
-                        FrameItem::Shape(hline, self.span()),
-                    );
+                // Render horizontal lines, leaving out the parts within cells
+                // that span multiple rows.
+                let heights = rows.iter().map(|piece| piece.height);
+                for (i, offset) in points(heights).enumerate() {
+                    let merged = |x: usize| {
+                        i > 0 && i < rows.len() && {
+                            let (above, below) = (rows[i - 1].y, rows[i].y);
+                            layout.parent(x, above) == layout.parent(x, below)
+                        }
+                    };
+
+                    for (start, len) in segments(layout.cols.iter().copied(), merged) {
+                        let target = Point::with_x(len + thickness);
+                        let hline = Geometry::Line(target).stroked(stroke.clone());
+                        frame.prepend(
+                            Point::new(start - half, offset),
+                            FrameItem::Shape(hline, self.span()),
+                        );
+                    }
                 }
 
-                // Render vertical lines.
-                for offset in points(layout.cols.iter().copied()) {
-                    let target = Point::with_y(frame.height() + thickness);
-                    let vline = Geometry::Line(target).stroked(stroke.clone());
-                    frame.prepend(
-                        Point::new(offset, -half),
-                        FrameItem::Shape(vline, self.span()),
-                    );
+                // Render vertical lines, leaving out the parts within cells
+                // that span multiple columns.
+                for (x, offset) in points(layout.cols.iter().copied()).enumerate() {
+                    let merged = |i: usize| {
+                        let y = rows[i].y;
+                        x > 0
+                            && x < layout.cols.len()
+                            && layout.parent(x - 1, y) == layout.parent(x, y)
+                    };
+
+                    let heights = rows.iter().map(|piece| piece.height);
+                    for (start, len) in segments(heights, merged) {
+                        let target = Point::with_y(len + thickness);
+                        let vline = Geometry::Line(target).stroked(stroke.clone());
+                        frame.prepend(
+                            Point::new(offset, start - half),
+                            FrameItem::Shape(vline, self.span()),
+                        );
+                    }
                 }
             }
 
-            // Render cell backgrounds.
+            // Render cell backgrounds. Cells spanning multiple tracks are
+            // filled according to the position they start at.
             let mut dx = Abs::zero();
             for (x, &col) in layout.cols.iter().enumerate() {
                 let mut dy = Abs::zero();
                 for row in rows {
-                    if let Some(fill) = fill.resolve(engine, x, row.y)? {
+                    let (lx, ly) = layout.logical(x, row.y);
+                    if let Some(fill) = fill.resolve(engine, lx, ly)? {
                         let pos = Point::new(dx, dy);
                         let size = Size::new(col, row.height);
                         let rect = Geometry::Rect(size).filled(fill);
@@ -249,8 +425,149 @@ impl Layout for TableElem {
             }
         }
 
-        Ok(layout.fragment)
+        Ok(fragment)
+    }
+}
+
+/// A repeatable table header.
+///
+/// The cells of a header are placed at the top of the table and occupy as many
+/// complete rows as they need. If the header consists of fewer cells than its
+/// rows have columns, its last row is filled up with empty cells. By default,
+/// the header is repeated at the top of each page the table breaks onto. It
+/// can only be placed as the first child of a table.
+///
+/// ```example
+/// >>> #set page(height: 100pt)
+/// #set table(
+///   fill: (_, y) => if y < 2 { luma(230) },
+/// )
+///
+/// #table(
+///   columns: 2,
+///   table.header(
+///     [*Measurement*], [],
+///     [*Time*], [*Value*],
+///   ),
+///   ..range(12).map(str),
+/// )
+/// ```
+#[elem(name = "header", title = "Table Header")]
+pub struct TableHeader {
+    /// Whether the header should be repeated at the top of each page the table
+    /// breaks onto.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the header, in row-major order.
+    #[variadic]
+    pub children: Vec<Content>,
+}
+
+/// A repeatable table footer.
+///
+/// The cells of a footer are placed at the bottom of the table, starting in a
+/// fresh row, and occupy as many complete rows as they need. By default, the
+/// footer is repeated at the bottom of each page the table breaks onto. It can
+/// only be placed as the last child of a table.
+///
+/// ```example
+/// >>> #set page(height: 100pt)
+/// #table(
+///   columns: 2,
+///   table.header([*Day*], [*Visitors*]),
+///   ..range(12).map(str),
+///   table.footer([*Day*], [*Visitors*]),
+/// )
+/// ```
+#[elem(name = "footer", title = "Table Footer")]
+pub struct TableFooter {
+    /// Whether the footer should be repeated at the bottom of each page the
+    /// table breaks onto.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells of the footer, in row-major order.
+    #[variadic]
+    pub children: Vec<Content>,
+}
+
+/// A cell in the table.
+///
+/// Each child of a table is laid out as a cell, so show rules on cells apply
+/// to all of them. Wrapping a child in a cell explicitly allows it to span
+/// multiple columns or rows. A cell is placed at the next position where it
+/// fits without overlapping previously placed cells. The lines between the
+/// tracks it spans are left out.
+///
+/// ```example
+/// #table(
+///   columns: 3,
+///   align: center + horizon,
+///   table.cell(colspan: 2)[*Fruit*], [*Price*],
+///   table.cell(rowspan: 2)[Apple], [Red], [1 €],
+///   [Green], [2 €],
+/// )
+/// ```
+#[elem(name = "cell", title = "Table Cell", Synthesize, Show)]
+pub struct TableCell {
+    /// The cell's body.
+    #[required]
+    pub body: Content,
+
+    /// The number of columns spanned by the cell.
+    #[default(NonZeroUsize::ONE)]
+    pub colspan: NonZeroUsize,
+
+    /// The number of rows spanned by the cell.
+    ///
+    /// If the last spanned row is `{auto}`-sized, it grows such that the cell's
+    /// content fits into the spanned rows. A cell spanning rows that break
+    /// across pages breaks along with them.
+    #[default(NonZeroUsize::ONE)]
+    pub rowspan: NonZeroUsize,
+
+    /// Whether the cell is part of a header that is repeated at the top of a
+    /// page after the first one.
+    ///
+    /// This is set automatically. Show rules can match on it to customize the
+    /// repeated header, for instance to mark it as continued.
+    #[default(false)]
+    pub continued: bool,
+}
+
+impl Synthesize for TableCell {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        self.push_continued(self.continued(styles));
+        Ok(())
+    }
+}
+
+impl Show for TableCell {
+    fn show(&self, _: &mut Engine, _: StyleChain) -> SourceResult<Content> {
+        Ok(self.body().clone())
+    }
+}
+
+/// Split a sequence of extents into runs of consecutive extents for which the
+/// predicate doesn't hold, returning the offset and length of each run.
+fn segments(
+    extents: impl IntoIterator<Item = Abs>,
+    interrupted: impl Fn(usize) -> bool,
+) -> Vec<(Abs, Abs)> {
+    let mut segments = vec![];
+    let mut current: Option<(Abs, Abs)> = None;
+    let mut offset = Abs::zero();
+    for (i, extent) in extents.into_iter().enumerate() {
+        if interrupted(i) {
+            segments.extend(current.take());
+        } else {
+            current.get_or_insert((offset, Abs::zero())).1 += extent;
+        }
+        offset += extent;
     }
+    segments.extend(current);
+    segments
 }
 
 /// Turn an iterator of extents into an iterator of offsets before, in between,
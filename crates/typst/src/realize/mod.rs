@@ -13,8 +13,8 @@ use typed_arena::Arena;
 use crate::diag::{bail, SourceResult};
 use crate::engine::{Engine, Route};
 use crate::foundations::{
-    Content, Finalize, Guard, NativeElement, Recipe, Selector, Show, StyleChain,
-    StyleVecBuilder, Styles, Synthesize,
+    Content, Finalize, Guard, IntoValue, NativeElement, Recipe, Selector, Show,
+    StyleChain, StyleVecBuilder, Styles, Synthesize, Value,
 };
 use crate::introspection::{Locatable, Meta, MetaElem};
 use crate::layout::{
@@ -218,13 +218,29 @@ fn try_apply(
 
             let text = elem.text();
 
-            for m in regex.find_iter(elem.text()) {
+            for caps in regex.captures_iter(elem.text()) {
+                let m = caps.get(0).expect("missing first match");
                 let start = m.start();
                 if cursor < start {
                     result.push(make(&text[cursor..start]));
                 }
 
-                let piece = make(m.as_str()).guarded(guard);
+                // Make the capturing groups available to the recipe.
+                let group = |g: Option<regex::Match>| {
+                    g.map_or(Value::None, |g| g.as_str().into_value())
+                };
+                let mut fresh = elem.clone();
+                fresh.push_text(m.as_str().into());
+                fresh.push_captures(caps.iter().skip(1).map(group).collect());
+                fresh.push_named_captures(
+                    regex
+                        .capture_names()
+                        .flatten()
+                        .map(|name| (name.into(), group(caps.name(name))))
+                        .collect(),
+                );
+
+                let piece = fresh.pack().guarded(guard);
                 let transformed = recipe.apply(engine, piece)?;
                 result.push(transformed);
                 cursor = m.end();
@@ -241,14 +257,36 @@ fn try_apply(
             Ok(Some(Content::sequence(result)))
         }
 
+        Some(Selector::Within { selector, ancestor }) => {
+            if !ancestor.matches(target) {
+                return Ok(None);
+            }
+
+            // Scope the recipe to the ancestor's contents.
+            let scoped = Recipe {
+                span: recipe.span,
+                selector: Some((**selector).clone()),
+                transform: recipe.transform.clone(),
+            };
+
+            Ok(Some(target.clone().guarded(guard).styled(scoped)))
+        }
+
         // Not supported here.
         Some(
             Selector::Or(_)
             | Selector::And(_)
+            | Selector::Not(_)
+            | Selector::Nth { .. }
             | Selector::Location(_)
+            | Selector::Compare { .. }
             | Selector::Can(_)
+            | Selector::LabelRegex(_)
+            | Selector::OnPage(_)
+            | Selector::InRegion { .. }
             | Selector::Before { .. }
-            | Selector::After { .. },
+            | Selector::After { .. }
+            | Selector::Between { .. },
         ) => Ok(None),
 
         None => Ok(None),
@@ -0,0 +1,635 @@
+//! Conversion of color glyphs.
+
+use std::fmt::Write;
+
+use base64::Engine;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use kurbo::{Affine, BezPath, Point, Rect, Shape, Vec2};
+use ttf_parser::colr::{ClipBox, ColorStop, CompositeMode, GradientExtend, Paint};
+use ttf_parser::{GlyphId, RgbaColor};
+
+use crate::text::Font;
+use crate::visualize::{Color, Image, VectorFormat};
+
+/// The number of pixels per em at which sweep gradients are rasterized.
+const SWEEP_PIXELS_PER_EM: f64 = 128.0;
+
+/// Convert a glyph from the font's `COLR` table into an SVG image.
+///
+/// Returns the image along with the area it spans in font units, with the
+/// y-axis pointing up like in the font. The parts of the glyph that take on
+/// the color of the text are drawn in the given foreground color.
+///
+/// Sweep gradients have no equivalent in SVG, so they are rasterized.
+#[comemo::memoize]
+pub fn colr_glyph_image(
+    font: &Font,
+    id: GlyphId,
+    foreground: Color,
+) -> Option<(Image, ttf_parser::Rect)> {
+    let ttf = font.ttf();
+    if !ttf.is_color_glyph(id) {
+        return None;
+    }
+
+    let [r, g, b, a] = foreground.to_vec4_u8();
+    let mut painter = SvgPainter::new(font);
+    ttf.paint_color_glyph(id, 0, RgbaColor::new(r, g, b, a), &mut painter)?;
+
+    let bounds = painter.bounds?.expand();
+    if bounds.area() <= 0.0 {
+        return None;
+    }
+
+    // Font units point up while SVG units point down, so we flip the glyph.
+    let mut svg = String::new();
+    write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        bounds.x0,
+        -bounds.y1,
+        bounds.width(),
+        bounds.height(),
+    )
+    .ok()?;
+    svg.push_str(r#"<g transform="matrix(1 0 0 -1 0 0)">"#);
+    svg.push_str(&painter.defs);
+    svg.push_str(&painter.body);
+    svg.push_str("</g></svg>");
+
+    let image =
+        Image::new(svg.into_bytes().into(), VectorFormat::Svg.into(), None).ok()?;
+    let rect = ttf_parser::Rect {
+        x_min: bounds.x0 as i16,
+        y_min: bounds.y0 as i16,
+        x_max: bounds.x1 as i16,
+        y_max: bounds.y1 as i16,
+    };
+
+    Some((image, rect))
+}
+
+/// Writes the layers of a color glyph into SVG markup.
+///
+/// All shapes are written in glyph space, so only gradients and rasterized
+/// paints need to know about the transform they were defined in.
+struct SvgPainter<'a> {
+    font: &'a Font,
+    /// Definitions of gradients and clip paths.
+    defs: String,
+    /// The drawn shapes.
+    body: String,
+    /// The outline that the next paint fills, in glyph space.
+    outline: BezPath,
+    /// The transform from paint space to glyph space.
+    transform: Affine,
+    /// The transforms to restore when transforms are popped.
+    transforms: Vec<Affine>,
+    /// The area covered by all outlines so far.
+    bounds: Option<Rect>,
+    /// The number of definitions so far, used to generate identifiers.
+    ids: usize,
+}
+
+impl<'a> SvgPainter<'a> {
+    fn new(font: &'a Font) -> Self {
+        Self {
+            font,
+            defs: String::new(),
+            body: String::new(),
+            outline: BezPath::new(),
+            transform: Affine::IDENTITY,
+            transforms: vec![],
+            bounds: None,
+            ids: 0,
+        }
+    }
+
+    /// Apply a transform to the following outlines and paints.
+    fn push_affine(&mut self, transform: Affine) {
+        self.transforms.push(self.transform);
+        self.transform *= transform;
+    }
+
+    /// Generate a new identifier for a definition.
+    fn id(&mut self, prefix: &str) -> String {
+        self.ids += 1;
+        format!("{prefix}{}", self.ids)
+    }
+
+    /// Fill the current outline with a solid color.
+    fn paint_solid(&mut self, color: RgbaColor) {
+        let d = self.outline.to_svg();
+        write!(self.body, r#"<path d="{d}" {}/>"#, Fill(color)).ok();
+    }
+
+    /// Fill the current outline with a linear gradient.
+    fn paint_linear(&mut self, gradient: ttf_parser::colr::LinearGradient) {
+        let coords = self.font.ttf().variation_coordinates();
+        let stops = sorted_stops(gradient.stops(0, coords));
+        let Some((min, max)) = stop_range(&stops) else {
+            return self.paint_solid(last_color(&stops));
+        };
+
+        // The gradient runs from p0 to p1, but its color lines are parallel to
+        // the line from p0 to p2, so we project p1 onto the normal of that
+        // line.
+        let p0 = point(gradient.x0, gradient.y0);
+        let p1 = point(gradient.x1, gradient.y1);
+        let p2 = point(gradient.x2, gradient.y2);
+        let dir = p2 - p0;
+        let normal = Vec2::new(-dir.y, dir.x);
+        let end = if normal.hypot2() > 0.0 {
+            p0 + normal * ((p1 - p0).dot(normal) / normal.hypot2())
+        } else {
+            p1
+        };
+
+        // SVG applies the extend mode to the gradient's ends instead of to its
+        // first and last stop, so we move the ends to the stops.
+        let start = p0.lerp(end, min);
+        let end = p0.lerp(end, max);
+
+        let id = self.id("lg");
+        write!(
+            self.defs,
+            r#"<linearGradient id="{id}" x1="{}" y1="{}" x2="{}" y2="{}" {}>"#,
+            start.x,
+            start.y,
+            end.x,
+            end.y,
+            GradientAttrs(gradient.extend, self.transform),
+        )
+        .ok();
+        write_stops(&mut self.defs, &stops, |offset| (offset - min) / (max - min));
+        self.defs.push_str("</linearGradient>");
+        self.paint_url(&id);
+    }
+
+    /// Fill the current outline with a radial gradient.
+    fn paint_radial(&mut self, gradient: ttf_parser::colr::RadialGradient) {
+        let coords = self.font.ttf().variation_coordinates();
+        let stops = sorted_stops(gradient.stops(0, coords));
+        let Some((min, max)) = stop_range(&stops) else {
+            return self.paint_solid(last_color(&stops));
+        };
+
+        // Move the circles to the first and last stop, like for linear
+        // gradients.
+        let c0 = point(gradient.x0, gradient.y0);
+        let c1 = point(gradient.x1, gradient.y1);
+        let (r0, r1) = (f64::from(gradient.r0), f64::from(gradient.r1));
+        let circle = |t: f64| (c0.lerp(c1, t), (r0 + t * (r1 - r0)).max(0.0));
+        let (mut start, mut end) = (circle(min), circle(max));
+
+        // SVG gradients always grow from the focal point to the circle.
+        let reversed = start.1 > end.1;
+        if reversed {
+            std::mem::swap(&mut start, &mut end);
+        }
+        if end.1 <= 0.0 {
+            return self.paint_solid(last_color(&stops));
+        }
+
+        // SVG has no radius for the focal point, so we instead move the stops
+        // outwards by the radius of the start circle. This is exact for
+        // concentric circles.
+        let offset = |offset: f64| {
+            let mut t = (offset - min) / (max - min);
+            if reversed {
+                t = 1.0 - t;
+            }
+            (start.1 + t * (end.1 - start.1)) / end.1
+        };
+
+        let id = self.id("rg");
+        write!(
+            self.defs,
+            r#"<radialGradient id="{id}" cx="{}" cy="{}" r="{}" fx="{}" fy="{}" {}>"#,
+            end.0.x,
+            end.0.y,
+            end.1,
+            start.0.x,
+            start.0.y,
+            GradientAttrs(gradient.extend, self.transform),
+        )
+        .ok();
+        if reversed {
+            let stops: Vec<_> = stops.iter().rev().copied().collect();
+            write_stops(&mut self.defs, &stops, offset);
+        } else {
+            write_stops(&mut self.defs, &stops, offset);
+        }
+        self.defs.push_str("</radialGradient>");
+        self.paint_url(&id);
+    }
+
+    /// Fill the current outline with a rasterized sweep gradient.
+    fn paint_sweep(&mut self, gradient: ttf_parser::colr::SweepGradient) {
+        let coords = self.font.ttf().variation_coordinates();
+        let stops = sorted_stops(gradient.stops(0, coords));
+        let Some((min, max)) = stop_range(&stops) else {
+            return self.paint_solid(last_color(&stops));
+        };
+
+        let bbox = self.outline.bounding_box();
+        let inverse = self.transform.inverse();
+        if bbox.area() <= 0.0 || !inverse.determinant().is_finite() {
+            return;
+        }
+
+        // Angles are given in half turns, starting at a half turn.
+        let center = point(gradient.center_x, gradient.center_y);
+        let start = (f64::from(gradient.start_angle) + 1.0) * 180.0;
+        let end = (f64::from(gradient.end_angle) + 1.0) * 180.0;
+        let extent = end - start;
+
+        let scale = SWEEP_PIXELS_PER_EM / self.font.units_per_em();
+        let w = (bbox.width() * scale).ceil().clamp(1.0, 1024.0) as u32;
+        let h = (bbox.height() * scale).ceil().clamp(1.0, 1024.0) as u32;
+
+        // The rows of the image run upwards like the y-axis of the glyph.
+        let mut pixels = Vec::with_capacity((4 * w * h) as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let point = Point::new(
+                    bbox.x0 + (x as f64 + 0.5) / w as f64 * bbox.width(),
+                    bbox.y0 + (y as f64 + 0.5) / h as f64 * bbox.height(),
+                );
+                let v = inverse * point - center;
+                let angle = v.y.atan2(v.x).to_degrees().rem_euclid(360.0);
+                // Without an extent, the gradient is split at the start angle.
+                let t = if extent != 0.0 {
+                    (angle - start) / extent
+                } else if angle < start {
+                    f64::NEG_INFINITY
+                } else {
+                    f64::INFINITY
+                };
+                let t = extend(gradient.extend, t, min, max);
+                pixels.extend(sample(&stops, t));
+            }
+        }
+
+        let mut png = vec![];
+        let encoder = PngEncoder::new(&mut png);
+        if encoder.write_image(&pixels, w, h, ColorType::Rgba8).is_err() {
+            return;
+        }
+
+        let id = self.id("cp");
+        let d = self.outline.to_svg();
+        write!(self.defs, r#"<clipPath id="{id}"><path d="{d}"/></clipPath>"#).ok();
+        write!(
+            self.body,
+            r#"<image clip-path="url(#{id})" x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+            bbox.x0,
+            bbox.y0,
+            bbox.width(),
+            bbox.height(),
+            base64::engine::general_purpose::STANDARD.encode(png),
+        )
+        .ok();
+    }
+
+    /// Fill the current outline with a gradient definition.
+    fn paint_url(&mut self, id: &str) {
+        let d = self.outline.to_svg();
+        write!(self.body, r#"<path d="{d}" fill="url(#{id})"/>"#).ok();
+    }
+
+    /// Clip the following paints to a path in glyph space.
+    fn push_clip_path(&mut self, path: &BezPath) {
+        let id = self.id("cp");
+        let d = path.to_svg();
+        write!(self.defs, r#"<clipPath id="{id}"><path d="{d}"/></clipPath>"#).ok();
+        write!(self.body, r#"<g clip-path="url(#{id})">"#).ok();
+    }
+}
+
+impl<'a> ttf_parser::colr::Painter<'a> for SvgPainter<'_> {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        let mut builder = PathBuilder(BezPath::new());
+        self.outline = match self.font.ttf().outline_glyph(glyph_id, &mut builder) {
+            Some(_) => self.transform * builder.0,
+            None => BezPath::new(),
+        };
+        if !self.outline.is_empty() {
+            let bbox = self.outline.bounding_box();
+            self.bounds = Some(self.bounds.map_or(bbox, |bounds| bounds.union(bbox)));
+        }
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        match paint {
+            Paint::Solid(color) => self.paint_solid(color),
+            Paint::LinearGradient(gradient) => self.paint_linear(gradient),
+            Paint::RadialGradient(gradient) => self.paint_radial(gradient),
+            Paint::SweepGradient(gradient) => self.paint_sweep(gradient),
+        }
+    }
+
+    fn push_clip(&mut self) {
+        let outline = std::mem::take(&mut self.outline);
+        self.push_clip_path(&outline);
+        self.outline = outline;
+    }
+
+    fn push_clip_box(&mut self, clipbox: ClipBox) {
+        let rect = Rect::new(
+            clipbox.x_min.into(),
+            clipbox.y_min.into(),
+            clipbox.x_max.into(),
+            clipbox.y_max.into(),
+        );
+        self.push_clip_path(&(self.transform * rect.to_path(0.1)));
+    }
+
+    fn pop_clip(&mut self) {
+        self.body.push_str("</g>");
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        // The Porter-Duff modes other than source-over can't be expressed in
+        // SVG.
+        let mode = match mode {
+            CompositeMode::Screen => "screen",
+            CompositeMode::Overlay => "overlay",
+            CompositeMode::Darken => "darken",
+            CompositeMode::Lighten => "lighten",
+            CompositeMode::ColorDodge => "color-dodge",
+            CompositeMode::ColorBurn => "color-burn",
+            CompositeMode::HardLight => "hard-light",
+            CompositeMode::SoftLight => "soft-light",
+            CompositeMode::Difference => "difference",
+            CompositeMode::Exclusion => "exclusion",
+            CompositeMode::Multiply => "multiply",
+            CompositeMode::Hue => "hue",
+            CompositeMode::Saturation => "saturation",
+            CompositeMode::Color => "color",
+            CompositeMode::Luminosity => "luminosity",
+            _ => "normal",
+        };
+        write!(self.body, r#"<g style="mix-blend-mode:{mode};isolation:isolate">"#).ok();
+    }
+
+    fn pop_layer(&mut self) {
+        self.body.push_str("</g>");
+    }
+
+    fn push_translate(&mut self, tx: f32, ty: f32) {
+        self.push_affine(Affine::translate((tx.into(), ty.into())));
+    }
+
+    fn push_scale(&mut self, sx: f32, sy: f32) {
+        self.push_affine(Affine::scale_non_uniform(sx.into(), sy.into()));
+    }
+
+    fn push_rotate(&mut self, angle: f32) {
+        // Angles are given in half turns.
+        self.push_affine(Affine::rotate(f64::from(angle) * std::f64::consts::PI));
+    }
+
+    fn push_skew(&mut self, skew_x: f32, skew_y: f32) {
+        // Angles are given in half turns and skews along the x-axis are
+        // clockwise.
+        let x = (-f64::from(skew_x) * std::f64::consts::PI).tan();
+        let y = (f64::from(skew_y) * std::f64::consts::PI).tan();
+        self.push_affine(Affine::new([1.0, y, x, 1.0, 0.0, 0.0]));
+    }
+
+    fn push_transform(&mut self, ts: ttf_parser::Transform) {
+        self.push_affine(Affine::new(
+            [ts.a, ts.b, ts.c, ts.d, ts.e, ts.f].map(f64::from),
+        ));
+    }
+
+    fn pop_transform(&mut self) {
+        if let Some(transform) = self.transforms.pop() {
+            self.transform = transform;
+        }
+    }
+}
+
+/// Builds a kurbo path from a glyph outline.
+struct PathBuilder(BezPath);
+
+impl ttf_parser::OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.quad_to(point(x1, y1), point(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.0.curve_to(point(x1, y1), point(x2, y2), point(x, y));
+    }
+
+    fn close(&mut self) {
+        self.0.close_path();
+    }
+}
+
+/// Convert a point in font units.
+fn point(x: f32, y: f32) -> Point {
+    Point::new(x.into(), y.into())
+}
+
+/// Formats the fill attributes for a solid color.
+struct Fill(RgbaColor);
+
+impl std::fmt::Display for Fill {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let RgbaColor { red, green, blue, alpha } = self.0;
+        write!(f, r#"fill="rgb({red},{green},{blue})""#)?;
+        if alpha != 255 {
+            write!(f, r#" fill-opacity="{}""#, alpha as f64 / 255.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the attributes shared by linear and radial gradients.
+struct GradientAttrs(GradientExtend, Affine);
+
+impl std::fmt::Display for GradientAttrs {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let spread = match self.0 {
+            GradientExtend::Pad => "pad",
+            GradientExtend::Repeat => "repeat",
+            GradientExtend::Reflect => "reflect",
+        };
+        let [a, b, c, d, e, g] = self.1.as_coeffs();
+        write!(
+            f,
+            r#"gradientUnits="userSpaceOnUse" spreadMethod="{spread}" gradientTransform="matrix({a} {b} {c} {d} {e} {g})""#
+        )
+    }
+}
+
+/// Collect the stops of a gradient in the order of their offsets.
+fn sorted_stops(stops: impl Iterator<Item = ColorStop>) -> Vec<ColorStop> {
+    let mut stops: Vec<_> = stops.collect();
+    stops.sort_by(|a, b| a.stop_offset.total_cmp(&b.stop_offset));
+    stops
+}
+
+/// The offsets of the first and last stop, if they differ.
+fn stop_range(stops: &[ColorStop]) -> Option<(f64, f64)> {
+    let min = f64::from(stops.first()?.stop_offset);
+    let max = f64::from(stops.last()?.stop_offset);
+    (min < max).then_some((min, max))
+}
+
+/// The color of the last stop, which is what a gradient without any extent
+/// shows.
+fn last_color(stops: &[ColorStop]) -> RgbaColor {
+    stops.last().map_or(RgbaColor::new(0, 0, 0, 0), |stop| stop.color)
+}
+
+/// Write gradient stops with their offsets mapped into the range from zero to
+/// one.
+fn write_stops(svg: &mut String, stops: &[ColorStop], offset: impl Fn(f64) -> f64) {
+    for stop in stops {
+        let RgbaColor { red, green, blue, alpha } = stop.color;
+        write!(
+            svg,
+            r#"<stop offset="{}" stop-color="rgb({red},{green},{blue})" stop-opacity="{}"/>"#,
+            offset(stop.stop_offset.into()).clamp(0.0, 1.0),
+            alpha as f64 / 255.0,
+        )
+        .ok();
+    }
+}
+
+/// Apply a gradient's extend mode to a position outside of its stops.
+fn extend(mode: GradientExtend, t: f64, min: f64, max: f64) -> f64 {
+    let len = max - min;
+    let u = (t - min) / len;
+    let u = match mode {
+        GradientExtend::Pad => u.clamp(0.0, 1.0),
+        GradientExtend::Repeat => u.rem_euclid(1.0),
+        GradientExtend::Reflect => 1.0 - ((u.rem_euclid(2.0)) - 1.0).abs(),
+    };
+    min + u * len
+}
+
+/// The color of a gradient at a position between its first and last stop.
+fn sample(stops: &[ColorStop], t: f64) -> [u8; 4] {
+    let color = |stop: &ColorStop| {
+        let c = stop.color;
+        [c.red, c.green, c.blue, c.alpha].map(f64::from)
+    };
+
+    let i = stops.partition_point(|stop| f64::from(stop.stop_offset) <= t);
+    let rgba = match (i.checked_sub(1).map(|j| &stops[j]), stops.get(i)) {
+        (Some(a), Some(b)) => {
+            let (a_t, b_t) = (f64::from(a.stop_offset), f64::from(b.stop_offset));
+            let k = if b_t > a_t { (t - a_t) / (b_t - a_t) } else { 0.0 };
+            let (a, b) = (color(a), color(b));
+            [0, 1, 2, 3].map(|j| a[j] + k * (b[j] - a[j]))
+        }
+        (Some(stop), None) | (None, Some(stop)) => color(stop),
+        (None, None) => [0.0; 4],
+    };
+
+    rgba.map(|v| v.round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::foundations::Bytes;
+
+    const COLR: &[u8] =
+        include_bytes!("../../../../../assets/fonts/COLRv1StaticTestGlyphs-Regular.ttf");
+
+    /// Convert a glyph of the COLRv1 test font and return its SVG markup.
+    fn svg(id: u16, foreground: Color) -> Option<String> {
+        let font = Font::new(Bytes::from_static(COLR), 0).unwrap();
+        let (image, _) = colr_glyph_image(&font, GlyphId(id), foreground)?;
+        Some(std::str::from_utf8(image.data()).unwrap().into())
+    }
+
+    fn stop(offset: f32, red: u8) -> ColorStop {
+        ColorStop {
+            stop_offset: offset,
+            color: RgbaColor::new(red, 0, 0, 255),
+        }
+    }
+
+    #[test]
+    fn test_colr_glyph_image_without_layers() {
+        assert_eq!(svg(1, Color::BLACK), None);
+    }
+
+    #[test]
+    fn test_colr_glyph_image_gradients() {
+        let linear = svg(9, Color::BLACK).unwrap();
+        assert!(linear.contains("<linearGradient"));
+        assert!(linear.contains(r#"spreadMethod="repeat""#));
+
+        let radial = svg(93, Color::BLACK).unwrap();
+        assert!(radial.contains("<radialGradient"));
+
+        // Sweep gradients are embedded as images.
+        let sweep = svg(13, Color::BLACK).unwrap();
+        assert!(!sweep.contains("Gradient"));
+        assert!(sweep.contains(r#"href="data:image/png;base64,"#));
+    }
+
+    #[test]
+    fn test_colr_glyph_image_foreground() {
+        let red = svg(154, Color::from_u8(255, 0, 0, 255)).unwrap();
+        let blue = svg(154, Color::from_u8(0, 0, 255, 128)).unwrap();
+        assert!(red.contains(r#"fill="rgb(255,0,0)""#));
+        assert!(blue.contains(r#"fill="rgb(0,0,255)" fill-opacity="0.5"#));
+    }
+
+    #[test]
+    fn test_colr_glyph_image_bounds() {
+        let font = Font::new(Bytes::from_static(COLR), 0).unwrap();
+        let (_, rect) = colr_glyph_image(&font, GlyphId(9), Color::BLACK).unwrap();
+        assert!(rect.x_min <= 100 && rect.y_min <= 250);
+        assert!(rect.x_max >= 900 && rect.y_max >= 950);
+    }
+
+    #[test]
+    fn test_stop_range() {
+        assert_eq!(stop_range(&[]), None);
+        assert_eq!(stop_range(&[stop(0.5, 0)]), None);
+        assert_eq!(stop_range(&[stop(0.25, 0), stop(0.75, 0)]), Some((0.25, 0.75)));
+        assert_eq!(last_color(&[]), RgbaColor::new(0, 0, 0, 0));
+        assert_eq!(last_color(&[stop(0.0, 10), stop(1.0, 20)]).red, 20);
+    }
+
+    #[test]
+    fn test_extend() {
+        assert_eq!(extend(GradientExtend::Pad, -1.0, 0.0, 1.0), 0.0);
+        assert_eq!(extend(GradientExtend::Pad, 0.5, 0.0, 1.0), 0.5);
+        assert_eq!(extend(GradientExtend::Pad, 3.0, 1.0, 2.0), 2.0);
+        assert_eq!(extend(GradientExtend::Repeat, 1.25, 0.0, 1.0), 0.25);
+        assert_eq!(extend(GradientExtend::Repeat, -0.25, 0.0, 1.0), 0.75);
+        assert_eq!(extend(GradientExtend::Reflect, 1.25, 0.0, 1.0), 0.75);
+        assert_eq!(extend(GradientExtend::Reflect, 2.25, 0.0, 1.0), 0.25);
+        assert_eq!(extend(GradientExtend::Reflect, -0.25, 0.0, 1.0), 0.25);
+    }
+
+    #[test]
+    fn test_sample() {
+        let stops = [stop(0.0, 0), stop(0.5, 100), stop(1.0, 200)];
+        assert_eq!(sample(&stops, 0.0), [0, 0, 0, 255]);
+        assert_eq!(sample(&stops, 0.25), [50, 0, 0, 255]);
+        assert_eq!(sample(&stops, 0.75), [150, 0, 0, 255]);
+        assert_eq!(sample(&stops, 1.0), [200, 0, 0, 255]);
+        assert_eq!(sample(&stops, 2.0), [200, 0, 0, 255]);
+        assert_eq!(sample(&[], 0.5), [0; 4]);
+    }
+}
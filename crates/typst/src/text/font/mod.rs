@@ -1,16 +1,18 @@
 //! Font handling.
 
 mod book;
+mod color;
 mod variant;
 
 pub use self::book::{Coverage, FontBook, FontFlags, FontInfo};
+pub use self::color::colr_glyph_image;
 pub use self::variant::{FontStretch, FontStyle, FontVariant, FontWeight};
 
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use ttf_parser::GlyphId;
+use ttf_parser::{GlyphId, Tag};
 
 use self::book::find_name;
 use crate::foundations::{Bytes, Cast};
@@ -38,6 +40,9 @@ struct Repr {
     ttf: ttf_parser::Face<'static>,
     /// The underlying rustybuzz face.
     rusty: rustybuzz::Face<'static>,
+    /// The coordinates on the font's variation axes, if this is an instance
+    /// of a variable font.
+    variations: Vec<(Tag, f32)>,
 }
 
 impl Font {
@@ -57,7 +62,66 @@ impl Font {
         let metrics = FontMetrics::from_ttf(&ttf);
         let info = FontInfo::from_ttf(&ttf)?;
 
-        Some(Self(Arc::new(Repr { data, index, info, metrics, ttf, rusty })))
+        Some(Self(Arc::new(Repr {
+            data,
+            index,
+            info,
+            metrics,
+            ttf,
+            rusty,
+            variations: vec![],
+        })))
+    }
+
+    /// Create an instance of this variable font with the given coordinates on
+    /// its variation axes.
+    ///
+    /// Axes that the font doesn't have are ignored and if an axis is given
+    /// multiple times, the first value is used. If no axis applies, the font
+    /// is returned unchanged.
+    pub fn with_variations(&self, axes: &[(Tag, f64)]) -> Self {
+        let mut variations: Vec<(Tag, f32)> = vec![];
+        for &(tag, value) in axes {
+            let exists = self.0.ttf.variation_axes().into_iter().any(|a| a.tag == tag);
+            if exists && variations.iter().all(|&(t, _)| t != tag) {
+                variations.push((tag, value as f32));
+            }
+        }
+
+        if variations.is_empty() || variations == self.0.variations {
+            return self.clone();
+        }
+
+        // The faces still point into the same, shared data.
+        let mut ttf = self.0.ttf.clone();
+        let mut rusty = self.0.rusty.clone();
+        for &(tag, value) in &variations {
+            ttf.set_variation(tag, value);
+        }
+        rusty.set_variations(
+            &variations
+                .iter()
+                .map(|&(tag, value)| rustybuzz::Variation { tag, value })
+                .collect::<Vec<_>>(),
+        );
+
+        Self(Arc::new(Repr {
+            data: self.0.data.clone(),
+            index: self.0.index,
+            info: self.0.info.clone(),
+            metrics: FontMetrics::from_ttf(&ttf),
+            ttf,
+            rusty,
+            variations,
+        }))
+    }
+
+    /// The coordinates on the font's variation axes.
+    ///
+    /// This is empty unless the font is an instance of a variable font
+    /// created with [`with_variations`](Self::with_variations).
+    pub fn variations(&self) -> &[(Tag, f32)] {
+        &self.0.variations
     }
 
     /// Parse all fonts in the given data.
@@ -128,6 +192,10 @@ impl Hash for Font {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.data.hash(state);
         self.0.index.hash(state);
+        for (tag, value) in &self.0.variations {
+            tag.hash(state);
+            value.to_bits().hash(state);
+        }
     }
 }
 
@@ -141,7 +209,9 @@ impl Eq for Font {}
 
 impl PartialEq for Font {
     fn eq(&self, other: &Self) -> bool {
-        self.0.data == other.0.data && self.0.index == other.0.index
+        self.0.data == other.0.data
+            && self.0.index == other.0.index
+            && self.0.variations == other.0.variations
     }
 }
 
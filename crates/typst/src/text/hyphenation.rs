@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use ecow::EcoString;
+
+use crate::diag::{bail, StrResult};
+use crate::foundations::{cast, Str};
+use crate::util::hash128;
+
+/// The minimum number of characters before a hyphen.
+const LEFT_MIN: usize = 2;
+
+/// The minimum number of characters after a hyphen.
+const RIGHT_MIN: usize = 3;
+
+/// Hyphenation patterns and exceptions in TeX format, loaded at runtime.
+///
+/// The patterns are applied with Liang's algorithm, just like the patterns
+/// that are bundled with Typst.
+#[derive(Clone, Hash, PartialEq)]
+pub struct Hyphenation(Arc<Repr>);
+
+/// The internal representation.
+struct Repr {
+    /// The text the patterns were parsed from.
+    source: EcoString,
+    /// A hash of the source.
+    hash: u128,
+    /// Maps from the letters of a pattern to its priorities between them.
+    patterns: HashMap<EcoString, Vec<u8>>,
+    /// The length of the longest pattern in characters.
+    longest: usize,
+    /// Maps from lowercase words to the character positions at which they
+    /// can be hyphenated.
+    exceptions: HashMap<EcoString, Vec<usize>>,
+}
+
+impl Hyphenation {
+    /// Parse hyphenation patterns.
+    ///
+    /// Patterns and exceptions are read from `\patterns{..}` and
+    /// `\hyphenation{..}` blocks. Without any such block, the whole text is
+    /// read as a whitespace-separated list, in which entries with hyphens are
+    /// exceptions and all others are patterns. Everything after a `%` on a
+    /// line is a comment.
+    #[comemo::memoize]
+    pub fn parse(source: &str) -> StrResult<Self> {
+        let text: Vec<&str> = source
+            .lines()
+            .map(|line| line.split('%').next().unwrap_or_default())
+            .collect();
+        let text = text.join("\n");
+
+        let mut patterns = HashMap::new();
+        let mut exceptions = HashMap::new();
+        let mut blocks = false;
+        let mut rest = text.as_str();
+
+        while let Some(start) = rest.find('\\') {
+            let after = &rest[start + 1..];
+            let len =
+                after.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(after.len());
+            let (name, tail) = after.split_at(len);
+            rest = tail;
+
+            if !matches!(name, "patterns" | "hyphenation") {
+                continue;
+            }
+
+            let Some(body) = tail.trim_start().strip_prefix('{') else {
+                bail!("expected opening brace after `\\{name}`");
+            };
+            let Some(end) = body.find('}') else {
+                bail!("unclosed `\\{name}` block");
+            };
+
+            for entry in body[..end].split_whitespace() {
+                if name == "patterns" {
+                    parse_pattern(entry, &mut patterns)?;
+                } else {
+                    parse_exception(entry, &mut exceptions);
+                }
+            }
+
+            blocks = true;
+            rest = &body[end + 1..];
+        }
+
+        if !blocks {
+            for entry in text.split_whitespace() {
+                if entry.contains('-') {
+                    parse_exception(entry, &mut exceptions);
+                } else {
+                    parse_pattern(entry, &mut patterns)?;
+                }
+            }
+        }
+
+        if patterns.is_empty() && exceptions.is_empty() {
+            bail!("hyphenation patterns contain no patterns or exceptions");
+        }
+
+        let longest = patterns.keys().map(|key| key.chars().count()).max();
+        Ok(Self(Arc::new(Repr {
+            source: source.into(),
+            hash: hash128(source),
+            patterns,
+            longest: longest.unwrap_or_default(),
+            exceptions,
+        })))
+    }
+
+    /// The text the patterns were parsed from.
+    pub fn source(&self) -> &EcoString {
+        &self.0.source
+    }
+
+    /// The byte offsets at which the word can be hyphenated.
+    ///
+    /// Returns `None` if the word is not an exception and there are no
+    /// patterns, so that the bundled patterns can be used instead.
+    pub fn hyphenate(&self, word: &str) -> Option<Vec<usize>> {
+        let lower: Vec<char> = word.chars().map(lowercase).collect();
+        let key: EcoString = lower.iter().copied().collect();
+        if let Some(positions) = self.0.exceptions.get(&key) {
+            return Some(byte_offsets(word, positions.iter().copied()));
+        }
+
+        if self.0.patterns.is_empty() {
+            return None;
+        }
+
+        // Apply all patterns that match anywhere in the word, delimited with
+        // dots, and keep the maximum priority between each pair of letters.
+        let chars: Vec<char> = std::iter::once('.')
+            .chain(lower.iter().copied())
+            .chain(['.'])
+            .collect();
+        let mut levels = vec![0; chars.len() + 1];
+        let mut key = String::new();
+        for i in 0..chars.len() {
+            key.clear();
+            for &c in chars[i..].iter().take(self.0.longest) {
+                key.push(c);
+                if let Some(priorities) = self.0.patterns.get(key.as_str()) {
+                    for (k, &priority) in priorities.iter().enumerate() {
+                        levels[i + k] = levels[i + k].max(priority);
+                    }
+                }
+            }
+        }
+
+        // The level before the word's p-th letter is at p + 1 due to the
+        // leading dot. Odd levels permit a hyphen.
+        let n = lower.len();
+        let positions =
+            (LEFT_MIN..=n.saturating_sub(RIGHT_MIN)).filter(|&p| levels[p + 1] % 2 == 1);
+        Some(byte_offsets(word, positions))
+    }
+}
+
+/// Convert character positions in a word into byte offsets.
+fn byte_offsets(word: &str, positions: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let starts: Vec<usize> = word.char_indices().map(|(i, _)| i).collect();
+    positions.into_iter().filter_map(|p| starts.get(p).copied()).collect()
+}
+
+/// Parse a single pattern like `.hy1p` into its letters and priorities.
+fn parse_pattern(
+    entry: &str,
+    patterns: &mut HashMap<EcoString, Vec<u8>>,
+) -> StrResult<()> {
+    let mut letters = EcoString::new();
+    let mut priorities = vec![0];
+    for c in entry.chars() {
+        match c.to_digit(10) {
+            Some(digit) => *priorities.last_mut().unwrap() = digit as u8,
+            None => {
+                letters.push(lowercase(c));
+                priorities.push(0);
+            }
+        }
+    }
+
+    if letters.is_empty() {
+        bail!("invalid hyphenation pattern `{entry}`");
+    }
+
+    patterns.insert(letters, priorities);
+    Ok(())
+}
+
+/// Parse a single exception like `ta-ble` into its word and hyphen positions.
+fn parse_exception(entry: &str, exceptions: &mut HashMap<EcoString, Vec<usize>>) {
+    let mut word = EcoString::new();
+    let mut positions = vec![];
+    let mut len = 0;
+    for c in entry.chars() {
+        if c == '-' {
+            positions.push(len);
+        } else {
+            word.push(lowercase(c));
+            len += 1;
+        }
+    }
+
+    positions.retain(|&p| p > 0 && p < len);
+    exceptions.insert(word, positions);
+}
+
+/// Lowercase a character, keeping it a single character.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+impl Debug for Hyphenation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.pad("Hyphenation(..)")
+    }
+}
+
+impl Hash for Repr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The patterns are fully defined by their source.
+        state.write_u128(self.hash);
+    }
+}
+
+impl PartialEq for Repr {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+cast! {
+    Hyphenation,
+    self => self.source().clone().into_value(),
+    v: Str => Self::parse(&v)?,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hyphenate(patterns: &str, word: &str) -> Vec<String> {
+        let hyphenation = Hyphenation::parse(patterns).unwrap();
+        let offsets = hyphenation.hyphenate(word).unwrap();
+        let mut syllables = vec![];
+        let mut last = 0;
+        for offset in offsets.into_iter().chain([word.len()]) {
+            syllables.push(word[last..offset].to_string());
+            last = offset;
+        }
+        syllables
+    }
+
+    #[test]
+    fn test_hyphenation_patterns() {
+        let patterns = r"
+            % A few patterns.
+            \patterns{
+                .hy3p he2n hena4 hen5at 1na n2at 1tio 2io o2n
+            }
+            \hyphenation{ ta-ble }
+        ";
+
+        assert_eq!(hyphenate(patterns, "hyphenation"), ["hy", "phen", "ation"]);
+        assert_eq!(hyphenate(patterns, "Table"), ["Ta", "ble"]);
+    }
+
+    #[test]
+    fn test_hyphenation_plain_list() {
+        let patterns = "1ba ko-ala";
+        assert_eq!(hyphenate(patterns, "ababab"), ["aba", "bab"]);
+        assert_eq!(hyphenate(patterns, "koala"), ["ko", "ala"]);
+    }
+
+    #[test]
+    fn test_hyphenation_errors() {
+        assert!(Hyphenation::parse("% nothing").is_err());
+        assert!(Hyphenation::parse(r"\patterns{ 12 }").is_err());
+        assert!(Hyphenation::parse(r"\patterns{ a1b").is_err());
+    }
+
+    #[test]
+    fn test_hyphenation_exceptions_only() {
+        let hyphenation = Hyphenation::parse(r"\hyphenation{ ta-ble }").unwrap();
+        assert_eq!(hyphenation.hyphenate("table"), Some(vec![2]));
+        assert_eq!(hyphenation.hyphenate("chair"), None);
+    }
+}
@@ -2,7 +2,11 @@ use std::str::FromStr;
 
 use ecow::EcoString;
 
-use crate::foundations::{cast, StyleChain};
+use crate::diag::{At, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    cast, elem, repr, Content, NativeElement, Show, Smart, StyleChain, Value,
+};
 use crate::layout::Dir;
 use crate::text::TextElem;
 
@@ -53,6 +57,36 @@ impl Lang {
             _ => Dir::LTR,
         }
     }
+
+    /// The character that conventionally separates the integral and the
+    /// fractional part of a decimal number in the language.
+    pub fn decimal_separator(self) -> char {
+        match self.as_str() {
+            "cs" | "da" | "de" | "el" | "es" | "et" | "fi" | "fr" | "gr" | "hu" | "it"
+            | "nb" | "nl" | "nn" | "pl" | "pt" | "ro" | "ru" | "sl" | "sq" | "sv"
+            | "tr" | "ua" | "uk" | "vi" => ',',
+            _ => '.',
+        }
+    }
+
+    /// The conventional date format in the language as a
+    /// [datetime pattern]($datetime.display), or `None` if dates are
+    /// conventionally written in the ISO 8601 format.
+    ///
+    /// English keeps the ISO format since it is the default language.
+    pub fn date_pattern(self, region: Option<Region>) -> Option<&'static str> {
+        Some(match self.as_str() {
+            "fr" if region.is_some_and(|region| region == "CA") => return None,
+            "cs" | "da" | "de" | "et" | "fi" | "nb" | "nn" | "pl" | "ro" | "ru" | "sl"
+            | "sq" | "tr" | "ua" | "uk" => "[day].[month].[year]",
+            "el" | "es" | "fr" | "gr" | "it" | "pt" | "vi" => "[day]/[month]/[year]",
+            "nl" => "[day]-[month]-[year]",
+            "hu" => "[year]. [month]. [day].",
+            "ja" | "zh" => "[year]年[month padding:none]月[day padding:none]日",
+            "tl" => "[month]/[day]/[year]",
+            _ => return None,
+        })
+    }
 }
 
 impl FromStr for Lang {
@@ -78,6 +112,41 @@ cast! {
     string: EcoString => Self::from_str(&string)?,
 }
 
+/// A value whose display depends on the [text language]($text.lang).
+///
+/// Decimal fractions and dates inserted into markup are wrapped in this element,
+/// so that they are displayed with the decimal separator and date format of
+/// the active language and region.
+#[elem(Show)]
+pub struct LocalizedElem {
+    /// The value to display.
+    #[required]
+    pub value: Value,
+}
+
+impl Show for LocalizedElem {
+    #[tracing::instrument(name = "LocalizedElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let lang = TextElem::lang_in(styles);
+        let region = TextElem::region_in(styles);
+        Ok(match self.value() {
+            Value::Float(v) => {
+                let text = repr::format_float(*v, None, "");
+                match lang.decimal_separator() {
+                    '.' => TextElem::packed(text),
+                    c => TextElem::packed(text.replace(".", &c.to_string())),
+                }
+            }
+            Value::Datetime(datetime) => TextElem::packed(
+                datetime
+                    .display(Smart::Auto, Some(lang), region)
+                    .at(self.span())?,
+            ),
+            value => value.clone().display(),
+        })
+    }
+}
+
 /// An identifier for a region somewhere in the world.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Region([u8; 2]);
@@ -3,6 +3,7 @@
 mod case;
 mod deco;
 mod font;
+mod hyphenation;
 mod item;
 mod lang;
 mod linebreak;
@@ -18,6 +19,7 @@ mod space;
 pub use self::case::*;
 pub use self::deco::*;
 pub use self::font::*;
+pub use self::hyphenation::*;
 pub use self::item::*;
 pub use self::lang::*;
 pub use self::linebreak::*;
@@ -31,8 +33,8 @@ pub use self::space::*;
 use std::fmt::{self, Debug, Formatter};
 
 use ecow::{eco_format, EcoString};
-use rustybuzz::{Feature, Tag};
-use ttf_parser::Rect;
+use rustybuzz::Feature;
+use ttf_parser::{Rect, Tag};
 
 use crate::diag::{bail, SourceResult, StrResult};
 use crate::engine::Engine;
@@ -43,6 +45,7 @@ use crate::foundations::{
 use crate::layout::{Abs, Axis, Dir, Length, Rel};
 use crate::model::ParElem;
 use crate::syntax::Spanned;
+use crate::util::Scalar;
 use crate::visualize::{Color, Paint, RelativeTo};
 
 /// Text styling.
@@ -451,6 +454,31 @@ pub struct TextElem {
     #[ghost]
     pub hyphenate: Hyphenate,
 
+    /// Custom hyphenation patterns to use instead of the bundled ones.
+    ///
+    /// This takes the contents of a pattern file in TeX format, for instance
+    /// one from the `hyph-utf8` collection. Patterns and exceptions are read
+    /// from `\patterns{..}` and `\hyphenation{..}` blocks. A file without
+    /// such blocks is read as a plain list of patterns, where entries with
+    /// hyphens like `{ta-ble}` are exceptions.
+    ///
+    /// This makes it possible to hyphenate languages for which Typst has no
+    /// bundled patterns and to fix the hyphenation of individual words. If
+    /// only exceptions are given, the bundled patterns for the
+    /// [text language]($text.lang) are still used for all other words. The
+    /// patterns only take effect where [hyphenation]($text.hyphenate) is
+    /// enabled.
+    ///
+    /// ```example
+    /// #set page(width: 80pt)
+    /// #set text(hyphenate: true)
+    ///
+    /// #set text(hyphenation: "\hyphenation{Ty-pe-set-ting}")
+    /// Typesetting typesetting
+    /// ```
+    #[ghost]
+    pub hyphenation: Option<Hyphenation>,
+
     /// Whether to apply kerning.
     ///
     /// When enabled, specific letter pairings move closer together or further
@@ -532,6 +560,9 @@ pub struct TextElem {
     /// Which kind of numbers / figures to select. When set to `{auto}`, the
     /// default numbers for the font are used.
     ///
+    /// If a font doesn't support the requested kind of numbers, its default
+    /// numbers are used instead and a warning is emitted.
+    ///
     /// ```example
     /// #set text(font: "Noto Sans", 20pt)
     /// #set text(number-type: "lining")
@@ -546,6 +577,9 @@ pub struct TextElem {
     /// The width of numbers / figures. When set to `{auto}`, the default
     /// numbers for the font are used.
     ///
+    /// If a font doesn't support the requested kind of numbers, its default
+    /// numbers are used instead and a warning is emitted.
+    ///
     /// ```example
     /// #set text(font: "Noto Sans", 20pt)
     /// #set text(number-width: "proportional")
@@ -591,6 +625,11 @@ pub struct TextElem {
     /// - If given a dictionary mapping to numbers, sets the features
     ///   identified by the keys to the values.
     ///
+    /// Features given here take precedence over the ones implied by
+    /// convenience properties like `kerning` and `ligatures`. For instance,
+    /// `{set text(ligatures: false, features: (liga: 1))}` keeps standard
+    /// ligatures enabled while still turning off contextual ones.
+    ///
     /// ```example
     /// // Enable the `frac` feature manually.
     /// #set text(features: ("frac",))
@@ -600,6 +639,22 @@ pub struct TextElem {
     #[ghost]
     pub features: FontFeatures,
 
+    /// Coordinates on the variation axes of variable fonts.
+    ///
+    /// Given as a dictionary mapping four-letter axis tags to values, for
+    /// instance `{(wght: 450, wdth: 85)}` for a weight of 450 and a width of
+    /// 85%. Axes that a font doesn't support are ignored, so non-variable
+    /// fonts are unaffected. The axis values are used for shaping as well as
+    /// for the glyph outlines in all export formats.
+    ///
+    /// ```typ
+    /// #set text(font: "Roboto Flex", axes: (wght: 450, wdth: 85))
+    /// Slightly condensed text.
+    /// ```
+    #[fold]
+    #[ghost]
+    pub axes: FontAxes,
+
     /// Content in which all text is styled according to the other arguments.
     #[external]
     #[required]
@@ -609,6 +664,17 @@ pub struct TextElem {
     #[required]
     pub text: EcoString,
 
+    /// The capturing groups of the regex show rule match that produced this
+    /// text. The first item contains the first capturing group, not the whole
+    /// match. Groups that did not participate in the match are `{none}`.
+    #[synthesized]
+    pub captures: Array,
+
+    /// The named capturing groups of the regex show rule match that produced
+    /// this text, mapped from their names to the matched strings.
+    #[synthesized]
+    pub named_captures: Dict,
+
     /// A delta to apply on the font weight.
     #[internal]
     #[fold]
@@ -1048,6 +1114,49 @@ impl Fold for FontFeatures {
     }
 }
 
+/// Coordinates on the variation axes of variable fonts.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct FontAxes(pub Vec<(Tag, Scalar)>);
+
+impl FontAxes {
+    /// The axes with their values as floats.
+    pub fn coords(&self) -> Vec<(Tag, f64)> {
+        self.0.iter().map(|&(tag, value)| (tag, value.get())).collect()
+    }
+}
+
+cast! {
+    FontAxes,
+    self => self.0
+        .into_iter()
+        .map(|(tag, value)| {
+            let bytes = tag.to_bytes();
+            let key = std::str::from_utf8(&bytes).unwrap_or_default();
+            (key.into(), value.get().into_value())
+        })
+        .collect::<Dict>()
+        .into_value(),
+    values: Dict => Self(values
+        .into_iter()
+        .map(|(k, v)| {
+            if k.len() != 4 || !k.is_ascii() {
+                bail!("axis tag must be exactly four characters long");
+            }
+            let value = v.cast::<f64>()?;
+            Ok((Tag::from_bytes_lossy(k.as_bytes()), Scalar::new(value)))
+        })
+        .collect::<StrResult<_>>()?),
+}
+
+impl Fold for FontAxes {
+    type Output = Self;
+
+    fn fold(mut self, outer: Self::Output) -> Self::Output {
+        self.0.extend(outer.0);
+        self
+    }
+}
+
 /// Collect the OpenType features to apply.
 pub(crate) fn features(styles: StyleChain) -> Vec<Feature> {
     let mut tags = vec![];
@@ -1108,6 +1217,7 @@ pub(crate) fn features(styles: StyleChain) -> Vec<Feature> {
         feat(b"frac", 1);
     }
 
+    // Raw features come last so that they override the toggles above.
     for (tag, value) in TextElem::features_in(styles).0 {
         tags.push(Feature::new(tag, value, ..))
     }
@@ -12,18 +12,18 @@ use unicode_segmentation::UnicodeSegmentation;
 use crate::diag::{At, FileError, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, scope, Args, Array, Bytes, Content, Finalize, Fold, NativeElement,
-    PlainText, Show, Smart, StyleChain, Styles, Synthesize, Value,
+    cast, elem, scope, Args, Array, Bytes, Cast, Content, Finalize, Fold,
+    NativeElement, PlainText, Show, Smart, StyleChain, Styles, Synthesize, Value,
 };
 use crate::layout::{BlockElem, Em, HAlign};
 use crate::model::Figurable;
 use crate::syntax::{split_newlines, LinkedNode, Spanned};
 use crate::text::{
-    FontFamily, FontList, Hyphenate, Lang, LinebreakElem, LocalName, Region,
-    SmartQuoteElem, TextElem, TextSize,
+    FontFamily, FontList, HighlightElem, Hyphenate, Lang, LinebreakElem, LocalName,
+    Region, SmartQuoteElem, TextElem, TextSize,
 };
 use crate::util::option_eq;
-use crate::visualize::Color;
+use crate::visualize::{Color, Paint};
 use crate::{syntax, World};
 
 // Shorthand for highlighter closures.
@@ -256,6 +256,49 @@ pub struct RawElem {
     #[default(2)]
     pub tab_size: usize,
 
+    /// Whether to highlight the lines as a unified diff.
+    ///
+    /// In diff mode, lines starting with `+` are marked as added, lines
+    /// starting with `-` as removed, and lines starting with `@@` as hunk
+    /// headers. Added and removed lines are highlighted with the
+    /// [`added-fill`]($raw.added-fill) and [`removed-fill`]($raw.removed-fill)
+    /// respectively. The text of each line is still highlighted according to
+    /// the raw's [language]($raw.lang), so you can show changes to code in
+    /// any supported language.
+    ///
+    /// If set to `{auto}`, diff mode is enabled for the `diff` and `patch`
+    /// languages.
+    ///
+    /// ````example
+    /// #set raw(diff: true)
+    ///
+    /// ```rust
+    /// @@ -1,3 +1,3 @@
+    ///  fn main() {
+    /// -    println!("Hello");
+    /// +    println!("Hello, World!");
+    ///  }
+    /// ```
+    /// ````
+    pub diff: Smart<bool>,
+
+    /// How to fill lines that are marked as added in diff mode.
+    ///
+    /// ````example
+    /// #set raw(added-fill: green.lighten(60%))
+    ///
+    /// ```diff
+    /// -old
+    /// +new
+    /// ```
+    /// ````
+    #[default(Some(Color::from_u8(0xE6, 0xFF, 0xEC, 0xFF).into()))]
+    pub added_fill: Option<Paint>,
+
+    /// How to fill lines that are marked as removed in diff mode.
+    #[default(Some(Color::from_u8(0xFF, 0xEB, 0xE9, 0xFF).into()))]
+    pub removed_fill: Option<Paint>,
+
     /// The stylized lines of raw text.
     ///
     /// Made accessible for the [`raw.line` element]($raw.line).
@@ -307,6 +350,10 @@ impl Synthesize for RawElem {
             .map(|s| s.to_lowercase())
             .or(Some("txt".into()));
 
+        let diff = self
+            .diff(styles)
+            .unwrap_or_else(|| matches!(lang.as_deref(), Some("diff" | "patch")));
+
         let extra_syntaxes = UnsyncLazy::new(|| {
             load_syntaxes(&self.syntaxes(styles), &self.syntaxes_data(styles)).unwrap()
         });
@@ -385,6 +432,13 @@ impl Synthesize for RawElem {
             }));
         };
 
+        if diff {
+            for line in &mut seq {
+                let change = RawChange::detect(line.text());
+                line.push_change(change);
+            }
+        }
+
         self.push_lines(seq);
 
         Ok(())
@@ -493,11 +547,28 @@ pub struct RawLine {
     /// The highlighted raw text.
     #[required]
     pub body: Content,
+
+    /// How the line was changed if the raw block is in
+    /// [diff mode]($raw.diff).
+    ///
+    /// This is `{"added"}`, `{"removed"}`, or `{"hunk"}` for lines that start
+    /// with the respective unified-diff marker and `{none}` otherwise.
+    pub change: Option<RawChange>,
 }
 
 impl Show for RawLine {
-    fn show(&self, _: &mut Engine, _styles: StyleChain) -> SourceResult<Content> {
-        Ok(self.body().clone())
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let fill = match self.change(styles) {
+            Some(RawChange::Added) => RawElem::added_fill_in(styles),
+            Some(RawChange::Removed) => RawElem::removed_fill_in(styles),
+            Some(RawChange::Hunk) | None => None,
+        };
+
+        let body = self.body().clone();
+        Ok(match fill {
+            Some(fill) => HighlightElem::new(body).with_fill(fill).pack(),
+            None => body,
+        })
     }
 }
 
@@ -507,6 +578,35 @@ impl PlainText for RawLine {
     }
 }
 
+/// How a line of a unified diff was changed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum RawChange {
+    /// The line was added.
+    Added,
+    /// The line was removed.
+    Removed,
+    /// The line is a hunk header.
+    Hunk,
+}
+
+impl RawChange {
+    /// Detect the change of a line from its unified-diff marker.
+    fn detect(line: &str) -> Option<Self> {
+        if line.starts_with("@@") {
+            Some(Self::Hunk)
+        } else if line.starts_with("+++") || line.starts_with("---") {
+            // File headers are neither additions nor removals.
+            None
+        } else if line.starts_with('+') {
+            Some(Self::Added)
+        } else if line.starts_with('-') {
+            Some(Self::Removed)
+        } else {
+            None
+        }
+    }
+}
+
 /// Wrapper struct for the state required to highlight typst code.
 struct ThemedHighlighter<'a> {
     /// The code being highlighted.
@@ -17,6 +17,7 @@ use crate::foundations::{
 };
 use crate::layout::{Angle, Ratio};
 use crate::syntax::{Span, Spanned};
+use crate::util::PicoStr;
 
 // Type aliases for `palette` internal types in f32.
 pub(crate) type Oklab = palette::oklab::Oklaba<f32>;
@@ -558,6 +559,51 @@ impl Color {
         })
     }
 
+    /// Create a spot color.
+    ///
+    /// Spot colors are premixed inks that are printed on their own separation
+    /// instead of being mixed from the process colors cyan, magenta, yellow,
+    /// and key. In the exported PDF, a spot color is emitted with a separation
+    /// color space of the given name, so that a printer can map it to the
+    /// corresponding ink. Viewers and other export formats instead show the
+    /// `alternate` CMYK color, scaled by the `tint`.
+    ///
+    /// Apart from export, a spot color behaves like its tinted CMYK alternate.
+    /// Color manipulations like [`lighten`]($color.lighten) produce plain CMYK
+    /// colors.
+    ///
+    /// ```example
+    /// #let brand = color.spot("Brand Red", cmyk(0%, 90%, 85%, 0%))
+    /// #square(fill: brand)
+    /// #square(fill: color.spot("Brand Red", cmyk(0%, 90%, 85%, 0%), tint: 40%))
+    /// ```
+    #[func]
+    pub fn spot(
+        /// The name of the ink.
+        name: Str,
+        /// The color that approximates the ink in CMYK. Colors in other color
+        /// spaces are converted to CMYK.
+        alternate: Color,
+        /// How much of the ink to apply.
+        #[named]
+        #[default(RatioComponent(Ratio::one()))]
+        tint: RatioComponent,
+    ) -> Color {
+        let Self::Cmyk(alternate) = alternate.to_cmyk() else { unreachable!() };
+        let tint = tint.0.get() as f32;
+        Self::Cmyk(Cmyk {
+            c: alternate.c * tint,
+            m: alternate.m * tint,
+            y: alternate.y * tint,
+            k: alternate.k * tint,
+            spot: Some(Spot {
+                name: PicoStr::new(&name),
+                tint,
+                alternate: [alternate.c, alternate.m, alternate.y, alternate.k],
+            }),
+        })
+    }
+
     /// Create an HSL color.
     ///
     /// This color space is useful for specifying colors by hue, saturation and
@@ -1406,6 +1452,24 @@ impl Repr for Color {
                     )
                 }
             }
+            Self::Cmyk(Cmyk { spot: Some(spot), .. }) => {
+                let [c, m, y, k] = spot.alternate;
+                let alternate = Self::Cmyk(Cmyk::new(c, m, y, k));
+                if spot.tint == 1.0 {
+                    eco_format!(
+                        "color.spot({}, {})",
+                        spot.name.resolve().repr(),
+                        alternate.repr(),
+                    )
+                } else {
+                    eco_format!(
+                        "color.spot({}, {}, tint: {})",
+                        spot.name.resolve().repr(),
+                        alternate.repr(),
+                        Ratio::new(spot.tint as _).repr(),
+                    )
+                }
+            }
             Self::Cmyk(c) => {
                 eco_format!(
                     "cmyk({}, {}, {}, {})",
@@ -1525,6 +1589,10 @@ impl Hash for Color {
         y.to_bits().hash(state);
         z.to_bits().hash(state);
         w.to_bits().hash(state);
+        if let Self::Cmyk(Cmyk { spot: Some(spot), .. }) = self {
+            spot.name.hash(state);
+            spot.tint.to_bits().hash(state);
+        }
     }
 }
 
@@ -1628,11 +1696,14 @@ pub struct Cmyk {
     pub y: f32,
     /// The key (black) component.
     pub k: f32,
+    /// The spot color this color represents, if any. The components above
+    /// are then the spot color's tinted alternate.
+    pub spot: Option<Spot>,
 }
 
 impl Cmyk {
     fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
-        Self { c, m, y, k }
+        Self { c, m, y, k, spot: None }
     }
 
     fn from_luma(luma: Luma) -> Self {
@@ -1676,6 +1747,17 @@ impl Cmyk {
     }
 }
 
+/// A named ink that is printed on its own separation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spot {
+    /// The name of the ink.
+    pub name: PicoStr,
+    /// How much of the ink is applied, between zero and one.
+    pub tint: f32,
+    /// The CMYK components approximating the ink at full tint.
+    pub alternate: [f32; 4],
+}
+
 /// A color with a weight.
 pub struct WeightedColor {
     color: Color,
@@ -35,6 +35,14 @@ use crate::World;
 ///
 /// Supported formats are PNG, JPEG, GIF and SVG.
 ///
+/// SVG images are embedded into PDFs as vector graphics, so they stay crisp at
+/// any zoom level. The only exception are filter effects, which have no
+/// equivalent in PDF: The parts of an SVG that use them are rasterized instead
+/// and Typst emits a warning during PDF export.
+///
+/// To tile an image across an area, for example across a page's background,
+/// use it as the body of a [pattern]($pattern) and fill the area with it.
+///
 /// _Note:_ Work on SVG export is ongoing and there might be visual inaccuracies
 /// in the resulting PDF. Make sure to double-check embedded SVG images. If you
 /// have an issue, also feel free to report it on [GitHub][gh-svg].
@@ -22,6 +22,7 @@ struct Repr {
     data: Bytes,
     size: Axes<u32>,
     font_hash: u128,
+    has_filters: bool,
     tree: sync::SyncTree,
 }
 
@@ -35,6 +36,7 @@ impl SvgImage {
             data,
             size: tree_size(&tree),
             font_hash: 0,
+            has_filters: tree_has_filters(&tree),
             // Safety: We just created the tree and hold the only reference.
             tree: unsafe { sync::SyncTree::new(tree) },
         })))
@@ -64,6 +66,7 @@ impl SvgImage {
             data,
             size: tree_size(&tree),
             font_hash,
+            has_filters: tree_has_filters(&tree),
             // Safety: We just created the tree and hold the only reference.
             tree: unsafe { sync::SyncTree::new(tree) },
         })))
@@ -84,6 +87,14 @@ impl SvgImage {
         self.0.size.y
     }
 
+    /// Whether the SVG uses filter effects.
+    ///
+    /// Filters have no vector equivalent in PDF, so exporters need to fall
+    /// back to rasterizing the parts of the image that use them.
+    pub fn has_filters(&self) -> bool {
+        self.0.has_filters
+    }
+
     /// Performs an operation with the usvg tree.
     ///
     /// This makes the tree uniquely available to the current thread and blocks
@@ -211,6 +222,14 @@ fn tree_size(tree: &usvg::Tree) -> Axes<u32> {
     Axes::new(tree.size.width().ceil() as u32, tree.size.height().ceil() as u32)
 }
 
+/// Whether any group in the tree applies a filter.
+fn tree_has_filters(tree: &usvg::Tree) -> bool {
+    tree.root.descendants().any(|node| match &*node.borrow() {
+        usvg::NodeKind::Group(group) => !group.filters.is_empty(),
+        _ => false,
+    })
+}
+
 /// Format the user-facing SVG decoding error message.
 fn format_usvg_error(error: usvg::Error) -> EcoString {
     match error {
@@ -78,6 +78,38 @@ use crate::World;
 /// )
 /// ```
 ///
+/// To shift the grid of cells, for example to align a tiled logo or
+/// watermark with other content, use the [`offset`]($pattern.offset). The
+/// pattern's body is only laid out (and, for images, decoded) once and then
+/// repeated when painting.
+///
+/// ```example
+/// #let pat = pattern(
+///   size: (20pt, 20pt),
+///   spacing: (10pt, 10pt),
+///   offset: (15pt, 5pt),
+///   circle(radius: 10pt, fill: teal),
+/// )
+///
+/// #rect(width: 100%, height: 60pt, fill: pat)
+/// ```
+///
+/// # Tiling images
+/// To repeat an image across an area, for example a logo across a page's
+/// background, use the image as the body of a pattern and the pattern as a
+/// fill. The image is only decoded once and exported as a single tile that
+/// PDF and SVG viewers repeat, so this stays compact even for large areas.
+///
+/// ```example
+/// #let logo = pattern(
+///   size: (20pt, 20pt),
+///   spacing: (5pt, 5pt),
+///   image("tiger.jpg", width: 20pt, height: 20pt),
+/// )
+///
+/// #rect(width: 100%, height: 60pt, fill: logo)
+/// ```
+///
 /// # Relativeness
 /// The location of the starting point of the pattern is dependent on the
 /// dimensions of a container. This container can either be the shape that it is
@@ -108,6 +140,8 @@ struct Repr {
     size: Size,
     /// The pattern's tile spacing.
     spacing: Size,
+    /// The offset of the pattern's grid of tiles.
+    offset: Size,
     /// The pattern's relative transform.
     relative: Smart<RelativeTo>,
 }
@@ -145,6 +179,11 @@ impl Pattern {
         #[named]
         #[default(Spanned::new(Axes::splat(Length::zero()), Span::detached()))]
         spacing: Spanned<Axes<Length>>,
+        /// How far to shift the grid of cells from the origin of the
+        /// [relative placement](#relativeness).
+        #[named]
+        #[default(Spanned::new(Axes::splat(Length::zero()), Span::detached()))]
+        offset: Spanned<Axes<Length>>,
         /// The [relative placement](#relativeness) of the pattern.
         ///
         /// For an element placed at the root/top level of the document, the
@@ -184,6 +223,15 @@ impl Pattern {
             bail!(spacing.span, "pattern tile spacing must be finite");
         }
 
+        // Ensure that the offset is absolute and finite.
+        if !offset.v.x.em.is_zero() || !offset.v.y.em.is_zero() {
+            bail!(offset.span, "pattern offset must be absolute");
+        }
+
+        if !offset.v.x.is_finite() || !offset.v.y.is_finite() {
+            bail!(offset.span, "pattern offset must be finite");
+        }
+
         // The size of the frame
         let size = size.v.map(|l| l.map(|a| a.abs));
         let region = size.unwrap_or_else(|| Axes::splat(Abs::inf()));
@@ -212,6 +260,7 @@ impl Pattern {
             size: frame.size(),
             frame: Prehashed::new(frame),
             spacing: spacing.v.map(|l| l.abs),
+            offset: offset.v.map(|l| l.abs),
             relative,
         })))
     }
@@ -247,6 +296,11 @@ impl Pattern {
         self.0.spacing
     }
 
+    /// Return the offset of the pattern's grid in absolute units.
+    pub fn offset(&self) -> Size {
+        self.0.offset
+    }
+
     /// Returns the relative placement of the pattern.
     pub fn relative(&self) -> Smart<RelativeTo> {
         self.0.relative
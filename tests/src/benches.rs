@@ -10,6 +10,17 @@ use typst::{Library, World};
 use unscanny::Scanner;
 
 const TEXT: &str = include_str!("../typ/compiler/bench.typ");
+// Every figure shows the final figure count through its own `locate` call.
+// These calls are still evaluated once per figure. Only the counter lookups
+// are cheap: they reuse the memoized counter sequence and count the preceding
+// elements without copying them.
+const FIGURES: &str = r#"
+#let total = locate(loc => counter(figure.where(kind: image)).final(loc).first())
+#set figure(numbering: n => [#n of #total])
+#for i in range(500) {
+  figure(rect(width: 1cm, height: 1cm), caption: [Square #i])
+}
+"#;
 const FONT: &[u8] = include_bytes!("../../assets/fonts/LinLibertine_R.ttf");
 
 main!(
@@ -19,6 +30,7 @@ main!(
     bench_edit,
     bench_eval,
     bench_compile,
+    bench_compile_figures,
     bench_render,
 );
 
@@ -71,6 +83,12 @@ fn bench_compile(iai: &mut Iai) {
     iai.run(|| typst::compile(&world, &mut tracer));
 }
 
+fn bench_compile_figures(iai: &mut Iai) {
+    let world = BenchWorld::with_text(FIGURES);
+    let mut tracer = Tracer::new();
+    iai.run(|| typst::compile(&world, &mut tracer));
+}
+
 fn bench_render(iai: &mut Iai) {
     let world = BenchWorld::new();
     let mut tracer = Tracer::new();
@@ -87,6 +105,10 @@ struct BenchWorld {
 
 impl BenchWorld {
     fn new() -> Self {
+        Self::with_text(TEXT)
+    }
+
+    fn with_text(text: &str) -> Self {
         let font = Font::new(FONT.into(), 0).unwrap();
         let book = FontBook::from_fonts([&font]);
 
@@ -94,7 +116,7 @@ impl BenchWorld {
             library: Prehashed::new(Library::build()),
             book: Prehashed::new(book),
             font,
-            source: Source::detached(TEXT),
+            source: Source::detached(text),
         }
     }
 
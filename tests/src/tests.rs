@@ -431,7 +431,10 @@ fn test(
                 &document,
                 Some(&format!("typst-test: {}", name.display())),
                 world.today(Some(0)),
-            );
+                typst_pdf::PdfOptions::default(),
+                &mut Tracer::new(),
+            )
+            .unwrap();
             fs::create_dir_all(pdf_path.parent().unwrap()).unwrap();
             fs::write(pdf_path, pdf_data).unwrap();
         }
@@ -557,7 +560,15 @@ fn test_part(
 
     let mut tracer = Tracer::new();
     let (mut frames, diagnostics) = match typst::compile(world, &mut tracer) {
-        Ok(document) => (document.pages, tracer.warnings()),
+        Ok(document) => {
+            // Some warnings are only emitted during export, so subtests that
+            // expect warnings are also exported to PDF.
+            if metadata.annotations.iter().any(|a| a.kind == AnnotationKind::Warning) {
+                let options = typst_pdf::PdfOptions::default();
+                typst_pdf::pdf(&document, None, None, options, &mut tracer).unwrap();
+            }
+            (document.pages, tracer.warnings())
+        }
         Err(errors) => {
             let mut warnings = tracer.warnings();
             warnings.extend(errors);
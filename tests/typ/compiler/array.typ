@@ -267,6 +267,22 @@
 #test((1, 2, 3).zip(), ((1,), (2,), (3,)))
 #test(array.zip(()), ())
 
+---
+// Test the `transpose` method.
+#test(().transpose(), ())
+#test(((),).transpose(), ())
+#test(((1, 2, 3),).transpose(), ((1,), (2,), (3,)))
+#test(((1, 2), (3, 4), (5, 6)).transpose(), ((1, 3, 5), (2, 4, 6)))
+#test(((1, 2), (3, 4)).transpose().transpose(), ((1, 2), (3, 4)))
+
+---
+// Error: 2-36 inner arrays must have the same length (array at index 1 has length 1, but the first has length 2)
+#((1, 2), (3,), (4, 5)).transpose()
+
+---
+// Error: 2-25 expected array, found integer
+#((1, 2), 3).transpose()
+
 
 ---
 // Test the `enumerate` method.
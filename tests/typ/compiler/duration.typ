@@ -75,6 +75,31 @@
 #test(datetime(day: 1, month: 2, year: 2000) - b, duration(days: 31))
 #test(datetime(day: 15, month: 1, year: 2000) - b, duration(weeks: 2))
 
+---
+// Test mixing dates and datetimes.
+#let date = datetime(day: 2, month: 1, year: 2000)
+#let noon = datetime(day: 1, month: 1, year: 2000, hour: 12, minute: 0, second: 0)
+#let midnight = datetime(day: 2, month: 1, year: 2000, hour: 0, minute: 0, second: 0)
+#test(date - noon, duration(hours: 12))
+#test(noon - date, duration(hours: -12))
+#test(noon < date, true)
+#test(date >= noon, true)
+#test(date == midnight, true)
+#test(date != midnight, false)
+#test(date <= midnight, true)
+#test(date >= midnight, true)
+#test(date < midnight, false)
+#test(date != noon, true)
+#test(date == datetime(hour: 0, minute: 0, second: 0), false)
+
+---
+// Error: 3-83 cannot subtract date from time
+#(datetime(hour: 1, minute: 0, second: 0) - datetime(day: 2, month: 1, year: 2000))
+
+---
+// Error: 3-83 cannot compare time and date
+#(datetime(hour: 1, minute: 0, second: 0) < datetime(day: 2, month: 1, year: 2000))
+
 ---
 // Test multiplying and dividing durations with numbers.
 #test(duration(minutes: 10) * 6, duration(hours: 1))
@@ -102,3 +127,27 @@
 #test(duration(minutes: 20) < duration(minutes: 10), false)
 #test(duration(minutes: 20) <= duration(minutes: 10), false)
 #test(duration(minutes: 20) == duration(minutes: 10), false)
+
+---
+// Test displaying durations.
+#test(duration(minutes: 90).display(), "1h 30m")
+#test(duration(days: 1, seconds: 5).display(), "1d 5s")
+#test(duration().display(), "0s")
+#test(duration(hours: -2).display(), "-2h")
+#test(duration(minutes: 90).display("[hours]h [minutes]m"), "1h 30m")
+#test(duration(minutes: 90).display("[minutes]m"), "90m")
+#test(duration(days: 2, minutes: 5).display("[hours]:[minutes padding:zero]"), "48:05")
+#test(duration(weeks: 1, days: 1, hours: 1).display("[days] days, [seconds] s"), "8 days, 3600 s")
+#test(duration(seconds: 3).display("[[[seconds]]"), "[3]")
+
+---
+// Error: 2-38 invalid component `hour` in duration format
+#duration(hours: 1).display("[hour]")
+
+---
+// Error: 2-53 invalid modifier `padding:space` in duration format
+#duration(hours: 1).display("[hours padding:space]")
+
+---
+// Error: 2-38 unclosed bracket in duration format
+#duration(hours: 1).display("[hours")
@@ -41,3 +41,29 @@ the ```rs &mut T``` reference.
 ---
 // Error: 7-35 this selector cannot be used with show
 #show selector(heading).or(figure): none
+
+---
+// Test scoping show rules to ancestors.
+#show selector(raw).within(figure): set text(blue)
+#show selector(heading).within(<special>): it => underline(it.body)
+
+`Black` and #figure(`Blue`, caption: [Code])
+
+= Normal
+#block[
+  = Underlined
+] <special>
+
+---
+// Test nested scoping.
+#show selector(strong).within(emph).within(list): set text(red)
+*Black* _*Black*_
+- *Black* _*Red*_
+
+---
+// Error: 7-43 this selector cannot be used as an ancestor
+#show selector(heading).within(regex("a")): none
+
+---
+// Error: 22-54 within selector is not locatable
+#locate(loc => query(selector(heading).within(figure), loc))
@@ -70,3 +70,20 @@ World
 #show "GRAPH": image("/files/graph.png")
 
 The GRAPH has nodes.
+
+---
+// Test accessing regex capture groups.
+#show regex("v(\d+)\.(\d+)(?:\.(\d+))?"): it => {
+  let (major, minor, patch) = it.captures
+  test(it.named-captures, (:))
+  box(fill: blue.lighten(80%), inset: 1pt)[#major/#minor/#patch]
+}
+Released v1.2.3 after v1.2.
+
+---
+// Test accessing named regex capture groups.
+#show regex("(?<key>\w+)=(?<value>\w+)"): it => {
+  test(it.captures, (it.named-captures.key, it.named-captures.value))
+  [#it.named-captures.value (#it.named-captures.key)]
+}
+Set color=red and size=big.
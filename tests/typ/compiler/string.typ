@@ -201,11 +201,37 @@
 #test("123abc456".trim(regex("\d+"), at: end, repeat: false), "123abc")
 #test("123abc456".trim(regex("\d{1,2}$"), repeat: false), "123abc4")
 #test("hello world".trim(regex(".")), "")
+#test("--==abc==--".trim(("-", "=")), "abc")
+#test("--==abc==--".trim(("-", "="), at: start), "abc==--")
+#test("--==abc==--".trim(("-", "="), repeat: false), "-==abc==-")
+#test("abc".trim(("a", "b", "c")), "")
+#test("2024abc1".trim(c => c.match(regex("\d")) != none), "abc")
+#test("2024abc1".trim(c => c.match(regex("\d")) != none, at: end), "2024abc")
+#test("2024abc1".trim(c => c.match(regex("\d")) != none, repeat: false), "024abc")
+#test("🏳️‍🌈a🏳️‍🌈".trim(c => c != "a"), "a")
 
 ---
 // Error: 17-21 expected either `start` or `end`
 #"abc".trim(at: left)
 
+---
+// Error: 13-14 expected boolean, found integer
+#"abc".trim(c => 1)
+
+---
+// Test the `pad-start` and `pad-end` methods.
+#test("7".pad-start(3), "  7")
+#test("7".pad-start(3, with: "0"), "007")
+#test("7".pad-end(3, with: "-"), "7--")
+#test("abcd".pad-start(3), "abcd")
+#test("abc".pad-end(3), "abc")
+#test("".pad-end(5, with: "ab"), "ababa")
+#test("ä".pad-start(2, with: "🏳️‍🌈"), "🏳️‍🌈ä")
+
+---
+// Error: 2-28 padding string must not be empty
+#"a".pad-start(3, with: "")
+
 ---
 // Test the `split` method.
 #test("abc".split(""), ("", "a", "b", "c", ""))
@@ -23,6 +23,30 @@
 #test(calc.round(calc.e, digits: 2), 2.72)
 #test(calc.round(calc.pi, digits: 2), 3.14)
 
+---
+// Test rounding and clamping arrays.
+#test(calc.round(()), ())
+#test(calc.round((1, 2.4, 2.5, -1.5)), (1, 2.0, 3.0, -2.0))
+#test(calc.round((1.234, 5.678), digits: 2), (1.23, 5.68))
+#test(calc.clamp((-4, 0.5, 12), 0, 10), (0, 0.5, 10))
+#test(calc.clamp(range(5), 1, 3), (1, 1, 2, 3, 3))
+
+---
+// Error: 13-21 expected integer or float, found string
+#calc.round((1, "a"))
+
+---
+// Error: 24-25 max must be greater than or equal to min
+#calc.clamp((1, 2), 3, 2)
+
+---
+// Error: 13-21 expected integer or float, found string
+#calc.clamp((1, "a"), 0, 1)
+
+---
+// Error: 13-16 expected integer, float, or array, found string
+#calc.round("1")
+
 ---
 // Error: 6-10 expected boolean, float, string, or integer, found length
 #int(10pt)
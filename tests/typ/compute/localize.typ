@@ -0,0 +1,37 @@
+// Test language-dependent display of dates and numbers.
+// Ref: false
+
+---
+// Test the default date formats of different languages.
+#let date = datetime(year: 2023, month: 4, day: 9)
+#test(date.display(lang: "en"), "2023-04-09")
+#test(date.display(lang: "de"), "09.04.2023")
+#test(date.display(lang: "fr"), "09/04/2023")
+#test(date.display(lang: "fr", region: "CA"), "2023-04-09")
+#test(date.display(lang: "nl"), "09-04-2023")
+#test(date.display(lang: "zh"), "2023年4月9日")
+#test(date.display(lang: "xy"), "2023-04-09")
+
+---
+// The time part is not localized.
+#let dt = datetime(year: 2023, month: 4, day: 9, hour: 8, minute: 5, second: 0)
+#test(dt.display(lang: "de"), "09.04.2023 08:05:00")
+#test(datetime(hour: 8, minute: 5, second: 0).display(lang: "de"), "08:05:00")
+
+---
+// An explicit pattern takes precedence.
+#test(datetime(year: 2023, month: 4, day: 9).display("[year]", lang: "de"), "2023")
+
+---
+// Test dates and numbers in localized markup.
+#set text(lang: "de")
+#show "09.04.2023": [Date]
+#show "3,5": [Number]
+#datetime(year: 2023, month: 4, day: 9) #3.5
+
+---
+// Values whose display doesn't depend on the language are plain text.
+#test([#1.0].func(), text)
+#test([#calc.inf].func(), text)
+#test([#datetime(hour: 8, minute: 5, second: 0)].func(), text)
+#test([#3.5].func() == text, false)
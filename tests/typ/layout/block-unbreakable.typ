@@ -0,0 +1,16 @@
+// Test unbreakable blocks near page boundaries.
+
+---
+// An unbreakable block with nested breakable content moves to the next page.
+#set page(height: 120pt)
+#lorem(20)
+#block(breakable: false, fill: aqua, inset: 4pt)[
+  #block(breakable: true, fill: white, inset: 4pt, lorem(20))
+]
+
+---
+// A tall unbreakable block is broken as a last resort.
+#set page(height: 100pt)
+Before
+#block(breakable: false, fill: aqua, inset: 4pt, lorem(50))
+After
@@ -0,0 +1,45 @@
+// Test balanced columns and weak column breaks.
+
+---
+// The columns in the last region end up with about the same height, so
+// the content ends in the second column.
+#set page(width: 200pt, height: 200pt)
+#columns(2, balance: true)[
+  #lorem(40) #metadata(none) <end>
+]
+
+#locate(loc => {
+  let pos = query(<end>, loc).first().location().position()
+  test(pos.page, 1)
+  test(pos.x > 100pt, true)
+})
+
+---
+// Balancing only affects the last page.
+#set page(height: 120pt, columns: 2)
+#set columns(balance: true)
+#lorem(60)
+
+---
+// Column breaks are respected when balancing.
+#columns(2, balance: true)[
+  A
+  #colbreak()
+  B \ C \ D
+]
+
+---
+// A weak column break in an empty column is skipped.
+#set page(height: 100pt, columns: 2)
+#colbreak(weak: true)
+First #metadata(none) <first>
+#colbreak(weak: true)
+Second #metadata(none) <second>
+
+#locate(loc => {
+  let first = query(<first>, loc).first().location().position()
+  let second = query(<second>, loc).first().location().position()
+  test(first.page, 1)
+  test(first.x < second.x, true)
+  test(first.page, second.page)
+})
@@ -0,0 +1,25 @@
+// Test content spanning multiple columns.
+
+---
+#set page(height: 200pt, columns: 2)
+#columns.span(align(center, text(14pt)[*Title*]))
+#lorem(20)
+
+#columns.span(rect(width: 100%, height: 30pt, fill: aqua))
+
+#lorem(40)
+
+---
+// Test spanning content in a columns container and with set rules.
+#set page(height: auto)
+#columns(3, gutter: 8pt)[
+  #set text(8pt)
+  #lorem(25)
+  #columns.span(line(length: 100%))
+  #set text(fill: eastern)
+  #lorem(25)
+]
+
+---
+// Spanning content outside of columns is laid out normally.
+#columns.span[Not in columns]
@@ -0,0 +1,32 @@
+// Test minimum and maximum row heights.
+
+---
+#set page(width: 120pt)
+#grid(
+  columns: (1fr, 1fr),
+  rows: ((min: 1cm), (max: 12pt), (min: 8pt, max: 2cm)),
+  gutter: 3pt,
+  rect(width: 100%, height: 100%, fill: red.lighten(60%))[Tall enough],
+  align(bottom)[Short],
+  lorem(8),
+  [Clipped],
+  [A],
+  align(horizon)[B],
+)
+
+---
+// Bounded rows inside of tables.
+#table(
+  columns: 3,
+  rows: (min: 24pt, max: 24pt),
+  align: horizon,
+  ..range(6).map(i => square(size: 8pt * (i + 1))),
+)
+
+---
+// Error: 2-30 minimum and maximum sizes are only supported for rows
+#grid(columns: (min: 1cm))[A]
+
+---
+// Error: 13-36 unexpected key "height", valid keys are "min" and "max"
+#grid(rows: (min: 1cm, height: 2cm))[A]
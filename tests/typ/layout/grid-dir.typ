@@ -0,0 +1,48 @@
+// Test overriding the direction of grid and table columns.
+
+---
+// In RTL text, the first column is on the right.
+#set text(dir: rtl)
+#grid(
+  columns: 2,
+  column-gutter: 6pt,
+  [A #metadata(none) <first>],
+  [B #metadata(none) <second>],
+)
+
+#locate(loc => {
+  let first = query(<first>, loc).first().location().position()
+  let second = query(<second>, loc).first().location().position()
+  test(first.x > second.x, true)
+})
+
+---
+// The direction can be overridden.
+#set text(dir: rtl)
+#grid(
+  columns: 2,
+  column-gutter: 6pt,
+  dir: ltr,
+  [A #metadata(none) <first>],
+  [B #metadata(none) <second>],
+)
+
+#locate(loc => {
+  let first = query(<first>, loc).first().location().position()
+  let second = query(<second>, loc).first().location().position()
+  test(first.x < second.x, true)
+})
+
+---
+// Fills count columns in the table's direction.
+#table(
+  columns: 3,
+  dir: rtl,
+  fill: (col, _) => if col == 0 { aqua },
+  table.cell(colspan: 2)[A], [B],
+  [C], [D], [E],
+)
+
+---
+// Error: 2-34 grid direction must be horizontal
+#grid(columns: 2, dir: ttb)[A][B]
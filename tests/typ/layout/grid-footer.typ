@@ -0,0 +1,53 @@
+// Test table and grid footers and continued headers.
+
+---
+// A footer repeats at the bottom of every page.
+#set page(height: 100pt)
+#table(
+  columns: 2,
+  table.header([*A*], [*B*]),
+  ..range(8 * 2).map(str),
+  table.footer([_A_ #metadata(none) <footer>], [_B_]),
+)
+
+#locate(loc => {
+  let pages = query(<footer>, loc).map(it => it.location().page())
+  test(pages.len() > 1, true)
+  test(pages, range(1, pages.len() + 1))
+})
+
+---
+// A footer that doesn't repeat appears only once, at the very end.
+#set page(height: 100pt)
+#grid(
+  columns: 2,
+  column-gutter: 6pt,
+  row-gutter: 3pt,
+  ..range(20).map(str),
+  grid.footer(repeat: false, [*Sum* #metadata(none) <once>], [190]),
+)
+
+#locate(loc => test(query(<once>, loc).len(), 1))
+
+---
+// Repeated headers can be marked as continued with a show rule.
+#set page(height: 100pt)
+#show table.cell.where(continued: true): it => {
+  it.body + [ (continued) #metadata(none) <continued>]
+}
+
+#table(
+  columns: 2,
+  table.header(table.cell(colspan: 2)[*Readings*]),
+  ..range(12).map(str),
+)
+
+#locate(loc => {
+  let pages = query(<continued>, loc).map(it => it.location().page())
+  test(pages.len() > 0, true)
+  test(pages.contains(1), false)
+})
+
+---
+// Error: 12-28 footer must be the last child
+#grid([a], grid.footer([b]), [c])
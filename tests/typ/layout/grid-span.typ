@@ -0,0 +1,74 @@
+// Test cells spanning multiple columns and rows.
+
+---
+#set page(width: 200pt)
+#table(
+  columns: 3,
+  table.cell(colspan: 2)[*Fruit*], [*Price*],
+  table.cell(rowspan: 2)[Apple], [Red], [1 €],
+  [Green], [2 €],
+  [Banana], table.cell(colspan: 2)[Out of stock],
+)
+
+---
+// Spanning cells with gutters and in RTL.
+#set text(dir: rtl)
+#grid(
+  columns: (auto, 1fr, auto),
+  gutter: 4pt,
+  grid.cell(rowspan: 3, rect(height: 100%)[A]),
+  grid.cell(colspan: 2)[B],
+  [C], [D],
+  grid.cell(colspan: 2)[E],
+)
+
+---
+// An auto row grows to fit a tall cell spanning it.
+#grid(
+  columns: 2,
+  gutter: 4pt,
+  grid.cell(rowspan: 2, rect(height: 60pt)),
+  [A],
+  [B],
+)
+#metadata(none) <below>
+
+#locate(loc => {
+  let pos = query(<below>, loc).first().location().position()
+  test(pos.y >= 70pt, true)
+})
+
+---
+// A cell that doesn't fit into the current row moves to the next one.
+#grid(
+  columns: 3,
+  gutter: 4pt,
+  [A], [B],
+  grid.cell(colspan: 2)[C],
+  [D],
+)
+
+---
+// Cells spanning rows break across pages along with their rows.
+#set page(height: 100pt)
+#table(
+  columns: 2,
+  table.cell(rowspan: 12)[Span],
+  ..range(12).map(str),
+)
+
+---
+// A cell spanning rows in a repeated header is repeated with it.
+#set page(height: 80pt)
+#table(
+  columns: 3,
+  table.header(
+    table.cell(rowspan: 2)[*Name*], [*Start*], [*End*],
+    [_(date)_], [_(date)_],
+  ),
+  ..range(9).map(str),
+)
+
+---
+// Error: 7-31 cell's colspan would exceed the available columns
+#grid(grid.cell(colspan: 2)[A])
@@ -0,0 +1,29 @@
+// Test measuring content with a width constraint.
+
+---
+#style(styles => {
+  let body = lorem(30)
+  let free = measure(body, styles)
+  let narrow = measure(body, styles, width: 3cm)
+  let wide = measure(body, styles, width: 6cm)
+  test(free.width > wide.width, true)
+  test(narrow.width <= 3cm, true)
+  test(wide.width <= 6cm, true)
+  test(narrow.height > wide.height, true)
+  test(wide.height > free.height, true)
+})
+
+---
+// The width is resolved with the given styles.
+#set text(size: 10pt)
+#style(styles => {
+  let body = lorem(10)
+  test(
+    measure(body, styles, width: 10em),
+    measure(body, styles, width: 100pt),
+  )
+})
+
+---
+// Error: 46-49 expected length or none, found ratio
+#style(styles => measure([A], styles, width: 50%))
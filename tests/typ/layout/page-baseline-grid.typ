@@ -0,0 +1,36 @@
+// Test snapping lines to the baseline grid.
+
+---
+// The lines in both columns line up even though the headings differ.
+#set page(height: 200pt, columns: 2, baseline-grid: 12pt)
+#set par(leading: 12pt - 1em)
+
+= Short
+First #metadata(none) <first>
+
+#colbreak()
+#text(1.5em)[Larger]
+
+Second #metadata(none) <second>
+
+#locate(loc => {
+  let first = query(<first>, loc).first().location().position()
+  let second = query(<second>, loc).first().location().position()
+  test(first.x < second.x, true)
+  let rest = calc.rem(calc.abs(first.y.pt() - second.y.pt()), 12)
+  test(rest < 0.01 or rest > 11.99, true)
+})
+
+---
+// Paragraphs can opt out of the grid.
+#set page(height: 100pt, baseline-grid: 12pt)
+#v(5pt)
+#set par(snap: false)
+Off the grid.
+
+---
+// Top floats take up whole grid cells.
+#set page(height: 200pt, baseline-grid: 14pt)
+#lorem(10)
+#place(top, float: true, rect(height: 20pt))
+#lorem(20)
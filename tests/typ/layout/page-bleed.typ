@@ -0,0 +1,34 @@
+// Test page bleed and crop marks.
+
+---
+// The fill and background extend into the bleed.
+#set page(
+  width: 80pt,
+  height: 60pt,
+  bleed: 9pt,
+  fill: aqua,
+  background: rect(width: 100%, height: 100%, stroke: 2pt + red),
+)
+Full bleed
+
+---
+// Crop marks with and without bleed.
+#set page(width: 80pt, height: 60pt, crop-marks: true)
+No bleed
+#pagebreak()
+#set page(bleed: 3mm)
+With bleed
+
+---
+// Positions are measured from the edge of the exported page, including the
+// bleed.
+#set page(width: 80pt, height: 60pt, margin: 10pt, bleed: 10pt)
+#locate(loc => {
+  let pos = loc.position()
+  test(pos.x, 20pt)
+  test(pos.y, 20pt)
+})
+
+---
+// Error: 18-22 expected length, found auto
+#set page(bleed: auto)
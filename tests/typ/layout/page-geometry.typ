@@ -0,0 +1,32 @@
+// Test retrieving the page geometry from styles.
+// Ref: false
+
+---
+#set page(width: 100pt, height: 200pt, margin: 10pt)
+#style(styles => {
+  let geometry = page.geometry(styles)
+  test(geometry.width, 100pt)
+  test(geometry.height, 200pt)
+  test(geometry.margin.left, 10pt)
+  test(geometry.inner.width, 80pt)
+  test(geometry.inner.height, 180pt)
+})
+
+---
+#set page(width: 100pt, height: 200pt, flipped: true, margin: (x: 10%, y: 5pt))
+#style(styles => {
+  let geometry = page.geometry(styles)
+  test(geometry.width, 200pt)
+  test(geometry.margin.right, 20pt)
+  test(geometry.margin.bottom, 5pt)
+  test(geometry.inner.height, 90pt)
+})
+
+---
+#set page(width: 100pt, height: auto, margin: 10pt)
+#style(styles => {
+  let geometry = page.geometry(styles)
+  test(geometry.height, auto)
+  test(geometry.inner.width, 80pt)
+  test(geometry.inner.height, auto)
+})
@@ -0,0 +1,24 @@
+// Test page watermarks.
+
+---
+// Plain content and dictionaries are accepted.
+#set page(width: 100pt, height: 100pt, watermark: [Draft])
+#set page(watermark: (body: [Draft], angle: 45deg, opacity: 50%, repeat: true))
+#set page(watermark: none)
+A
+
+---
+// A single watermark and a repeated one.
+#set page(width: 100pt, height: 100pt, watermark: [Draft])
+A
+#pagebreak()
+#set page(watermark: (body: text(red)[Draft], angle: 45deg, opacity: 50%, repeat: true))
+B
+
+---
+// Error: 36-61 unexpected key "angel", valid keys are "body", "angle", "opacity", and "repeat"
+#set page(width: 100pt, watermark: (body: [A], angel: 45deg))
+
+---
+// Error: 22-36 dictionary does not contain key "body"
+#set page(watermark: (opacity: 50%))
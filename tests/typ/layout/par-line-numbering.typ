@@ -0,0 +1,57 @@
+// Test paragraph line numbering.
+
+---
+// Lines are numbered continuously across paragraphs.
+#set page(width: 120pt, margin: (left: 24pt, rest: 10pt))
+#set par(line-numbering: "1")
+#lorem(12)
+
+#lorem(12) #metadata(none) <end>
+
+#locate(loc => {
+  let lines = query(par.line, loc)
+  test(lines.map(it => it.number), range(1, lines.len() + 1))
+  test(counter(par.line).at(query(<end>, loc).first().location()).first() > 4, true)
+})
+
+---
+// Numbering can start at a different number and restart on every page.
+#set page(width: 120pt, height: 80pt, margin: (left: 24pt, rest: 10pt))
+#set par(line-numbering: (numbering: "1", start: 0, step: 5, restart: true))
+#lorem(40)
+
+#locate(loc => {
+  let lines = query(par.line, loc)
+  let first = lines.filter(it => it.location().page() == 1)
+  let second = lines.filter(it => it.location().page() == 2)
+  test(first.first().number, 0)
+  test(second.first().number, 0)
+  test(second.last().number, second.len() - 1)
+})
+
+---
+// Numbers line up in the page margin, also for indented lines. List markers
+// and boxes don't get numbers of their own.
+#set page(width: 120pt, margin: (left: 24pt, rest: 10pt))
+#set par(line-numbering: "1")
+Some text that wraps.
+- A list item that
+  wraps as well.
+#pad(left: 16pt)[Padded text.]
+Inline #box(stroke: 0.5pt)[boxed] text.
+
+#locate(loc => {
+  let lines = query(par.line, loc)
+  test(lines.map(it => it.number), range(1, lines.len() + 1))
+})
+
+---
+// Numbers go into the right margin for right-to-left text.
+#set page(width: 120pt, margin: (right: 24pt, rest: 10pt))
+#set text(dir: rtl)
+#set par(line-numbering: "1")
+#lorem(8)
+
+---
+// Error: 26-52 unexpected key "gap", valid keys are "numbering", "start", "step", and "restart"
+#set par(line-numbering: (numbering: "1", gap: 1em))
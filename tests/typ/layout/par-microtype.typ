@@ -0,0 +1,19 @@
+// Test character protrusion and font expansion.
+
+---
+#set page(width: 180pt)
+#set par(justify: true, microtype: (protrusion: true, expansion: 2%))
+"Quotes and punctuation," they said, "protrude into the margins." #lorem(30)
+
+---
+// Protrusion alone also works for ragged text.
+#set par(microtype: (protrusion: true))
+#lorem(20)
+
+---
+// Error: 21-37 expansion must be between 0% and 10%
+#set par(microtype: (expansion: 20%))
+
+---
+// Error: 21-36 unexpected key "tracking", valid keys are "protrusion" and "expansion"
+#set par(microtype: (tracking: 1pt))
@@ -0,0 +1,69 @@
+// Test float placement preferences and deferral.
+
+---
+// A preferred area is tried first.
+#set page(height: 200pt)
+#lorem(5)
+#place(float: true, placement: (prefer: bottom, allow: (top, bottom)))[
+  Bottom #metadata(none) <bottom>
+]
+#lorem(5) #metadata(none) <text>
+
+#locate(loc => {
+  let float = query(<bottom>, loc).first().location().position()
+  let text = query(<text>, loc).first().location().position()
+  test(float.y > text.y, true)
+})
+
+---
+// A float that only fits on a page of its own gets one, and the text around
+// it continues on the pages before and after.
+#set page(height: 200pt)
+#lorem(10) #metadata(none) <before>
+#figure(
+  placement: (allow: (top, bottom, page)),
+  rect(height: 170pt)[Tall #metadata(none) <tall>],
+)
+#lorem(40) #metadata(none) <after>
+
+#locate(loc => {
+  let before = query(<before>, loc).first().location().page()
+  let tall = query(<tall>, loc).first().location().page()
+  let after = query(<after>, loc).first().location().page()
+  test(before, 1)
+  test(tall, 2)
+  test(after, 3)
+})
+
+---
+// Floats keep their order even if a later one would fit earlier.
+#set page(height: 150pt)
+#set place(clearance: 5pt)
+#lorem(30)
+#place(top, float: true, rect(height: 90pt)[A #metadata(none) <a>])
+#place(top, float: true, rect(height: 10pt)[B #metadata(none) <b>])
+#lorem(10)
+
+#locate(loc => {
+  let a = query(<a>, loc).first().location().page()
+  let b = query(<b>, loc).first().location().page()
+  test(a, 2)
+  test(b, 2)
+})
+
+---
+// A float that doesn't fit anywhere overflows its preferred area.
+// Warning: 2:2-2:58 floating element does not fit into any allowed area
+// Hint: 2:2-2:58 it is placed into its preferred area and overflows it
+// Hint: 2:2-2:58 allowing `page` would place it on a page of its own
+#set page(height: 100pt)
+#place(top, float: true, rect(height: 70pt, width: 100%))
+#lorem(5)
+
+---
+// Error: 32-63 preferred float area must also be allowed
+#place(float: true, placement: (prefer: top, allow: (bottom,)))[A]
+
+---
+// Error: 32-36 expected `top`, `bottom`, or `page`, found text
+#place(float: true, placement: text)[A]
@@ -0,0 +1,64 @@
+// Test table and grid headers.
+
+---
+// A two-row header repeats as a unit at the top of every page.
+#set page(height: 100pt)
+#table(
+  columns: 3,
+  table.header(
+    [*Group* #metadata(none) <header>], [], [],
+    [*A*], [*B*], [*C*],
+  ),
+  ..range(8 * 3).map(str),
+)
+
+#locate(loc => {
+  let pages = query(<header>, loc).map(it => it.location().page())
+  test(pages.len() > 1, true)
+  test(pages, range(1, pages.len() + 1))
+})
+
+---
+// A header with spanned cells repeats with all of its rows.
+#set page(height: 100pt)
+#table(
+  columns: 3,
+  table.header(
+    table.cell(rowspan: 2)[*Item* #metadata(none) <spanned>],
+    table.cell(colspan: 2)[*Price*],
+    [*Net*], [*Gross*],
+  ),
+  ..range(8 * 3).map(str),
+)
+
+#locate(loc => {
+  let pages = query(<spanned>, loc).map(it => it.location().page())
+  test(pages.len() > 1, true)
+  test(pages, range(1, pages.len() + 1))
+})
+
+---
+// A header that doesn't repeat appears only once.
+#set page(height: 60pt)
+#grid(
+  columns: 2,
+  column-gutter: 6pt,
+  row-gutter: 3pt,
+  grid.header(repeat: false, [*Key* #metadata(none) <once>], [*Value*]),
+  ..range(10).map(str),
+)
+
+#locate(loc => test(query(<once>, loc).len(), 1))
+
+---
+// A header with fewer cells than columns still occupies a full row.
+#grid(
+  columns: 3,
+  column-gutter: 6pt,
+  grid.header([A]),
+  [B], [C], [D],
+)
+
+---
+// Error: 12-28 header must be the first child
+#grid([a], grid.header([b]))
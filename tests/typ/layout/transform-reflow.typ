@@ -0,0 +1,28 @@
+// Test rotation that affects the layout.
+
+---
+// A reflowed rotation takes up its rotated size in the line.
+A #box(rotate(90deg, reflow: true, rect(width: 30pt, height: 10pt, fill: aqua))) B
+#box(rotate(-90deg, reflow: true)[Up]) C
+
+---
+#let body = box(width: 40pt, height: 10pt)
+#style(styles => {
+  let measured(body) = {
+    let (width, height) = measure(body, styles)
+    (calc.round(width / 1pt, digits: 3), calc.round(height / 1pt, digits: 3))
+  }
+  test(measured(rotate(90deg, body)), (40.0, 10.0))
+  test(measured(rotate(90deg, reflow: true, body)), (10.0, 40.0))
+  test(measured(rotate(-90deg, reflow: true, body)), (10.0, 40.0))
+  test(measured(rotate(180deg, reflow: true, body)), (40.0, 10.0))
+})
+
+---
+// A quarter-turned body may extend along the height of its container.
+#set page(width: 100pt, height: 100pt)
+#style(styles => {
+  let body = rotate(90deg, reflow: true, lorem(10))
+  let (width, height) = measure(block(width: 20pt, body), styles)
+  test(height > 20pt, true)
+})
@@ -0,0 +1,29 @@
+// Test accents stretching over wide bases.
+
+---
+// Wide bar and tilde over three-letter bases.
+$macron(A B C), tilde(A B C), macron(x y z), tilde(x y z)$
+
+---
+// Wide hat and arrows.
+$hat(A B C), arrow(A B C), arrow.l(x y z)$
+
+---
+// Accents wider than the font's widest variant are scaled.
+$tilde(a b c d e f g h i j), hat(a b c d e f g h i j)$
+
+---
+// Stretched accents add height but keep the width of their base.
+#set text(top-edge: "bounds", bottom-edge: "bounds")
+#style(styles => {
+  let size(body) = measure(body, styles)
+  for (accented, base) in (
+    ($macron(A B C)$, $A B C$),
+    ($tilde(x y z)$, $x y z$),
+    ($hat(A B C)$, $A B C$),
+    ($arrow(A B C)$, $A B C$),
+  ) {
+    test(size(accented).width, size(base).width)
+    test(size(accented).height > size(base).height, true)
+  }
+})
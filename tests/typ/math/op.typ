@@ -28,3 +28,10 @@ $ bold(op("bold", limits: #true))_x y $
 ---
 // With non-text content
 $ op(#underline[ul]) a $
+
+---
+// Test modular arithmetic.
+$ a equiv b pmod(n) \
+  gcd(a bmod b, b) \
+  a mod b $
+Inline, $a equiv b pmod(n)$ and $a bmod b$.
@@ -0,0 +1,21 @@
+// Test radical sizing and index placement.
+
+---
+// Cube root over a fraction.
+$ root(3, a/b) = root(3, a) / root(3, b) $
+
+---
+// Nested radicals.
+$ sqrt(1 + sqrt(1 + sqrt(1 + x))) \ root(3, 1 + root(3, 1 + x)) $
+
+---
+// Narrow and wide indices.
+$ root(3, x), root(i, x), root(n + 1, x^2 + 1) $
+
+---
+// The measured width must include a narrow index.
+#style(styles => {
+  let width(body) = measure(body, styles).width
+  test(width($root(3, x)$) > width($sqrt(x)$), true)
+  test(width($root(n + 1, x)$) > width($root(3, x)$), true)
+})
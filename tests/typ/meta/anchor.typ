@@ -0,0 +1,31 @@
+// Test anchors in running text.
+// Ref: false
+
+---
+// Reference an anchor on a later page.
+#set page(numbering: "1")
+#show ref: it => {
+  if it.element != none {
+    test(it.element.func(), anchor)
+  }
+  it
+}
+
+See @phrase.
+#pagebreak()
+The #anchor() <phrase> phrase.
+
+---
+// The anchor is locatable and has a position.
+#set page(numbering: "i")
+#pagebreak()
+Some text #anchor() <here>
+#locate(loc => {
+  let elem = query(<here>, loc).first()
+  test(elem.location().page(), 2)
+  test(elem.numbering, "i")
+})
+
+---
+// Links can target anchors.
+#link(<target>)[Go] #anchor() <target>
@@ -0,0 +1,17 @@
+// Test footnotes that span the full width below columns.
+
+---
+#set page(height: 120pt, columns: 2)
+#set footnote.entry(spanning: true)
+#lorem(8) #footnote[Left]
+#colbreak()
+#lorem(4) #footnote[Right]
+
+---
+// Test that the space for long footnotes is reserved on the page they are
+// referenced on and that the rest continues on the next page.
+#set page(height: 120pt)
+#set footnote.entry(spanning: true)
+#show: columns.with(2)
+#lorem(10) #footnote(lorem(30))
+#lorem(20)
@@ -0,0 +1,34 @@
+// Test restarting the footnote numbering.
+
+---
+// Without a reset, the third footnote would be numbered three.
+#set page(height: 100pt)
+#set footnote(reset: "per-page", numbering: n => {
+  assert(n <= 2)
+  str(n)
+})
+
+A #footnote[One] <one>
+B #footnote[Two]
+#pagebreak()
+C #footnote[One again], see also @one.
+
+---
+#set page(height: 140pt)
+#set footnote(reset: "per-chapter", numbering: n => {
+  assert(n <= 2)
+  str(n)
+})
+
+= Chapter
+A #footnote[One]
+B #footnote[Two]
+
+== Section
+= Chapter
+C #footnote[One] <again>
+D #footnote[Two], see @again.
+
+---
+// Error: 22-28 expected "continuous", "per-page", or "per-chapter"
+#set footnote(reset: "page")
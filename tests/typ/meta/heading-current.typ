@@ -0,0 +1,65 @@
+// Test displaying the current heading in running heads.
+
+---
+#set page(
+  paper: "a7",
+  margin: (y: 1cm, x: 0.5cm),
+  header: {
+    smallcaps(heading.current())
+    h(1fr)
+    heading.current(level: 2, it => emph(it.body))
+    line(length: 100%)
+  },
+)
+
+#outline()
+
+= Introduction
+#lorem(35)
+
+== Background
+#lorem(40)
+
+= Methods
+== Data
+#lorem(25)
+
+---
+// Test that the heading is passed to the function.
+#set heading(numbering: "1.")
+#set page(height: 100pt, margin: (top: 30pt, rest: 10pt), header: heading.current(it => {
+  test(it.func(), heading)
+  test(it.level, 1)
+  it.body
+}))
+
+= Chapter
+#lorem(10)
+
+---
+// A heading of a higher level ends the sections of lower levels, also when
+// it starts on the same page.
+#set page(height: 80pt, margin: (top: 24pt, rest: 10pt), header: {
+  heading.current()
+  [ | ]
+  heading.current(level: 2, it => [#metadata(it.body.text) <sec>#it.body])
+})
+#show heading: set text(8pt)
+
+= One
+== Alpha
+Text
+#pagebreak()
+Text
+#pagebreak()
+= Two
+Text
+#pagebreak()
+Text
+== Beta
+
+#locate(loc => {
+  let sections = query(<sec>, loc)
+  test(sections.map(it => it.value), ("Alpha", "Alpha", "Beta"))
+  test(sections.map(it => it.location().page()), (1, 2, 4))
+})
@@ -0,0 +1,40 @@
+// Test the reporting of dangling links and references.
+// Ref: false
+
+---
+// Error: 2-26 label `<missing>` does not exist in the document
+#link(<missing>)[Nowhere]
+
+---
+// With the automatic setting, references to missing labels are errors, too.
+#set link(dangling: auto)
+
+// Error: 1-9 label `<missing>` does not exist in the document
+@missing
+
+---
+// Links to labels that appear later in the document are fine.
+#link(<later>)[Later]
+#metadata(none) <later>
+
+---
+#set link(dangling: "warning")
+
+// Warning: 2-26 label `<missing>` does not exist in the document
+#link(<missing>)[Nowhere]
+
+// Warning: 1-9 label `<missing>` does not exist in the document
+@missing
+
+---
+// Duplicate labels are still errors with a lenient setting.
+#set link(dangling: "warning")
+#metadata(1) <twice>
+#metadata(2) <twice>
+
+// Error: 2-22 label `<twice>` occurs multiple times in the document
+#link(<twice>)[Twice]
+
+---
+// Error: 21-30 expected "error", "warning", or auto
+#set link(dangling: "nowhere")
@@ -0,0 +1,17 @@
+// Test whether the layout has stabilized.
+
+---
+// Forward queries are only complete once the layout has stabilized.
+#locate(loc => if loc.stable() [
+  #test(query(heading, loc).len(), 2)
+  Stable with #query(heading, loc).len() headings.
+] else [
+  Unstable.
+])
+
+= One
+= Two
+
+---
+// Even without any queries, the layout is eventually stable.
+#locate(loc => if loc.stable() [Stable.] else [Unstable.])
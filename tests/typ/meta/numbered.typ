@@ -0,0 +1,44 @@
+// Test numbered blocks of custom kinds.
+
+---
+#show outline.entry: it => {
+  test(it.element.func(), numbered)
+  test(it.element.kind, "theorem")
+  it
+}
+#outline(title: none, target: numbered.where(kind: "theorem"))
+
+#let theorem = numbered.with("theorem", supplement: [Theorem])
+#let lemma = numbered.with("lemma")
+
+#theorem[A] <a>
+#lemma[B]
+#theorem(title: [Third])[C] <c>
+
+#locate(loc => {
+  let theorems = query(numbered.where(kind: "theorem"), loc)
+  test(theorems.len(), 2)
+  test(theorems.last().title, [Third])
+  test(theorems.last().counter.at(theorems.last().location()), (2,))
+  test(query(numbered.where(kind: "lemma"), loc).first().supplement, [Lemma])
+})
+
+#show ref: it => {
+  if it.element != none {
+    test(it.element.func(), numbered)
+    test(it.element.supplement, [Theorem])
+  }
+  it
+}
+
+See @a and @c[Thm.].
+
+---
+// Unnumbered blocks don't step the counter.
+#numbered("note", numbering: none)[A]
+#numbered("note")[B] <b>
+
+#locate(loc => {
+  let b = query(<b>, loc).first()
+  test(b.counter.at(b.location()), (1,))
+})
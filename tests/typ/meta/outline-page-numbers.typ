@@ -0,0 +1,33 @@
+// Test conditional page number display in the outline.
+// Ref: false
+
+---
+#set page(height: 200pt)
+#show outline.entry: it => {
+  test(it.page, [])
+  it
+}
+#outline(page-numbers: "multiple-pages")
+
+= Introduction
+= Conclusion
+
+---
+#set page(height: 200pt)
+#show outline.entry: it => {
+  if it.element.body == [Introduction] {
+    test(it.page, [])
+  } else {
+    test(it.page != [], true)
+  }
+  it
+}
+#outline(page-numbers: "other-pages")
+
+= Introduction
+#pagebreak()
+= Conclusion
+
+---
+// Error: 24-30 expected "always", "other-pages", or "multiple-pages"
+#outline(page-numbers: "some")
@@ -0,0 +1,38 @@
+// Test selecting elements by comparing their fields.
+// Ref: false
+
+---
+= A
+== B
+=== C
+== D
+#figure([Cat], kind: "cat", supplement: none)
+#figure([Frog], kind: "frog", supplement: none, numbering: none)
+
+#let test-selector(selector, ref) = locate(loc => {
+  let elems = query(selector, loc)
+  test(elems.map(e => e.body), ref)
+})
+
+#test-selector(heading.compare("level", ">", 1), ([B], [C], [D]))
+#test-selector(heading.compare("level", "<=", 2), ([A], [B], [D]))
+#test-selector(heading.compare("level", "!=", 2), ([A], [C]))
+#test-selector(figure.compare("numbering", "==", none), ([Frog],))
+#test-selector(
+  heading.compare("level", ">=", 2).and(heading.compare("level", "<", 3)),
+  ([B], [D]),
+)
+
+// Values that cannot be compared don't match.
+#test-selector(heading.compare("level", ">", "1"), ())
+
+---
+#test(repr(heading.compare("level", ">", 1)), "heading.compare(\"level\", \">\", 1)")
+
+---
+// Error: 2-33 element `heading` does not have field `size`
+#heading.compare("size", ">", 1)
+
+---
+// Error: 27-31 expected "==", "!=", "<", "<=", ">", or ">="
+#heading.compare("level", "=>", 1)
@@ -0,0 +1,33 @@
+// Test querying for the last element before a location.
+// Ref: false
+
+---
+#set page(height: 100pt, header: locate(loc => {
+  let chapter = query(heading, loc, before: loc)
+  [#chapter.map(it => it.body).join() #metadata(none) <header>]
+}))
+
+= Alpha
+#lorem(10)
+#pagebreak()
+
+= Beta
+#lorem(100)
+
+#locate(loc => {
+  let headers = query(<header>, loc)
+  test(headers.len() > 2, true)
+  test(query(heading, loc, before: headers.first().location()), ())
+  let last = query(heading, loc, before: headers.last().location())
+  test(last.map(it => it.body), ([Beta],))
+})
+
+---
+// The result is empty if nothing matches before the location.
+= Only
+#metadata(none) <start>
+#locate(loc => {
+  let start = query(<start>, loc).first().location()
+  test(query(<missing>, loc, before: start), ())
+  test(query(heading, loc, before: start).len(), 1)
+})
@@ -0,0 +1,40 @@
+// Test selecting elements by page and region.
+// Ref: false
+
+---
+#set page(height: 100pt)
+
+= A
+#pagebreak()
+= B
+= C
+
+#let test-selector(selector, ref) = locate(loc => {
+  test(query(selector, loc).map(e => e.body), ref)
+})
+
+#test-selector(selector(heading).on-page(1), ([A],))
+#test-selector(selector(heading).on-page(2), ([B], [C]))
+#test-selector(selector(heading).on-page(3), ())
+#test-selector(selector(heading).in-region(2), ([B], [C]))
+#test-selector(selector(heading).in-region(2, width: 0pt), ())
+
+#locate(loc => {
+  let pos = query(heading.where(body: [C]), loc).first().location().position()
+  let selector = selector(heading).in-region(2, x: pos.x, y: pos.y)
+  test(query(selector, loc).map(e => e.body), ([C],))
+})
+
+---
+// Error: 2-40 region cannot be measured in em units
+#selector(heading).in-region(1, x: 1em)
+
+---
+// Test the position of queried content.
+= A
+#locate(loc => {
+  let heading = query(heading, loc).first()
+  test(heading.position(), heading.location().position())
+  test(heading.position().page, 1)
+  test([Unlocated].position(), none)
+})
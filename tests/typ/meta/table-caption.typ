@@ -0,0 +1,34 @@
+// Test tables with a caption.
+
+---
+#outline(title: none, target: figure.where(kind: table))
+
+#figure(table[A], caption: [First]) <first>
+#table(caption: [Second])[B] <second>
+
+#locate(loc => {
+  let tables = query(figure.where(kind: table), loc)
+  test(tables.len(), 2)
+  test(tables.last().caption.body, [Second])
+  test(tables.last().counter.at(tables.last().location()), (2,))
+})
+
+#show ref: it => {
+  if it.element != none {
+    test(it.element.func(), figure)
+    test(it.element.supplement, [Table])
+    test(it.element.counter.at(it.element.location()).len(), 1)
+  }
+  it
+}
+
+See @first and @second.
+
+---
+// A table without a caption stays a plain table.
+#show figure: it => panic("should not be a figure")
+#table[A]
+
+---
+// Error: 1:19-1:25 cannot reference table directly, try giving it a caption
+#table[A] <plain> @plain
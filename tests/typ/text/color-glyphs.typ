@@ -0,0 +1,18 @@
+// Test color glyphs.
+
+---
+// COLR glyphs with linear, sweep and radial gradients and a transform.
+#set text(font: "COLRv1 Static Test Glyphs", size: 24pt)
+\u{F0101} \u{F0201} \u{F0503} \u{F0600}
+
+---
+// Layers in the foreground color take on the fill of the text.
+#set text(font: "COLRv1 Static Test Glyphs", size: 24pt)
+\u{F0B06} \u{F0B00} \
+#set text(fill: blue)
+\u{F0B06} \u{F0B00}
+
+---
+// SVG glyphs.
+#set text(font: "Twitter Color Emoji", size: 24pt)
+🐪 🌋 🏞
@@ -31,6 +31,12 @@ fi vs. #text(ligatures: false)[No fi]
 #text(number-width: "tabular")[3456789123] \
 #text(number-width: "tabular")[0123456789]
 
+---
+// Test warning for number features that the font doesn't support.
+// Warning: 59-68 font "DejaVu Sans Mono" does not support old-style numbers
+// Hint: 59-68 try using a font that has the `onum` feature
+#text(font: "DejaVu Sans Mono", number-type: "old-style")[Year 2024]
+
 ---
 // Test extra number stuff.
 #set text(font: "IBM Plex Serif")
@@ -42,6 +48,14 @@ fi vs. #text(ligatures: false)[No fi]
 #text(features: ("smcp",))[Smcp] \
 fi vs. #text(features: (liga: 0))[No fi]
 
+---
+// Test that raw features override the convenience toggles.
+#set text(font: "IBM Plex Serif")
+#text(ligatures: false)[fi] vs.
+#text(ligatures: false, features: (liga: 1))[fi] \
+#text(kerning: false)[Tq] vs.
+#text(kerning: false, features: (kern: 1))[Tq]
+
 ---
 // Error: 26-31 expected integer or none, found boolean
 #set text(stylistic-set: false)
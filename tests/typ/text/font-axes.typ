@@ -0,0 +1,39 @@
+// Test variable font axes.
+
+---
+// Axes that a font doesn't have are ignored.
+#style(styles => {
+  let plain = measure([Typst], styles).width
+  let varied = measure(text(axes: (wght: 700, wdth: 85))[Typst], styles).width
+  test(plain, varied)
+})
+
+---
+// The weight and width axes change the outlines and advances of a variable
+// font.
+#set text(font: "Typst Test Variable", size: 16pt)
+HOTEL FILE \
+#text(axes: (wght: 900))[HOTEL FILE] \
+#text(axes: (wght: 100))[HOTEL FILE] \
+#text(axes: (wdth: 50))[HOTEL FILE] \
+#text(axes: (wght: 700, wdth: 75))[HOTEL FILE]
+
+#style(styles => {
+  let width(axes) = measure(text(axes: axes)[FILE], styles).width
+  test(width((wght: 900)) > width((:)), true)
+  test(width((wdth: 50)) < width((:)), true)
+})
+
+---
+// Axes and raw features can be combined.
+#set text(axes: (wght: 450), features: ("ss01", "rlig"))
+#set text(axes: (wdth: 85))
+Variable text.
+
+---
+// Error: 17-28 axis tag must be exactly four characters long
+#set text(axes: (weight: 4))
+
+---
+// Error: 17-30 expected float, found string
+#set text(axes: (wght: "400"))
@@ -0,0 +1,33 @@
+// Test custom hyphenation patterns.
+// Ref: false
+
+---
+// Custom patterns hyphenate languages without bundled patterns.
+#set text(lang: "tlh", hyphenate: true)
+#style(styles => {
+  let word(..args) = block(width: 30pt, text(..args)[bortasbortas])
+  let plain = measure(word(), styles).height
+  let custom = measure(word(hyphenation: "1ta"), styles).height
+  test(custom > plain, true)
+})
+
+---
+// Exceptions in TeX format override the patterns.
+#set text(lang: "tlh", hyphenate: true)
+#style(styles => {
+  let word(..args) = block(width: 30pt, text(..args)[bortasbortas])
+  let plain = measure(word(), styles).height
+  let custom = measure(
+    word(hyphenation: "% Exceptions\n\\hyphenation{bor-tas-bor-tas}"),
+    styles,
+  ).height
+  test(custom > plain, true)
+})
+
+---
+// Error: 24-28 invalid hyphenation pattern `12`
+#set text(hyphenation: "12")
+
+---
+// Error: 24-33 hyphenation patterns contain no patterns or exceptions
+#set text(hyphenation: "% empty")
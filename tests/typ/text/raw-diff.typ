@@ -0,0 +1,39 @@
+// Test diff highlighting of raw lines.
+
+---
+#show raw: it => {
+  test(it.lines.map(line => line.change), ("hunk", none, "removed", "added"))
+  it
+}
+
+```diff
+@@ -1,2 +1,2 @@
+ fn main() {
+-    old();
++    new();
+```
+
+---
+// Diff mode can be enabled for other languages.
+#set raw(diff: true, added-fill: none)
+#show raw: it => {
+  test(it.lines.map(line => line.change), ("added", "removed", none))
+  it
+}
+
+```rust
++let a = 1;
+-let b = 2;
+ let c = 3;
+```
+
+---
+// Diff mode is off by default for other languages.
+#show raw: it => {
+  test(it.lines.all(line => not line.has("change")), true)
+  it
+}
+
+```rust
++let a = 1;
+```
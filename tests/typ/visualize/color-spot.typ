@@ -0,0 +1,21 @@
+// Test spot colors.
+
+---
+// A spot color behaves like its tinted CMYK alternate.
+#let red = color.spot("Brand Red", cmyk(0%, 80%, 60%, 0%))
+#let light = color.spot("Brand Red", cmyk(0%, 80%, 60%, 0%), tint: 50%)
+#test(red.space(), cmyk)
+#test-repr(light.components(), (0%, 40%, 30%, 0%))
+#test(red == cmyk(0%, 80%, 60%, 0%), false)
+#test(light == color.spot("Brand Red", cmyk(0%, 80%, 60%, 0%), tint: 50%), true)
+#test(repr(red), "color.spot(\"Brand Red\", cmyk(0%, 80%, 60%, 0%))")
+#test(repr(light), "color.spot(\"Brand Red\", cmyk(0%, 80%, 60%, 0%), tint: 50%)")
+#square(fill: red, stroke: 2pt + light)
+
+---
+// Other colors are converted to CMYK for the alternate.
+#test(color.spot("Ink", luma(0)).space(), cmyk)
+
+---
+// Error: 33-37 ratio must be between 0% and 100%
+#color.spot("Ink", black, tint: 120%)
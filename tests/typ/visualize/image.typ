@@ -80,3 +80,9 @@ A #box(image("/files/tiger.jpg", height: 1cm, width: 80%)) B
 ---
 // Error: 2-83 failed to decode image (Format error decoding Png: Invalid PNG signature.)
 #image.decode(read("/files/tiger.jpg", encoding: none), format: "png", width: 80%)
+
+---
+// Test that SVG filters fall back to rasterization.
+// Warning: 2-214 SVG filters cannot be exported as vector graphics
+// Hint: 2-214 the parts of the image that use filters are rasterized
+#image.decode(`<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40"><filter id="f"><feGaussianBlur stdDeviation="2"/></filter><circle cx="20" cy="20" r="10" filter="url(#f)"/></svg>`.text, format: "svg")
@@ -0,0 +1,17 @@
+// Test pattern offsets.
+
+---
+// Tile an image with spacing and an offset.
+#let logo = pattern(
+  size: (20pt, 20pt),
+  spacing: (5pt, 5pt),
+  offset: (10pt, 10pt),
+  image("/files/tiger.jpg", width: 20pt, height: 20pt),
+)
+#rect(width: 100%, height: 60pt, fill: logo)
+#set text(fill: logo, size: 30pt)
+Tiger
+
+---
+// Error: 38-48 pattern offset must be absolute
+#pattern(size: (10pt, 10pt), offset: (1em, 0pt))[]